@@ -1,14 +1,32 @@
-use std::{collections::BTreeMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use dyn_fmt::AsStrFormatExt;
 use parking_lot::RwLock;
 
 use crate::{
-    DataType, DatatypeBuilder, DatatypeError, DatatypeHandler, DatatypeState, IntoString,
-    clients::{common::ClientCommon, datatype_manager::DatatypeManager},
+    BoxedError, CustomCrdt, DataType, DatatypeBuilder, DatatypeError, DatatypeHandler,
+    DatatypeState, IntoString,
+    clients::{
+        common::ClientCommon,
+        datatype_manager::DatatypeManager,
+        listing::{DatatypeInfo, RemoteDatatypeFilter, RemoteDatatypeInfo},
+        transaction::{self as client_transaction, ClientTransaction},
+    },
     connectivity::{Connectivity, null_connectivity::NullConnectivity},
-    datatypes::{datatype_set::DatatypeSet, option::DatatypeOption},
-    errors::clients::{CLIENT_ERROR_MSG_COLLECTION_NAME, ClientError},
+    datatypes::{
+        crdts::custom_crdt::CustomCrdtFactory, datatype_set::DatatypeSet, option::DatatypeOption,
+        wired::push_pull_batch,
+    },
+    errors::{
+        clients::{CLIENT_ERROR_MSG_COLLECTION_NAME, ClientError},
+        connectivity::ConnectivityError,
+        with_err_out,
+    },
+    types::uid::Cuid,
     utils::name_validator::is_valid_collection_name,
 };
 
@@ -27,7 +45,9 @@ use crate::{
 pub struct ClientBuilder {
     collection: String,
     alias: String,
+    cuid: Option<String>,
     connectivity: Arc<dyn Connectivity>,
+    custom_crdts: HashMap<&'static str, CustomCrdtFactory>,
 }
 
 impl ClientBuilder {
@@ -36,21 +56,41 @@ impl ClientBuilder {
     /// It initializes client metadata and datatype management structures.
     ///
     /// # Errors
-    /// Returns [`ClientError::InvalidCollectionName`] if the collection name is invalid.
+    /// - [`ClientError::InvalidCollectionName`] if the collection name is invalid.
+    /// - [`ClientError::InvalidCuid`] if the cuid given by [`ClientBuilder::with_cuid`] is
+    ///   invalid.
+    /// - [`ClientError::DuplicateSession`] if another client of the same cuid is still open
+    ///   on the connectivity backend.
+    /// - [`ClientError::FailedToOpenSession`] if the connectivity backend does not answer in
+    ///   time; building again may succeed.
     pub fn build(self) -> Result<Client, ClientError> {
         if !is_valid_collection_name(&self.collection) {
             return Err(ClientError::InvalidCollectionName(
                 CLIENT_ERROR_MSG_COLLECTION_NAME.format(&[self.collection]),
             ));
         }
+        let cuid = match self.cuid {
+            Some(cuid) => Cuid::try_from(cuid.as_str())
+                .map_err(|e| ClientError::InvalidCuid(format!("'{cuid}': {e}")))?,
+            None => Cuid::new(),
+        };
+        self.connectivity.open_session(&cuid).map_err(|e| match e {
+            ConnectivityError::SessionConflict(msg) => ClientError::DuplicateSession(msg),
+            ConnectivityError::TimedOut(msg) => ClientError::FailedToOpenSession(msg),
+        })?;
 
-        let common =
-            ClientCommon::new_arc(self.collection.into(), self.alias.into(), self.connectivity);
+        let common = ClientCommon::new_arc(
+            self.collection.into(),
+            self.alias.into(),
+            cuid,
+            self.connectivity,
+        );
         let datatype_manager = Arc::new(RwLock::new(DatatypeManager::new(common.clone())));
         common.set_datatype_manager(Arc::downgrade(&datatype_manager));
         Ok(Client {
             datatype_manager,
             common,
+            custom_crdts: self.custom_crdts,
         })
     }
 
@@ -79,6 +119,57 @@ impl ClientBuilder {
         self.connectivity = connectivity;
         self
     }
+
+    /// Sets the identity of the client, so that it resumes as the same client after a
+    /// restart.
+    ///
+    /// By default, a new cuid is generated. Pass the one returned by [`Client::get_cuid`]
+    /// of an earlier client; it is validated when the client is built. Only one client of
+    /// a cuid can be open on a connectivity backend at a time, until it is closed, or
+    /// dropped along with all of its datatypes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qortoo::Client;
+    ///
+    /// let cuid = Client::builder("collection", "alias").build().unwrap().get_cuid().to_owned();
+    /// let client = Client::builder("collection", "alias")
+    ///     .with_cuid(cuid.as_str())
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(client.get_cuid(), cuid);
+    /// ```
+    pub fn with_cuid(mut self, cuid: impl IntoString) -> Self {
+        self.cuid = Some(cuid.into());
+        self
+    }
+
+    /// Registers the user-defined CRDT `T`, so that the client can build datatypes of it
+    /// with [`DatatypeBuilder::build_custom`].
+    ///
+    /// A type registered later under the same [`CustomCrdt::TYPE_NAME`] replaces the earlier.
+    /// See [`CustomCrdt`] for an example.
+    pub fn with_custom_crdt<T: CustomCrdt>(mut self) -> Self {
+        self.custom_crdts
+            .insert(T::TYPE_NAME, CustomCrdtFactory::of::<T>());
+        self
+    }
+}
+
+/// The outcome of [`Client::close`].
+#[derive(Debug, Default)]
+pub struct CloseReport {
+    /// The datatypes that could not be flushed and unsubscribed, by key, with the error
+    /// that stopped them. Their unpushed transactions are lost.
+    pub unflushed: Vec<(String, DatatypeError)>,
+}
+
+impl CloseReport {
+    /// Returns `true` if every datatype was flushed and unsubscribed.
+    pub fn is_clean(&self) -> bool {
+        self.unflushed.is_empty()
+    }
 }
 
 /// Facade for creating and subscribing to Qortoo datatypes.
@@ -92,6 +183,7 @@ impl ClientBuilder {
 pub struct Client {
     common: Arc<ClientCommon>,
     datatype_manager: Arc<RwLock<DatatypeManager>>,
+    custom_crdts: HashMap<&'static str, CustomCrdtFactory>,
 }
 
 impl Client {
@@ -108,10 +200,16 @@ impl Client {
         ClientBuilder {
             collection: collection.into(),
             alias: alias.into(),
+            cuid: None,
             connectivity: Arc::new(NullConnectivity::new()),
+            custom_crdts: HashMap::new(),
         }
     }
 
+    pub(crate) fn get_custom_crdt_factory(&self, type_name: &str) -> Option<CustomCrdtFactory> {
+        self.custom_crdts.get(type_name).copied()
+    }
+
     pub(crate) fn do_subscribe_or_create_datatype(
         &self,
         key: String,
@@ -137,6 +235,64 @@ impl Client {
         self.datatype_manager.read().get_datatype(key)
     }
 
+    /// Returns the key, type and state of every datatype attached to this client, sorted
+    /// by key.
+    pub fn datatypes(&self) -> Vec<DatatypeInfo> {
+        let mut infos: Vec<_> = self
+            .datatype_manager
+            .read()
+            .get_datatypes()
+            .iter()
+            .map(|d| DatatypeInfo {
+                key: d.get_core().attr.key.to_string(),
+                r#type: d.get_type(),
+                state: d.get_state(),
+            })
+            .collect();
+        infos.sort_by(|a, b| a.key.cmp(&b.key));
+        infos
+    }
+
+    /// Lists the datatypes existing on the server in the collection of this client that
+    /// `filter` selects, sorted by key.
+    ///
+    /// The list is answered by the connectivity backend, including datatypes this client
+    /// has never attached; the default backend has none.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::FailedToListDatatypes`] if the backend cannot answer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qortoo::{Client, DataType, Datatype, LocalConnectivity, RemoteDatatypeFilter};
+    ///
+    /// let connectivity = LocalConnectivity::new_arc();
+    /// connectivity.set_realtime(false);
+    /// let client = Client::builder("doc-example", "Client-list_remote_datatypes")
+    ///     .with_connectivity(connectivity)
+    ///     .build()
+    ///     .unwrap();
+    /// client.create_datatype("a").build_counter().unwrap().sync().unwrap();
+    /// client.create_datatype("b").build_map().unwrap().sync().unwrap();
+    ///
+    /// let counters = client
+    ///     .list_remote_datatypes(&RemoteDatatypeFilter::new().with_type(DataType::Counter))
+    ///     .unwrap();
+    /// assert_eq!(counters.len(), 1);
+    /// assert_eq!(counters[0].key, "a");
+    /// ```
+    pub fn list_remote_datatypes(
+        &self,
+        filter: &RemoteDatatypeFilter,
+    ) -> Result<Vec<RemoteDatatypeInfo>, ClientError> {
+        self.common
+            .connectivity
+            .list_datatypes(&self.common.collection, filter)
+            .map_err(|e| with_err_out!(ClientError::FailedToListDatatypes(e.to_string())))
+    }
+
     /// Unsubscribes the datatype identified by `key` from this client.
     ///
     /// This is a key-based convenience API over [`Datatype::unsubscribe`](crate::Datatype::unsubscribe):
@@ -167,6 +323,13 @@ impl Client {
         &self.common.alias
     }
 
+    /// Returns the identity of this client.
+    ///
+    /// Keep it to build the client again with [`ClientBuilder::with_cuid`] after a restart.
+    pub fn get_cuid(&self) -> &str {
+        &self.common.cuid
+    }
+
     /// Get `DatatypeBuilder` to subscribe a `Datatype` identified by `key`.
     ///
     /// The `Datatype` built by this builder will be marked
@@ -191,21 +354,181 @@ impl Client {
         DatatypeBuilder::new(self, key.into(), DatatypeState::SubscribingOrCreating)
     }
 
-    #[cfg(test)]
-    pub fn get_cuid(&self) -> crate::types::uid::Cuid {
-        self.common.cuid.clone()
+    /// Executes `tx_func` as one atomic transaction over the datatypes of this client.
+    ///
+    /// Every datatype of this client written in `tx_func` on the calling thread joins the
+    /// transaction, including through its own `transaction()`. If `tx_func` returns an error,
+    /// the changes to all of them are rolled back. Otherwise each datatype commits a
+    /// transaction tagged with `tag`, and the server applies them all or none: other clients
+    /// see none of them until every one has been synchronized. If one of them can no longer
+    /// arrive, because its datatype unsubscribes or resubscribes or the session of this
+    /// client is closed, the others are applied as they arrive.
+    ///
+    /// A datatype is locked for the transaction once it is written, so concurrent
+    /// transactions should write their datatypes in the same order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatatypeError::TransactionFailed`] if `tx_func` returns an error. If any of
+    /// the datatypes cannot commit, e.g., [`DatatypeError::PushBufferExceededMaxMemSize`],
+    /// all of them are rolled back and its error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qortoo::Client;
+    /// let client = Client::builder("doc-example", "Client-transaction").build().unwrap();
+    /// let from = client.create_datatype("from").build_counter().unwrap();
+    /// let to = client.create_datatype("to").build_counter().unwrap();
+    /// from.increase_by(10).unwrap();
+    ///
+    /// client
+    ///     .transaction("move", |_| {
+    ///         from.increase_by(-4)?;
+    ///         to.increase_by(4)?;
+    ///         Ok(())
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!((from.get_value(), to.get_value()), (6, 4));
+    ///
+    /// let result = client.transaction("overdraw", |_| {
+    ///     from.increase_by(-10)?;
+    ///     if from.get_value() < 0 {
+    ///         return Err("insufficient".into());
+    ///     }
+    ///     to.increase_by(10)?;
+    ///     Ok(())
+    /// });
+    /// assert!(result.is_err());
+    /// assert_eq!((from.get_value(), to.get_value()), (6, 4));
+    /// ```
+    pub fn transaction<F>(&self, tag: impl IntoString, tx_func: F) -> Result<(), DatatypeError>
+    where
+        F: FnOnce(&ClientTransaction) -> Result<(), BoxedError>,
+    {
+        client_transaction::run(&self.common, tag.into(), tx_func)
+    }
+
+    /// Synchronizes every datatype of this client in one batched push-pull of its
+    /// connectivity, instead of a round trip for each.
+    ///
+    /// Each datatype applies its own result; an error is handled by the datatype as its
+    /// [`sync()`](crate::Datatype::sync) would, e.g., disabling it. Disabled datatypes are
+    /// skipped.
+    ///
+    /// Returns the datatypes that failed, by key, with their errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qortoo::{Client, Datatype, LocalConnectivity};
+    ///
+    /// let connectivity = LocalConnectivity::new_arc();
+    /// connectivity.set_realtime(false);
+    /// let client = Client::builder("doc-example", "Client-sync_all")
+    ///     .with_connectivity(connectivity)
+    ///     .build()
+    ///     .unwrap();
+    /// let counter = client.create_datatype("counter").build_counter().unwrap();
+    /// let map = client.create_datatype("map").build_map().unwrap();
+    /// counter.increase().unwrap();
+    /// map.put("k", 1).unwrap();
+    ///
+    /// assert!(client.sync_all().is_empty());
+    /// assert_eq!(counter.get_synced_client_version(), 1);
+    /// assert_eq!(map.get_synced_client_version(), 1);
+    /// ```
+    pub fn sync_all(&self) -> Vec<(String, DatatypeError)> {
+        let mut datatypes = self.datatype_manager.read().get_datatypes();
+        datatypes.retain(|d| d.get_state() != DatatypeState::Disabled);
+        // sorted, so that concurrent batches lock the datatypes in the same order
+        datatypes.sort_by_key(|d| d.get_core().attr.key.to_string());
+        let wired_datatypes: Vec<_> = datatypes
+            .iter()
+            .map(|d| d.get_core().get_wired_datatype())
+            .collect();
+        datatypes
+            .iter()
+            .zip(push_pull_batch(&wired_datatypes))
+            .filter_map(|(d, result)| Some((d.get_core().attr.key.to_string(), result.err()?)))
+            .collect()
+    }
+
+    /// Closes this client, flushing its datatypes within `timeout`.
+    ///
+    /// Every datatype of this client pushes its pending transactions and unsubscribes, in
+    /// turn, within what remains of `timeout`. Then, flushed or not, it is disabled and
+    /// detached, and its event loop is stopped. Finally, the session of the client is closed,
+    /// so that its cuid can be used again, and the runtime of the client is shut down,
+    /// waiting for its tasks with the rest of `timeout`.
+    ///
+    /// Handles to the datatypes kept by the caller stay readable, but are
+    /// [`DatatypeState::Disabled`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use qortoo::{Client, Datatype, DatatypeState, LocalConnectivity};
+    ///
+    /// let connectivity = LocalConnectivity::new_arc();
+    /// connectivity.set_realtime(false);
+    /// let client = Client::builder("doc-example", "Client-close")
+    ///     .with_connectivity(connectivity)
+    ///     .build()
+    ///     .unwrap();
+    /// let counter = client.create_datatype("key").build_counter().unwrap();
+    /// counter.increase().unwrap();
+    ///
+    /// let report = client.close(Duration::from_secs(1));
+    /// assert!(report.is_clean());
+    /// assert_eq!(counter.get_state(), DatatypeState::Disabled);
+    /// assert_eq!(counter.get_synced_client_version(), 1);
+    /// ```
+    pub fn close(self, timeout: Duration) -> CloseReport {
+        let deadline = Instant::now() + timeout;
+        let mut datatypes = self.datatype_manager.read().get_datatypes();
+        datatypes.sort_by_key(|d| d.get_core().attr.key.to_string());
+        let mut report = CloseReport::default();
+        for datatype in datatypes {
+            let core = datatype.get_core();
+            if let Err(err) = core.close(deadline.saturating_duration_since(Instant::now())) {
+                report.unflushed.push((core.attr.key.to_string(), err));
+            }
+        }
+        self.common.close_session();
+        self.common
+            .shutdown_runtime(deadline.saturating_duration_since(Instant::now()));
+        report
     }
 }
 
 #[cfg(test)]
 mod tests_client {
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        time::Duration,
+    };
+
+    use crossbeam_channel::Sender;
     use tracing::instrument;
 
     use crate::{
-        Datatype, DatatypeState, LocalConnectivity,
-        clients::client::Client,
-        datatypes::datatype::DatatypeBlanket,
-        utils::test_utils::{get_test_collection_name, get_test_func_name},
+        ClientError, DataType, Datatype, DatatypeError, DatatypeState, LocalConnectivity,
+        ServerRejectReason,
+        clients::{
+            client::Client,
+            listing::{DatatypeInfo, RemoteDatatypeFilter, RemoteDatatypeInfo},
+        },
+        connectivity::Connectivity,
+        datatypes::{datatype::DatatypeBlanket, event_loop::Event, wired::WiredDatatype},
+        errors::connectivity::ConnectivityError,
+        types::{push_pull_pack::PushPullPack, uid::Cuid},
+        utils::test_utils::{get_test_collection_name, get_test_func_name, get_test_ids},
     };
 
     #[test]
@@ -475,4 +798,459 @@ mod tests_client {
             crate::DatatypeError::Disallowed(_)
         ));
     }
+
+    #[test]
+    #[instrument]
+    fn can_close_client_after_flushing_datatypes() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let (collection, key, resource_id) = get_test_ids!();
+        let client1 = Client::builder(collection.clone(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(collection, "client2")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let failing_key = format!("{key}-failing");
+        let counter1 = client1
+            .create_datatype(key.clone())
+            .build_counter()
+            .unwrap();
+        let failing = client1
+            .create_datatype(failing_key.clone())
+            .build_counter()
+            .unwrap();
+        counter1.sync().unwrap();
+        let counter2 = client2
+            .subscribe_datatype(key.clone())
+            .build_counter()
+            .unwrap();
+        counter2.sync().unwrap();
+        counter1.increase_by(2).unwrap();
+        failing.sync().unwrap();
+        failing.increase().unwrap();
+        connectivity
+            .get_wired_interceptor(&format!("{resource_id}-failing"), client1.get_cuid())
+            .unwrap()
+            .set_after_pull(|_| {
+                Err(
+                    DatatypeError::ServerRejected(ServerRejectReason::ProtocolViolation(
+                        "injected".to_string(),
+                    ))
+                    .mapping(),
+                )
+            });
+
+        let report = client1.close(Duration::from_secs(1));
+        assert!(!report.is_clean());
+        assert_eq!(report.unflushed.len(), 1);
+        assert_eq!(report.unflushed[0].0, failing_key);
+        assert_eq!(counter1.get_state(), DatatypeState::Disabled);
+        assert_eq!(failing.get_state(), DatatypeState::Disabled);
+        assert!(counter1.sync().is_err());
+        assert_eq!(counter1.get_value(), 2);
+
+        counter2.sync().unwrap();
+        assert_eq!(counter2.get_value(), 2);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_close_client_with_datatypes_leaving() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let (collection, key, resource_id) = get_test_ids!();
+        let client = Client::builder(collection, get_test_func_name!())
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let deleting = client.create_datatype(key.clone()).build_counter().unwrap();
+        let unsubscribing = client
+            .create_datatype(format!("{key}-unsubscribing"))
+            .build_counter()
+            .unwrap();
+        deleting.sync().unwrap();
+        unsubscribing.sync().unwrap();
+        deleting.increase().unwrap();
+        deleting.delete().unwrap();
+        unsubscribing.increase().unwrap();
+        unsubscribing.unsubscribe().unwrap();
+
+        // both become Disabled with nothing left to push, which is not a failure
+        let report = client.close(Duration::from_secs(1));
+        assert!(report.is_clean(), "{:?}", report.unflushed);
+        assert_eq!(deleting.get_state(), DatatypeState::Disabled);
+        assert_eq!(unsubscribing.get_synced_client_version(), 1);
+        assert!(
+            connectivity
+                .get_local_datatype_server(&resource_id)
+                .is_none()
+        );
+    }
+
+    #[derive(Debug)]
+    struct BatchCountingConnectivity {
+        inner: Arc<LocalConnectivity>,
+        batches: AtomicUsize,
+    }
+
+    impl Connectivity for BatchCountingConnectivity {
+        fn register(&self, wired: Arc<WiredDatatype>, sender: Sender<Event>) {
+            self.inner.register(wired, sender);
+        }
+
+        fn push_pull(&self, ppp: &PushPullPack) -> Result<PushPullPack, ConnectivityError> {
+            self.inner.push_pull(ppp)
+        }
+
+        fn push_pull_batch(
+            &self,
+            ppps: &[PushPullPack],
+        ) -> Vec<Result<PushPullPack, ConnectivityError>> {
+            self.batches.fetch_add(1, Ordering::Relaxed);
+            self.inner.push_pull_batch(ppps)
+        }
+
+        fn is_realtime(&self) -> bool {
+            false
+        }
+
+        fn list_datatypes(
+            &self,
+            collection: &str,
+            filter: &RemoteDatatypeFilter,
+        ) -> Result<Vec<RemoteDatatypeInfo>, ConnectivityError> {
+            self.inner.list_datatypes(collection, filter)
+        }
+
+        fn open_session(&self, cuid: &Cuid) -> Result<(), ConnectivityError> {
+            self.inner.open_session(cuid)
+        }
+
+        fn close_session(&self, cuid: &Cuid) {
+            self.inner.close_session(cuid)
+        }
+    }
+
+    #[derive(Debug)]
+    struct SessionTimingOutConnectivity {
+        inner: Arc<LocalConnectivity>,
+    }
+
+    impl Connectivity for SessionTimingOutConnectivity {
+        fn register(&self, wired: Arc<WiredDatatype>, sender: Sender<Event>) {
+            self.inner.register(wired, sender);
+        }
+
+        fn push_pull(&self, ppp: &PushPullPack) -> Result<PushPullPack, ConnectivityError> {
+            self.inner.push_pull(ppp)
+        }
+
+        fn is_realtime(&self) -> bool {
+            false
+        }
+
+        fn list_datatypes(
+            &self,
+            collection: &str,
+            filter: &RemoteDatatypeFilter,
+        ) -> Result<Vec<RemoteDatatypeInfo>, ConnectivityError> {
+            self.inner.list_datatypes(collection, filter)
+        }
+
+        fn open_session(&self, cuid: &Cuid) -> Result<(), ConnectivityError> {
+            Err(ConnectivityError::TimedOut(format!(
+                "opening session of '{cuid}'"
+            )))
+        }
+
+        fn close_session(&self, cuid: &Cuid) {
+            self.inner.close_session(cuid)
+        }
+    }
+
+    #[test]
+    #[instrument]
+    fn can_fail_to_build_client_when_session_times_out() {
+        let connectivity = Arc::new(SessionTimingOutConnectivity {
+            inner: LocalConnectivity::new_arc(),
+        });
+        let Err(err) = Client::builder(get_test_collection_name!(), get_test_func_name!())
+            .with_connectivity(connectivity)
+            .build()
+        else {
+            panic!("a session that timed out must fail the build");
+        };
+        assert_eq!(err, ClientError::FailedToOpenSession(String::new()));
+        assert!(err.to_string().contains("opening session"));
+    }
+
+    #[test]
+    #[instrument]
+    fn can_sync_all_datatypes_in_a_batch() {
+        let local = LocalConnectivity::new_arc();
+        let connectivity = Arc::new(BatchCountingConnectivity {
+            inner: local.clone(),
+            batches: AtomicUsize::new(0),
+        });
+        let (collection, key, resource_id) = get_test_ids!();
+        let client = Client::builder(collection, get_test_func_name!())
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let counters: Vec<_> = (0..3)
+            .map(|i| {
+                client
+                    .create_datatype(format!("{key}-{i}"))
+                    .build_counter()
+                    .unwrap()
+            })
+            .collect();
+        assert!(client.sync_all().is_empty());
+        assert_eq!(connectivity.batches.load(Ordering::Relaxed), 1);
+        assert!(
+            counters
+                .iter()
+                .all(|c| c.get_state() == DatatypeState::Subscribed)
+        );
+
+        for counter in &counters {
+            counter.increase().unwrap();
+        }
+        local
+            .get_wired_interceptor(&format!("{resource_id}-1"), client.get_cuid())
+            .unwrap()
+            .set_after_pull(|_| {
+                Err(
+                    DatatypeError::ServerRejected(ServerRejectReason::ProtocolViolation(
+                        "injected".to_string(),
+                    ))
+                    .mapping(),
+                )
+            });
+        let failures = client.sync_all();
+        assert_eq!(connectivity.batches.load(Ordering::Relaxed), 2);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, format!("{key}-1"));
+        // the error is routed through the RecoveryAction of the failed datatype only
+        assert_eq!(counters[1].get_state(), DatatypeState::Disabled);
+        assert_eq!(counters[0].get_synced_client_version(), 1);
+        assert_eq!(counters[2].get_synced_client_version(), 1);
+
+        // a datatype failing to prepare its pack does not shift the results of the others
+        counters[0].increase().unwrap();
+        counters[2].increase().unwrap();
+        client
+            .get_datatype(&format!("{key}-0"))
+            .unwrap()
+            .get_core()
+            .mutable
+            .write()
+            .push_buffer
+            .first_cseq = u64::MAX;
+        let failures = client.sync_all();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, format!("{key}-0"));
+        assert_eq!(counters[2].get_synced_client_version(), 2);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_sync_client_transaction_in_one_batch() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let (collection, key, _) = get_test_ids!();
+        let client1 = Client::builder(collection.clone(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(collection, "client2")
+            .with_connectivity(connectivity)
+            .build()
+            .unwrap();
+        let key_a = format!("{key}-a");
+        let key_b = format!("{key}-b");
+        let a1 = client1.create_datatype(&key_a).build_counter().unwrap();
+        let b1 = client1.create_datatype(&key_b).build_counter().unwrap();
+        assert!(client1.sync_all().is_empty());
+
+        client1
+            .transaction("move", |_| {
+                a1.increase_by(-3)?;
+                b1.increase_by(3)?;
+                Ok(())
+            })
+            .unwrap();
+        assert!(client1.sync_all().is_empty());
+        // the group is complete within the batch, so both members are acknowledged at once
+        assert_eq!(a1.get_synced_client_version(), a1.get_client_version());
+        assert_eq!(b1.get_synced_client_version(), b1.get_client_version());
+
+        let a2 = client2.subscribe_datatype(&key_a).build_counter().unwrap();
+        let b2 = client2.subscribe_datatype(&key_b).build_counter().unwrap();
+        assert!(client2.sync_all().is_empty());
+        assert_eq!((a2.get_value(), b2.get_value()), (-3, 3));
+    }
+
+    #[test]
+    #[instrument]
+    fn can_list_local_and_remote_datatypes() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let collection = get_test_collection_name!();
+        let client1 = Client::builder(collection.clone(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(collection, "client2")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let other = Client::builder("other-collection", get_test_func_name!())
+            .with_connectivity(connectivity)
+            .build()
+            .unwrap();
+
+        let counter = client1
+            .create_datatype("k/counter")
+            .build_counter()
+            .unwrap();
+        let map = client1.create_datatype("k/map").build_map().unwrap();
+        let pending = client1.create_datatype("pending").build_counter().unwrap();
+        counter.increase().unwrap();
+        counter.sync().unwrap();
+        map.sync().unwrap();
+        other
+            .create_datatype("k/other")
+            .build_counter()
+            .unwrap()
+            .sync()
+            .unwrap();
+
+        assert_eq!(
+            client1.datatypes(),
+            vec![
+                DatatypeInfo {
+                    key: "k/counter".into(),
+                    r#type: DataType::Counter,
+                    state: DatatypeState::Subscribed,
+                },
+                DatatypeInfo {
+                    key: "k/map".into(),
+                    r#type: DataType::Map,
+                    state: DatatypeState::Subscribed,
+                },
+                DatatypeInfo {
+                    key: "pending".into(),
+                    r#type: DataType::Counter,
+                    state: DatatypeState::Creating,
+                },
+            ]
+        );
+        assert!(client2.datatypes().is_empty());
+
+        // a datatype not yet created on the server is not listed
+        let remote = client2
+            .list_remote_datatypes(&RemoteDatatypeFilter::new())
+            .unwrap();
+        assert_eq!(
+            remote,
+            vec![
+                RemoteDatatypeInfo {
+                    key: "k/counter".into(),
+                    r#type: DataType::Counter,
+                    server_version: 1,
+                    subscribers: 1,
+                },
+                RemoteDatatypeInfo {
+                    key: "k/map".into(),
+                    r#type: DataType::Map,
+                    server_version: 0,
+                    subscribers: 1,
+                },
+            ]
+        );
+        let filtered = client2
+            .list_remote_datatypes(
+                &RemoteDatatypeFilter::new()
+                    .with_key_prefix("k/")
+                    .with_type(DataType::Map),
+            )
+            .unwrap();
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].key, "k/map");
+        drop(pending);
+
+        let offline = Client::builder(get_test_collection_name!(), get_test_func_name!())
+            .build()
+            .unwrap();
+        assert!(
+            offline
+                .list_remote_datatypes(&RemoteDatatypeFilter::new())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    #[instrument]
+    fn can_resume_client_with_same_cuid() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let collection = get_test_collection_name!();
+        let client1 = Client::builder(collection.clone(), get_test_func_name!())
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let cuid = client1.get_cuid().to_owned();
+        let counter1 = client1
+            .create_datatype(get_test_func_name!())
+            .build_counter()
+            .unwrap();
+        counter1.increase().unwrap();
+
+        let builder = || {
+            Client::builder(collection.clone(), get_test_func_name!())
+                .with_connectivity(connectivity.clone())
+        };
+        assert_eq!(
+            builder().with_cuid("invalid").build().err(),
+            Some(ClientError::InvalidCuid(String::new()))
+        );
+        let Err(ClientError::DuplicateSession(msg)) = builder().with_cuid(&cuid).build() else {
+            panic!("a second session of the same cuid must be rejected");
+        };
+        assert!(msg.contains(&cuid) && !msg.contains("[ConnectivityError]"));
+        // a client of another cuid is not affected
+        assert_ne!(builder().build().unwrap().get_cuid(), cuid);
+
+        assert!(client1.close(Duration::from_secs(1)).is_clean());
+        let client2 = builder().with_cuid(&cuid).build().unwrap();
+        assert_eq!(client2.get_cuid(), cuid);
+        let counter2 = client2
+            .create_datatype(format!("{}-2", get_test_func_name!()))
+            .build_counter()
+            .unwrap();
+        counter2.increase().unwrap();
+        counter2.sync().unwrap();
+        let server = connectivity
+            .get_local_datatype_server(&counter2.get_attr().resource_id())
+            .unwrap();
+        assert_eq!(server.read().creator().to_string(), cuid);
+
+        // a dropped client keeps its session while its datatypes are alive
+        drop(client2);
+        assert!(matches!(
+            builder().with_cuid(&cuid).build(),
+            Err(ClientError::DuplicateSession(_))
+        ));
+        counter2.unsubscribe().unwrap();
+        counter2.sync().unwrap();
+        drop(counter2);
+        awaitility::at_most(Duration::from_secs(1))
+            .poll_interval(Duration::from_millis(1))
+            .until(|| builder().with_cuid(&cuid).build().is_ok());
+    }
 }
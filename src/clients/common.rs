@@ -1,6 +1,10 @@
 use std::{
     fmt::{Debug, Display, Formatter},
-    sync::{Arc, Weak},
+    sync::{
+        Arc, Weak,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
 };
 
 use parking_lot::RwLock;
@@ -9,8 +13,9 @@ use tokio::runtime::Handle;
 use crate::{
     clients::datatype_manager::DatatypeManager,
     connectivity::Connectivity,
+    datatypes::datatype_set::DatatypeSet,
     types::{common::ArcStr, uid::Cuid},
-    utils::runtime::{get_or_init_runtime_handle, reserve_to_shutdown_runtime},
+    utils::runtime::{get_or_init_runtime_handle, reserve_to_shutdown_runtime, shutdown_runtime},
 };
 
 pub struct ClientCommon {
@@ -20,26 +25,36 @@ pub struct ClientCommon {
     pub handle: Handle,
     pub connectivity: Arc<dyn Connectivity>,
     datatype_manager: RwLock<Weak<RwLock<DatatypeManager>>>,
+    is_session_closed: AtomicBool,
 }
 
 impl ClientCommon {
+    /// Creates the common part of a client of `cuid`, whose session on `connectivity` must
+    /// have been opened.
     pub fn new_arc(
         collection: ArcStr,
         alias: ArcStr,
+        cuid: Cuid,
         connectivity: Arc<dyn Connectivity>,
     ) -> Arc<Self> {
-        let cuid = Cuid::new();
-        let thread_name = format!("{collection}/{alias}/{cuid}");
         Arc::new(Self {
-            handle: get_or_init_runtime_handle(thread_name.as_str()),
+            handle: get_or_init_runtime_handle(&runtime_group(&collection, &alias, &cuid)),
             collection,
             alias,
             cuid,
             connectivity,
             datatype_manager: RwLock::new(Weak::new()),
+            is_session_closed: AtomicBool::new(false),
         })
     }
 
+    /// Closes the session of this client on its connectivity, once.
+    pub(crate) fn close_session(&self) {
+        if !self.is_session_closed.swap(true, Ordering::AcqRel) {
+            self.connectivity.close_session(&self.cuid);
+        }
+    }
+
     pub(crate) fn set_datatype_manager(&self, manager: Weak<RwLock<DatatypeManager>>) {
         *self.datatype_manager.write() = manager;
     }
@@ -50,6 +65,23 @@ impl ClientCommon {
         }
     }
 
+    /// Shuts the runtime of this client down, waiting up to `timeout` for its tasks.
+    pub(crate) fn shutdown_runtime(&self, timeout: Duration) {
+        shutdown_runtime(
+            &runtime_group(&self.collection, &self.alias, &self.cuid),
+            timeout,
+        );
+    }
+
+    /// Attaches `datatype` back to the client; returns `false` if the client is gone or has
+    /// another instance for `key`.
+    pub(crate) fn attach_datatype(&self, key: &str, datatype: DatatypeSet) -> bool {
+        self.datatype_manager
+            .read()
+            .upgrade()
+            .is_some_and(|manager| manager.write().attach(key, datatype))
+    }
+
     #[cfg(test)]
     pub fn new_for_test(mut paths: std::collections::VecDeque<String>) -> Arc<Self> {
         use crate::connectivity::null_connectivity::NullConnectivity;
@@ -57,7 +89,12 @@ impl ClientCommon {
         paths.pop_back();
         let alias = paths.pop_back().unwrap_or("collection".into()).into();
         let collection = paths.pop_back().unwrap_or("client".into()).into();
-        Self::new_arc(collection, alias, Arc::new(NullConnectivity::new()))
+        Self::new_arc(
+            collection,
+            alias,
+            Cuid::new(),
+            Arc::new(NullConnectivity::new()),
+        )
     }
 }
 
@@ -79,11 +116,17 @@ impl Debug for ClientCommon {
 
 impl Drop for ClientCommon {
     fn drop(&mut self) {
-        let thread_name = format!("{}/{}/{}", self.collection, self.alias, self.cuid);
-        reserve_to_shutdown_runtime(thread_name.as_str());
+        // dropped once no datatype of the client is alive, so that none syncs under the
+        // cuid of a later session
+        self.close_session();
+        reserve_to_shutdown_runtime(&runtime_group(&self.collection, &self.alias, &self.cuid));
     }
 }
 
+fn runtime_group(collection: &str, alias: &str, cuid: &Cuid) -> String {
+    format!("{collection}/{alias}/{cuid}")
+}
+
 #[cfg(test)]
 macro_rules! new_client_common {
     () => {{
@@ -28,6 +28,10 @@ impl DatatypeManager {
         self.datatypes.get(key).cloned()
     }
 
+    pub fn get_datatypes(&self) -> Vec<DatatypeSet> {
+        self.datatypes.values().cloned().collect()
+    }
+
     pub fn remove_if_same_instance(&mut self, key: &str, core_id: usize) -> Option<DatatypeSet> {
         if self
             .datatypes
@@ -40,6 +44,17 @@ impl DatatypeManager {
         }
     }
 
+    /// Attaches `datatype` back under `key`, unless another instance holds the key.
+    pub fn attach(&mut self, key: &str, datatype: DatatypeSet) -> bool {
+        match self.datatypes.entry(key.into()) {
+            Entry::Occupied(entry) => entry.get().get_core_id() == datatype.get_core_id(),
+            Entry::Vacant(entry) => {
+                entry.insert(datatype);
+                true
+            }
+        }
+    }
+
     pub fn subscribe_or_create_datatype(
         &mut self,
         key: &str,
@@ -70,7 +85,12 @@ impl DatatypeManager {
                     option,
                     is_readonly,
                     handlers,
-                );
+                )
+                .map_err(|e| {
+                    with_err_out!(ClientError::FailedToSubscribeOrCreateDatatype(format!(
+                        "{type:?} '{key}': {e}"
+                    )))
+                })?;
                 entry.insert(dt.clone());
                 Ok(dt)
             }
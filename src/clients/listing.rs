@@ -0,0 +1,67 @@
+use crate::{DataType, DatatypeState};
+
+/// A datatype attached to a [`Client`](crate::Client), as listed by
+/// [`Client::datatypes`](crate::Client::datatypes).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatatypeInfo {
+    pub key: String,
+    pub r#type: DataType,
+    pub state: DatatypeState,
+}
+
+/// A datatype existing on the server of a collection, as listed by
+/// [`Client::list_remote_datatypes`](crate::Client::list_remote_datatypes).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteDatatypeInfo {
+    pub key: String,
+    pub r#type: DataType,
+    /// The number of transactions the server has sequenced.
+    pub server_version: u64,
+    /// The number of clients subscribing to the datatype.
+    pub subscribers: usize,
+}
+
+/// Selects the datatypes [`Client::list_remote_datatypes`](crate::Client::list_remote_datatypes)
+/// returns.
+///
+/// An empty filter selects every datatype of the collection; each condition set narrows
+/// the selection.
+///
+/// # Examples
+///
+/// ```
+/// use qortoo::{DataType, RemoteDatatypeFilter};
+/// let filter = RemoteDatatypeFilter::new()
+///     .with_key_prefix("orders/")
+///     .with_type(DataType::Map);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RemoteDatatypeFilter {
+    key_prefix: Option<String>,
+    r#type: Option<DataType>,
+}
+
+impl RemoteDatatypeFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Selects the datatypes whose key starts with `prefix`.
+    pub fn with_key_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.key_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Selects the datatypes of `type`.
+    pub fn with_type(mut self, r#type: DataType) -> Self {
+        self.r#type = Some(r#type);
+        self
+    }
+
+    pub(crate) fn matches(&self, key: &str, r#type: DataType) -> bool {
+        self.key_prefix
+            .as_ref()
+            .is_none_or(|prefix| key.starts_with(prefix.as_str()))
+            && self.r#type.is_none_or(|t| t == r#type)
+    }
+}
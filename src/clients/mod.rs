@@ -1,3 +1,5 @@
 pub mod client;
 pub mod common;
 mod datatype_manager;
+pub mod listing;
+pub mod transaction;
@@ -0,0 +1,441 @@
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+use crate::{
+    DatatypeError,
+    clients::common::ClientCommon,
+    datatypes::transactional::{TransactionContext, TransactionalDatatype},
+    errors::BoxedError,
+    operations::transaction::TxGroup,
+    types::uid::Uid,
+    utils::defer_guard::DeferGuard,
+};
+
+thread_local! {
+    static CURRENT: RefCell<Option<Rc<Scope>>> = const { RefCell::new(None) };
+}
+
+/// A transaction of a [`Client`](crate::Client) spanning several of its datatypes.
+///
+/// Passed to the function of [`Client::transaction`](crate::Client::transaction).
+#[derive(Debug)]
+pub struct ClientTransaction {
+    tag: String,
+}
+
+impl ClientTransaction {
+    /// Returns the tag given to every transaction this one makes on its datatypes.
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+}
+
+/// The client transaction running on the current thread.
+struct Scope {
+    common: Arc<ClientCommon>,
+    tag: String,
+    tx_ctx: Arc<TransactionContext>,
+    enlisted: RefCell<Vec<Arc<TransactionalDatatype>>>,
+}
+
+impl Scope {
+    /// Checks that every enlisted datatype can commit its transaction, so that they are
+    /// committed all together or rolled back all together.
+    fn check_commit(&self) -> Result<(), DatatypeError> {
+        self.enlisted
+            .borrow()
+            .iter()
+            .try_for_each(|d| d.check_commit(Some(self.tag.clone())))
+    }
+
+    /// Ends the transaction of every enlisted datatype, marking them as a group when more
+    /// than one has something to commit.
+    fn finish(&self, committed: bool) {
+        let enlisted = self.enlisted.take();
+        let size = enlisted
+            .iter()
+            .filter(|d| d.has_pending_transaction())
+            .count();
+        let group = (committed && size > 1).then(|| TxGroup {
+            id: Uid::new(),
+            size,
+        });
+        for datatype in enlisted {
+            datatype.end_transaction(Some(self.tag.clone()), group.clone(), committed);
+        }
+    }
+}
+
+/// Runs `tx_func` as a transaction of the client of `common`.
+///
+/// Nested in another transaction of the client, `tx_func` joins it.
+pub(crate) fn run<F>(
+    common: &Arc<ClientCommon>,
+    tag: String,
+    tx_func: F,
+) -> Result<(), DatatypeError>
+where
+    F: FnOnce(&ClientTransaction) -> Result<(), BoxedError>,
+{
+    let handle = ClientTransaction { tag: tag.clone() };
+    let outer = CURRENT.with_borrow(|current| current.clone());
+    if outer.is_some_and(|scope| Arc::ptr_eq(&scope.common, common)) {
+        return tx_func(&handle).map_err(|e| DatatypeError::TransactionFailed(e.to_string()));
+    }
+    let scope = Rc::new(Scope {
+        common: common.clone(),
+        tx_ctx: Arc::new(TransactionContext::new(tag.clone())),
+        tag,
+        enlisted: RefCell::new(Vec::new()),
+    });
+    let mut outer = CURRENT.replace(Some(scope.clone()));
+    let mut guard = DeferGuard::new();
+    guard.add_defer_func(|committed| {
+        CURRENT.set(outer.take());
+        scope.finish(committed);
+    });
+    let result = tx_func(&handle)
+        .map_err(|e| DatatypeError::TransactionFailed(e.to_string()))
+        .and_then(|()| scope.check_commit());
+    if result.is_ok() {
+        guard.commit();
+    }
+    result
+}
+
+/// Enlists `datatype` into the transaction its client is running on the current thread,
+/// if any, and returns the context its operations must run with.
+///
+/// The datatype stays locked for the client transaction until it ends.
+pub(crate) fn enlist(datatype: &TransactionalDatatype) -> Option<Arc<TransactionContext>> {
+    let scope = CURRENT.with_borrow(|current| current.clone())?;
+    if !Arc::ptr_eq(&scope.common, &datatype.attr.client_common) {
+        return None;
+    }
+    let is_enlisted = scope
+        .enlisted
+        .borrow()
+        .iter()
+        .any(|d| std::ptr::eq(d.as_ref(), datatype));
+    if !is_enlisted {
+        let arc = datatype
+            .attr
+            .weak_transactional
+            .read()
+            .as_ref()?
+            .upgrade()?;
+        datatype.begin_shared_transaction(scope.tx_ctx.clone());
+        scope.enlisted.borrow_mut().push(arc);
+    }
+    Some(scope.tx_ctx.clone())
+}
+
+#[cfg(test)]
+mod tests_client_transaction {
+    use std::sync::Arc;
+
+    use tracing::instrument;
+
+    use crate::{
+        Client, Datatype, DatatypeError, LocalConnectivity,
+        connectivity::Connectivity,
+        datatypes::option::DatatypeOption,
+        types::uid::Cuid,
+        utils::test_utils::{get_test_collection_name, get_test_func_name},
+    };
+
+    #[test]
+    #[instrument]
+    fn can_apply_client_transaction_all_or_nothing() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let client1 = Client::builder(get_test_collection_name!(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(get_test_collection_name!(), "client2")
+            .with_connectivity(connectivity)
+            .build()
+            .unwrap();
+        let key_a = format!("{}-a", get_test_func_name!());
+        let key_b = format!("{}-b", get_test_func_name!());
+        let a1 = client1.create_datatype(&key_a).build_counter().unwrap();
+        let b1 = client1.create_datatype(&key_b).build_counter().unwrap();
+        a1.sync().unwrap();
+        b1.sync().unwrap();
+        let a2 = client2.subscribe_datatype(&key_a).build_counter().unwrap();
+        let b2 = client2.subscribe_datatype(&key_b).build_counter().unwrap();
+        a2.sync().unwrap();
+        b2.sync().unwrap();
+
+        client1
+            .transaction("move", |tx| {
+                assert_eq!(tx.tag(), "move");
+                a1.increase_by(-3)?;
+                b1.increase_by(3)?;
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!((a1.get_value(), b1.get_value()), (-3, 3));
+
+        // Until both transactions of the group reach the server, neither is applied.
+        a1.sync().unwrap();
+        a2.sync().unwrap();
+        b2.sync().unwrap();
+        assert_eq!((a2.get_value(), b2.get_value()), (0, 0));
+
+        b1.sync().unwrap();
+        a2.sync().unwrap();
+        b2.sync().unwrap();
+        assert_eq!((a2.get_value(), b2.get_value()), (-3, 3));
+        assert_eq!(a2.history()[0].tag.as_deref(), Some("move"));
+
+        // Later transactions of the client are not held behind the applied group.
+        a1.increase().unwrap();
+        a1.sync().unwrap();
+        a2.sync().unwrap();
+        assert_eq!(a2.get_value(), -2);
+        assert_eq!(a1.get_synced_client_version(), a1.get_client_version());
+    }
+
+    #[test]
+    #[instrument]
+    fn can_roll_back_every_datatype_of_client_transaction() {
+        let client = Client::builder(get_test_collection_name!(), get_test_func_name!())
+            .build()
+            .unwrap();
+        let counter = client
+            .create_datatype(format!("{}-counter", get_test_func_name!()))
+            .build_counter()
+            .unwrap();
+        let map = client
+            .create_datatype(format!("{}-map", get_test_func_name!()))
+            .build_map()
+            .unwrap();
+        counter.increase().unwrap();
+
+        let err = client
+            .transaction("failed", |_| {
+                counter.increase_by(10)?;
+                map.put("k", 1)?;
+                // A transaction of one datatype joins the client transaction.
+                counter.transaction("inner", |c| {
+                    c.increase_by(100)?;
+                    Ok(())
+                })?;
+                assert_eq!(counter.get_value(), 111);
+                Err("abort".into())
+            })
+            .unwrap_err();
+        assert!(matches!(err, DatatypeError::TransactionFailed(_)));
+        assert_eq!(counter.get_value(), 1);
+        assert_eq!(map.get("k"), None);
+        assert_eq!(counter.history().len(), 1);
+
+        client
+            .transaction("done", |_| {
+                counter.increase()?;
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(counter.get_value(), 2);
+        assert_eq!(counter.history()[1].tag.as_deref(), Some("done"));
+    }
+
+    #[test]
+    #[instrument]
+    fn can_roll_back_every_datatype_when_one_cannot_commit() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let client = Client::builder(get_test_collection_name!(), get_test_func_name!())
+            .with_connectivity(connectivity)
+            .build()
+            .unwrap();
+        let key_b = format!("{}-b", get_test_func_name!());
+        let a = client
+            .create_datatype(format!("{}-a", get_test_func_name!()))
+            .build_counter()
+            .unwrap();
+        let b = client.create_datatype(&key_b).build_counter().unwrap();
+        a.sync().unwrap();
+        b.sync().unwrap();
+
+        let err = client
+            .transaction("move", |_| {
+                a.increase_by(-3)?;
+                b.increase_by(3)?;
+                // Shrink the buffer limit of the second member so that it cannot enqueue
+                // (bypassing the clamp in DatatypeOption::new).
+                client
+                    .get_datatype(&key_b)
+                    .unwrap()
+                    .get_core()
+                    .mutable
+                    .write()
+                    .push_buffer
+                    .option = Arc::new(DatatypeOption {
+                    max_mem_size_of_push_buffer: 0,
+                    ..Default::default()
+                });
+                Ok(())
+            })
+            .unwrap_err();
+        assert_eq!(err, DatatypeError::PushBufferExceededMaxMemSize);
+        assert_eq!((a.get_value(), b.get_value()), (0, 0));
+        assert_eq!(a.get_client_version(), a.get_synced_client_version());
+        assert!(a.history().is_empty() && b.history().is_empty());
+
+        // The first member pushes nothing that would hold its later transactions back.
+        a.increase().unwrap();
+        a.sync().unwrap();
+        assert_eq!(a.get_synced_client_version(), a.get_client_version());
+    }
+
+    #[test]
+    #[instrument]
+    fn can_apply_group_when_member_unsubscribes() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let client1 = Client::builder(get_test_collection_name!(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(get_test_collection_name!(), "client2")
+            .with_connectivity(connectivity)
+            .build()
+            .unwrap();
+        let key_a = format!("{}-a", get_test_func_name!());
+        let key_b = format!("{}-b", get_test_func_name!());
+        let a1 = client1.create_datatype(&key_a).build_counter().unwrap();
+        let b1 = client1.create_datatype(&key_b).build_counter().unwrap();
+        a1.sync().unwrap();
+        b1.sync().unwrap();
+        let a2 = client2.subscribe_datatype(&key_a).build_counter().unwrap();
+        a2.sync().unwrap();
+
+        client1
+            .transaction("move", |_| {
+                a1.increase_by(3)?;
+                b1.increase_by(3)?;
+                Ok(())
+            })
+            .unwrap();
+        a1.sync().unwrap();
+        a2.sync().unwrap();
+        assert_eq!(a2.get_value(), 0);
+
+        // Once a member leaves, the group cannot complete and is applied as it arrives.
+        a1.unsubscribe().unwrap();
+        a1.sync().unwrap();
+        a2.sync().unwrap();
+        assert_eq!(a2.get_value(), 3);
+
+        b1.increase().unwrap();
+        b1.sync().unwrap();
+        b1.sync().unwrap();
+        assert_eq!(b1.get_synced_client_version(), b1.get_client_version());
+        let b2 = client2.subscribe_datatype(&key_b).build_counter().unwrap();
+        b2.sync().unwrap();
+        assert_eq!(b2.get_value(), 4);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_apply_group_when_member_resubscribes() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let client1 = Client::builder(get_test_collection_name!(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(get_test_collection_name!(), "client2")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let key_a = format!("{}-a", get_test_func_name!());
+        let key_b = format!("{}-b", get_test_func_name!());
+        let a1 = client1.create_datatype(&key_a).build_counter().unwrap();
+        let b1 = client1.create_datatype(&key_b).build_counter().unwrap();
+        a1.sync().unwrap();
+        b1.sync().unwrap();
+        let a2 = client2.subscribe_datatype(&key_a).build_counter().unwrap();
+        a2.sync().unwrap();
+
+        client1
+            .transaction("move", |_| {
+                a1.increase_by(3)?;
+                b1.increase_by(3)?;
+                Ok(())
+            })
+            .unwrap();
+        a1.sync().unwrap();
+
+        // The held transaction is executed again under the new cseqs of the resubscription.
+        let resource_id_a = format!("{}/{key_a}", get_test_collection_name!());
+        connectivity.remove_client_subscription(&resource_id_a, client1.get_cuid());
+        assert!(a1.sync().is_err());
+        a1.resubscribe().unwrap();
+        a1.sync().unwrap();
+        a1.sync().unwrap();
+        a2.sync().unwrap();
+        assert_eq!(a1.get_synced_client_version(), a1.get_client_version());
+        assert_eq!((a1.get_value(), a2.get_value()), (3, 3));
+        a1.increase().unwrap();
+        a1.sync().unwrap();
+        a2.sync().unwrap();
+        assert_eq!(a2.get_value(), 4);
+
+        // The group is given up, so the other member is applied as it arrives.
+        b1.sync().unwrap();
+        b1.sync().unwrap();
+        assert_eq!(b1.get_synced_client_version(), b1.get_client_version());
+        let b2 = client2.subscribe_datatype(&key_b).build_counter().unwrap();
+        b2.sync().unwrap();
+        assert_eq!(b2.get_value(), 3);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_apply_group_when_member_is_never_synced() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let client1 = Client::builder(get_test_collection_name!(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(get_test_collection_name!(), "client2")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let key_a = format!("{}-a", get_test_func_name!());
+        let key_b = format!("{}-b", get_test_func_name!());
+        let a1 = client1.create_datatype(&key_a).build_counter().unwrap();
+        let b1 = client1.create_datatype(&key_b).build_counter().unwrap();
+        a1.sync().unwrap();
+        b1.sync().unwrap();
+        let a2 = client2.subscribe_datatype(&key_a).build_counter().unwrap();
+        a2.sync().unwrap();
+
+        client1
+            .transaction("move", |_| {
+                a1.increase_by(3)?;
+                b1.increase_by(3)?;
+                Ok(())
+            })
+            .unwrap();
+        a1.sync().unwrap();
+        a2.sync().unwrap();
+        assert_eq!(a2.get_value(), 0);
+
+        // The client crashes before syncing the other member, and the backend closes its
+        // session; the group cannot complete and is applied as it arrived.
+        connectivity.close_session(&Cuid::try_from(client1.get_cuid()).unwrap());
+        a2.sync().unwrap();
+        assert_eq!(a2.get_value(), 3);
+
+        // Later transactions of the client are not held behind the given-up group.
+        a1.increase().unwrap();
+        a1.sync().unwrap();
+        a2.sync().unwrap();
+        assert_eq!(a2.get_value(), 4);
+    }
+}
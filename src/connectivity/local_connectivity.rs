@@ -1,10 +1,11 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fmt::{Debug, Formatter},
     sync::{
         Arc,
         atomic::{AtomicBool, Ordering},
     },
+    time::{Duration, Instant},
 };
 
 use crossbeam_channel::Sender;
@@ -12,10 +13,16 @@ use parking_lot::RwLock;
 
 use crate::{
     DatatypeState,
+    clients::listing::{RemoteDatatypeFilter, RemoteDatatypeInfo},
     connectivity::{Connectivity, local_datatype_server::LocalDatatypeServer},
     datatypes::{event_loop::Event, wired::WiredDatatype},
     errors::{connectivity::ConnectivityError, push_pull::PushPullError},
-    types::{common::ResourceID, push_pull_pack::PushPullPack},
+    operations::transaction::TxGroup,
+    types::{
+        common::ResourceID,
+        push_pull_pack::PushPullPack,
+        uid::{Cuid, Uid},
+    },
 };
 
 /// An in-memory connectivity backend for local testing and development.
@@ -70,6 +77,15 @@ use crate::{
 #[allow(dead_code)]
 pub struct LocalConnectivity {
     datatype_servers: RwLock<HashMap<ResourceID, Arc<RwLock<LocalDatatypeServer>>>>,
+    // the datatypes on which each incomplete transaction group has arrived
+    tx_groups: RwLock<HashMap<Uid, Vec<ResourceID>>>,
+    // groups that can no longer complete, applied on whichever datatype they arrive
+    abandoned_groups: RwLock<HashSet<Uid>>,
+    // deleted datatypes, until when they are kept; `None` keeps them forever
+    tombstones: RwLock<HashMap<ResourceID, Option<Instant>>>,
+    tombstone_period: RwLock<Option<Duration>>,
+    // the clients whose session is open
+    sessions: RwLock<HashSet<Cuid>>,
     is_realtime: AtomicBool,
 }
 
@@ -89,6 +105,11 @@ impl LocalConnectivity {
     pub fn new_arc() -> Arc<Self> {
         Arc::new(Self {
             datatype_servers: RwLock::new(HashMap::new()),
+            tx_groups: RwLock::new(HashMap::new()),
+            abandoned_groups: RwLock::new(HashSet::new()),
+            tombstones: RwLock::new(HashMap::new()),
+            tombstone_period: RwLock::new(None),
+            sessions: RwLock::new(HashSet::new()),
             is_realtime: AtomicBool::new(true),
         })
     }
@@ -125,25 +146,105 @@ impl LocalConnectivity {
         self.is_realtime.store(tf, Ordering::Relaxed);
     }
 
+    /// Sets how long a deleted datatype is kept as a tombstone.
+    ///
+    /// While the tombstone is kept, pushes to the datatype, including attempts to subscribe
+    /// or create it again, are rejected with
+    /// [`ServerRejectReason::Deleted`](crate::ServerRejectReason::Deleted). Afterwards, its
+    /// key is free as if it never existed. With `None`, the default, tombstones are kept
+    /// forever. The period applies to datatypes deleted after this call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use qortoo::LocalConnectivity;
+    ///
+    /// let connectivity = LocalConnectivity::new_arc();
+    /// connectivity.set_tombstone_period(Some(Duration::from_secs(60)));
+    /// ```
+    pub fn set_tombstone_period(&self, period: Option<Duration>) {
+        *self.tombstone_period.write() = period;
+    }
+
+    /// Returns whether the datatype of `resource_id` has been deleted and is still kept as
+    /// a tombstone, dropping the tombstone once its period is over.
+    fn is_tombstoned(&self, resource_id: &str) -> bool {
+        let until = match self.tombstones.read().get(resource_id) {
+            None => return false,
+            Some(until) => *until,
+        };
+        if until.is_none_or(|until| Instant::now() < until) {
+            return true;
+        }
+        self.tombstones.write().remove(resource_id);
+        false
+    }
+
+    fn reject_deleted(pushed: &PushPullPack, resource_id: ResourceID) -> PushPullPack {
+        let mut pulled = pushed.get_pulled_stub();
+        pulled.error = Some(PushPullError::Deleted(resource_id));
+        pulled.state = DatatypeState::Disabled;
+        pulled
+    }
+
+    /// Records that `group` has arrived on the datatype of `resource_id`, and applies it on
+    /// every datatype once it has arrived on all of them.
+    fn arrive_group(&self, group: TxGroup, resource_id: &ResourceID) {
+        if self.abandoned_groups.read().contains(&group.id) {
+            if let Some(server) = self.get_local_datatype_server(resource_id) {
+                server.write().complete_group(&group.id, self.is_realtime());
+            }
+            return;
+        }
+        let members = {
+            let mut tx_groups = self.tx_groups.write();
+            let members = tx_groups.entry(group.id.clone()).or_default();
+            members.push(resource_id.clone());
+            if members.len() < group.size {
+                return;
+            }
+            tx_groups.remove(&group.id).unwrap_or_default()
+        };
+        for member in members {
+            if let Some(server) = self.get_local_datatype_server(&member) {
+                server.write().complete_group(&group.id, self.is_realtime());
+            }
+        }
+    }
+
+    /// Gives up on `id`, which was abandoned on the datatype of `resource_id`, and applies it
+    /// on the other datatypes it has arrived on or will arrive on.
+    fn abandon_group(&self, id: Uid, resource_id: &ResourceID) {
+        let members = self.tx_groups.write().remove(&id).unwrap_or_default();
+        self.abandoned_groups.write().insert(id.clone());
+        for member in members.iter().filter(|member| *member != resource_id) {
+            if let Some(server) = self.get_local_datatype_server(member) {
+                server.write().complete_group(&id, self.is_realtime());
+            }
+        }
+    }
+
     #[cfg(test)]
     pub fn get_wired_interceptor(
         &self,
         resource_id: &ResourceID,
-        cuid: &crate::types::uid::Cuid,
+        cuid: &str,
     ) -> Option<Arc<crate::datatypes::wired_interceptor::WiredInterceptor>> {
         let server = self.get_local_datatype_server(resource_id)?;
-        let wired_datatype = server.read().get_wired_datatype(cuid)?;
+        let wired_datatype = server
+            .read()
+            .get_wired_datatype(&Cuid::try_from(cuid).ok()?)?;
         Some(wired_datatype.get_wired_interceptor())
     }
 
     #[cfg(test)]
-    pub fn remove_client_subscription(
-        &self,
-        resource_id: &ResourceID,
-        cuid: &crate::types::uid::Cuid,
-    ) {
-        if let Some(server) = self.get_local_datatype_server(resource_id) {
-            server.write().remove_client_subscription(cuid);
+    pub fn remove_client_subscription(&self, resource_id: &ResourceID, cuid: &str) {
+        if let (Some(server), Ok(cuid)) = (
+            self.get_local_datatype_server(resource_id),
+            Cuid::try_from(cuid),
+        ) {
+            server.write().remove_client_subscription(&cuid);
         }
     }
 }
@@ -181,14 +282,21 @@ impl Connectivity for LocalConnectivity {
     fn push_pull(&self, pushed: &PushPullPack) -> Result<PushPullPack, ConnectivityError> {
         let resource_id = pushed.resource_id();
 
+        if self.is_tombstoned(&resource_id) {
+            return Ok(Self::reject_deleted(pushed, resource_id));
+        }
         let Some(server_with_lock) = self.get_local_datatype_server(&resource_id) else {
             let mut pulled = pushed.get_pulled_stub();
             pulled.error = Some(PushPullError::ResourceNotFound(resource_id.clone()));
             pulled.state = DatatypeState::Disabled;
             return Ok(pulled);
         };
-        let (pulled, should_remove_server) = {
+        let (pulled, should_remove_server, arrived_groups, abandoned_groups) = {
             let mut server = server_with_lock.write();
+            // the server of a deleted datatype can be reached until its tombstone is recorded.
+            if server.is_deleted() {
+                return Ok(Self::reject_deleted(pushed, resource_id));
+            }
             let pulled = match pushed.state {
                 DatatypeState::Creating => server.process_creating(pushed)?,
                 DatatypeState::Subscribing => server.process_subscribing(pushed)?,
@@ -201,17 +309,32 @@ impl Connectivity for LocalConnectivity {
                 DatatypeState::Unsubscribing => {
                     server.process_unsubscribing(pushed, self.is_realtime())?
                 }
-                DatatypeState::Deleting => server.process_deleting(pushed)?,
+                DatatypeState::Deleting => server.process_deleting(pushed, self.is_realtime())?,
                 DatatypeState::Disabled => server.process_disabled(pushed)?,
             };
             (
                 pulled,
-                pushed.state == DatatypeState::Unsubscribing && server.is_empty(),
+                (pushed.state == DatatypeState::Unsubscribing && server.is_empty())
+                    || server.is_deleted(),
+                server.take_arrived_groups(),
+                server.take_abandoned_groups(),
             )
         };
+        for group in arrived_groups {
+            self.arrive_group(group, &resource_id);
+        }
+        for id in abandoned_groups {
+            self.abandon_group(id, &resource_id);
+        }
 
         if should_remove_server {
             let mut datatypes = self.datatype_servers.write();
+            // the tombstone is recorded before the server is removed, so that the key cannot
+            // be created again in between.
+            if server_with_lock.read().is_deleted() {
+                let until = self.tombstone_period.read().map(|p| Instant::now() + p);
+                self.tombstones.write().insert(resource_id.clone(), until);
+            }
             if datatypes
                 .get(&resource_id)
                 .is_some_and(|server| Arc::ptr_eq(server, &server_with_lock))
@@ -223,9 +346,73 @@ impl Connectivity for LocalConnectivity {
         Ok(pulled)
     }
 
+    /// Push-pulls the packs one by one, then again those of subscribed datatypes carrying
+    /// grouped transactions, so that a group whose members are all in the batch is applied
+    /// and acknowledged within it.
+    fn push_pull_batch(
+        &self,
+        ppps: &[PushPullPack],
+    ) -> Vec<Result<PushPullPack, ConnectivityError>> {
+        let mut results: Vec<_> = ppps.iter().map(|ppp| self.push_pull(ppp)).collect();
+        for (ppp, result) in ppps.iter().zip(results.iter_mut()) {
+            let is_grouped = ppp.transactions.iter().any(|tx| tx.group.is_some());
+            let is_accepted = result.as_ref().is_ok_and(|pulled| pulled.error.is_none());
+            if is_grouped && is_accepted && ppp.state == DatatypeState::Subscribed {
+                // pushing again is idempotent: the transactions already pushed are skipped.
+                *result = self.push_pull(ppp);
+            }
+        }
+        results
+    }
+
     fn is_realtime(&self) -> bool {
         self.is_realtime.load(Ordering::Relaxed)
     }
+
+    fn list_datatypes(
+        &self,
+        collection: &str,
+        filter: &RemoteDatatypeFilter,
+    ) -> Result<Vec<RemoteDatatypeInfo>, ConnectivityError> {
+        let servers: Vec<_> = self.datatype_servers.read().values().cloned().collect();
+        let mut listed: Vec<_> = servers
+            .iter()
+            .filter_map(|server| server.read().describe(collection, filter))
+            .collect();
+        listed.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(listed)
+    }
+
+    fn open_session(&self, cuid: &Cuid) -> Result<(), ConnectivityError> {
+        if !self.sessions.write().insert(cuid.clone()) {
+            return Err(ConnectivityError::SessionConflict(format!(
+                "client '{cuid}' already has an open session"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Closes the session of `cuid` and gives up on its incomplete groups, whose missing
+    /// members may never arrive, so that they no longer hold back its transactions.
+    fn close_session(&self, cuid: &Cuid) {
+        self.sessions.write().remove(cuid);
+        let servers: Vec<_> = self
+            .datatype_servers
+            .read()
+            .iter()
+            .map(|(resource_id, server)| (resource_id.clone(), server.clone()))
+            .collect();
+        for (resource_id, server) in servers {
+            let abandoned = {
+                let mut server = server.write();
+                server.abandon_groups_of(cuid, self.is_realtime());
+                server.take_abandoned_groups()
+            };
+            for id in abandoned {
+                self.abandon_group(id, &resource_id);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -235,7 +422,7 @@ mod tests_local_connectivity {
     use tracing::instrument;
 
     use crate::{
-        Client, Datatype, DatatypeState,
+        Client, Datatype, DatatypeError, DatatypeState, ServerRejectReason,
         connectivity::local_connectivity::LocalConnectivity,
         utils::test_utils::{get_test_collection_name, get_test_func_name, get_test_ids},
     };
@@ -382,7 +569,7 @@ mod tests_local_connectivity {
         let server = connectivity
             .get_local_datatype_server(&resource_id)
             .unwrap();
-        assert_eq!(server.read().creator(), &client2.get_cuid());
+        assert_eq!(server.read().creator().to_string(), client2.get_cuid());
 
         counter2.increase_by(5).unwrap();
         counter2.sync().unwrap();
@@ -427,4 +614,169 @@ mod tests_local_connectivity {
         assert_eq!(counter2.get_value(), 0);
         assert_eq!(counter2.get_state(), DatatypeState::Subscribed);
     }
+
+    #[test]
+    #[instrument]
+    fn can_delete_datatype_for_every_subscriber() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let (collection, key, resource_id) = get_test_ids!();
+        let client1 = Client::builder(collection.clone(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(collection.clone(), "client2")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client3 = Client::builder(collection, "client3")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+
+        let counter1 = client1
+            .create_datatype(key.clone())
+            .build_counter()
+            .unwrap();
+        counter1.sync().unwrap();
+        let counter2 = client2
+            .subscribe_datatype(key.clone())
+            .build_counter()
+            .unwrap();
+        counter2.sync().unwrap();
+
+        counter1.increase_by(3).unwrap();
+        counter1.delete().unwrap();
+        assert_eq!(counter1.get_state(), DatatypeState::Deleting);
+        assert!(counter1.increase().is_err());
+        counter1.sync().unwrap();
+        assert_eq!(counter1.get_state(), DatatypeState::Disabled);
+        assert!(
+            connectivity
+                .get_local_datatype_server(&resource_id)
+                .is_none()
+        );
+
+        let err = counter2.sync().unwrap_err();
+        assert!(matches!(
+            err,
+            DatatypeError::ServerRejected(ServerRejectReason::Deleted(_))
+        ));
+        assert_eq!(counter2.get_state(), DatatypeState::Disabled);
+
+        let counter3 = client3
+            .subscribe_or_create_datatype(key)
+            .build_counter()
+            .unwrap();
+        assert!(counter3.sync().is_err());
+        assert_eq!(counter3.get_state(), DatatypeState::Disabled);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_release_groups_held_by_deleted_datatype() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let (collection, key, resource_id) = get_test_ids!();
+        let client1 = Client::builder(collection.clone(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(collection, "client2")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let key_b = format!("{key}-b");
+        let a1 = client1.create_datatype(&key).build_counter().unwrap();
+        let b1 = client1.create_datatype(&key_b).build_counter().unwrap();
+        a1.sync().unwrap();
+        b1.sync().unwrap();
+
+        client1
+            .transaction("move", |_| {
+                a1.increase_by(-3)?;
+                b1.increase_by(3)?;
+                Ok(())
+            })
+            .unwrap();
+        a1.sync().unwrap();
+        assert_eq!(connectivity.tx_groups.read().len(), 1);
+        a1.delete().unwrap();
+        a1.sync().unwrap();
+        assert!(connectivity.is_tombstoned(&resource_id));
+        assert!(connectivity.tx_groups.read().is_empty());
+
+        // the other member is applied as it arrives, without holding later transactions
+        b1.sync().unwrap();
+        b1.increase().unwrap();
+        b1.sync().unwrap();
+        b1.sync().unwrap();
+        assert_eq!(b1.get_synced_client_version(), b1.get_client_version());
+        let b2 = client2.subscribe_datatype(&key_b).build_counter().unwrap();
+        b2.sync().unwrap();
+        assert_eq!(b2.get_value(), 4);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_notify_deletion_in_realtime() {
+        let connectivity = LocalConnectivity::new_arc();
+        let (collection, key, _) = get_test_ids!();
+        let client1 = Client::builder(collection.clone(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(collection, "client2")
+            .with_connectivity(connectivity)
+            .build()
+            .unwrap();
+
+        let counter1 = client1
+            .create_datatype(key.clone())
+            .build_counter()
+            .unwrap();
+        counter1.sync().unwrap();
+        let counter2 = client2.subscribe_datatype(key).build_counter().unwrap();
+        counter2.sync().unwrap();
+
+        counter1.delete().unwrap();
+        awaitility::at_most(Duration::from_secs(1))
+            .poll_interval(Duration::from_millis(10))
+            .until(|| counter2.get_state() == DatatypeState::Disabled);
+        assert_eq!(counter1.get_state(), DatatypeState::Disabled);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_reuse_key_after_tombstone_period() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        connectivity.set_tombstone_period(Some(Duration::from_millis(50)));
+        let client = Client::builder(get_test_collection_name!(), get_test_func_name!())
+            .with_connectivity(connectivity)
+            .build()
+            .unwrap();
+
+        let counter = client
+            .create_datatype(get_test_func_name!())
+            .build_counter()
+            .unwrap();
+        counter.sync().unwrap();
+        counter.delete().unwrap();
+        counter.sync().unwrap();
+
+        let recreated = client
+            .create_datatype(get_test_func_name!())
+            .build_counter()
+            .unwrap();
+        assert!(recreated.sync().is_err());
+
+        std::thread::sleep(Duration::from_millis(60));
+        let recreated = client
+            .create_datatype(get_test_func_name!())
+            .build_counter()
+            .unwrap();
+        recreated.sync().unwrap();
+        assert_eq!(recreated.get_state(), DatatypeState::Subscribed);
+    }
 }
@@ -1,19 +1,24 @@
-use std::{collections::HashMap, fmt::Display, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Display,
+    sync::Arc,
+};
 
 use crossbeam_channel::Sender;
 use tracing::{instrument, trace};
 
 use crate::{
     DataType, DatatypeState,
+    clients::listing::{RemoteDatatypeFilter, RemoteDatatypeInfo},
     datatypes::{common::Attribute, event_loop::Event, wired::WiredDatatype},
     errors::{connectivity::ConnectivityError, push_pull::PushPullError},
-    operations::transaction::Transaction,
+    operations::transaction::{Transaction, TxGroup},
     types::{
         checkpoint::CheckPoint,
         common::ArcStr,
         notification::Notification,
         push_pull_pack::PushPullPack,
-        uid::{Cuid, Duid},
+        uid::{Cuid, Duid, Uid},
     },
 };
 
@@ -41,10 +46,17 @@ pub struct LocalDatatypeServer {
     r#type: DataType,
     duid: Duid,
     created: bool,
+    deleted: bool,
     creator: Cuid,
     sseq: u64,
     cseq_map: HashMap<Cuid, CheckPoint>,
     history: Vec<Arc<Transaction>>,
+    // transactions held back until their groups are complete, in the order they arrived
+    held: VecDeque<Arc<Transaction>>,
+    arrived_groups: Vec<TxGroup>,
+    complete_groups: HashSet<Uid>,
+    // groups given up on since the last take, which the other datatypes must not wait for
+    abandoned_groups: Vec<Uid>,
 }
 
 impl Display for LocalDatatypeServer {
@@ -66,12 +78,17 @@ impl LocalDatatypeServer {
             wired_map: HashMap::new(),
             sender_map: HashMap::new(),
             created: false,
+            deleted: false,
             // creator is temporarily assigned; it should be reassigned when this datatype is created
             creator: attr.get_cuid(),
             collection: attr.client_common.collection.clone(),
             sseq: 0,
             cseq_map: HashMap::new(),
             history: Vec::new(),
+            held: VecDeque::new(),
+            arrived_groups: Vec::new(),
+            complete_groups: HashSet::new(),
+            abandoned_groups: Vec::new(),
             key: attr.key.clone(),
             r#type: attr.r#type,
             duid: attr.get_duid(),
@@ -87,6 +104,25 @@ impl LocalDatatypeServer {
         self.wired_map.is_empty()
     }
 
+    pub fn is_deleted(&self) -> bool {
+        self.deleted
+    }
+
+    /// Describes this datatype if it exists in `collection` and `filter` selects it.
+    pub fn describe(
+        &self,
+        collection: &str,
+        filter: &RemoteDatatypeFilter,
+    ) -> Option<RemoteDatatypeInfo> {
+        let exists = self.created && !self.deleted && &*self.collection == collection;
+        (exists && filter.matches(&self.key, self.r#type)).then(|| RemoteDatatypeInfo {
+            key: self.key.to_string(),
+            r#type: self.r#type,
+            server_version: self.sseq,
+            subscribers: self.wired_map.len(),
+        })
+    }
+
     #[cfg(test)]
     pub fn remove_client_subscription(&mut self, cuid: &Cuid) {
         self.wired_map.remove(cuid);
@@ -94,27 +130,125 @@ impl LocalDatatypeServer {
     }
 
     fn push_transactions(&mut self, pushed: &PushPullPack) -> (u64, bool) {
-        let client_cp = self
-            .cseq_map
-            .entry(pushed.cuid.clone())
-            .or_insert(CheckPoint::new(0, 0));
         let mut pushed_any = false;
 
         for tx in pushed.transactions.iter() {
-            if tx.cseq <= client_cp.cseq {
+            let acked_cseq = self.cseq_map.get(&tx.cuid).map_or(0, |cp| cp.cseq);
+            let is_held = |h: &Arc<Transaction>| h.cuid == tx.cuid && h.cseq == tx.cseq;
+            if tx.cseq <= acked_cseq || self.held.iter().any(is_held) {
+                continue;
+            }
+            // a grouped transaction is held until its group is complete,
+            // and so are the following ones of the same client to keep their order.
+            if let Some(group) = &tx.group {
+                self.arrived_groups.push(group.clone());
+                self.held.push_back(tx.clone());
+                continue;
+            }
+            if self.held.iter().any(|h| h.cuid == tx.cuid) {
+                self.held.push_back(tx.clone());
                 continue;
             }
+            self.append(tx);
             pushed_any = true;
-            self.sseq += 1;
-            let mut owned_tx = (**tx).clone();
-            owned_tx.sseq = self.sseq;
-            self.history.push(Arc::new(owned_tx));
-            client_cp.cseq = tx.cseq;
         }
+        let client_cp = self
+            .cseq_map
+            .entry(pushed.cuid.clone())
+            .or_insert(CheckPoint::new(0, 0));
         client_cp.sseq = self.sseq;
         (client_cp.cseq, pushed_any)
     }
 
+    fn append(&mut self, tx: &Transaction) {
+        self.sseq += 1;
+        let mut owned_tx = tx.clone();
+        owned_tx.sseq = self.sseq;
+        self.history.push(Arc::new(owned_tx));
+        self.cseq_map
+            .entry(tx.cuid.clone())
+            .or_insert(CheckPoint::new(0, 0))
+            .cseq = tx.cseq;
+    }
+
+    /// Takes the groups whose first transaction on this datatype arrived since the last call.
+    pub fn take_arrived_groups(&mut self) -> Vec<TxGroup> {
+        std::mem::take(&mut self.arrived_groups)
+    }
+
+    /// Takes the groups given up on since the last call.
+    pub fn take_abandoned_groups(&mut self) -> Vec<Uid> {
+        std::mem::take(&mut self.abandoned_groups)
+    }
+
+    /// Gives up on the groups of the held transactions of `cuid`, which leaves this
+    /// datatype or whose session is closed, so that they are applied without waiting for
+    /// their other members.
+    pub fn abandon_groups_of(&mut self, cuid: &Cuid, is_realtime: bool) {
+        let abandoned = self.held_groups(Some(cuid));
+        for id in &abandoned {
+            self.complete_group(id, is_realtime);
+        }
+        self.abandoned_groups.extend(abandoned);
+    }
+
+    /// Drops the held transactions of `cuid`, or of every client, and gives up on their
+    /// groups.
+    fn drop_held(&mut self, cuid: Option<&Cuid>) {
+        let abandoned = self.held_groups(cuid);
+        self.held
+            .retain(|tx| cuid.is_some_and(|cuid| tx.cuid != *cuid));
+        self.arrived_groups
+            .retain(|group| !abandoned.contains(&group.id));
+        self.complete_groups
+            .retain(|id| cuid.is_some() && !abandoned.contains(id));
+        self.abandoned_groups.extend(abandoned);
+    }
+
+    /// Returns the groups of the held transactions of `cuid`, or of every client.
+    fn held_groups(&self, cuid: Option<&Cuid>) -> Vec<Uid> {
+        let mut groups: Vec<Uid> = Vec::new();
+        for tx in self.held.iter() {
+            if let Some(group) = &tx.group
+                && cuid.is_none_or(|cuid| tx.cuid == *cuid)
+                && !groups.contains(&group.id)
+            {
+                groups.push(group.id.clone());
+            }
+        }
+        groups
+    }
+
+    /// Marks the group as complete and applies the held transactions it no longer blocks.
+    pub fn complete_group(&mut self, id: &Uid, is_realtime: bool) {
+        self.complete_groups.insert(id.clone());
+        let mut blocked = HashSet::new();
+        let mut released = Vec::new();
+        for tx in std::mem::take(&mut self.held) {
+            let is_ready = tx
+                .group
+                .as_ref()
+                .is_none_or(|g| self.complete_groups.contains(&g.id));
+            if !is_ready || blocked.contains(&tx.cuid) {
+                blocked.insert(tx.cuid.clone());
+                self.held.push_back(tx);
+                continue;
+            }
+            if let Some(group) = &tx.group {
+                self.complete_groups.remove(&group.id);
+            }
+            self.append(&tx);
+            if !released.contains(&tx.cuid) {
+                released.push(tx.cuid.clone());
+            }
+        }
+        if is_realtime {
+            for cuid in &released {
+                self.notify_pushed(cuid);
+            }
+        }
+    }
+
     datatype_server_instrument! {
     pub fn process_creating(
         &mut self,
@@ -151,7 +285,9 @@ impl LocalDatatypeServer {
         pushed: &PushPullPack,
     ) -> Result<PushPullPack, ConnectivityError> {
         if self.created {
-            self.process_subscribing(pushed)
+            // the transactions pushed to create the datatype are not acknowledged;
+            // the client executes them again on top of the snapshot.
+            self.subscribe(pushed, true)
         } else {
             self.process_creating(pushed)
         }
@@ -245,6 +381,7 @@ impl LocalDatatypeServer {
         }
 
         let pulled = self.process_client_push(pushed, DatatypeState::Disabled, is_realtime);
+        self.abandon_groups_of(&pushed.cuid, is_realtime);
 
         // Always clean up client subscription regardless of error: the client will be Disabled
         // either way, and leaving stale entries would cause infinite unsubscribe retry loops.
@@ -263,8 +400,31 @@ impl LocalDatatypeServer {
     pub fn process_deleting(
         &mut self,
         pushed: &PushPullPack,
+        is_realtime: bool,
     ) -> Result<PushPullPack, ConnectivityError> {
-        let pulled = pushed.get_pulled_stub();
+        if !self.wired_map.contains_key(&pushed.cuid) {
+            let mut pulled = pushed.get_pulled_stub();
+            pulled.error = Some(PushPullError::MissingSubscription(format!(
+                "cuid '{}' has no active datatype subscription on this server",
+                pushed.cuid
+            )));
+            pulled.state = DatatypeState::Disabled;
+            return Ok(pulled);
+        }
+        // the transactions pushed along are applied, so that subscribers pulling before
+        // learning about the deletion see the final state.
+        let pulled = self.process_client_push(pushed, DatatypeState::Disabled, false);
+        if pulled.error.is_some() {
+            return Ok(pulled);
+        }
+        self.deleted = true;
+        self.drop_held(None);
+        // the deletion advances the server version, so that notified subscribers push-pull
+        // and learn about it.
+        self.sseq += 1;
+        if is_realtime {
+            self.notify_pushed(&pushed.cuid);
+        }
         Ok(pulled)
     }}
 
@@ -285,6 +445,14 @@ impl LocalDatatypeServer {
     pub fn process_subscribing(
         &mut self,
         pushed: &PushPullPack,
+    ) -> Result<PushPullPack, ConnectivityError> {
+        self.subscribe(pushed, false)
+    }}
+
+    fn subscribe(
+        &mut self,
+        pushed: &PushPullPack,
+        ignores_transactions: bool,
     ) -> Result<PushPullPack, ConnectivityError> {
         let mut pulled = pushed.get_pulled_stub();
         if !self.created {
@@ -306,7 +474,7 @@ impl LocalDatatypeServer {
             pulled.state = DatatypeState::Disabled;
             return Ok(pulled);
         }
-        if !pushed.transactions.is_empty() {
+        if !ignores_transactions && !pushed.transactions.is_empty() {
             pulled.error = Some(PushPullError::ProtocolViolation(
                 "cannot push transactions when subscribing".to_string(),
             ));
@@ -328,10 +496,22 @@ impl LocalDatatypeServer {
         let tx = wired_of_creator.get_subscribe_snapshot();
         pulled.checkpoint.sseq = tx.sseq;
         pulled.snapshot_transaction = Some(Arc::new(tx));
-        self.pull_transactions(&mut pulled);
+        // A subscribing client starts over from the snapshot, so it pulls its own
+        // transactions too, and numbers its next ones anew.
+        for tx in &self.history {
+            if tx.sseq > pulled.checkpoint.sseq {
+                pulled.transactions.push(tx.clone());
+            }
+        }
+        pulled.checkpoint.sseq = self.sseq;
+        // the held transactions are executed again by the client under the new cseqs,
+        // so they must neither be applied nor block those.
+        self.drop_held(Some(&pushed.cuid));
+        self.cseq_map
+            .insert(pushed.cuid.clone(), CheckPoint::new(self.sseq, 0));
         pulled.state = DatatypeState::Subscribed;
         Ok(pulled)
-    }}
+    }
 
     fn get_creator_wired_datatype(&self) -> Option<Arc<WiredDatatype>> {
         self.wired_map.get(&self.creator).cloned()
@@ -461,7 +641,7 @@ mod tests_local_datatype_server {
             .get_local_datatype_server(&resource_id)
             .unwrap();
         let wired_interceptor1 = connectivity
-            .get_wired_interceptor(&resource_id, &client1.get_cuid())
+            .get_wired_interceptor(&resource_id, client1.get_cuid())
             .unwrap();
 
         if pre_create {
@@ -570,7 +750,7 @@ mod tests_local_datatype_server {
             .build_counter()
             .unwrap();
         let interceptor2 = connectivity
-            .get_wired_interceptor(&resource_id, &client2.get_cuid())
+            .get_wired_interceptor(&resource_id, client2.get_cuid())
             .unwrap();
 
         let expected_error = Arc::new(expected_error);
@@ -624,7 +804,7 @@ mod tests_local_datatype_server {
         counter.unsubscribe().unwrap();
 
         let interceptor = connectivity
-            .get_wired_interceptor(&resource_id, &client.get_cuid())
+            .get_wired_interceptor(&resource_id, client.get_cuid())
             .unwrap();
         interceptor
             .set_before_push(push_set_readonly)
@@ -719,7 +899,7 @@ mod tests_local_datatype_server {
             assert!(counter1.sync().is_ok());
 
             let interceptor2 = connectivity
-                .get_wired_interceptor(&resource_id, &client2.get_cuid())
+                .get_wired_interceptor(&resource_id, client2.get_cuid())
                 .unwrap();
             let expected_error = Arc::new(expected_error);
             let expected_error2 = expected_error.clone();
@@ -743,7 +923,7 @@ mod tests_local_datatype_server {
                 counter1.increase_by(i).unwrap();
             }
             let interceptor1 = connectivity
-                .get_wired_interceptor(&resource_id, &client1.get_cuid())
+                .get_wired_interceptor(&resource_id, client1.get_cuid())
                 .unwrap();
             let expected_error = Arc::new(expected_error);
             let expected_error2 = expected_error.clone();
@@ -803,7 +983,7 @@ mod tests_local_datatype_server {
             .unwrap();
 
         let interceptor1 = connectivity
-            .get_wired_interceptor(&resource_id, &client1.get_cuid())
+            .get_wired_interceptor(&resource_id, client1.get_cuid())
             .unwrap();
         interceptor1
             .set_before_push(|push| {
@@ -822,7 +1002,7 @@ mod tests_local_datatype_server {
             .unwrap();
 
         let interceptor2 = connectivity
-            .get_wired_interceptor(&resource_id, &client2.get_cuid())
+            .get_wired_interceptor(&resource_id, client2.get_cuid())
             .unwrap();
         interceptor2
             .set_before_push(|push| {
@@ -916,7 +1096,7 @@ mod tests_local_datatype_server {
         assert_eq!(counter1.get_state(), DatatypeState::Subscribed);
 
         // Simulate the server losing the client's subscription (e.g., after a server restart).
-        connectivity.remove_client_subscription(&resource_id, &client1.get_cuid());
+        connectivity.remove_client_subscription(&resource_id, client1.get_cuid());
 
         // The next sync must fail with ServerRejected and disable the datatype.
         let result = counter1.sync();
@@ -929,6 +1109,67 @@ mod tests_local_datatype_server {
             .until(|| counter1.get_state() == DatatypeState::Disabled);
     }
 
+    #[test]
+    #[instrument]
+    fn can_resubscribe_with_unsynced_transactions() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let (collection, key, resource_id) = get_test_ids!();
+        let client1 = Client::builder(collection.clone(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(collection, "client2")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let counter1 = client1
+            .create_datatype(key.clone())
+            .build_counter()
+            .unwrap();
+        counter1.sync().unwrap();
+        let counter2 = client2
+            .subscribe_datatype(key.clone())
+            .build_counter()
+            .unwrap();
+        counter2.sync().unwrap();
+        assert!(counter2.resubscribe().is_err());
+
+        counter1.increase_by(1).unwrap();
+        counter1.sync().unwrap();
+        counter2.increase_by(10).unwrap();
+        connectivity.remove_client_subscription(&resource_id, client2.get_cuid());
+        assert!(counter2.sync().is_err());
+        assert_eq!(counter2.get_state(), DatatypeState::Disabled);
+        assert!(counter2.increase().is_err());
+
+        counter2.resubscribe().unwrap();
+        assert_eq!(counter2.get_state(), DatatypeState::SubscribingOrCreating);
+        assert_eq!(counter2.get_value(), 0);
+        counter2.increase_by(100).unwrap();
+        counter2.sync().unwrap();
+        assert_eq!(counter2.get_state(), DatatypeState::Subscribed);
+        assert_eq!(counter2.get_value(), 111);
+        counter2.sync().unwrap();
+        counter1.sync().unwrap();
+        assert_eq!(counter1.get_value(), 111);
+        assert_eq!(
+            counter2.get_synced_client_version(),
+            counter2.get_client_version()
+        );
+
+        // The creator starts over from its own state.
+        counter1.increase_by(1000).unwrap();
+        connectivity.remove_client_subscription(&resource_id, client1.get_cuid());
+        assert!(counter1.sync().is_err());
+        counter1.resubscribe().unwrap();
+        counter1.sync().unwrap();
+        counter1.sync().unwrap();
+        counter2.sync().unwrap();
+        assert_eq!(counter1.get_value(), 1111);
+        assert_eq!(counter2.get_value(), 1111);
+    }
+
     #[test]
     #[instrument]
     fn can_unsubscribe_not_subscribed_client_gracefully() {
@@ -950,7 +1191,7 @@ mod tests_local_datatype_server {
         assert_eq!(counter1.get_state(), DatatypeState::Subscribed);
 
         // Simulate the server losing the client's subscription.
-        connectivity.remove_client_subscription(&resource_id, &client1.get_cuid());
+        connectivity.remove_client_subscription(&resource_id, client1.get_cuid());
 
         // Unsubscribing a client whose server-side subscription is gone must complete
         // gracefully: no error, and the datatype reaches Disabled.
@@ -979,7 +1220,7 @@ mod tests_local_datatype_server {
 
         // Simulate a client sending a push with a different DataType after subscription.
         let interceptor = connectivity
-            .get_wired_interceptor(&resource_id, &client.get_cuid())
+            .get_wired_interceptor(&resource_id, client.get_cuid())
             .unwrap();
         interceptor.set_before_push(push_set_variable_type);
 
@@ -1009,7 +1250,7 @@ mod tests_local_datatype_server {
 
         // Simulate a buggy or malicious client sending a push with Disabled state.
         let interceptor = connectivity
-            .get_wired_interceptor(&resource_id, &client.get_cuid())
+            .get_wired_interceptor(&resource_id, client.get_cuid())
             .unwrap();
         interceptor.set_before_push(push_set_disabled_state);
 
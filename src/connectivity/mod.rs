@@ -3,9 +3,10 @@ use std::{fmt::Debug, sync::Arc};
 use crossbeam_channel::Sender;
 
 use crate::{
+    clients::listing::{RemoteDatatypeFilter, RemoteDatatypeInfo},
     datatypes::{event_loop::Event, wired::WiredDatatype},
     errors::connectivity::ConnectivityError,
-    types::push_pull_pack::PushPullPack,
+    types::{push_pull_pack::PushPullPack, uid::Cuid},
 };
 
 #[allow(dead_code)]
@@ -16,5 +17,26 @@ pub mod null_connectivity;
 pub trait Connectivity: Send + Sync + Debug {
     fn register(&self, wired: Arc<WiredDatatype>, sender: Sender<Event>);
     fn push_pull(&self, ppp: &PushPullPack) -> Result<PushPullPack, ConnectivityError>;
+    /// Push-pulls the packs of many datatypes in one request, returning the result of each
+    /// in the order of `ppps`.
+    ///
+    /// By default, the packs are push-pulled one by one.
+    fn push_pull_batch(
+        &self,
+        ppps: &[PushPullPack],
+    ) -> Vec<Result<PushPullPack, ConnectivityError>> {
+        ppps.iter().map(|ppp| self.push_pull(ppp)).collect()
+    }
     fn is_realtime(&self) -> bool;
+    /// Lists the datatypes existing in `collection` that `filter` selects.
+    fn list_datatypes(
+        &self,
+        collection: &str,
+        filter: &RemoteDatatypeFilter,
+    ) -> Result<Vec<RemoteDatatypeInfo>, ConnectivityError>;
+    /// Opens the session of the client of `cuid`, rejecting it with
+    /// [`ConnectivityError::SessionConflict`] while another session of the same client is open.
+    fn open_session(&self, cuid: &Cuid) -> Result<(), ConnectivityError>;
+    /// Closes the session of the client of `cuid`, so that it can be opened again.
+    fn close_session(&self, cuid: &Cuid);
 }
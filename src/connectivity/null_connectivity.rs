@@ -4,10 +4,11 @@ use crossbeam_channel::Sender;
 
 use crate::{
     DatatypeState,
+    clients::listing::{RemoteDatatypeFilter, RemoteDatatypeInfo},
     connectivity::Connectivity,
     datatypes::{event_loop::Event, wired::WiredDatatype},
     errors::{connectivity::ConnectivityError, push_pull::PushPullError},
-    types::push_pull_pack::PushPullPack,
+    types::{push_pull_pack::PushPullPack, uid::Cuid},
 };
 
 #[derive(Debug)]
@@ -66,7 +67,8 @@ impl Connectivity for NullConnectivity {
                 self.push_transaction(pushed, &mut pulled);
             }
             DatatypeState::Deleting => {
-                todo!()
+                pulled.state = DatatypeState::Disabled;
+                self.push_transaction(pushed, &mut pulled);
             }
             DatatypeState::Disabled => {
                 unreachable!("this cannot be happened")
@@ -78,6 +80,22 @@ impl Connectivity for NullConnectivity {
     fn is_realtime(&self) -> bool {
         true
     }
+
+    fn list_datatypes(
+        &self,
+        _collection: &str,
+        _filter: &RemoteDatatypeFilter,
+    ) -> Result<Vec<RemoteDatatypeInfo>, ConnectivityError> {
+        Ok(Vec::new())
+    }
+
+    fn open_session(&self, _cuid: &Cuid) -> Result<(), ConnectivityError> {
+        Ok(())
+    }
+
+    fn close_session(&self, _cuid: &Cuid) {
+        // do nothing
+    }
 }
 
 #[cfg(test)]
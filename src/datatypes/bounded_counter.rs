@@ -0,0 +1,313 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use futures::Stream;
+use tracing::trace;
+
+use crate::{
+    DatatypeError, IntoString,
+    datatypes::{
+        common::{ReturnType, datatype_instrument},
+        crdts::{Crdt, bounded_counter_crdt::BoundedCounterCrdt},
+        datatype::DatatypeBlanket,
+        transactional::{TransactionContext, TransactionalDatatype},
+        watch,
+    },
+    errors::{BoxedError, datatypes::InternalReason},
+    operations::Operation,
+    types::uid::Cuid,
+};
+
+/// A bounded counter is a conflict-free counter that never goes below zero.
+///
+/// Each client holds an escrow of rights to decrease: increasing the counter grants the
+/// client as many rights, and a client can decrease only by the rights it holds, so that
+/// concurrent decreases of different clients can never overdraw the counter. Rights can be
+/// transferred to another client, and reach it through the server like any operation.
+///
+/// # Examples
+///
+/// ```
+/// # use qortoo::{Client, DatatypeError};
+/// let client = Client::builder("doc-example", "bounded-counter-test").build().unwrap();
+/// let stock = client.create_datatype("test-stock").build_bounded_counter().unwrap();
+/// stock.increase_by(3).unwrap();
+/// assert_eq!(stock.decrease_by(2).unwrap(), 1);
+/// assert!(matches!(
+///     stock.decrease_by(2).unwrap_err(),
+///     DatatypeError::InsufficientRights(_)
+/// ));
+/// ```
+#[derive(Clone)]
+pub struct BoundedCounter {
+    datatype: Arc<TransactionalDatatype>,
+    tx_ctx: Arc<TransactionContext>,
+}
+
+impl BoundedCounter {
+    pub(crate) fn new(datatype: Arc<TransactionalDatatype>) -> Self {
+        BoundedCounter {
+            datatype,
+            tx_ctx: Default::default(),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn new_for_test(state: crate::DatatypeState) -> Self {
+        Self::new(
+            TransactionalDatatype::new_arc(
+                crate::datatypes::common::new_attribute!(crate::DataType::BoundedCounter),
+                state,
+                Default::default(),
+            )
+            .unwrap(),
+        )
+    }
+
+    fn execute(&self, op: Operation) -> Result<u64, DatatypeError> {
+        let ret = self
+            .datatype
+            .execute_local_operation_as_tx(self.tx_ctx.clone(), op)?;
+        trace!("executed -> {ret:?}");
+        match ret {
+            ReturnType::BoundedCounter(v) => Ok(v),
+            _ => {
+                Err(InternalReason::ExecuteOperation("unexpected return type".into()).into_error())
+            }
+        }
+    }
+
+    fn read<R>(&self, f: impl FnOnce(&BoundedCounterCrdt) -> R) -> R {
+        let mutable = self.datatype.mutable.read();
+        match &mutable.crdt {
+            Crdt::BoundedCounter(c) => f(c),
+            _ => unreachable!("bounded counter must have a bounded counter crdt"),
+        }
+    }
+
+    datatype_instrument! {
+    /// Increases the counter by `delta`, granting this client as many rights to decrease.
+    ///
+    /// Returns the new counter-value.
+    ///
+    /// # Errors
+    /// - [`DatatypeError::OutOfBounds`] if the counter would overflow `u64`, counting what
+    ///   every client has ever increased
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::Client;
+    /// let client = Client::builder("doc-example", "bounded-increase-test").build().unwrap();
+    /// let stock = client.create_datatype("test-stock").build_bounded_counter().unwrap();
+    /// assert_eq!(stock.increase_by(5).unwrap(), 5);
+    /// assert_eq!(stock.get_rights(), 5);
+    /// ```
+    pub fn increase_by(&self, delta: u64) -> Result<u64, DatatypeError> {
+        self.execute(Operation::new_bounded_counter_increase(delta))
+    }}
+
+    datatype_instrument! {
+    /// Decreases the counter by `delta`, spending as many rights of this client.
+    ///
+    /// Returns the new counter-value.
+    ///
+    /// # Errors
+    /// - [`DatatypeError::InsufficientRights`] if this client holds fewer rights than `delta`,
+    ///   even if the counter-value is large enough
+    pub fn decrease_by(&self, delta: u64) -> Result<u64, DatatypeError> {
+        self.execute(Operation::new_bounded_counter_decrease(delta))
+    }}
+
+    datatype_instrument! {
+    /// Transfers `amount` rights of this client to the client whose cuid is `to`.
+    ///
+    /// The receiving client can spend them once it has synced the transfer.
+    /// Returns the rights this client holds after the transfer.
+    ///
+    /// # Errors
+    /// - [`DatatypeError::InsufficientRights`] if this client holds fewer rights than `amount`
+    /// - [`DatatypeError::Disallowed`] if `to` is not a valid cuid or is this client's own
+    pub fn transfer(&self, to: &str, amount: u64) -> Result<u64, DatatypeError> {
+        let to = Cuid::try_from(to).map_err(|e| {
+            DatatypeError::Disallowed(format!("transfer rights to {to:?}: {e}"))
+        })?;
+        self.execute(Operation::new_bounded_counter_transfer(to, amount))
+    }}
+
+    /// Gets the current counter-value, which is never negative.
+    pub fn get_value(&self) -> u64 {
+        self.read(|c| c.value())
+    }
+
+    /// Returns a stream of the counter-value after every change.
+    ///
+    /// See [`Counter::watch`](crate::Counter::watch) for when values are yielded.
+    pub fn watch(&self) -> impl Stream<Item = u64> + Send + 'static {
+        watch::watch(&self.datatype, |crdt| match crdt {
+            Crdt::BoundedCounter(c) => c.value(),
+            _ => unreachable!("bounded counter must have a bounded counter crdt"),
+        })
+    }
+
+    /// Gets the rights to decrease held by this client.
+    pub fn get_rights(&self) -> u64 {
+        let cuid = self.datatype.attr.get_cuid();
+        self.read(|c| c.rights(&cuid))
+    }
+
+    /// Gets the rights held by every client holding any, keyed by cuid.
+    ///
+    /// The rights of all clients add up to the counter-value.
+    pub fn get_rights_by_client(&self) -> BTreeMap<String, u64> {
+        self.read(|c| {
+            c.rights_by_client()
+                .into_iter()
+                .map(|(cuid, rights)| (cuid.to_string(), rights))
+                .collect()
+        })
+    }
+
+    datatype_instrument! {
+    /// Executes multiple operations atomically within a transaction.
+    ///
+    /// If the transaction function returns an error, all operations within
+    /// the transaction are rolled back, leaving the counter and the rights unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::Client;
+    /// let client = Client::builder("doc-example", "bounded-transaction-test").build().unwrap();
+    /// let stock = client.create_datatype("test-stock").build_bounded_counter().unwrap();
+    /// stock.increase_by(1).unwrap();
+    ///
+    /// let result = stock.transaction("take-two", |s| {
+    ///     s.decrease_by(1)?;
+    ///     s.decrease_by(1)?;
+    ///     Ok(())
+    /// });
+    /// assert!(result.is_err());
+    /// assert_eq!(stock.get_value(), 1); // unchanged
+    /// ```
+    pub fn transaction<T>(
+        &self,
+        tag: impl IntoString,
+        tx_func: T,
+    ) -> Result<(), DatatypeError>
+    where
+        T: FnOnce(Self) -> Result<(), BoxedError> + Send + Sync + 'static,
+    {
+        self.datatype.check_writable()?;
+        let this_tx_ctx = Arc::new(TransactionContext::new(tag));
+        let this_tx_ctx_clone = this_tx_ctx.clone();
+        let do_tx_func = move || {
+            let mut counter_clone = self.clone();
+            counter_clone.tx_ctx = this_tx_ctx_clone.clone();
+            match tx_func(counter_clone) {
+                Ok(_) => Ok(()),
+                Err(e) => Err(DatatypeError::TransactionFailed(e.to_string())),
+            }
+        };
+        self.datatype.do_transaction(this_tx_ctx, do_tx_func)
+    }}
+}
+
+impl DatatypeBlanket for BoundedCounter {
+    fn get_core(&self) -> &TransactionalDatatype {
+        self.datatype.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests_bounded_counter {
+    use tracing::instrument;
+
+    use crate::{
+        Client, DataType, Datatype, DatatypeError, DatatypeState, LocalConnectivity,
+        datatypes::bounded_counter::BoundedCounter,
+        utils::test_utils::{get_test_collection_name, get_test_func_name, get_test_ids},
+    };
+
+    #[test]
+    fn can_assert_send_and_sync_traits() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<BoundedCounter>();
+    }
+
+    #[test]
+    #[instrument]
+    fn can_use_bounded_counter_operations() {
+        let counter = BoundedCounter::new_for_test(DatatypeState::Creating);
+        assert_eq!(counter.get_type(), DataType::BoundedCounter);
+        assert_eq!(counter.increase_by(10).unwrap(), 10);
+        assert_eq!(counter.decrease_by(4).unwrap(), 6);
+        assert_eq!(
+            counter.decrease_by(7).unwrap_err(),
+            DatatypeError::InsufficientRights(String::new())
+        );
+        assert_eq!(
+            counter.transfer("not-a-cuid", 1).unwrap_err(),
+            DatatypeError::Disallowed(String::new())
+        );
+        assert_eq!(counter.get_value(), 6);
+        assert_eq!(counter.get_rights(), 6);
+
+        let result = counter.transaction("failure", |c| {
+            c.increase_by(1)?;
+            c.decrease_by(5)?;
+            c.decrease_by(5)?;
+            Ok(())
+        });
+        assert!(result.is_err());
+        assert_eq!(counter.get_value(), 6);
+        assert_eq!(counter.get_rights(), 6);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_transfer_rights_between_clients() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let (collection, key, _) = get_test_ids!();
+        let client1 = Client::builder(collection.clone(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(collection, "client2")
+            .with_connectivity(connectivity)
+            .build()
+            .unwrap();
+
+        let stock1 = client1
+            .create_datatype(key.clone())
+            .build_bounded_counter()
+            .unwrap();
+        stock1.increase_by(10).unwrap();
+        stock1.sync().unwrap();
+
+        let stock2 = client2
+            .subscribe_datatype(key)
+            .build_bounded_counter()
+            .unwrap();
+        stock2.sync().unwrap();
+        assert_eq!(stock2.get_value(), 10);
+        assert_eq!(stock2.get_rights(), 0);
+        assert!(stock2.decrease_by(1).is_err());
+
+        let cuid2 = client2.get_cuid().to_string();
+        assert_eq!(stock1.transfer(&cuid2, 4).unwrap(), 6);
+        stock1.sync().unwrap();
+        stock2.sync().unwrap();
+        assert_eq!(stock2.get_rights(), 4);
+
+        // both clients spend all their rights concurrently, and the counter stays at zero
+        stock1.decrease_by(6).unwrap();
+        stock2.decrease_by(4).unwrap();
+        stock1.sync().unwrap();
+        stock2.sync().unwrap();
+        stock1.sync().unwrap();
+        assert_eq!(stock1.get_value(), 0);
+        assert_eq!(stock2.get_value(), 0);
+        assert!(stock1.get_rights_by_client().is_empty());
+    }
+}
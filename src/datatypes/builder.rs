@@ -1,9 +1,10 @@
-use std::collections::BTreeMap;
+use std::{any::TypeId, collections::BTreeMap};
 
 use dyn_fmt::AsStrFormatExt;
 
 use crate::{
-    Client, ClientError, Counter, DataType, DatatypeHandler, DatatypeState,
+    BoundedCounter, Client, ClientError, Counter, Custom, CustomCrdt, DataType, DatatypeHandler,
+    DatatypeState, Document, Flag, FlagPolicy, List, Map, MultiValueRegister, Set, Text, Variable,
     datatypes::{datatype_set::DatatypeSet, option::DatatypeOption},
     errors::{clients::CLIENT_ERROR_MSG_DATATYPE_KEY, with_err_out},
     utils::name_validator::is_valid_datatype_key,
@@ -85,6 +86,246 @@ impl<'c> DatatypeBuilder<'c> {
     /// assert_eq!(counter.get_value(), 0);
     /// ```
     pub fn build_counter(self) -> Result<Counter, ClientError> {
+        match self.build(DataType::Counter)? {
+            DatatypeSet::Counter(c) => Ok(c),
+            _ => unreachable!("built datatype must be a counter"),
+        }
+    }
+
+    /// Finalizes the builder and constructs a [`Variable`].
+    ///
+    /// Uses the builder's lifecycle state (subscribe/create/subscribe-or-create)
+    /// to return a ready-to-use variable.
+    ///
+    /// # Errors
+    /// Returns [`ClientError`] if the underlying creation/subscription fails.
+    ///
+    /// # Examples
+    /// ```
+    /// use qortoo::{Client, Value};
+    /// let client = Client::builder("doc-example", "build_variable-test").build().unwrap();
+    /// let variable = client
+    ///     .create_datatype("variable-1")
+    ///     .build_variable()
+    ///     .unwrap();
+    /// assert_eq!(variable.get(), Value::Null);
+    /// ```
+    pub fn build_variable(self) -> Result<Variable, ClientError> {
+        match self.build(DataType::Variable)? {
+            DatatypeSet::Variable(v) => Ok(v),
+            _ => unreachable!("built datatype must be a variable"),
+        }
+    }
+
+    /// Finalizes the builder and constructs a [`Map`].
+    ///
+    /// Uses the builder's lifecycle state (subscribe/create/subscribe-or-create)
+    /// to return a ready-to-use map.
+    ///
+    /// # Errors
+    /// Returns [`ClientError`] if the underlying creation/subscription fails.
+    ///
+    /// # Examples
+    /// ```
+    /// use qortoo::Client;
+    /// let client = Client::builder("doc-example", "build_map-test").build().unwrap();
+    /// let map = client.create_datatype("map-1").build_map().unwrap();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn build_map(self) -> Result<Map, ClientError> {
+        match self.build(DataType::Map)? {
+            DatatypeSet::Map(m) => Ok(m),
+            _ => unreachable!("built datatype must be a map"),
+        }
+    }
+
+    /// Finalizes the builder and constructs a [`List`].
+    ///
+    /// Uses the builder's lifecycle state (subscribe/create/subscribe-or-create)
+    /// to return a ready-to-use list.
+    ///
+    /// # Errors
+    /// Returns [`ClientError`] if the underlying creation/subscription fails.
+    ///
+    /// # Examples
+    /// ```
+    /// use qortoo::Client;
+    /// let client = Client::builder("doc-example", "build_list-test").build().unwrap();
+    /// let list = client.create_datatype("list-1").build_list().unwrap();
+    /// assert!(list.is_empty());
+    /// ```
+    pub fn build_list(self) -> Result<List, ClientError> {
+        match self.build(DataType::List)? {
+            DatatypeSet::List(l) => Ok(l),
+            _ => unreachable!("built datatype must be a list"),
+        }
+    }
+
+    /// Finalizes the builder and constructs a [`Text`].
+    ///
+    /// Uses the builder's lifecycle state (subscribe/create/subscribe-or-create)
+    /// to return a ready-to-use text.
+    ///
+    /// # Errors
+    /// Returns [`ClientError`] if the underlying creation/subscription fails.
+    ///
+    /// # Examples
+    /// ```
+    /// use qortoo::Client;
+    /// let client = Client::builder("doc-example", "build_text-test").build().unwrap();
+    /// let text = client.create_datatype("text-1").build_text().unwrap();
+    /// assert!(text.is_empty());
+    /// ```
+    pub fn build_text(self) -> Result<Text, ClientError> {
+        match self.build(DataType::Text)? {
+            DatatypeSet::Text(t) => Ok(t),
+            _ => unreachable!("built datatype must be a text"),
+        }
+    }
+
+    /// Finalizes the builder and constructs a [`Set`].
+    ///
+    /// Uses the builder's lifecycle state (subscribe/create/subscribe-or-create)
+    /// to return a ready-to-use set.
+    ///
+    /// # Errors
+    /// Returns [`ClientError`] if the underlying creation/subscription fails.
+    ///
+    /// # Examples
+    /// ```
+    /// use qortoo::Client;
+    /// let client = Client::builder("doc-example", "build_set-test").build().unwrap();
+    /// let set = client.create_datatype("set-1").build_set().unwrap();
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn build_set(self) -> Result<Set, ClientError> {
+        match self.build(DataType::Set)? {
+            DatatypeSet::Set(s) => Ok(s),
+            _ => unreachable!("built datatype must be a set"),
+        }
+    }
+
+    /// Finalizes the builder and constructs a [`MultiValueRegister`].
+    ///
+    /// Uses the builder's lifecycle state (subscribe/create/subscribe-or-create)
+    /// to return a ready-to-use multi-value register.
+    ///
+    /// # Errors
+    /// Returns [`ClientError`] if the underlying creation/subscription fails.
+    ///
+    /// # Examples
+    /// ```
+    /// use qortoo::Client;
+    /// let client = Client::builder("doc-example", "build_mvr-test").build().unwrap();
+    /// let register = client
+    ///     .create_datatype("register-1")
+    ///     .build_multi_value_register()
+    ///     .unwrap();
+    /// assert!(register.get().is_empty());
+    /// ```
+    pub fn build_multi_value_register(self) -> Result<MultiValueRegister, ClientError> {
+        match self.build(DataType::MultiValueRegister)? {
+            DatatypeSet::MultiValueRegister(r) => Ok(r),
+            _ => unreachable!("built datatype must be a multi-value register"),
+        }
+    }
+
+    /// Finalizes the builder and constructs a [`Document`].
+    ///
+    /// Uses the builder's lifecycle state (subscribe/create/subscribe-or-create)
+    /// to return a ready-to-use document.
+    ///
+    /// # Errors
+    /// Returns [`ClientError`] if the underlying creation/subscription fails.
+    ///
+    /// # Examples
+    /// ```
+    /// use qortoo::Client;
+    /// let client = Client::builder("doc-example", "build_document-test").build().unwrap();
+    /// let doc = client.create_datatype("document-1").build_document().unwrap();
+    /// assert_eq!(doc.to_json(), "{}");
+    /// ```
+    pub fn build_document(self) -> Result<Document, ClientError> {
+        match self.build(DataType::Document)? {
+            DatatypeSet::Document(d) => Ok(d),
+            _ => unreachable!("built datatype must be a document"),
+        }
+    }
+
+    /// Finalizes the builder and constructs a [`BoundedCounter`].
+    ///
+    /// Uses the builder's lifecycle state (subscribe/create/subscribe-or-create)
+    /// to return a ready-to-use bounded counter.
+    ///
+    /// # Errors
+    /// Returns [`ClientError`] if the underlying creation/subscription fails.
+    ///
+    /// # Examples
+    /// ```
+    /// use qortoo::Client;
+    /// let client = Client::builder("doc-example", "build_bounded_counter-test").build().unwrap();
+    /// let stock = client.create_datatype("stock-1").build_bounded_counter().unwrap();
+    /// assert_eq!(stock.get_value(), 0);
+    /// ```
+    pub fn build_bounded_counter(self) -> Result<BoundedCounter, ClientError> {
+        match self.build(DataType::BoundedCounter)? {
+            DatatypeSet::BoundedCounter(c) => Ok(c),
+            _ => unreachable!("built datatype must be a bounded counter"),
+        }
+    }
+
+    /// Finalizes the builder and constructs a [`Flag`].
+    ///
+    /// Uses the builder's lifecycle state (subscribe/create/subscribe-or-create)
+    /// to return a ready-to-use flag.
+    ///
+    /// # Errors
+    /// Returns [`ClientError`] if the underlying creation/subscription fails.
+    ///
+    /// # Examples
+    /// ```
+    /// use qortoo::Client;
+    /// let client = Client::builder("doc-example", "build_flag-test").build().unwrap();
+    /// let flag = client.create_datatype("flag-1").build_flag().unwrap();
+    /// assert!(!flag.is_enabled());
+    /// ```
+    pub fn build_flag(self) -> Result<Flag, ClientError> {
+        match self.build(DataType::Flag)? {
+            DatatypeSet::Flag(f) => Ok(f),
+            _ => unreachable!("built datatype must be a flag"),
+        }
+    }
+
+    /// Finalizes the builder and constructs a [`Custom`] datatype of the user-defined CRDT `T`.
+    ///
+    /// Uses the builder's lifecycle state (subscribe/create/subscribe-or-create)
+    /// to return a ready-to-use custom datatype. See [`CustomCrdt`] for an example.
+    ///
+    /// # Errors
+    /// Returns [`ClientError::FailedToSubscribeOrCreateDatatype`] if `T` is not registered
+    /// with [`ClientBuilder::with_custom_crdt`](crate::ClientBuilder::with_custom_crdt),
+    /// or if the underlying creation/subscription fails.
+    pub fn build_custom<T: CustomCrdt>(mut self) -> Result<Custom<T>, ClientError> {
+        let factory = self
+            .client
+            .get_custom_crdt_factory(T::TYPE_NAME)
+            .filter(|f| f.type_id == TypeId::of::<T>())
+            .ok_or_else(|| {
+                with_err_out!(ClientError::FailedToSubscribeOrCreateDatatype(format!(
+                    "custom CRDT '{}' is not registered on the client",
+                    T::TYPE_NAME
+                )))
+            })?;
+        self.option.custom_crdt = Some(factory);
+        match self.build(DataType::Custom)? {
+            DatatypeSet::Custom(c) => Ok(c
+                .downcast()
+                .expect("built custom datatype must have the registered crdt")),
+            _ => unreachable!("built datatype must be a custom datatype"),
+        }
+    }
+
+    fn build(self, r#type: DataType) -> Result<DatatypeSet, ClientError> {
         if !is_valid_datatype_key(&self.key) {
             return Err(with_err_out!(
                 ClientError::FailedToSubscribeOrCreateDatatype(
@@ -92,16 +333,14 @@ impl<'c> DatatypeBuilder<'c> {
                 )
             ));
         }
-        let ds = self.client.do_subscribe_or_create_datatype(
+        self.client.do_subscribe_or_create_datatype(
             self.key,
-            DataType::Counter,
+            r#type,
             self.state,
             self.option,
             self.is_readonly,
             self.handlers,
-        )?;
-        let DatatypeSet::Counter(c) = ds;
-        Ok(c)
+        )
     }
 
     /// Configures the maximum memory size for the push buffer.
@@ -127,7 +366,52 @@ impl<'c> DatatypeBuilder<'c> {
     /// ```
     pub fn with_max_memory_size_of_push_buffer(mut self, size: u64) -> Self {
         let option = DatatypeOption::new(size);
-        self.option = option;
+        self.option.max_mem_size_of_push_buffer = option.max_mem_size_of_push_buffer;
+        self
+    }
+
+    /// Configures how a [`Flag`] resolves an enable and a disable made concurrently.
+    ///
+    /// Defaults to [`FlagPolicy::EnableWins`]. The policy takes effect when this client
+    /// creates the flag; a subscribed flag follows the policy of its creator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qortoo::{Client, FlagPolicy};
+    /// let client = Client::builder("doc-example", "flag-policy-test").build().unwrap();
+    /// let flag = client
+    ///     .create_datatype("my-flag")
+    ///     .with_flag_policy(FlagPolicy::DisableWins)
+    ///     .build_flag()
+    ///     .unwrap();
+    /// assert_eq!(flag.get_policy(), FlagPolicy::DisableWins);
+    /// ```
+    pub fn with_flag_policy(mut self, policy: FlagPolicy) -> Self {
+        self.option.flag_policy = policy;
+        self
+    }
+
+    /// Configures how many committed transactions [`Datatype::history`] keeps.
+    ///
+    /// Defaults to 1000; the oldest transaction is dropped once the limit is reached, and
+    /// `0` disables the history.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qortoo::{Client, Datatype};
+    /// let client = Client::builder("doc-example", "history-retention-test").build().unwrap();
+    /// let counter = client
+    ///     .create_datatype("my-counter")
+    ///     .with_history_retention(10)
+    ///     .build_counter()
+    ///     .unwrap();
+    /// counter.increase().unwrap();
+    /// assert_eq!(counter.history().len(), 1);
+    /// ```
+    pub fn with_history_retention(mut self, transactions: usize) -> Self {
+        self.option.history_retention = transactions;
         self
     }
 
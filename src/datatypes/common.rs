@@ -6,7 +6,7 @@ use std::{
 use parking_lot::RwLock;
 
 use crate::{
-    Counter, DataType,
+    ConcurrentValue, DataType, DocumentValue,
     clients::common::ClientCommon,
     datatypes::{
         datatype_set::DatatypeSet, option::DatatypeOption, transactional::TransactionalDatatype,
@@ -14,6 +14,7 @@ use crate::{
     types::{
         common::{ArcStr, ResourceID},
         uid::{Cuid, Duid},
+        value::Value,
     },
 };
 
@@ -107,7 +108,8 @@ impl Attribute {
         let key = paths.pop_back().unwrap_or(format!("{type}")).into();
         let client_alias = paths.pop_back().unwrap_or("client".into()).into();
         let collection = paths.pop_back().unwrap_or("collection".to_owned()).into();
-        let client_common = ClientCommon::new_arc(collection, client_alias, connectivity);
+        let client_common =
+            ClientCommon::new_arc(collection, client_alias, Cuid::new(), connectivity);
         Arc::new(Self {
             key,
             r#type,
@@ -146,10 +148,7 @@ impl Attribute {
 
     pub fn get_datatype_set(&self) -> Option<DatatypeSet> {
         let transactional = self.weak_transactional.read().as_ref()?.upgrade()?;
-        Some(match self.r#type {
-            DataType::Counter => DatatypeSet::Counter(Counter::new(transactional)),
-            _ => return None,
-        })
+        Some(DatatypeSet::from_transactional(transactional))
     }
 
     pub(crate) fn detach_datatype_if_same_instance(&self) {
@@ -181,6 +180,16 @@ pub(crate) use new_attribute;
 pub enum ReturnType {
     None,
     Counter(i64),
+    BoundedCounter(u64),
+    Variable(Value),
+    Map(Option<Value>),
+    List(Option<Value>),
+    Text(String),
+    Set(bool),
+    Flag(bool),
+    MultiValueRegister(Vec<ConcurrentValue>),
+    Document(Option<DocumentValue>),
+    Custom(Value),
 }
 
 impl Debug for ReturnType {
@@ -188,6 +197,20 @@ impl Debug for ReturnType {
         match self {
             ReturnType::None => f.write_str("None"),
             ReturnType::Counter(value) => f.debug_tuple("Counter").field(value).finish(),
+            ReturnType::BoundedCounter(value) => {
+                f.debug_tuple("BoundedCounter").field(value).finish()
+            }
+            ReturnType::Variable(value) => f.debug_tuple("Variable").field(value).finish(),
+            ReturnType::Map(value) => f.debug_tuple("Map").field(value).finish(),
+            ReturnType::List(value) => f.debug_tuple("List").field(value).finish(),
+            ReturnType::Text(value) => f.debug_tuple("Text").field(value).finish(),
+            ReturnType::Set(value) => f.debug_tuple("Set").field(value).finish(),
+            ReturnType::Flag(value) => f.debug_tuple("Flag").field(value).finish(),
+            ReturnType::MultiValueRegister(values) => {
+                f.debug_tuple("MultiValueRegister").field(values).finish()
+            }
+            ReturnType::Document(value) => f.debug_tuple("Document").field(value).finish(),
+            ReturnType::Custom(value) => f.debug_tuple("Custom").field(value).finish(),
         }
     }
 }
@@ -239,5 +262,9 @@ mod tests_attribute {
             format!("{:?}", super::ReturnType::Counter(42)),
             "Counter(42)"
         );
+        assert_eq!(
+            format!("{:?}", super::ReturnType::Variable(1.into())),
+            "Variable(Int(1))"
+        );
     }
 }
@@ -1,5 +1,6 @@
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
+use futures::Stream;
 use tracing::trace;
 
 use crate::{
@@ -9,6 +10,7 @@ use crate::{
         crdts::Crdt,
         datatype::DatatypeBlanket,
         transactional::{TransactionContext, TransactionalDatatype},
+        watch,
     },
     errors::{BoxedError, datatypes::InternalReason},
     operations::Operation,
@@ -31,11 +33,14 @@ impl Counter {
 
     #[cfg(test)]
     pub(crate) fn new_for_test(state: crate::DatatypeState) -> Self {
-        Self::new(TransactionalDatatype::new_arc(
-            crate::datatypes::common::new_attribute!(crate::DataType::Counter),
-            state,
-            Default::default(),
-        ))
+        Self::new(
+            TransactionalDatatype::new_arc(
+                crate::datatypes::common::new_attribute!(crate::DataType::Counter),
+                state,
+                Default::default(),
+            )
+            .unwrap(),
+        )
     }
 
     datatype_instrument! {
@@ -95,6 +100,67 @@ impl Counter {
         self.increase_by(1)
     }
 
+    datatype_instrument! {
+    /// Resets the counter by cancelling every increase this replica has observed.
+    ///
+    /// Increases made concurrently by other clients are not observed yet and are kept,
+    /// so the counter may not read zero once they arrive. Resetting the same increases
+    /// concurrently on several replicas cancels them only once.
+    ///
+    /// Returns the new counter-value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::Client;
+    /// let client = Client::builder("doc-example", "reset-test").build().unwrap();
+    /// let counter = client.create_datatype("test-counter").build_counter().unwrap();
+    /// counter.increase_by(5).unwrap();
+    /// assert_eq!(counter.reset().unwrap(), 0);
+    /// assert_eq!(counter.increase().unwrap(), 1);
+    /// ```
+    pub fn reset(&self) -> Result<i64, DatatypeError> {
+        let op = Operation::new_counter_reset();
+
+        let ret = self
+            .datatype
+            .execute_local_operation_as_tx(self.tx_ctx.clone(), op)?;
+        trace!("reset -> {ret:?}");
+        match ret {
+            ReturnType::Counter(cv) => Ok(cv),
+            _ => Err(InternalReason::ExecuteOperation("unexpected return type".into()).into_error())
+        }
+    }}
+
+    /// Gets the contribution of each client to the counter-value since the latest reset,
+    /// keyed by cuid.
+    ///
+    /// Clients whose increases add up to zero are omitted, and the contributions add up to
+    /// the counter-value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::Client;
+    /// let client = Client::builder("doc-example", "contributions-test").build().unwrap();
+    /// let counter = client.create_datatype("test-counter").build_counter().unwrap();
+    /// counter.increase_by(3).unwrap();
+    /// let contributions = counter.contributions();
+    /// assert_eq!(contributions.len(), 1);
+    /// assert_eq!(contributions.values().sum::<i64>(), counter.get_value());
+    /// ```
+    pub fn contributions(&self) -> BTreeMap<String, i64> {
+        let mutable = self.datatype.mutable.read();
+        match &mutable.crdt {
+            Crdt::Counter(c) => c
+                .contributions()
+                .into_iter()
+                .map(|(cuid, value)| (cuid.to_string(), value))
+                .collect(),
+            _ => unreachable!("counter must have a counter crdt"),
+        }
+    }
+
     /// Gets the current counter-value without modifying it.
     ///
     /// # Returns
@@ -113,8 +179,37 @@ impl Counter {
     /// ```
     pub fn get_value(&self) -> i64 {
         let mutable = self.datatype.mutable.read();
-        let Crdt::Counter(c) = &mutable.crdt;
-        c.value()
+        match &mutable.crdt {
+            Crdt::Counter(c) => c.value(),
+            _ => unreachable!("counter must have a counter crdt"),
+        }
+    }
+
+    /// Returns a stream of the counter-value after every change.
+    ///
+    /// A value is yielded after every committed local transaction and every pull that
+    /// applied remote changes; rolled-back transactions yield nothing. A consumer that
+    /// falls more than 64 values behind skips the oldest ones. Dropping the stream stops
+    /// watching.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use futures::{StreamExt, executor::block_on};
+    /// # use qortoo::Client;
+    /// let client = Client::builder("doc-example", "watch-test").build().unwrap();
+    /// let counter = client.create_datatype("test-counter").build_counter().unwrap();
+    /// let mut values = Box::pin(counter.watch());
+    /// counter.increase_by(2).unwrap();
+    /// counter.increase_by(3).unwrap();
+    /// assert_eq!(block_on(values.next()), Some(2));
+    /// assert_eq!(block_on(values.next()), Some(5));
+    /// ```
+    pub fn watch(&self) -> impl Stream<Item = i64> + Send + 'static {
+        watch::watch(&self.datatype, |crdt| match crdt {
+            Crdt::Counter(c) => c.value(),
+            _ => unreachable!("counter must have a counter crdt"),
+        })
     }
 
     datatype_instrument! {
@@ -194,9 +289,9 @@ mod tests_counter {
     use tracing_opentelemetry::OpenTelemetrySpanExt;
 
     use crate::{
-        Client, DataType, DatatypeError, DatatypeHandler, DatatypeState,
+        Client, DataType, DatatypeError, DatatypeHandler, DatatypeState, LocalConnectivity,
         datatypes::{counter::Counter, datatype::Datatype, option::DatatypeOption},
-        utils::test_utils::{get_test_collection_name, get_test_func_name},
+        utils::test_utils::{get_test_collection_name, get_test_func_name, get_test_ids},
     };
 
     #[test]
@@ -226,6 +321,7 @@ mod tests_counter {
             // PushBufferExceededMaxMemSize (bypassing the clamp in DatatypeOption::new).
             c.datatype.mutable.write().push_buffer.option = Arc::new(DatatypeOption {
                 max_mem_size_of_push_buffer: 0,
+                ..Default::default()
             });
             Ok(())
         });
@@ -268,6 +364,58 @@ mod tests_counter {
         assert_eq!(1, counter.increase().unwrap());
         assert_eq!(11, counter.increase_by(10).unwrap());
         assert_eq!(11, counter.get_value());
+        assert_eq!(0, counter.reset().unwrap());
+        assert_eq!(-2, counter.increase_by(-2).unwrap());
+        assert_eq!(
+            vec![-2],
+            counter.contributions().into_values().collect::<Vec<_>>()
+        );
+
+        let result = counter.transaction("failure", |c| {
+            c.increase_by(5)?;
+            c.reset()?;
+            Err("failed".into())
+        });
+        assert!(result.is_err());
+        assert_eq!(-2, counter.get_value());
+    }
+
+    #[test]
+    #[instrument]
+    fn can_keep_concurrent_increases_on_reset() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let (collection, key, _) = get_test_ids!();
+        let client1 = Client::builder(collection.clone(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(collection, "client2")
+            .with_connectivity(connectivity)
+            .build()
+            .unwrap();
+
+        let counter1 = client1
+            .create_datatype(key.clone())
+            .build_counter()
+            .unwrap();
+        counter1.increase_by(10).unwrap();
+        counter1.sync().unwrap();
+        let counter2 = client2.subscribe_datatype(key).build_counter().unwrap();
+        counter2.sync().unwrap();
+        assert_eq!(counter2.get_value(), 10);
+
+        counter1.reset().unwrap();
+        counter2.increase_by(3).unwrap();
+        counter1.sync().unwrap();
+        counter2.sync().unwrap();
+        counter1.sync().unwrap();
+
+        assert_eq!(counter1.get_value(), 3);
+        assert_eq!(counter2.get_value(), 3);
+        let contributions = counter1.contributions();
+        assert_eq!(contributions, counter2.contributions());
+        assert_eq!(contributions.get(client2.get_cuid()), Some(&3));
     }
 
     #[test]
@@ -0,0 +1,329 @@
+use std::collections::BTreeMap;
+
+use derive_more::Display;
+
+use crate::{
+    DatatypeError,
+    datatypes::common::ReturnType,
+    errors::datatypes::InternalReason,
+    operations::{Operation, body::OperationBody},
+    types::{operation_id::OperationId, uid::Cuid},
+    utils::codec::{ByteReader, ByteWriter},
+};
+
+/// What a client has contributed to a bounded counter and how its rights moved.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Escrow {
+    increased: u64,
+    decreased: u64,
+    received: u64,
+    sent: u64,
+}
+
+impl Escrow {
+    /// The rights to decrease this client holds: what it increased or received
+    /// and has neither spent nor sent yet.
+    fn rights(&self) -> u64 {
+        (self.increased.saturating_add(self.received))
+            .saturating_sub(self.decreased.saturating_add(self.sent))
+    }
+}
+
+/// A counter that never goes below zero, based on escrow of decrement rights.
+///
+/// Increasing the counter grants the increasing client as many rights, and a client may
+/// only decrease by the rights it holds or transfer them to another client. Since every
+/// client spends only its own rights, concurrent decreases can never overdraw the counter,
+/// and remote operations are applied without checks.
+#[derive(Debug, Default, Clone, Display)]
+#[display("{}", self.value())]
+pub struct BoundedCounterCrdt {
+    escrows: BTreeMap<Cuid, Escrow>,
+}
+
+impl BoundedCounterCrdt {
+    pub fn value(&self) -> u64 {
+        let (increased, decreased) = self.escrows.values().fold((0u64, 0u64), |(i, d), e| {
+            (i.saturating_add(e.increased), d.saturating_add(e.decreased))
+        });
+        increased.saturating_sub(decreased)
+    }
+
+    /// Returns the rights to decrease held by `cuid`.
+    pub fn rights(&self, cuid: &Cuid) -> u64 {
+        self.escrows
+            .get(cuid)
+            .map(Escrow::rights)
+            .unwrap_or_default()
+    }
+
+    /// Returns the rights held by each client that holds any.
+    pub fn rights_by_client(&self) -> BTreeMap<Cuid, u64> {
+        self.escrows
+            .iter()
+            .map(|(cuid, e)| (cuid.clone(), e.rights()))
+            .filter(|(_, rights)| *rights > 0)
+            .collect()
+    }
+
+    fn escrow(&mut self, cuid: &Cuid) -> &mut Escrow {
+        self.escrows.entry(cuid.clone()).or_default()
+    }
+
+    fn check_rights(&self, cuid: &Cuid, amount: u64) -> Result<(), DatatypeError> {
+        let rights = self.rights(cuid);
+        if amount > rights {
+            return Err(DatatypeError::InsufficientRights(format!(
+                "requested {amount}, but holding {rights}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks that the total increased by every client stays within `u64` after `delta`,
+    /// which bounds every other sum of the escrows too.
+    fn check_increase(&self, delta: u64) -> Result<(), DatatypeError> {
+        self.escrows
+            .values()
+            .try_fold(delta, |sum, e| sum.checked_add(e.increased))
+            .map(|_| ())
+            .ok_or_else(|| {
+                DatatypeError::OutOfBounds(format!("increasing by {delta} overflows the counter"))
+            })
+    }
+
+    pub fn execute_local_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::BoundedCounterIncrease(ref body) => {
+                self.check_increase(body.delta)?;
+            }
+            OperationBody::BoundedCounterDecrease(ref body) => {
+                self.check_rights(&op_id.cuid, body.delta)?;
+            }
+            OperationBody::BoundedCounterTransfer(ref body) => {
+                if body.to == op_id.cuid {
+                    return Err(DatatypeError::Disallowed(
+                        "transfer rights to the own client".into(),
+                    ));
+                }
+                self.check_rights(&op_id.cuid, body.amount)?;
+            }
+            _ => {}
+        }
+        self.execute_remote_operation(op_id, op)
+    }
+
+    pub fn execute_remote_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            // remote operations passed the local checks of their clients, so saturating
+            // only keeps a corrupted one from panicking.
+            OperationBody::BoundedCounterIncrease(ref body) => {
+                let escrow = self.escrow(&op_id.cuid);
+                escrow.increased = escrow.increased.saturating_add(body.delta);
+                Ok(ReturnType::BoundedCounter(self.value()))
+            }
+            OperationBody::BoundedCounterDecrease(ref body) => {
+                let escrow = self.escrow(&op_id.cuid);
+                escrow.decreased = escrow.decreased.saturating_add(body.delta);
+                Ok(ReturnType::BoundedCounter(self.value()))
+            }
+            OperationBody::BoundedCounterTransfer(ref body) => {
+                let sender = self.escrow(&op_id.cuid);
+                sender.sent = sender.sent.saturating_add(body.amount);
+                let receiver = self.escrow(&body.to);
+                receiver.received = receiver.received.saturating_add(body.amount);
+                Ok(ReturnType::BoundedCounter(self.rights(&op_id.cuid)))
+            }
+            _ => Err(
+                InternalReason::ExecuteOperation(format!("unexpected {}", op.body)).into_error(),
+            ),
+        }
+    }
+
+    pub fn execute_inverse_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::BoundedCounterIncrease(ref body) => {
+                let escrow = self.escrow(&op_id.cuid);
+                escrow.increased = escrow.increased.saturating_sub(body.delta);
+                Ok(ReturnType::BoundedCounter(self.value()))
+            }
+            OperationBody::BoundedCounterDecrease(ref body) => {
+                let escrow = self.escrow(&op_id.cuid);
+                escrow.decreased = escrow.decreased.saturating_sub(body.delta);
+                Ok(ReturnType::BoundedCounter(self.value()))
+            }
+            OperationBody::BoundedCounterTransfer(ref body) => {
+                let sender = self.escrow(&op_id.cuid);
+                sender.sent = sender.sent.saturating_sub(body.amount);
+                let receiver = self.escrow(&body.to);
+                receiver.received = receiver.received.saturating_sub(body.amount);
+                Ok(ReturnType::BoundedCounter(self.rights(&op_id.cuid)))
+            }
+            _ => Err(
+                InternalReason::ExecuteOperation(format!("unexpected {}", op.body)).into_error(),
+            ),
+        }
+    }
+
+    /// Returns a decrease for an increase and an increase for a decrease, by the same delta.
+    pub fn compensate(&self, op: &Operation) -> Result<Vec<Operation>, DatatypeError> {
+        match op.body {
+            OperationBody::BoundedCounterIncrease(ref body) => {
+                Ok(vec![Operation::new_bounded_counter_decrease(body.delta)])
+            }
+            OperationBody::BoundedCounterDecrease(ref body) => {
+                Ok(vec![Operation::new_bounded_counter_increase(body.delta)])
+            }
+            _ => Err(DatatypeError::Disallowed(format!(
+                "cannot undo {}",
+                op.body
+            ))),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Box<[u8]> {
+        let mut w = ByteWriter::new();
+        w.write_varint(self.escrows.len() as u64);
+        for (cuid, e) in self.escrows.iter() {
+            w.write_str(cuid);
+            w.write_varint(e.increased);
+            w.write_varint(e.decreased);
+            w.write_varint(e.received);
+            w.write_varint(e.sent);
+        }
+        w.into_boxed_slice()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DatatypeError> {
+        let mut r = ByteReader::new(bytes);
+        let mut escrows = BTreeMap::new();
+        for _ in 0..r.read_len()? {
+            let cuid = Cuid::try_from(r.read_string()?)
+                .map_err(|e| InternalReason::Deserialize(e.to_string()).into_error())?;
+            let escrow = Escrow {
+                increased: r.read_varint()?,
+                decreased: r.read_varint()?,
+                received: r.read_varint()?,
+                sent: r.read_varint()?,
+            };
+            escrows.insert(cuid, escrow);
+        }
+        r.finish()?;
+        Ok(Self { escrows })
+    }
+}
+
+#[cfg(test)]
+mod tests_bounded_counter_crdt {
+    use crate::{
+        DatatypeError,
+        datatypes::crdts::bounded_counter_crdt::BoundedCounterCrdt,
+        operations::Operation,
+        types::{operation_id::OperationId, uid::Cuid},
+    };
+
+    fn op_id(cuid: &Cuid, cseq: u64) -> OperationId {
+        OperationId {
+            lamport: cseq,
+            cuid: cuid.clone(),
+            cseq,
+        }
+    }
+
+    #[test]
+    fn can_bound_decreases_by_escrowed_rights() {
+        let (c1, c2) = (Cuid::new(), Cuid::new());
+        let mut crdt = BoundedCounterCrdt::default();
+        let increase = Operation::new_bounded_counter_increase(10);
+        crdt.execute_local_operation(&op_id(&c1, 1), &increase)
+            .unwrap();
+        assert_eq!(crdt.rights(&c1), 10);
+        assert_eq!(crdt.rights(&c2), 0);
+
+        let decrease = Operation::new_bounded_counter_decrease(1);
+        assert_eq!(
+            crdt.execute_local_operation(&op_id(&c2, 1), &decrease)
+                .unwrap_err(),
+            DatatypeError::InsufficientRights(String::new())
+        );
+
+        let transfer = Operation::new_bounded_counter_transfer(c2.clone(), 4);
+        crdt.execute_local_operation(&op_id(&c1, 2), &transfer)
+            .unwrap();
+        let decrease = Operation::new_bounded_counter_decrease(4);
+        crdt.execute_local_operation(&op_id(&c2, 1), &decrease)
+            .unwrap();
+        let over = Operation::new_bounded_counter_decrease(7);
+        assert!(crdt.execute_local_operation(&op_id(&c1, 3), &over).is_err());
+        assert_eq!(crdt.value(), 6);
+        assert_eq!(crdt.rights(&c1), 6);
+        assert_eq!(crdt.rights(&c2), 0);
+        assert_eq!(crdt.rights_by_client().len(), 1);
+
+        crdt.execute_inverse_operation(&op_id(&c2, 1), &decrease)
+            .unwrap();
+        crdt.execute_inverse_operation(&op_id(&c1, 2), &transfer)
+            .unwrap();
+        assert_eq!(crdt.value(), 10);
+        assert_eq!(crdt.rights(&c1), 10);
+        assert_eq!(crdt.rights(&c2), 0);
+    }
+
+    #[test]
+    fn can_reject_increase_overflowing_the_counter() {
+        let (c1, c2) = (Cuid::new(), Cuid::new());
+        let mut crdt = BoundedCounterCrdt::default();
+        let max = Operation::new_bounded_counter_increase(u64::MAX);
+        crdt.execute_local_operation(&op_id(&c1, 1), &max).unwrap();
+        let one = Operation::new_bounded_counter_increase(1);
+        assert_eq!(
+            crdt.execute_local_operation(&op_id(&c1, 2), &one)
+                .unwrap_err(),
+            DatatypeError::OutOfBounds(String::new())
+        );
+        // the increases of every client count, even after the rights are spent
+        let spend = Operation::new_bounded_counter_decrease(u64::MAX);
+        crdt.execute_local_operation(&op_id(&c1, 2), &spend)
+            .unwrap();
+        assert!(crdt.execute_local_operation(&op_id(&c2, 1), &one).is_err());
+        assert_eq!(crdt.value(), 0);
+
+        // a remote overflow saturates instead of panicking
+        crdt.execute_remote_operation(&op_id(&c2, 1), &max).unwrap();
+        crdt.execute_remote_operation(&op_id(&c2, 2), &one).unwrap();
+        assert_eq!(crdt.rights(&c2), u64::MAX);
+    }
+
+    #[test]
+    fn can_serialize_and_deserialize_bounded_counter_crdt() {
+        let (c1, c2) = (Cuid::new(), Cuid::new());
+        let mut crdt = BoundedCounterCrdt::default();
+        for (cseq, op) in [
+            Operation::new_bounded_counter_increase(5),
+            Operation::new_bounded_counter_transfer(c2.clone(), 2),
+            Operation::new_bounded_counter_decrease(1),
+        ]
+        .iter()
+        .enumerate()
+        {
+            crdt.execute_remote_operation(&op_id(&c1, cseq as u64 + 1), op)
+                .unwrap();
+        }
+        let decoded = BoundedCounterCrdt::from_bytes(&crdt.to_bytes()).unwrap();
+        assert_eq!(decoded.value(), 4);
+        assert_eq!(decoded.rights(&c1), 2);
+        assert_eq!(decoded.rights(&c2), 2);
+    }
+}
@@ -1,91 +1,281 @@
+use std::collections::BTreeMap;
+
 use derive_more::Display;
 
 use crate::{
     DatatypeError,
     datatypes::common::ReturnType,
-    operations::{Operation, body::OperationBody},
+    errors::datatypes::InternalReason,
+    operations::{
+        Operation,
+        body::{CounterMarks, OperationBody},
+    },
+    types::{operation_id::OperationId, uid::Cuid},
+    utils::codec::{ByteReader, ByteWriter},
 };
 
+/// The increases of a client and the part of them already reset.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Contribution {
+    /// The number of increases applied.
+    count: u64,
+    /// The sum of the increases applied.
+    total: i64,
+    /// The `(count, total)` of this client as observed by the latest reset.
+    reset: (u64, i64),
+}
+
+impl Contribution {
+    fn value(&self) -> i64 {
+        self.total - self.reset.1
+    }
+}
+
+/// A counter made of the contributions of each client.
+///
+/// A reset does not set the counter to zero but subtracts what its replica had observed
+/// of each client, so that increases concurrent with the reset are kept. Increases of a
+/// client reach every replica in order, so the number of increases identifies how much of
+/// a contribution a reset covers, and concurrent resets covering the same increases
+/// subtract them only once.
 #[derive(Debug, Default, Clone, Display)]
+#[display("{}", self.value())]
 pub struct CounterCrdt {
-    value: i64,
+    contributions: BTreeMap<Cuid, Contribution>,
 }
 
 impl CounterCrdt {
-    pub fn increase_by(&mut self, value: i64) -> i64 {
-        self.value += value;
-        self.value
+    pub fn increase_by(&mut self, cuid: &Cuid, delta: i64) -> i64 {
+        let contribution = self.contributions.entry(cuid.clone()).or_default();
+        contribution.count += 1;
+        contribution.total += delta;
+        self.value()
     }
 
     pub fn value(&self) -> i64 {
-        self.value
+        self.contributions.values().map(Contribution::value).sum()
+    }
+
+    /// Returns the non-zero contribution of each client since the latest reset.
+    pub fn contributions(&self) -> BTreeMap<Cuid, i64> {
+        self.contributions
+            .iter()
+            .map(|(cuid, c)| (cuid.clone(), c.value()))
+            .filter(|(_, value)| *value != 0)
+            .collect()
+    }
+
+    /// Moves the reset mark of each client in `marks` forward; older marks are ignored.
+    fn reset(&mut self, marks: &CounterMarks) {
+        for (cuid, mark) in marks.iter() {
+            let contribution = self.contributions.entry(cuid.clone()).or_default();
+            if mark.0 > contribution.reset.0 {
+                contribution.reset = *mark;
+            }
+        }
+    }
+
+    pub fn execute_local_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &mut Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::CounterReset(ref mut body) => {
+                let unreset = self
+                    .contributions
+                    .iter()
+                    .filter(|(_, c)| c.count > c.reset.0);
+                body.observed = unreset
+                    .clone()
+                    .map(|(cuid, c)| (cuid.clone(), (c.count, c.total)))
+                    .collect();
+                body.prev = Some(unreset.map(|(cuid, c)| (cuid.clone(), c.reset)).collect());
+                self.reset(&body.observed);
+                Ok(ReturnType::Counter(self.value()))
+            }
+            _ => self.execute_remote_operation(op_id, op),
+        }
     }
 
-    pub fn execute_common_operation(
+    pub fn execute_remote_operation(
         &mut self,
+        op_id: &OperationId,
         op: &Operation,
     ) -> Result<ReturnType, DatatypeError> {
         match op.body {
             OperationBody::CounterIncrease(ref body) => {
-                let ret = self.increase_by(body.delta);
+                let ret = self.increase_by(&op_id.cuid, body.delta);
                 Ok(ReturnType::Counter(ret))
             }
-            #[allow(unreachable_patterns)]
-            _ => unimplemented!(),
+            OperationBody::CounterReset(ref body) => {
+                self.reset(&body.observed);
+                Ok(ReturnType::Counter(self.value()))
+            }
+            _ => Err(
+                InternalReason::ExecuteOperation(format!("unexpected {}", op.body)).into_error(),
+            ),
         }
     }
 
     pub fn execute_inverse_operation(
         &mut self,
+        op_id: &OperationId,
         op: &Operation,
     ) -> Result<ReturnType, DatatypeError> {
         match op.body {
             OperationBody::CounterIncrease(ref body) => {
-                let ret = self.increase_by(-body.delta);
-                Ok(ReturnType::Counter(ret))
+                let contribution = self.contributions.entry(op_id.cuid.clone()).or_default();
+                contribution.count -= 1;
+                contribution.total -= body.delta;
+                Ok(ReturnType::Counter(self.value()))
+            }
+            OperationBody::CounterReset(ref body) => {
+                for (cuid, mark) in body.prev.iter().flatten() {
+                    self.contributions.entry(cuid.clone()).or_default().reset = *mark;
+                }
+                Ok(ReturnType::Counter(self.value()))
+            }
+            _ => Err(
+                InternalReason::ExecuteOperation(format!("unexpected {}", op.body)).into_error(),
+            ),
+        }
+    }
+
+    /// Returns an increase by the opposite delta, or, for a reset, by the total it removed.
+    pub fn compensate(&self, op: &Operation) -> Vec<Operation> {
+        match op.body {
+            OperationBody::CounterIncrease(ref body) => {
+                vec![Operation::new_counter_increase(body.delta.saturating_neg())]
             }
-            #[allow(unreachable_patterns)]
-            _ => unimplemented!(),
+            OperationBody::CounterReset(ref body) => {
+                let prev = body.prev.as_ref();
+                let removed: i64 = body
+                    .observed
+                    .iter()
+                    .map(|(cuid, (_, total))| {
+                        let reset = prev.and_then(|p| p.get(cuid)).map_or(0, |m| m.1);
+                        total - reset
+                    })
+                    .sum();
+                if removed == 0 {
+                    return vec![];
+                }
+                vec![Operation::new_counter_increase(removed)]
+            }
+            _ => vec![],
         }
     }
 
-    #[inline]
-    pub fn to_bytes(&self) -> [u8; 8] {
-        self.value.to_le_bytes()
+    pub fn to_bytes(&self) -> Box<[u8]> {
+        let mut w = ByteWriter::new();
+        w.write_varint(self.contributions.len() as u64);
+        for (cuid, c) in self.contributions.iter() {
+            w.write_str(cuid);
+            w.write_varint(c.count);
+            w.write_i64(c.total);
+            w.write_varint(c.reset.0);
+            w.write_i64(c.reset.1);
+        }
+        w.into_boxed_slice()
     }
 
-    #[inline]
-    pub fn from_bytes(bytes: &[u8; 8]) -> Self {
-        Self {
-            value: i64::from_le_bytes(*bytes),
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DatatypeError> {
+        let mut r = ByteReader::new(bytes);
+        let mut contributions = BTreeMap::new();
+        for _ in 0..r.read_len()? {
+            let cuid = Cuid::try_from(r.read_string()?)
+                .map_err(|e| InternalReason::Deserialize(e.to_string()).into_error())?;
+            let contribution = Contribution {
+                count: r.read_varint()?,
+                total: r.read_i64()?,
+                reset: (r.read_varint()?, r.read_i64()?),
+            };
+            contributions.insert(cuid, contribution);
         }
+        r.finish()?;
+        Ok(Self { contributions })
     }
 }
 
 #[cfg(test)]
 mod tests_counter_crdt {
-    use tracing::info;
-
-    use crate::datatypes::crdts::counter_crdt::CounterCrdt;
+    use crate::{
+        datatypes::crdts::counter_crdt::CounterCrdt,
+        operations::Operation,
+        types::{operation_id::OperationId, uid::Cuid},
+    };
 
     #[test]
     fn can_new_and_increase_counter() {
+        let cuid = Cuid::new();
         let mut counter = CounterCrdt::default();
-        counter.increase_by(1);
-        counter.increase_by(-2);
+        counter.increase_by(&cuid, 1);
+        counter.increase_by(&cuid, -2);
         assert_eq!(counter.value(), -1);
+        assert_eq!(counter.contributions().get(&cuid), Some(&-1));
+    }
+
+    #[test]
+    fn can_reset_only_observed_increases() {
+        let (c1, c2) = (Cuid::new(), Cuid::new());
+        let (id1, id2) = (
+            OperationId::new_with_cuid(&c1),
+            OperationId::new_with_cuid(&c2),
+        );
+        let mut replica1 = CounterCrdt::default();
+        let mut replica2 = CounterCrdt::default();
+        for replica in [&mut replica1, &mut replica2] {
+            replica.increase_by(&c1, 5);
+            replica.increase_by(&c2, 3);
+        }
+
+        // replica1 resets while replica2 increases concurrently
+        let mut reset1 = Operation::new_counter_reset();
+        replica1.execute_local_operation(&id1, &mut reset1).unwrap();
+        let mut increase2 = Operation::new_counter_increase(4);
+        replica2
+            .execute_local_operation(&id2, &mut increase2)
+            .unwrap();
+        assert_eq!(replica1.value(), 0);
+
+        replica1.execute_remote_operation(&id2, &increase2).unwrap();
+        replica2.execute_remote_operation(&id1, &reset1).unwrap();
+        assert_eq!(replica1.value(), 4);
+        assert_eq!(replica2.value(), 4);
+        assert_eq!(replica1.contributions(), replica2.contributions());
+
+        // a concurrent reset of the same increases subtracts them only once
+        let mut reset2 = Operation::new_counter_reset();
+        let mut stale = CounterCrdt::default();
+        stale.increase_by(&c1, 5);
+        stale.execute_local_operation(&id2, &mut reset2).unwrap();
+        replica1.execute_remote_operation(&id2, &reset2).unwrap();
+        assert_eq!(replica1.value(), 4);
+
+        // rollback restores the reset marks
+        replica2.execute_local_operation(&id2, &mut reset2).unwrap();
+        assert_eq!(replica2.value(), 0);
+        replica2.execute_inverse_operation(&id2, &reset2).unwrap();
+        assert_eq!(replica2.value(), 4);
+        replica2
+            .execute_inverse_operation(&id2, &increase2)
+            .unwrap();
+        assert_eq!(replica2.value(), 0);
     }
 
     #[test]
     fn can_serialize_and_deserialize_counter_crdt() {
         let mut counter = CounterCrdt::default();
-        counter.increase_by(123);
-
-        let serialized = counter.to_bytes();
-        info!("serialized counter: {serialized:?}");
-        assert_eq!(serialized, 123_i64.to_le_bytes());
+        counter.increase_by(&Cuid::new(), 123);
+        let mut reset = Operation::new_counter_reset();
+        counter
+            .execute_local_operation(&OperationId::new(), &mut reset)
+            .unwrap();
+        counter.increase_by(&Cuid::new(), -7);
 
-        let deserialized: CounterCrdt = CounterCrdt::from_bytes(&serialized);
-        assert_eq!(deserialized.value(), counter.value());
+        let deserialized = CounterCrdt::from_bytes(&counter.to_bytes()).unwrap();
+        assert_eq!(deserialized.value(), -7);
+        assert_eq!(deserialized.contributions(), counter.contributions());
     }
 }
@@ -0,0 +1,322 @@
+use std::{
+    any::{Any, TypeId},
+    fmt::{Debug, Display, Formatter},
+};
+
+use crate::{
+    CustomCrdt, CustomOperationId, DatatypeError,
+    datatypes::common::ReturnType,
+    errors::datatypes::InternalReason,
+    operations::{Operation, body::OperationBody},
+    types::{operation_id::OperationId, value::Value},
+    utils::codec::{ByteReader, ByteWriter},
+};
+
+/// The object-safe view of a [`CustomCrdt`], implemented for every such type.
+pub trait DynCustomCrdt: Debug + Send + Sync {
+    fn type_name(&self) -> &'static str;
+    fn clone_box(&self) -> Box<dyn DynCustomCrdt>;
+    fn as_any(&self) -> &dyn Any;
+    fn execute_local(
+        &mut self,
+        op_id: &CustomOperationId,
+        body: &mut Vec<u8>,
+    ) -> Result<Value, DatatypeError>;
+    fn execute_remote(
+        &mut self,
+        op_id: &CustomOperationId,
+        body: &[u8],
+    ) -> Result<Value, DatatypeError>;
+    fn execute_inverse(
+        &mut self,
+        op_id: &CustomOperationId,
+        body: &[u8],
+    ) -> Result<Value, DatatypeError>;
+    fn to_bytes(&self) -> Vec<u8>;
+    fn replace_from_bytes(&mut self, bytes: &[u8]) -> Result<(), DatatypeError>;
+    fn size(&self) -> u64;
+}
+
+impl<T: CustomCrdt> DynCustomCrdt for T {
+    fn type_name(&self) -> &'static str {
+        T::TYPE_NAME
+    }
+
+    fn clone_box(&self) -> Box<dyn DynCustomCrdt> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn execute_local(
+        &mut self,
+        op_id: &CustomOperationId,
+        body: &mut Vec<u8>,
+    ) -> Result<Value, DatatypeError> {
+        self.execute_local_operation(op_id, body)
+    }
+
+    fn execute_remote(
+        &mut self,
+        op_id: &CustomOperationId,
+        body: &[u8],
+    ) -> Result<Value, DatatypeError> {
+        self.execute_remote_operation(op_id, body)
+    }
+
+    fn execute_inverse(
+        &mut self,
+        op_id: &CustomOperationId,
+        body: &[u8],
+    ) -> Result<Value, DatatypeError> {
+        self.execute_inverse_operation(op_id, body)
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        CustomCrdt::to_bytes(self)
+    }
+
+    fn replace_from_bytes(&mut self, bytes: &[u8]) -> Result<(), DatatypeError> {
+        *self = T::from_bytes(bytes)?;
+        Ok(())
+    }
+
+    fn size(&self) -> u64 {
+        CustomCrdt::size(self)
+    }
+}
+
+/// Creates the empty state of a [`CustomCrdt`] registered on a client.
+#[derive(Debug, Clone, Copy)]
+pub struct CustomCrdtFactory {
+    pub type_name: &'static str,
+    pub type_id: TypeId,
+    new: fn() -> Box<dyn DynCustomCrdt>,
+}
+
+impl CustomCrdtFactory {
+    pub fn of<T: CustomCrdt>() -> Self {
+        Self {
+            type_name: T::TYPE_NAME,
+            type_id: TypeId::of::<T>(),
+            new: || Box::new(T::default()),
+        }
+    }
+
+    pub fn new_crdt(&self) -> CustomCrdtAdapter {
+        CustomCrdtAdapter((self.new)())
+    }
+}
+
+/// Drives a user-defined [`CustomCrdt`] like a built-in CRDT.
+///
+/// Operations carry opaque bytes that only the custom type interprets. Snapshots are
+/// prefixed with the type name, so that a replica built for another custom type rejects
+/// them instead of misreading them.
+#[derive(Debug)]
+pub struct CustomCrdtAdapter(Box<dyn DynCustomCrdt>);
+
+impl Clone for CustomCrdtAdapter {
+    fn clone(&self) -> Self {
+        Self(self.0.clone_box())
+    }
+}
+
+impl Display for CustomCrdtAdapter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
+impl CustomCrdtAdapter {
+    pub fn type_name(&self) -> &'static str {
+        self.0.type_name()
+    }
+
+    pub fn downcast_ref<T: CustomCrdt>(&self) -> Option<&T> {
+        self.0.as_any().downcast_ref()
+    }
+
+    pub fn size(&self) -> u64 {
+        self.0.size()
+    }
+
+    pub fn execute_local_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &mut Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::Custom(ref mut body) => self
+                .0
+                .execute_local(&op_id.into(), &mut body.data)
+                .map(ReturnType::Custom),
+            _ => Err(
+                InternalReason::ExecuteOperation(format!("unexpected {}", op.body)).into_error(),
+            ),
+        }
+    }
+
+    pub fn execute_remote_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::Custom(ref body) => self
+                .0
+                .execute_remote(&op_id.into(), &body.data)
+                .map(ReturnType::Custom),
+            _ => Err(
+                InternalReason::ExecuteOperation(format!("unexpected {}", op.body)).into_error(),
+            ),
+        }
+    }
+
+    pub fn execute_inverse_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::Custom(ref body) => self
+                .0
+                .execute_inverse(&op_id.into(), &body.data)
+                .map(ReturnType::Custom),
+            _ => Err(
+                InternalReason::ExecuteOperation(format!("unexpected {}", op.body)).into_error(),
+            ),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Box<[u8]> {
+        let mut w = ByteWriter::new();
+        w.write_str(self.type_name());
+        w.write_bytes(&self.0.to_bytes());
+        w.into_boxed_slice()
+    }
+
+    /// Replaces the state with a snapshot, which must be of the same custom type.
+    pub fn deserialize(&mut self, bytes: &[u8]) -> Result<(), DatatypeError> {
+        let mut r = ByteReader::new(bytes);
+        let type_name = r.read_string()?;
+        if type_name != self.type_name() {
+            return Err(InternalReason::Deserialize(format!(
+                "snapshot of custom type '{type_name}' for '{}'",
+                self.type_name()
+            ))
+            .into_error());
+        }
+        let state = r.read_bytes()?;
+        r.finish()?;
+        self.0.replace_from_bytes(state)
+    }
+}
+
+#[cfg(test)]
+mod tests_custom_crdt {
+    use crate::{
+        CustomCrdt, CustomOperationId, DatatypeError, Value,
+        datatypes::crdts::custom_crdt::CustomCrdtFactory,
+        operations::Operation,
+        types::{operation_id::OperationId, uid::Cuid},
+    };
+
+    /// A grow-only register keeping the largest value written.
+    #[derive(Debug, Default, Clone)]
+    struct MaxRegister(u8);
+
+    impl CustomCrdt for MaxRegister {
+        const TYPE_NAME: &'static str = "max-register";
+
+        fn execute_remote_operation(
+            &mut self,
+            _: &CustomOperationId,
+            body: &[u8],
+        ) -> Result<Value, DatatypeError> {
+            self.0 = self.0.max(body[0]);
+            Ok(Value::from(self.0))
+        }
+
+        fn execute_inverse_operation(
+            &mut self,
+            _: &CustomOperationId,
+            body: &[u8],
+        ) -> Result<Value, DatatypeError> {
+            self.0 = body[1];
+            Ok(Value::from(self.0))
+        }
+
+        fn execute_local_operation(
+            &mut self,
+            op_id: &CustomOperationId,
+            body: &mut Vec<u8>,
+        ) -> Result<Value, DatatypeError> {
+            body.push(self.0);
+            self.execute_remote_operation(op_id, body)
+        }
+
+        fn to_bytes(&self) -> Vec<u8> {
+            vec![self.0]
+        }
+
+        fn from_bytes(bytes: &[u8]) -> Result<Self, DatatypeError> {
+            Ok(Self(bytes[0]))
+        }
+    }
+
+    #[derive(Debug, Default, Clone)]
+    struct Other;
+
+    impl CustomCrdt for Other {
+        const TYPE_NAME: &'static str = "other";
+
+        fn execute_remote_operation(
+            &mut self,
+            _: &CustomOperationId,
+            _: &[u8],
+        ) -> Result<Value, DatatypeError> {
+            Ok(Value::Null)
+        }
+
+        fn execute_inverse_operation(
+            &mut self,
+            _: &CustomOperationId,
+            _: &[u8],
+        ) -> Result<Value, DatatypeError> {
+            Ok(Value::Null)
+        }
+
+        fn to_bytes(&self) -> Vec<u8> {
+            vec![]
+        }
+
+        fn from_bytes(_: &[u8]) -> Result<Self, DatatypeError> {
+            Ok(Self)
+        }
+    }
+
+    #[test]
+    fn can_execute_and_serialize_custom_crdt() {
+        let mut crdt = CustomCrdtFactory::of::<MaxRegister>().new_crdt();
+        let op_id = OperationId::new_with_cuid(&Cuid::new());
+        let mut op = Operation::new_custom(vec![5]);
+        crdt.execute_local_operation(&op_id, &mut op).unwrap();
+        crdt.execute_remote_operation(&op_id, &Operation::new_custom(vec![3]))
+            .unwrap();
+        assert_eq!(crdt.downcast_ref::<MaxRegister>().unwrap().0, 5);
+
+        let mut decoded = CustomCrdtFactory::of::<MaxRegister>().new_crdt();
+        decoded.deserialize(&crdt.to_bytes()).unwrap();
+        assert_eq!(decoded.downcast_ref::<MaxRegister>().unwrap().0, 5);
+
+        crdt.execute_inverse_operation(&op_id, &op).unwrap();
+        assert_eq!(crdt.downcast_ref::<MaxRegister>().unwrap().0, 0);
+        assert!(crdt.downcast_ref::<Other>().is_none());
+
+        let mut other = CustomCrdtFactory::of::<Other>().new_crdt();
+        assert!(other.deserialize(&decoded.to_bytes()).is_err());
+    }
+}
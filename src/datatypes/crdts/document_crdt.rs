@@ -0,0 +1,674 @@
+use std::collections::BTreeMap;
+
+use derive_more::Display;
+
+use crate::{
+    DatatypeError, DocumentValue,
+    datatypes::{
+        common::ReturnType,
+        crdts::rga::{Rga, RgaValue},
+    },
+    errors::datatypes::InternalReason,
+    operations::{
+        Operation,
+        body::{DocumentKey, DocumentSlot, OperationBody},
+    },
+    types::{node_id::NodeId, operation_id::OperationId},
+    utils::{
+        codec::{ByteReader, ByteWriter},
+        json::{write_json_string, write_json_value},
+    },
+};
+
+const KIND_OBJECT: u8 = 0;
+const KIND_ARRAY: u8 = 1;
+
+/// A step of a document path: a field of an object or an index of an array.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PathSegment {
+    Field(String),
+    Index(usize),
+}
+
+/// Parses a path such as `a.b[2].c`; the empty path is the root object.
+fn parse_path(path: &str) -> Result<Vec<PathSegment>, DatatypeError> {
+    let invalid = || DatatypeError::InvalidPath(format!("malformed path {path:?}"));
+    let mut segments = Vec::new();
+    if path.is_empty() {
+        return Ok(segments);
+    }
+    for part in path.split('.') {
+        let (field, mut rest) = part.split_at(part.find('[').unwrap_or(part.len()));
+        if field.is_empty() || field.contains(']') {
+            return Err(invalid());
+        }
+        segments.push(PathSegment::Field(field.to_owned()));
+        while !rest.is_empty() {
+            let inner = rest.strip_prefix('[').ok_or_else(invalid)?;
+            let end = inner.find(']').ok_or_else(invalid)?;
+            let index = inner[..end].parse().map_err(|_| invalid())?;
+            segments.push(PathSegment::Index(index));
+            rest = &inner[end + 1..];
+        }
+    }
+    Ok(segments)
+}
+
+impl RgaValue for DocumentSlot {
+    fn encode(&self, w: &mut ByteWriter) {
+        let (value, written_by) = self;
+        w.write_op_id(written_by);
+        match value {
+            Some(value) => {
+                w.write_bool(true);
+                value.encode(w);
+            }
+            None => w.write_bool(false),
+        }
+    }
+
+    fn decode(r: &mut ByteReader) -> Result<Self, DatatypeError> {
+        let written_by = r.read_op_id()?;
+        let value = if r.read_bool()? {
+            Some(DocumentValue::decode(r)?)
+        } else {
+            None
+        };
+        Ok((value, written_by))
+    }
+}
+
+/// Returns the container created by the write of `slot`, if it holds one.
+fn child_of(slot: &DocumentSlot) -> Option<NodeId> {
+    match slot.0 {
+        Some(DocumentValue::Object | DocumentValue::Array) => Some(NodeId::from(&slot.1)),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Container {
+    /// Fields of last-writer-wins slots, with tombstones like a [`MapCrdt`](super::map_crdt::MapCrdt).
+    Object(BTreeMap<String, DocumentSlot>),
+    /// Elements of last-writer-wins slots in an [`Rga`], like a [`ListCrdt`](super::list_crdt::ListCrdt).
+    Array(Rga<DocumentSlot>),
+}
+
+impl Container {
+    fn new(value: &DocumentValue) -> Option<Self> {
+        match value {
+            DocumentValue::Object => Some(Container::Object(BTreeMap::new())),
+            DocumentValue::Array => Some(Container::Array(Rga::default())),
+            DocumentValue::Primitive(_) => None,
+        }
+    }
+}
+
+/// A JSON-like tree of objects, arrays and primitive values.
+///
+/// Every container is a CRDT of its own, identified by the [`NodeId`] of the write that
+/// created it; operations address a container by that id rather than by path, so edits of
+/// different containers merge independently and an edit follows its container even when
+/// concurrent inserts shift array indexes. When two writes put a container at the same
+/// field concurrently, the last writer wins and edits made in the losing container are
+/// kept out of sight.
+#[derive(Debug, Clone, Display)]
+#[display("{}", self.to_json())]
+pub struct DocumentCrdt {
+    /// All containers, the root object at [`NodeId::head`] included.
+    containers: BTreeMap<NodeId, Container>,
+}
+
+impl Default for DocumentCrdt {
+    fn default() -> Self {
+        Self {
+            containers: BTreeMap::from([(NodeId::head(), Container::Object(BTreeMap::new()))]),
+        }
+    }
+}
+
+impl DocumentCrdt {
+    /// Returns the live slot at `segment` of `container`.
+    fn entry(
+        &self,
+        container: &NodeId,
+        segment: &PathSegment,
+    ) -> Result<&DocumentSlot, DatatypeError> {
+        match (self.containers.get(container), segment) {
+            (Some(Container::Object(fields)), PathSegment::Field(field)) => fields
+                .get(field)
+                .filter(|slot| slot.0.is_some())
+                .ok_or_else(|| DatatypeError::InvalidPath(format!("no field {field:?}"))),
+            (Some(Container::Array(rga)), PathSegment::Index(index)) => {
+                rga.get(*index).ok_or_else(|| {
+                    DatatypeError::OutOfBounds(format!("index {index} >= len {}", rga.len()))
+                })
+            }
+            (_, segment) => Err(DatatypeError::InvalidPath(format!(
+                "{segment:?} does not match the container"
+            ))),
+        }
+    }
+
+    /// Resolves the container at the end of `segments`.
+    fn resolve(&self, segments: &[PathSegment]) -> Result<NodeId, DatatypeError> {
+        let mut container = NodeId::head();
+        for segment in segments {
+            container = child_of(self.entry(&container, segment)?).ok_or_else(|| {
+                DatatypeError::InvalidPath(format!("{segment:?} is not a container"))
+            })?;
+        }
+        Ok(container)
+    }
+
+    /// Returns the value at `path`, where containers are returned without their content.
+    pub fn get(&self, path: &str) -> Option<DocumentValue> {
+        let segments = parse_path(path).ok()?;
+        let Some((last, parent)) = segments.split_last() else {
+            return Some(DocumentValue::Object);
+        };
+        let container = self.resolve(parent).ok()?;
+        self.entry(&container, last).ok()?.0.clone()
+    }
+
+    /// Returns the number of live fields or elements of the container at `path`.
+    pub fn len(&self, path: &str) -> Option<usize> {
+        let container = self.resolve(&parse_path(path).ok()?).ok()?;
+        match self.containers.get(&container)? {
+            Container::Object(fields) => Some(fields.values().filter(|s| s.0.is_some()).count()),
+            Container::Array(rga) => Some(rga.len()),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        self.write_container(&mut out, &NodeId::head());
+        out
+    }
+
+    fn write_container(&self, out: &mut String, id: &NodeId) {
+        match self.containers.get(id) {
+            Some(Container::Object(fields)) => {
+                out.push('{');
+                let live = fields.iter().filter(|(_, slot)| slot.0.is_some());
+                for (i, (field, slot)) in live.enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    write_json_string(out, field);
+                    out.push(':');
+                    self.write_slot(out, slot);
+                }
+                out.push('}');
+            }
+            Some(Container::Array(rga)) => {
+                out.push('[');
+                for (i, slot) in rga.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    self.write_slot(out, slot);
+                }
+                out.push(']');
+            }
+            None => out.push_str("null"),
+        }
+    }
+
+    fn write_slot(&self, out: &mut String, slot: &DocumentSlot) {
+        match (&slot.0, child_of(slot)) {
+            (_, Some(child)) => self.write_container(out, &child),
+            (Some(DocumentValue::Primitive(value)), None) => write_json_value(out, value),
+            _ => out.push_str("null"),
+        }
+    }
+
+    /// Creates the container written by `op_id`, if `value` is one.
+    fn create_container(&mut self, op_id: &OperationId, value: &DocumentValue) {
+        if let Some(container) = Container::new(value) {
+            self.containers
+                .entry(NodeId::from(op_id))
+                .or_insert(container);
+        }
+    }
+
+    /// Writes `value` (or a tombstone) to `key` of `container` if `op_id` wins over its
+    /// last write; an element removed from its array stays removed.
+    fn write(
+        &mut self,
+        container: &NodeId,
+        key: &DocumentKey,
+        value: Option<DocumentValue>,
+        op_id: &OperationId,
+    ) -> bool {
+        let slot = match (self.containers.get_mut(container), key) {
+            (Some(Container::Object(fields)), DocumentKey::Field(field)) => fields
+                .entry(field.clone())
+                .or_insert((None, OperationId::new())),
+            (Some(Container::Array(rga)), DocumentKey::Element(id)) => {
+                let Some(slot) = rga.get_mut_by_id(id) else {
+                    return false;
+                };
+                slot
+            }
+            _ => return false,
+        };
+        if *op_id <= slot.1 {
+            return false;
+        }
+        *slot = (value, op_id.clone());
+        true
+    }
+
+    pub fn execute_local_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &mut Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        // A local write always carries the largest lamport seen so far, so it wins.
+        match op.body {
+            OperationBody::DocumentSet(ref mut body) => {
+                let segments = parse_path(&body.path)?;
+                let (last, parent) = segments
+                    .split_last()
+                    .ok_or_else(|| DatatypeError::InvalidPath("cannot set the root".into()))?;
+                body.container = self.resolve(parent)?;
+                let new_slot = (Some(body.value.clone()), op_id.clone());
+                body.prev = match (self.containers.get_mut(&body.container), last) {
+                    (Some(Container::Object(fields)), PathSegment::Field(field)) => {
+                        body.key = DocumentKey::Field(field.clone());
+                        fields.insert(field.clone(), new_slot)
+                    }
+                    (Some(Container::Array(rga)), PathSegment::Index(index)) => {
+                        let id = rga.ids_at(*index, 1).and_then(|mut ids| ids.pop());
+                        let id = id.ok_or_else(|| {
+                            DatatypeError::OutOfBounds(format!(
+                                "index {index} >= len {}",
+                                rga.len()
+                            ))
+                        })?;
+                        let slot = rga.get_mut_by_id(&id).expect("a visible element");
+                        body.key = DocumentKey::Element(id);
+                        Some(std::mem::replace(slot, new_slot))
+                    }
+                    (_, segment) => {
+                        return Err(DatatypeError::InvalidPath(format!(
+                            "{segment:?} does not match the container"
+                        )));
+                    }
+                };
+                self.create_container(op_id, &body.value);
+                Ok(ReturnType::Document(
+                    body.prev.as_ref().and_then(|(v, _)| v.clone()),
+                ))
+            }
+            OperationBody::DocumentDelete(ref mut body) => {
+                let segments = parse_path(&body.path)?;
+                let (last, parent) = segments
+                    .split_last()
+                    .ok_or_else(|| DatatypeError::InvalidPath("cannot delete the root".into()))?;
+                body.container = self.resolve(parent)?;
+                let deleted = self.entry(&body.container, last)?.0.clone();
+                match (self.containers.get_mut(&body.container), last) {
+                    (Some(Container::Object(fields)), PathSegment::Field(field)) => {
+                        body.key = DocumentKey::Field(field.clone());
+                        body.prev = fields.insert(field.clone(), (None, op_id.clone()));
+                    }
+                    (Some(Container::Array(rga)), PathSegment::Index(index)) => {
+                        let mut ids = rga.ids_at(*index, 1).unwrap_or_default();
+                        rga.remove(&ids, op_id);
+                        body.key = DocumentKey::Element(ids.remove(0));
+                    }
+                    _ => unreachable!("the entry has been resolved"),
+                }
+                Ok(ReturnType::Document(deleted))
+            }
+            OperationBody::DocumentInsert(ref mut body) => {
+                body.container = self.resolve(&parse_path(&body.path)?)?;
+                let Some(Container::Array(rga)) = self.containers.get_mut(&body.container) else {
+                    return Err(DatatypeError::InvalidPath(format!(
+                        "{:?} is not an array",
+                        body.path
+                    )));
+                };
+                body.origin = rga.origin_at(body.index).ok_or_else(|| {
+                    DatatypeError::OutOfBounds(format!(
+                        "insert index {} > len {}",
+                        body.index,
+                        rga.len()
+                    ))
+                })?;
+                rga.insert(
+                    &body.origin,
+                    &NodeId::from(op_id),
+                    vec![(Some(body.value.clone()), op_id.clone())],
+                )?;
+                self.create_container(op_id, &body.value);
+                Ok(ReturnType::Document(None))
+            }
+            _ => Err(
+                InternalReason::ExecuteOperation(format!("unexpected {}", op.body)).into_error(),
+            ),
+        }
+    }
+
+    pub fn execute_remote_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        // An operation on a container this replica does not have, i.e., one that lost a
+        // concurrent write, has nothing visible to change and is skipped.
+        match op.body {
+            OperationBody::DocumentSet(ref body) => {
+                if self.write(&body.container, &body.key, Some(body.value.clone()), op_id) {
+                    self.create_container(op_id, &body.value);
+                }
+            }
+            OperationBody::DocumentDelete(ref body) => match &body.key {
+                DocumentKey::Field(_) => {
+                    self.write(&body.container, &body.key, None, op_id);
+                }
+                DocumentKey::Element(id) => {
+                    if let Some(Container::Array(rga)) = self.containers.get_mut(&body.container) {
+                        rga.remove(std::slice::from_ref(id), op_id);
+                    }
+                }
+            },
+            OperationBody::DocumentInsert(ref body) => {
+                if let Some(Container::Array(rga)) = self.containers.get_mut(&body.container) {
+                    rga.insert(
+                        &body.origin,
+                        &NodeId::from(op_id),
+                        vec![(Some(body.value.clone()), op_id.clone())],
+                    )?;
+                    self.create_container(op_id, &body.value);
+                }
+            }
+            _ => {
+                return Err(
+                    InternalReason::ExecuteOperation(format!("unexpected {}", op.body))
+                        .into_error(),
+                );
+            }
+        }
+        Ok(ReturnType::Document(None))
+    }
+
+    pub fn execute_inverse_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::DocumentSet(ref body) => {
+                self.restore(&body.container, &body.key, &body.prev, op_id);
+                if Container::new(&body.value).is_some() {
+                    self.containers.remove(&NodeId::from(op_id));
+                }
+            }
+            OperationBody::DocumentDelete(ref body) => match &body.key {
+                DocumentKey::Field(_) => {
+                    self.restore(&body.container, &body.key, &body.prev, op_id)
+                }
+                DocumentKey::Element(id) => {
+                    if let Some(Container::Array(rga)) = self.containers.get_mut(&body.container) {
+                        rga.undo_remove(std::slice::from_ref(id), op_id);
+                    }
+                }
+            },
+            OperationBody::DocumentInsert(ref body) => {
+                if let Some(Container::Array(rga)) = self.containers.get_mut(&body.container) {
+                    rga.undo_insert(&NodeId::from(op_id), 1);
+                }
+                if Container::new(&body.value).is_some() {
+                    self.containers.remove(&NodeId::from(op_id));
+                }
+            }
+            _ => {
+                return Err(
+                    InternalReason::ExecuteOperation(format!("unexpected {}", op.body))
+                        .into_error(),
+                );
+            }
+        }
+        Ok(ReturnType::Document(None))
+    }
+
+    /// Puts `prev` back into `key` of `container` if the write of `op_id` is still the winner.
+    fn restore(
+        &mut self,
+        container: &NodeId,
+        key: &DocumentKey,
+        prev: &Option<DocumentSlot>,
+        op_id: &OperationId,
+    ) {
+        match (self.containers.get_mut(container), key) {
+            (Some(Container::Object(fields)), DocumentKey::Field(field))
+                if fields.get(field).is_some_and(|slot| slot.1 == *op_id) =>
+            {
+                match prev {
+                    Some(slot) => fields.insert(field.clone(), slot.clone()),
+                    None => fields.remove(field),
+                };
+            }
+            (Some(Container::Array(rga)), DocumentKey::Element(id)) => {
+                if let Some(slot) = rga.get_mut_by_id(id)
+                    && slot.1 == *op_id
+                    && let Some(prev) = prev
+                {
+                    *slot = prev.clone();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Encodes every container, tombstones included, so that a subscriber resolves later
+    /// concurrent writes exactly as the source replica does.
+    pub fn to_bytes(&self) -> Box<[u8]> {
+        let mut w = ByteWriter::new();
+        w.write_varint(self.containers.len() as u64);
+        for (id, container) in self.containers.iter() {
+            w.write_node_id(id);
+            match container {
+                Container::Object(fields) => {
+                    w.write_u8(KIND_OBJECT);
+                    w.write_varint(fields.len() as u64);
+                    for (field, slot) in fields.iter() {
+                        w.write_str(field);
+                        slot.encode(&mut w);
+                    }
+                }
+                Container::Array(rga) => {
+                    w.write_u8(KIND_ARRAY);
+                    rga.encode(&mut w);
+                }
+            }
+        }
+        w.into_boxed_slice()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DatatypeError> {
+        let mut r = ByteReader::new(bytes);
+        let mut containers = BTreeMap::new();
+        for _ in 0..r.read_len()? {
+            let id = r.read_node_id()?;
+            let container =
+                match r.read_u8()? {
+                    KIND_OBJECT => {
+                        let mut fields = BTreeMap::new();
+                        for _ in 0..r.read_len()? {
+                            let field = r.read_string()?;
+                            fields.insert(field, DocumentSlot::decode(&mut r)?);
+                        }
+                        Container::Object(fields)
+                    }
+                    KIND_ARRAY => Container::Array(Rga::decode(&mut r)?),
+                    kind => {
+                        return Err(crate::errors::datatypes::InternalReason::Deserialize(
+                            format!("unknown document container kind {kind}"),
+                        )
+                        .into_error());
+                    }
+                };
+            containers.insert(id, container);
+        }
+        r.finish()?;
+        if !matches!(containers.get(&NodeId::head()), Some(Container::Object(_))) {
+            return Err(crate::errors::datatypes::InternalReason::Deserialize(
+                "document without a root object".into(),
+            )
+            .into_error());
+        }
+        Ok(Self { containers })
+    }
+}
+
+#[cfg(test)]
+mod tests_document_crdt {
+    use crate::{
+        DatatypeError, DocumentValue,
+        datatypes::crdts::document_crdt::{DocumentCrdt, PathSegment, parse_path},
+        operations::Operation,
+        types::{operation_id::OperationId, uid::Cuid},
+    };
+
+    fn op_id(lamport: u64, cuid: &Cuid) -> OperationId {
+        let mut op_id = OperationId::new_with_cuid(cuid);
+        op_id.lamport = lamport;
+        op_id.cseq = 1;
+        op_id
+    }
+
+    #[test]
+    fn can_parse_paths() {
+        assert_eq!(parse_path("").unwrap(), vec![]);
+        assert_eq!(
+            parse_path("a.b[2][0].c").unwrap(),
+            vec![
+                PathSegment::Field("a".into()),
+                PathSegment::Field("b".into()),
+                PathSegment::Index(2),
+                PathSegment::Index(0),
+                PathSegment::Field("c".into()),
+            ]
+        );
+        for malformed in [".a", "a.", "a[", "a[x]", "a]b", "[0]", "a[1]b"] {
+            assert!(
+                matches!(parse_path(malformed), Err(DatatypeError::InvalidPath(_))),
+                "{malformed}"
+            );
+        }
+    }
+
+    #[test]
+    fn can_edit_nested_containers() {
+        let cuid = Cuid::new();
+        let mut doc = DocumentCrdt::default();
+        let mut ops = [
+            Operation::new_document_set("a", DocumentValue::Object),
+            Operation::new_document_set("a.b", DocumentValue::Array),
+            Operation::new_document_insert("a.b", 0, 1.into()),
+            Operation::new_document_insert("a.b", 1, DocumentValue::Object),
+            Operation::new_document_set("a.b[1].c", "x\"y".into()),
+            Operation::new_document_set("a.b[0]", 2.5.into()),
+        ];
+        for (i, op) in ops.iter_mut().enumerate() {
+            doc.execute_local_operation(&op_id(i as u64 + 1, &cuid), op)
+                .unwrap();
+        }
+        assert_eq!(doc.to_json(), r#"{"a":{"b":[2.5,{"c":"x\"y"}]}}"#);
+        assert_eq!(doc.get("a.b[1].c"), Some("x\"y".into()));
+        assert_eq!(doc.get("a.b"), Some(DocumentValue::Array));
+        assert_eq!(doc.len("a.b"), Some(2));
+        assert_eq!(doc.get("a.x"), None);
+
+        assert!(matches!(
+            doc.execute_local_operation(
+                &op_id(7, &cuid),
+                &mut Operation::new_document_set("a.b[2]", 0.into())
+            ),
+            Err(DatatypeError::OutOfBounds(_))
+        ));
+        assert!(matches!(
+            doc.execute_local_operation(
+                &op_id(7, &cuid),
+                &mut Operation::new_document_set("a.b.c", 0.into())
+            ),
+            Err(DatatypeError::InvalidPath(_))
+        ));
+        assert!(matches!(
+            doc.execute_local_operation(
+                &op_id(7, &cuid),
+                &mut Operation::new_document_insert("a", 0, 0.into())
+            ),
+            Err(DatatypeError::InvalidPath(_))
+        ));
+
+        // rolling back in reverse order restores the empty document
+        for (i, op) in ops.iter().enumerate().rev() {
+            doc.execute_inverse_operation(&op_id(i as u64 + 1, &cuid), op)
+                .unwrap();
+        }
+        assert_eq!(doc.to_json(), "{}");
+        assert_eq!(doc.containers.len(), 1);
+    }
+
+    #[test]
+    fn can_merge_concurrent_edits_of_nested_containers() {
+        let (c1, c2) = (Cuid::new(), Cuid::new());
+        let mut doc1 = DocumentCrdt::default();
+        let mut doc2 = DocumentCrdt::default();
+
+        let mut base = [
+            Operation::new_document_set("user", DocumentValue::Object),
+            Operation::new_document_set("user.tags", DocumentValue::Array),
+        ];
+        for (i, op) in base.iter_mut().enumerate() {
+            let id = op_id(i as u64 + 1, &c1);
+            doc1.execute_local_operation(&id, op).unwrap();
+            doc2.execute_remote_operation(&id, op).unwrap();
+        }
+
+        let mut op1 = Operation::new_document_set("user.name", "kim".into());
+        let mut op2 = Operation::new_document_insert("user.tags", 0, "admin".into());
+        let mut op3 = Operation::new_document_delete("user.tags");
+        doc1.execute_local_operation(&op_id(3, &c1), &mut op1)
+            .unwrap();
+        doc2.execute_local_operation(&op_id(3, &c2), &mut op2)
+            .unwrap();
+        doc1.execute_local_operation(&op_id(4, &c1), &mut op3)
+            .unwrap();
+        doc2.execute_remote_operation(&op_id(3, &c1), &op1).unwrap();
+        doc1.execute_remote_operation(&op_id(3, &c2), &op2).unwrap();
+        doc2.execute_remote_operation(&op_id(4, &c1), &op3).unwrap();
+
+        assert_eq!(doc1.to_json(), r#"{"user":{"name":"kim"}}"#);
+        assert_eq!(doc1.to_json(), doc2.to_json());
+        assert_eq!(doc1.to_bytes(), doc2.to_bytes());
+    }
+
+    #[test]
+    fn can_serialize_and_deserialize_document_crdt() {
+        let cuid = Cuid::new();
+        let mut doc = DocumentCrdt::default();
+        let mut ops = [
+            Operation::new_document_set("list", DocumentValue::Array),
+            Operation::new_document_insert("list", 0, vec![1u8, 2].into()),
+            Operation::new_document_insert("list", 0, DocumentValue::Object),
+            Operation::new_document_set("flag", true.into()),
+            Operation::new_document_delete("flag"),
+        ];
+        for (i, op) in ops.iter_mut().enumerate() {
+            doc.execute_local_operation(&op_id(i as u64 + 1, &cuid), op)
+                .unwrap();
+        }
+
+        let bytes = doc.to_bytes();
+        let restored = DocumentCrdt::from_bytes(&bytes).unwrap();
+        assert_eq!(restored.to_json(), r#"{"list":[{},[1,2]]}"#);
+        assert_eq!(restored.to_bytes(), bytes);
+        assert!(DocumentCrdt::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
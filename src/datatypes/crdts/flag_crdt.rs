@@ -0,0 +1,257 @@
+use std::collections::BTreeSet;
+
+use derive_more::Display;
+
+use crate::{
+    DatatypeError, FlagPolicy,
+    datatypes::common::ReturnType,
+    errors::datatypes::InternalReason,
+    operations::{
+        Operation,
+        body::{FlagBody, OperationBody},
+    },
+    types::{node_id::NodeId, operation_id::OperationId},
+    utils::codec::{ByteReader, ByteWriter},
+};
+
+/// A boolean flag resolving a concurrent enable and disable by its [`FlagPolicy`].
+///
+/// Every enable or disable is tagged with its id and supersedes the tags its replica had
+/// observed, like an add of a [`SetCrdt`](super::set_crdt::SetCrdt). Without concurrency one
+/// of the two tag sets is empty; both are non-empty only after a concurrent enable and
+/// disable, which the policy resolves until a later write supersedes them.
+#[derive(Debug, Default, Clone, Display)]
+#[display("{}", self.is_enabled())]
+pub struct FlagCrdt {
+    policy: FlagPolicy,
+    enables: BTreeSet<NodeId>,
+    disables: BTreeSet<NodeId>,
+}
+
+impl FlagCrdt {
+    pub fn new(policy: FlagPolicy) -> Self {
+        Self {
+            policy,
+            ..Default::default()
+        }
+    }
+
+    pub fn policy(&self) -> FlagPolicy {
+        self.policy
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        match (self.enables.is_empty(), self.disables.is_empty()) {
+            (false, true) => true,
+            (false, false) => self.policy == FlagPolicy::EnableWins,
+            _ => false,
+        }
+    }
+
+    /// Removes the `observed` tags and adds `tag` to the enables or the disables.
+    fn write(&mut self, tag: NodeId, enable: bool, observed: &[NodeId]) {
+        for t in observed {
+            self.enables.remove(t);
+            self.disables.remove(t);
+        }
+        if enable {
+            self.enables.insert(tag);
+        } else {
+            self.disables.insert(tag);
+        }
+    }
+
+    pub fn execute_local_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &mut Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        let (enable, body) = match op.body {
+            OperationBody::FlagEnable(ref mut body) => (true, body),
+            OperationBody::FlagDisable(ref mut body) => (false, body),
+            _ => {
+                return Err(
+                    InternalReason::ExecuteOperation(format!("unexpected {}", op.body))
+                        .into_error(),
+                );
+            }
+        };
+        let was_enabled = self.is_enabled();
+        body.observed = self
+            .enables
+            .iter()
+            .chain(self.disables.iter())
+            .cloned()
+            .collect();
+        body.prev = Some((self.enables.clone(), self.disables.clone()));
+        self.write(NodeId::from(op_id), enable, &body.observed);
+        Ok(ReturnType::Flag(was_enabled))
+    }
+
+    pub fn execute_remote_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        let was_enabled = self.is_enabled();
+        match op.body {
+            OperationBody::FlagEnable(ref body) => {
+                self.write(NodeId::from(op_id), true, &body.observed)
+            }
+            OperationBody::FlagDisable(ref body) => {
+                self.write(NodeId::from(op_id), false, &body.observed)
+            }
+            _ => {
+                return Err(
+                    InternalReason::ExecuteOperation(format!("unexpected {}", op.body))
+                        .into_error(),
+                );
+            }
+        }
+        Ok(ReturnType::Flag(was_enabled))
+    }
+
+    pub fn execute_inverse_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        let body: &FlagBody = match op.body {
+            OperationBody::FlagEnable(ref body) | OperationBody::FlagDisable(ref body) => body,
+            _ => {
+                return Err(
+                    InternalReason::ExecuteOperation(format!("unexpected {}", op.body))
+                        .into_error(),
+                );
+            }
+        };
+        let tag = NodeId::from(op_id);
+        // Only undo the write if it is still effective; a later write stays in place.
+        if (self.enables.contains(&tag) || self.disables.contains(&tag))
+            && let Some((enables, disables)) = &body.prev
+        {
+            self.enables = enables.clone();
+            self.disables = disables.clone();
+        }
+        Ok(ReturnType::Flag(self.is_enabled()))
+    }
+
+    /// Returns the operation switching the flag back, if `op` switched it and it still stands.
+    pub fn compensate(&self, op: &Operation) -> Vec<Operation> {
+        let (body, enabled): (&FlagBody, bool) = match op.body {
+            OperationBody::FlagEnable(ref body) => (body, true),
+            OperationBody::FlagDisable(ref body) => (body, false),
+            _ => return vec![],
+        };
+        if self.is_enabled() != enabled {
+            return vec![];
+        }
+        let Some((enables, disables)) = &body.prev else {
+            return vec![];
+        };
+        let prev = FlagCrdt {
+            policy: self.policy,
+            enables: enables.clone(),
+            disables: disables.clone(),
+        };
+        match (prev.is_enabled(), self.is_enabled()) {
+            (true, false) => vec![Operation::new_flag_enable()],
+            (false, true) => vec![Operation::new_flag_disable()],
+            _ => vec![],
+        }
+    }
+
+    pub fn to_bytes(&self) -> Box<[u8]> {
+        let mut w = ByteWriter::new();
+        w.write_u8(self.policy as u8);
+        for tags in [&self.enables, &self.disables] {
+            w.write_varint(tags.len() as u64);
+            for tag in tags.iter() {
+                w.write_node_id(tag);
+            }
+        }
+        w.into_boxed_slice()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DatatypeError> {
+        let mut r = ByteReader::new(bytes);
+        let policy = match r.read_u8()? {
+            0 => FlagPolicy::EnableWins,
+            1 => FlagPolicy::DisableWins,
+            policy => {
+                return Err(
+                    InternalReason::Deserialize(format!("unknown flag policy {policy}"))
+                        .into_error(),
+                );
+            }
+        };
+        let mut read_tags = || -> Result<BTreeSet<NodeId>, DatatypeError> {
+            (0..r.read_len()?).map(|_| r.read_node_id()).collect()
+        };
+        let enables = read_tags()?;
+        let disables = read_tags()?;
+        r.finish()?;
+        Ok(Self {
+            policy,
+            enables,
+            disables,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests_flag_crdt {
+    use crate::{
+        FlagPolicy,
+        datatypes::crdts::flag_crdt::FlagCrdt,
+        operations::Operation,
+        types::{operation_id::OperationId, uid::Cuid},
+    };
+
+    fn concurrent_enable_and_disable(policy: FlagPolicy) -> (FlagCrdt, FlagCrdt) {
+        let (id1, id2) = (
+            OperationId::new_with_cuid(&Cuid::new()),
+            OperationId::new_with_cuid(&Cuid::new()),
+        );
+        let mut replica1 = FlagCrdt::new(policy);
+        let mut replica2 = FlagCrdt::new(policy);
+        let mut enable = Operation::new_flag_enable();
+        let mut disable = Operation::new_flag_disable();
+        replica1.execute_local_operation(&id1, &mut enable).unwrap();
+        replica2
+            .execute_local_operation(&id2, &mut disable)
+            .unwrap();
+        replica1.execute_remote_operation(&id2, &disable).unwrap();
+        replica2.execute_remote_operation(&id1, &enable).unwrap();
+        (replica1, replica2)
+    }
+
+    #[test]
+    fn can_resolve_concurrent_writes_by_policy() {
+        let (r1, r2) = concurrent_enable_and_disable(FlagPolicy::EnableWins);
+        assert!(r1.is_enabled());
+        assert!(r2.is_enabled());
+        let (r1, r2) = concurrent_enable_and_disable(FlagPolicy::DisableWins);
+        assert!(!r1.is_enabled());
+        assert!(!r2.is_enabled());
+
+        // a later write supersedes both
+        let (mut r1, _) = concurrent_enable_and_disable(FlagPolicy::DisableWins);
+        let id = OperationId::new_with_cuid(&Cuid::new());
+        let mut enable = Operation::new_flag_enable();
+        r1.execute_local_operation(&id, &mut enable).unwrap();
+        assert!(r1.is_enabled());
+        r1.execute_inverse_operation(&id, &enable).unwrap();
+        assert!(!r1.is_enabled());
+    }
+
+    #[test]
+    fn can_serialize_and_deserialize_flag_crdt() {
+        let (r1, _) = concurrent_enable_and_disable(FlagPolicy::DisableWins);
+        let decoded = FlagCrdt::from_bytes(&r1.to_bytes()).unwrap();
+        assert_eq!(decoded.policy(), FlagPolicy::DisableWins);
+        assert_eq!(decoded.enables, r1.enables);
+        assert_eq!(decoded.disables, r1.disables);
+        assert!(FlagCrdt::from_bytes(&[2, 0, 0]).is_err());
+    }
+}
@@ -0,0 +1,281 @@
+use std::collections::BTreeSet;
+
+use derive_more::Display;
+
+use crate::{
+    DatatypeError,
+    datatypes::{
+        common::ReturnType,
+        crdts::{Compensation, rga::Rga},
+        undo::NodeAliases,
+    },
+    errors::datatypes::InternalReason,
+    operations::{Operation, body::OperationBody},
+    types::{node_id::NodeId, operation_id::OperationId, value::Value},
+    utils::codec::{ByteReader, ByteWriter},
+};
+
+/// An ordered list of [`Value`]s on top of an [`Rga`].
+///
+/// Local operations address elements by index; local execution resolves the index into
+/// the [`NodeId`] of the neighbouring element, which is what remote replicas apply.
+#[derive(Debug, Default, Clone, Display)]
+#[display("{:?}", rga.iter().collect::<Vec<_>>())]
+pub struct ListCrdt {
+    rga: Rga<Value>,
+}
+
+impl ListCrdt {
+    pub fn len(&self) -> usize {
+        self.rga.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rga.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Value> {
+        self.rga.get(index)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Value> {
+        self.rga.iter()
+    }
+
+    pub fn execute_local_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &mut Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::ListInsert(ref mut body) => {
+                body.origin = self.rga.origin_at(body.index).ok_or_else(|| {
+                    DatatypeError::OutOfBounds(format!(
+                        "insert index {} > len {}",
+                        body.index,
+                        self.len()
+                    ))
+                })?;
+                self.rga
+                    .insert(&body.origin, &NodeId::from(op_id), vec![body.value.clone()])?;
+                Ok(ReturnType::List(None))
+            }
+            OperationBody::ListDelete(ref mut body) => {
+                let mut ids = self.rga.ids_at(body.index, 1).ok_or_else(|| {
+                    DatatypeError::OutOfBounds(format!(
+                        "delete index {} >= len {}",
+                        body.index,
+                        self.len()
+                    ))
+                })?;
+                body.target = ids.remove(0);
+                let removed = self.rga.remove(std::slice::from_ref(&body.target), op_id);
+                Ok(ReturnType::List(removed.into_iter().next()))
+            }
+            _ => Err(
+                InternalReason::ExecuteOperation(format!("unexpected {}", op.body)).into_error(),
+            ),
+        }
+    }
+
+    pub fn execute_remote_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::ListInsert(ref body) => {
+                self.rga
+                    .insert(&body.origin, &NodeId::from(op_id), vec![body.value.clone()])?;
+                Ok(ReturnType::List(None))
+            }
+            OperationBody::ListDelete(ref body) => {
+                let removed = self.rga.remove(std::slice::from_ref(&body.target), op_id);
+                Ok(ReturnType::List(removed.into_iter().next()))
+            }
+            _ => Err(
+                InternalReason::ExecuteOperation(format!("unexpected {}", op.body)).into_error(),
+            ),
+        }
+    }
+
+    pub fn execute_inverse_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::ListInsert(_) => {
+                self.rga.undo_insert(&NodeId::from(op_id), 1);
+            }
+            OperationBody::ListDelete(ref body) => {
+                self.rga
+                    .undo_remove(std::slice::from_ref(&body.target), op_id);
+            }
+            _ => {
+                return Err(
+                    InternalReason::ExecuteOperation(format!("unexpected {}", op.body))
+                        .into_error(),
+                );
+            }
+        }
+        Ok(ReturnType::List(None))
+    }
+
+    /// Returns deletes of the still-visible inserted element, or re-inserts of the removed ones.
+    pub fn compensate(
+        &self,
+        op_id: &OperationId,
+        op: &Operation,
+        aliases: &NodeAliases,
+    ) -> Vec<Compensation> {
+        match op.body {
+            OperationBody::ListInsert(_) => self
+                .rga
+                .visible_ranges_of(&BTreeSet::from([aliases.resolve(&NodeId::from(op_id))]))
+                .into_iter()
+                .map(|(index, _)| Operation::new_list_delete(index).into())
+                .collect(),
+            OperationBody::ListDelete(ref body) => self
+                .rga
+                .hidden_groups_of(&BTreeSet::from([aliases.resolve(&body.target)]))
+                .into_iter()
+                .flat_map(|(index, nodes)| {
+                    nodes
+                        .into_iter()
+                        .enumerate()
+                        .map(move |(i, (id, value))| Compensation {
+                            op: Operation::new_list_insert(index + i, value),
+                            revives: vec![id],
+                        })
+                })
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    pub fn to_bytes(&self) -> Box<[u8]> {
+        let mut w = ByteWriter::new();
+        self.rga.encode(&mut w);
+        w.into_boxed_slice()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DatatypeError> {
+        let mut r = ByteReader::new(bytes);
+        let rga = Rga::decode(&mut r)?;
+        r.finish()?;
+        Ok(Self { rga })
+    }
+}
+
+#[cfg(test)]
+mod tests_list_crdt {
+    use crate::{
+        DatatypeError,
+        datatypes::crdts::list_crdt::ListCrdt,
+        operations::Operation,
+        types::{operation_id::OperationId, uid::Cuid, value::Value},
+    };
+
+    fn op_id(lamport: u64, cuid: &Cuid) -> OperationId {
+        let mut op_id = OperationId::new_with_cuid(cuid);
+        op_id.lamport = lamport;
+        op_id.cseq = 1;
+        op_id
+    }
+
+    fn values(list: &ListCrdt) -> Vec<Value> {
+        list.iter().cloned().collect()
+    }
+
+    #[test]
+    fn can_converge_concurrent_inserts() {
+        let (c1, c2) = (Cuid::new(), Cuid::new());
+        let mut list1 = ListCrdt::default();
+        let mut list2 = ListCrdt::default();
+
+        let mut base = Operation::new_list_insert(0, "base".into());
+        list1
+            .execute_local_operation(&op_id(1, &c1), &mut base)
+            .unwrap();
+        list2
+            .execute_remote_operation(&op_id(1, &c1), &base)
+            .unwrap();
+
+        // both replicas insert at index 1 concurrently with the same lamport
+        let mut op1 = Operation::new_list_insert(1, "one".into());
+        let mut op2 = Operation::new_list_insert(1, "two".into());
+        list1
+            .execute_local_operation(&op_id(2, &c1), &mut op1)
+            .unwrap();
+        list2
+            .execute_local_operation(&op_id(2, &c2), &mut op2)
+            .unwrap();
+        list1
+            .execute_remote_operation(&op_id(2, &c2), &op2)
+            .unwrap();
+        list2
+            .execute_remote_operation(&op_id(2, &c1), &op1)
+            .unwrap();
+
+        assert_eq!(values(&list1), values(&list2));
+        assert_eq!(list1.len(), 3);
+        assert_eq!(list1.to_bytes(), list2.to_bytes());
+    }
+
+    #[test]
+    fn can_undo_local_operations_and_reject_out_of_bounds() {
+        let cuid = Cuid::new();
+        let mut list = ListCrdt::default();
+        let mut insert1 = Operation::new_list_insert(0, 1.into());
+        let mut insert2 = Operation::new_list_insert(1, 2.into());
+        let mut delete = Operation::new_list_delete(0);
+        list.execute_local_operation(&op_id(1, &cuid), &mut insert1)
+            .unwrap();
+        list.execute_local_operation(&op_id(2, &cuid), &mut insert2)
+            .unwrap();
+        list.execute_local_operation(&op_id(3, &cuid), &mut delete)
+            .unwrap();
+        assert_eq!(values(&list), vec![Value::Int(2)]);
+
+        assert!(matches!(
+            list.execute_local_operation(
+                &op_id(4, &cuid),
+                &mut Operation::new_list_insert(2, 0.into())
+            ),
+            Err(DatatypeError::OutOfBounds(_))
+        ));
+        assert!(matches!(
+            list.execute_local_operation(&op_id(4, &cuid), &mut Operation::new_list_delete(1)),
+            Err(DatatypeError::OutOfBounds(_))
+        ));
+
+        list.execute_inverse_operation(&op_id(3, &cuid), &delete)
+            .unwrap();
+        assert_eq!(values(&list), vec![Value::Int(1), Value::Int(2)]);
+        list.execute_inverse_operation(&op_id(2, &cuid), &insert2)
+            .unwrap();
+        list.execute_inverse_operation(&op_id(1, &cuid), &insert1)
+            .unwrap();
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn can_serialize_and_deserialize_list_crdt() {
+        let cuid = Cuid::new();
+        let mut list = ListCrdt::default();
+        for (i, v) in ["a", "b", "c"].into_iter().enumerate() {
+            let mut op = Operation::new_list_insert(i, v.into());
+            list.execute_local_operation(&op_id(i as u64 + 1, &cuid), &mut op)
+                .unwrap();
+        }
+        let mut delete = Operation::new_list_delete(1);
+        list.execute_local_operation(&op_id(4, &cuid), &mut delete)
+            .unwrap();
+
+        let bytes = list.to_bytes();
+        let restored = ListCrdt::from_bytes(&bytes).unwrap();
+        assert_eq!(values(&restored), values(&list));
+        assert!(ListCrdt::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
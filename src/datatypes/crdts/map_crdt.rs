@@ -0,0 +1,299 @@
+use std::collections::BTreeMap;
+
+use derive_more::Display;
+
+use crate::{
+    DatatypeError,
+    datatypes::common::ReturnType,
+    errors::datatypes::InternalReason,
+    operations::{
+        Operation,
+        body::{MapEntry, OperationBody},
+    },
+    types::{operation_id::OperationId, value::Value},
+    utils::codec::{ByteReader, ByteWriter},
+};
+
+/// A map of last-writer-wins registers.
+///
+/// Each key keeps the [`OperationId`] of its last write, and removals leave a tombstone
+/// so that an older concurrent put cannot resurrect the key on another replica.
+#[derive(Debug, Default, Clone, Display)]
+#[display("{entries:?}")]
+pub struct MapCrdt {
+    entries: BTreeMap<String, MapEntry>,
+}
+
+impl MapCrdt {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries.get(key).and_then(|(value, _)| value.as_ref())
+    }
+
+    /// Iterates over live entries in key order.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.entries
+            .iter()
+            .filter_map(|(key, (value, _))| value.as_ref().map(|v| (key, v)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Writes `value` (or a tombstone) to `key` if `op_id` wins over its last write.
+    fn write(&mut self, key: &str, value: Option<Value>, op_id: &OperationId) {
+        if let Some((_, written_by)) = self.entries.get(key)
+            && *op_id <= *written_by
+        {
+            return;
+        }
+        self.entries.insert(key.to_owned(), (value, op_id.clone()));
+    }
+
+    /// Overwrites `key` unconditionally, returning the previous entry.
+    fn write_local(
+        &mut self,
+        key: &str,
+        value: Option<Value>,
+        op_id: &OperationId,
+    ) -> Option<MapEntry> {
+        self.entries.insert(key.to_owned(), (value, op_id.clone()))
+    }
+
+    pub fn execute_local_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &mut Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        // A local write always carries the largest lamport seen so far, so it wins.
+        let prev = match op.body {
+            OperationBody::MapPut(ref mut body) => {
+                body.prev = self.write_local(&body.key, Some(body.value.clone()), op_id);
+                &body.prev
+            }
+            OperationBody::MapRemove(ref mut body) => {
+                body.prev = self.write_local(&body.key, None, op_id);
+                &body.prev
+            }
+            _ => {
+                return Err(
+                    InternalReason::ExecuteOperation(format!("unexpected {}", op.body))
+                        .into_error(),
+                );
+            }
+        };
+        Ok(ReturnType::Map(prev.as_ref().and_then(|(v, _)| v.clone())))
+    }
+
+    pub fn execute_remote_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        let key = match op.body {
+            OperationBody::MapPut(ref body) => {
+                self.write(&body.key, Some(body.value.clone()), op_id);
+                &body.key
+            }
+            OperationBody::MapRemove(ref body) => {
+                self.write(&body.key, None, op_id);
+                &body.key
+            }
+            _ => {
+                return Err(
+                    InternalReason::ExecuteOperation(format!("unexpected {}", op.body))
+                        .into_error(),
+                );
+            }
+        };
+        Ok(ReturnType::Map(self.get(key).cloned()))
+    }
+
+    pub fn execute_inverse_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        let (key, prev) = match op.body {
+            OperationBody::MapPut(ref body) => (&body.key, &body.prev),
+            OperationBody::MapRemove(ref body) => (&body.key, &body.prev),
+            _ => {
+                return Err(
+                    InternalReason::ExecuteOperation(format!("unexpected {}", op.body))
+                        .into_error(),
+                );
+            }
+        };
+        // Only undo the write if it is still the winner; a later write stays in place.
+        if self
+            .entries
+            .get(key)
+            .is_some_and(|(_, written_by)| written_by == op_id)
+        {
+            match prev {
+                Some(entry) => self.entries.insert(key.clone(), entry.clone()),
+                None => self.entries.remove(key),
+            };
+        }
+        Ok(ReturnType::Map(self.get(key).cloned()))
+    }
+
+    /// Returns the put or remove writing back the entry `op` replaced, if its write is still in
+    /// place.
+    pub fn compensate(&self, op: &Operation) -> Vec<Operation> {
+        let (key, written, prev) = match op.body {
+            OperationBody::MapPut(ref body) => (&body.key, Some(&body.value), &body.prev),
+            OperationBody::MapRemove(ref body) => (&body.key, None, &body.prev),
+            _ => return vec![],
+        };
+        let current = self.entries.get(key).and_then(|(v, _)| v.as_ref());
+        if current != written {
+            return vec![];
+        }
+        match prev.as_ref().and_then(|(v, _)| v.as_ref()) {
+            Some(value) if current != Some(value) => {
+                vec![Operation::new_map_put(key, value.clone())]
+            }
+            None if current.is_some() => vec![Operation::new_map_remove(key)],
+            _ => vec![],
+        }
+    }
+
+    /// Encodes all entries, tombstones included, so that a subscriber resolves
+    /// later concurrent writes exactly as the source replica does.
+    pub fn to_bytes(&self) -> Box<[u8]> {
+        let mut w = ByteWriter::new();
+        w.write_varint(self.entries.len() as u64);
+        for (key, (value, written_by)) in self.entries.iter() {
+            w.write_str(key);
+            w.write_op_id(written_by);
+            match value {
+                Some(value) => {
+                    w.write_bool(true);
+                    value.encode(&mut w);
+                }
+                None => w.write_bool(false),
+            }
+        }
+        w.into_boxed_slice()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DatatypeError> {
+        let mut r = ByteReader::new(bytes);
+        let len = r.read_len()?;
+        let mut entries = BTreeMap::new();
+        for _ in 0..len {
+            let key = r.read_string()?;
+            let written_by = r.read_op_id()?;
+            let value = if r.read_bool()? {
+                Some(Value::decode(&mut r)?)
+            } else {
+                None
+            };
+            entries.insert(key, (value, written_by));
+        }
+        r.finish()?;
+        Ok(Self { entries })
+    }
+}
+
+#[cfg(test)]
+mod tests_map_crdt {
+    use crate::{
+        datatypes::crdts::map_crdt::MapCrdt,
+        operations::Operation,
+        types::{operation_id::OperationId, uid::Cuid, value::Value},
+    };
+
+    fn op_id(lamport: u64, cuid: &Cuid) -> OperationId {
+        let mut op_id = OperationId::new_with_cuid(cuid);
+        op_id.lamport = lamport;
+        op_id.cseq = 1;
+        op_id
+    }
+
+    #[test]
+    fn can_converge_regardless_of_order() {
+        let (c1, c2) = (Cuid::new(), Cuid::new());
+        let ops = [
+            (op_id(1, &c1), Operation::new_map_put("k", "a".into())),
+            (op_id(2, &c1), Operation::new_map_remove("k")),
+            (op_id(2, &c2), Operation::new_map_put("k", "b".into())),
+            (op_id(1, &c2), Operation::new_map_put("x", 1.into())),
+        ];
+        let expected = if c1 > c2 {
+            None
+        } else {
+            Some(Value::from("b"))
+        };
+
+        let mut forward = MapCrdt::default();
+        for (id, op) in ops.iter() {
+            forward.execute_remote_operation(id, op).unwrap();
+        }
+        let mut backward = MapCrdt::default();
+        for (id, op) in ops.iter().rev() {
+            backward.execute_remote_operation(id, op).unwrap();
+        }
+        assert_eq!(forward.get("k").cloned(), expected);
+        assert_eq!(backward.get("k").cloned(), expected);
+        assert_eq!(forward.to_bytes(), backward.to_bytes());
+        assert_eq!(forward.len(), backward.len());
+    }
+
+    #[test]
+    fn can_keep_tombstones_against_older_puts() {
+        let cuid = Cuid::new();
+        let mut map = MapCrdt::default();
+        map.execute_remote_operation(&op_id(5, &cuid), &Operation::new_map_remove("k"))
+            .unwrap();
+        map.execute_remote_operation(&op_id(3, &cuid), &Operation::new_map_put("k", 1.into()))
+            .unwrap();
+        assert_eq!(map.get("k"), None);
+        assert_eq!(map.len(), 0);
+    }
+
+    #[test]
+    fn can_undo_local_operations() {
+        let cuid = Cuid::new();
+        let mut map = MapCrdt::default();
+        let mut put1 = Operation::new_map_put("k", 1.into());
+        let mut put2 = Operation::new_map_put("k", 2.into());
+        let mut remove = Operation::new_map_remove("k");
+        map.execute_local_operation(&op_id(1, &cuid), &mut put1)
+            .unwrap();
+        map.execute_local_operation(&op_id(2, &cuid), &mut put2)
+            .unwrap();
+        map.execute_local_operation(&op_id(3, &cuid), &mut remove)
+            .unwrap();
+        assert_eq!(map.len(), 0);
+
+        map.execute_inverse_operation(&op_id(3, &cuid), &remove)
+            .unwrap();
+        assert_eq!(map.get("k"), Some(&Value::Int(2)));
+        map.execute_inverse_operation(&op_id(2, &cuid), &put2)
+            .unwrap();
+        assert_eq!(map.get("k"), Some(&Value::Int(1)));
+        map.execute_inverse_operation(&op_id(1, &cuid), &put1)
+            .unwrap();
+        assert!(map.entries.is_empty());
+    }
+
+    #[test]
+    fn can_serialize_and_deserialize_map_crdt() {
+        let cuid = Cuid::new();
+        let mut map = MapCrdt::default();
+        map.execute_remote_operation(&op_id(1, &cuid), &Operation::new_map_put("a", "x".into()))
+            .unwrap();
+        map.execute_remote_operation(&op_id(2, &cuid), &Operation::new_map_remove("b"))
+            .unwrap();
+        let bytes = map.to_bytes();
+        let restored = MapCrdt::from_bytes(&bytes).unwrap();
+        assert_eq!(restored.entries, map.entries);
+        assert!(MapCrdt::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
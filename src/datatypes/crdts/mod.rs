@@ -4,27 +4,112 @@ use derive_more::Display;
 use crate::operations::body::OperationBody;
 use crate::{
     DataType, DatatypeError,
-    datatypes::{common::ReturnType, crdts::counter_crdt::CounterCrdt},
+    datatypes::{
+        common::ReturnType,
+        crdts::{
+            bounded_counter_crdt::BoundedCounterCrdt, counter_crdt::CounterCrdt,
+            custom_crdt::CustomCrdtAdapter, document_crdt::DocumentCrdt, flag_crdt::FlagCrdt,
+            list_crdt::ListCrdt, map_crdt::MapCrdt,
+            multi_value_register_crdt::MultiValueRegisterCrdt, set_crdt::SetCrdt,
+            text_crdt::TextCrdt, variable_crdt::VariableCrdt,
+        },
+        option::DatatypeOption,
+        undo::NodeAliases,
+    },
     errors::datatypes::InternalReason,
     operations::Operation,
+    types::{node_id::NodeId, operation_id::OperationId, uid::Cuid},
 };
 
+pub mod bounded_counter_crdt;
 pub mod counter_crdt;
+pub mod custom_crdt;
+pub mod document_crdt;
+pub mod flag_crdt;
+pub mod list_crdt;
+pub mod map_crdt;
+pub mod multi_value_register_crdt;
+pub mod rga;
+pub mod set_crdt;
+pub mod text_crdt;
+pub mod variable_crdt;
 
+/// A local operation that compensates a committed one; see [`Crdt::compensate`].
+#[derive(Debug)]
+pub struct Compensation {
+    pub op: Operation,
+    /// The hidden nodes the operation inserts again, in order, as the new nodes it creates.
+    pub revives: Vec<NodeId>,
+}
+
+impl From<Operation> for Compensation {
+    fn from(op: Operation) -> Self {
+        Self {
+            op,
+            revives: vec![],
+        }
+    }
+}
+
+/// The replicated state of a datatype.
+///
+/// Every operation is executed with the [`OperationId`] it has (or will have) in its
+/// transaction, so that CRDTs can order concurrent operations and identify their own
+/// elements. Local operations are passed mutably: a CRDT may record in the body what it needs
+/// to apply the operation remotely or to invert it on rollback.
 #[derive(Debug, Clone, Display)]
 pub enum Crdt {
     Counter(CounterCrdt),
+    Variable(VariableCrdt),
+    Map(MapCrdt),
+    List(ListCrdt),
+    Text(TextCrdt),
+    Set(SetCrdt),
+    MultiValueRegister(MultiValueRegisterCrdt),
+    Document(DocumentCrdt),
+    BoundedCounter(BoundedCounterCrdt),
+    Flag(FlagCrdt),
+    Custom(CustomCrdtAdapter),
 }
 
 impl Crdt {
-    pub fn new(r#type: DataType) -> Self {
-        match r#type {
+    /// Creates the empty CRDT of `type`.
+    ///
+    /// Fails for a custom datatype whose `option` lacks the factory of its CRDT.
+    pub fn new(r#type: DataType, option: &DatatypeOption) -> Result<Self, DatatypeError> {
+        Ok(match r#type {
             DataType::Counter => Crdt::Counter(CounterCrdt::default()),
-            _ => unreachable!("invalid type"),
-        }
+            DataType::Variable => Crdt::Variable(VariableCrdt::default()),
+            DataType::Map => Crdt::Map(MapCrdt::default()),
+            DataType::List => Crdt::List(ListCrdt::default()),
+            DataType::Text => Crdt::Text(TextCrdt::default()),
+            DataType::Set => Crdt::Set(SetCrdt::default()),
+            DataType::MultiValueRegister => {
+                Crdt::MultiValueRegister(MultiValueRegisterCrdt::default())
+            }
+            DataType::Document => Crdt::Document(DocumentCrdt::default()),
+            DataType::BoundedCounter => Crdt::BoundedCounter(BoundedCounterCrdt::default()),
+            DataType::Flag => Crdt::Flag(FlagCrdt::new(option.flag_policy)),
+            DataType::Custom => Crdt::Custom(
+                option
+                    .custom_crdt
+                    .as_ref()
+                    .ok_or_else(|| {
+                        InternalReason::Deserialize(
+                            "custom datatype without the factory of its crdt".into(),
+                        )
+                        .into_error()
+                    })?
+                    .new_crdt(),
+            ),
+        })
     }
 
-    pub fn execute_local_operation(&mut self, op: &Operation) -> Result<ReturnType, DatatypeError> {
+    pub fn execute_local_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &mut Operation,
+    ) -> Result<ReturnType, DatatypeError> {
         #[cfg(test)]
         {
             if let OperationBody::Delay4Test(body) = &op.body {
@@ -35,12 +120,23 @@ impl Crdt {
             }
         }
         match self {
-            Crdt::Counter(c) => c.execute_common_operation(op),
+            Crdt::Counter(c) => c.execute_local_operation(op_id, op),
+            Crdt::Variable(v) => v.execute_local_operation(op_id, op),
+            Crdt::Map(m) => m.execute_local_operation(op_id, op),
+            Crdt::List(l) => l.execute_local_operation(op_id, op),
+            Crdt::Text(t) => t.execute_local_operation(op_id, op),
+            Crdt::Set(s) => s.execute_local_operation(op_id, op),
+            Crdt::MultiValueRegister(r) => r.execute_local_operation(op_id, op),
+            Crdt::Document(d) => d.execute_local_operation(op_id, op),
+            Crdt::BoundedCounter(c) => c.execute_local_operation(op_id, op),
+            Crdt::Flag(f) => f.execute_local_operation(op_id, op),
+            Crdt::Custom(c) => c.execute_local_operation(op_id, op),
         }
     }
 
     pub fn execute_remote_operation(
         &mut self,
+        op_id: &OperationId,
         op: &Operation,
     ) -> Result<ReturnType, DatatypeError> {
         #[cfg(test)]
@@ -53,40 +149,141 @@ impl Crdt {
             }
         }
         match self {
-            Crdt::Counter(c) => c.execute_common_operation(op),
+            Crdt::Counter(c) => c.execute_remote_operation(op_id, op),
+            Crdt::Variable(v) => v.execute_remote_operation(op_id, op),
+            Crdt::Map(m) => m.execute_remote_operation(op_id, op),
+            Crdt::List(l) => l.execute_remote_operation(op_id, op),
+            Crdt::Text(t) => t.execute_remote_operation(op_id, op),
+            Crdt::Set(s) => s.execute_remote_operation(op_id, op),
+            Crdt::MultiValueRegister(r) => r.execute_remote_operation(op_id, op),
+            Crdt::Document(d) => d.execute_remote_operation(op_id, op),
+            Crdt::BoundedCounter(c) => c.execute_remote_operation(op_id, op),
+            Crdt::Flag(f) => f.execute_remote_operation(op_id, op),
+            Crdt::Custom(c) => c.execute_remote_operation(op_id, op),
         }
     }
 
     pub fn execute_inverse_operation(
         &mut self,
+        op_id: &OperationId,
         op: &Operation,
     ) -> Result<ReturnType, DatatypeError> {
         match self {
-            Crdt::Counter(c) => c.execute_inverse_operation(op),
+            Crdt::Counter(c) => c.execute_inverse_operation(op_id, op),
+            Crdt::Variable(v) => v.execute_inverse_operation(op_id, op),
+            Crdt::Map(m) => m.execute_inverse_operation(op_id, op),
+            Crdt::List(l) => l.execute_inverse_operation(op_id, op),
+            Crdt::Text(t) => t.execute_inverse_operation(op_id, op),
+            Crdt::Set(s) => s.execute_inverse_operation(op_id, op),
+            Crdt::MultiValueRegister(r) => r.execute_inverse_operation(op_id, op),
+            Crdt::Document(d) => d.execute_inverse_operation(op_id, op),
+            Crdt::BoundedCounter(c) => c.execute_inverse_operation(op_id, op),
+            Crdt::Flag(f) => f.execute_inverse_operation(op_id, op),
+            Crdt::Custom(c) => c.execute_inverse_operation(op_id, op),
+        }
+    }
+
+    /// Returns new local operations that undo the committed local `op`, which had `op_id`.
+    ///
+    /// Unlike [`execute_inverse_operation`](Self::execute_inverse_operation), which rolls
+    /// back an operation before anyone else sees it, this compensates an operation that may
+    /// already be known to other replicas, so the result is applied and synchronized like any
+    /// other operation. An effect already overwritten or undone by a later operation is not
+    /// compensated, so the result may be empty. Nodes of `op` revived by an earlier undo are
+    /// found through `aliases`.
+    pub fn compensate(
+        &self,
+        op_id: &OperationId,
+        op: &Operation,
+        aliases: &NodeAliases,
+    ) -> Result<Vec<Compensation>, DatatypeError> {
+        let ops = match self {
+            Crdt::Counter(c) => c.compensate(op),
+            Crdt::Variable(v) => v.compensate(op),
+            Crdt::Map(m) => m.compensate(op),
+            Crdt::List(l) => return Ok(l.compensate(op_id, op, aliases)),
+            Crdt::Text(t) => return Ok(t.compensate(op_id, op, aliases)),
+            Crdt::Set(s) => s.compensate(op_id, op),
+            Crdt::MultiValueRegister(r) => r.compensate(op)?,
+            Crdt::BoundedCounter(c) => c.compensate(op)?,
+            Crdt::Flag(f) => f.compensate(op),
+            Crdt::Document(_) | Crdt::Custom(_) => {
+                return Err(DatatypeError::Disallowed(format!(
+                    "cannot undo {}",
+                    op.body
+                )));
+            }
+        };
+        Ok(ops.into_iter().map(Compensation::from).collect())
+    }
+
+    /// Drops the metadata kept only until the server has ordered the local operations
+    /// up to `synced_cseq`, e.g., the tombstones of a [`SetCrdt`].
+    pub fn collect_garbage(&mut self, cuid: &Cuid, synced_cseq: u64) {
+        if let Crdt::Set(s) = self {
+            s.collect_garbage(cuid, synced_cseq);
         }
     }
 
     pub fn serialize(&self) -> Box<[u8]> {
         match self {
-            Self::Counter(c) => Box::new(c.to_bytes()),
+            Self::Counter(c) => c.to_bytes(),
+            Self::Variable(v) => v.to_bytes(),
+            Self::Map(m) => m.to_bytes(),
+            Self::List(l) => l.to_bytes(),
+            Self::Text(t) => t.to_bytes(),
+            Self::Set(s) => s.to_bytes(),
+            Self::MultiValueRegister(r) => r.to_bytes(),
+            Self::Document(d) => d.to_bytes(),
+            Self::BoundedCounter(c) => c.to_bytes(),
+            Self::Flag(f) => f.to_bytes(),
+            Self::Custom(c) => c.to_bytes(),
         }
     }
 
     pub fn deserialize(&mut self, serialized: &[u8]) -> Result<(), DatatypeError> {
         match self {
             Self::Counter(c) => {
-                if serialized.len() != 8 {
-                    return Err(InternalReason::Deserialize(format!(
-                        "counter crdt: expected 8 bytes, got {}",
-                        serialized.len()
-                    ))
-                    .into_error());
-                }
-                let mut array = [0u8; 8];
-                array.copy_from_slice(serialized);
-                *c = CounterCrdt::from_bytes(&array);
+                *c = CounterCrdt::from_bytes(serialized)?;
+                Ok(())
+            }
+            Self::Variable(v) => {
+                *v = VariableCrdt::from_bytes(serialized)?;
+                Ok(())
+            }
+            Self::Map(m) => {
+                *m = MapCrdt::from_bytes(serialized)?;
+                Ok(())
+            }
+            Self::List(l) => {
+                *l = ListCrdt::from_bytes(serialized)?;
+                Ok(())
+            }
+            Self::Text(t) => {
+                *t = TextCrdt::from_bytes(serialized)?;
+                Ok(())
+            }
+            Self::Set(s) => {
+                *s = SetCrdt::from_bytes(serialized)?;
                 Ok(())
             }
+            Self::MultiValueRegister(r) => {
+                *r = MultiValueRegisterCrdt::from_bytes(serialized)?;
+                Ok(())
+            }
+            Self::Document(d) => {
+                *d = DocumentCrdt::from_bytes(serialized)?;
+                Ok(())
+            }
+            Self::BoundedCounter(c) => {
+                *c = BoundedCounterCrdt::from_bytes(serialized)?;
+                Ok(())
+            }
+            Self::Flag(f) => {
+                *f = FlagCrdt::from_bytes(serialized)?;
+                Ok(())
+            }
+            Self::Custom(c) => c.deserialize(serialized),
         }
     }
 }
@@ -96,19 +293,62 @@ mod tests_crdts {
     use crate::{
         DataType, DatatypeError,
         datatypes::crdts::{Crdt, counter_crdt::CounterCrdt},
+        operations::Operation,
+        types::{operation_id::OperationId, uid::Cuid, value::Value},
     };
 
+    #[test]
+    fn can_fail_to_create_custom_crdt_without_factory() {
+        assert!(matches!(
+            Crdt::new(DataType::Custom, &Default::default()),
+            Err(DatatypeError::Internal(_))
+        ));
+    }
+
+    #[test]
+    fn can_fail_to_execute_operation_of_other_type() {
+        let op_id = OperationId::new_with_cuid(&Cuid::new());
+        let mut op = Operation::new_variable_set("v".into());
+        for r#type in [
+            DataType::Counter,
+            DataType::Map,
+            DataType::List,
+            DataType::Flag,
+        ] {
+            let mut crdt = Crdt::new(r#type, &Default::default()).unwrap();
+            assert!(matches!(
+                crdt.execute_local_operation(&op_id, &mut op),
+                Err(DatatypeError::Internal(_))
+            ));
+            assert!(matches!(
+                crdt.execute_remote_operation(&op_id, &op),
+                Err(DatatypeError::Internal(_))
+            ));
+            assert!(matches!(
+                crdt.execute_inverse_operation(&op_id, &op),
+                Err(DatatypeError::Internal(_))
+            ));
+            assert!(
+                crdt.compensate(&op_id, &op, &Default::default())
+                    .unwrap()
+                    .is_empty()
+            );
+        }
+    }
+
     #[test]
     fn can_serialize_and_deserialize() {
         let mut counter = CounterCrdt::default();
-        counter.increase_by(100);
+        counter.increase_by(&Cuid::new(), 100);
         let crdt1 = Crdt::Counter(counter);
 
-        let mut crdt2 = Crdt::new(DataType::Counter);
+        let mut crdt2 = Crdt::new(DataType::Counter, &Default::default()).unwrap();
         let serialized = crdt1.serialize();
         crdt2.deserialize(&serialized).unwrap();
 
-        let Crdt::Counter(c) = &crdt2;
+        let Crdt::Counter(c) = &crdt2 else {
+            unreachable!()
+        };
         assert_eq!(c.value(), 100);
 
         // Invalid input returns Err; counter value must not change.
@@ -116,7 +356,29 @@ mod tests_crdts {
             crdt2.deserialize("{}".as_bytes()),
             Err(DatatypeError::Internal(_))
         ));
-        let Crdt::Counter(c) = &crdt2;
+        let Crdt::Counter(c) = &crdt2 else {
+            unreachable!()
+        };
         assert_eq!(c.value(), 100);
     }
+
+    #[test]
+    fn can_serialize_and_deserialize_variable() {
+        let mut op = Operation::new_variable_set("v".into());
+        let mut op_id = OperationId::new_with_cuid(&Cuid::new());
+        op_id.lamport = 1;
+        let mut crdt1 = Crdt::new(DataType::Variable, &Default::default()).unwrap();
+        crdt1.execute_local_operation(&op_id, &mut op).unwrap();
+
+        let mut crdt2 = Crdt::new(DataType::Variable, &Default::default()).unwrap();
+        crdt2.deserialize(&crdt1.serialize()).unwrap();
+        let Crdt::Variable(v) = &crdt2 else {
+            unreachable!()
+        };
+        assert_eq!(v.value(), &Value::from("v"));
+        assert!(matches!(
+            crdt2.deserialize("{}".as_bytes()),
+            Err(DatatypeError::Internal(_))
+        ));
+    }
 }
@@ -0,0 +1,243 @@
+use std::collections::BTreeMap;
+
+use derive_more::Display;
+
+use crate::{
+    DatatypeError,
+    datatypes::{common::ReturnType, multi_value_register::ConcurrentValue},
+    errors::datatypes::InternalReason,
+    operations::{Operation, body::OperationBody},
+    types::{
+        node_id::NodeId, operation_id::OperationId, value::Value, version_vector::VersionVector,
+    },
+    utils::codec::{ByteReader, ByteWriter},
+};
+
+/// A multi-value register that keeps every value written concurrently.
+///
+/// A write carries the [`VersionVector`] of what its replica had observed, and replaces
+/// exactly the values that vector covers. Values written without observing each other
+/// all stay until a later write supersedes them.
+#[derive(Debug, Default, Clone, Display)]
+#[display("{:?}", entries.values().collect::<Vec<_>>())]
+pub struct MultiValueRegisterCrdt {
+    /// The concurrent values, keyed by the id of their write.
+    entries: BTreeMap<NodeId, Value>,
+    /// Every write observed, including superseded ones.
+    context: VersionVector,
+}
+
+impl MultiValueRegisterCrdt {
+    /// Returns the concurrent values in the order of their writes.
+    pub fn values(&self) -> Vec<ConcurrentValue> {
+        self.entries
+            .iter()
+            .map(|(id, value)| ConcurrentValue::new(value.clone(), id))
+            .collect()
+    }
+
+    pub fn execute_local_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &mut Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::MultiValueSet(ref mut body) => {
+                let id = NodeId::from(op_id);
+                let superseded = self.values();
+                body.context = self.context.clone();
+                let prev_entries = std::mem::replace(
+                    &mut self.entries,
+                    BTreeMap::from([(id.clone(), body.value.clone())]),
+                );
+                body.prev = Some((prev_entries, self.context.clone()));
+                self.context.observe(&id);
+                Ok(ReturnType::MultiValueRegister(superseded))
+            }
+            _ => Err(
+                InternalReason::ExecuteOperation(format!("unexpected {}", op.body)).into_error(),
+            ),
+        }
+    }
+
+    pub fn execute_remote_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::MultiValueSet(ref body) => {
+                let id = NodeId::from(op_id);
+                // An observed write is either present already or superseded.
+                if !self.context.covers(&id) {
+                    self.entries.retain(|e, _| !body.context.covers(e));
+                    self.entries.insert(id.clone(), body.value.clone());
+                    self.context.merge(&body.context);
+                    self.context.observe(&id);
+                }
+                Ok(ReturnType::MultiValueRegister(self.values()))
+            }
+            _ => Err(
+                InternalReason::ExecuteOperation(format!("unexpected {}", op.body)).into_error(),
+            ),
+        }
+    }
+
+    pub fn execute_inverse_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::MultiValueSet(ref body) => {
+                // Only undo the write if it is still present; a later write stays in place.
+                if self.entries.contains_key(&NodeId::from(op_id))
+                    && let Some((prev_entries, prev_context)) = &body.prev
+                {
+                    self.entries = prev_entries.clone();
+                    self.context = prev_context.clone();
+                }
+                Ok(ReturnType::MultiValueRegister(self.values()))
+            }
+            _ => Err(
+                InternalReason::ExecuteOperation(format!("unexpected {}", op.body)).into_error(),
+            ),
+        }
+    }
+
+    /// Returns the set writing back the single value `op` superseded, if the register still
+    /// holds only the value it wrote.
+    pub fn compensate(&self, op: &Operation) -> Result<Vec<Operation>, DatatypeError> {
+        match op.body {
+            OperationBody::MultiValueSet(ref body) => {
+                if !self.entries.values().eq([&body.value]) {
+                    return Ok(vec![]);
+                }
+                let prev_entries = body.prev.as_ref().map(|(entries, _)| entries);
+                match prev_entries
+                    .map(|e| e.values().collect::<Vec<_>>())
+                    .as_deref()
+                {
+                    Some([value]) => Ok(vec![Operation::new_multi_value_set((*value).clone())]),
+                    _ => Err(DatatypeError::Disallowed(format!(
+                        "cannot undo {}: it did not supersede a single value",
+                        op.body
+                    ))),
+                }
+            }
+            _ => Ok(vec![]),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Box<[u8]> {
+        let mut w = ByteWriter::new();
+        w.write_varint(self.entries.len() as u64);
+        for (id, value) in self.entries.iter() {
+            w.write_node_id(id);
+            value.encode(&mut w);
+        }
+        self.context.encode(&mut w);
+        w.into_boxed_slice()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DatatypeError> {
+        let mut r = ByteReader::new(bytes);
+        let mut entries = BTreeMap::new();
+        for _ in 0..r.read_len()? {
+            let id = r.read_node_id()?;
+            entries.insert(id, Value::decode(&mut r)?);
+        }
+        let context = VersionVector::decode(&mut r)?;
+        r.finish()?;
+        Ok(Self { entries, context })
+    }
+}
+
+#[cfg(test)]
+mod tests_multi_value_register_crdt {
+    use crate::{
+        datatypes::crdts::multi_value_register_crdt::MultiValueRegisterCrdt,
+        operations::Operation,
+        types::{operation_id::OperationId, uid::Cuid, value::Value},
+    };
+
+    fn op_id(lamport: u64, cuid: &Cuid) -> OperationId {
+        let mut op_id = OperationId::new_with_cuid(cuid);
+        op_id.lamport = lamport;
+        op_id.cseq = 1;
+        op_id
+    }
+
+    fn values(crdt: &MultiValueRegisterCrdt) -> Vec<Value> {
+        crdt.values().into_iter().map(|v| v.value).collect()
+    }
+
+    #[test]
+    fn can_keep_concurrent_writes_until_superseded() {
+        let (c1, c2) = (Cuid::new(), Cuid::new());
+        let mut reg1 = MultiValueRegisterCrdt::default();
+        let mut reg2 = MultiValueRegisterCrdt::default();
+
+        let mut op1 = Operation::new_multi_value_set("a".into());
+        let mut op2 = Operation::new_multi_value_set("b".into());
+        reg1.execute_local_operation(&op_id(1, &c1), &mut op1)
+            .unwrap();
+        reg2.execute_local_operation(&op_id(1, &c2), &mut op2)
+            .unwrap();
+        reg1.execute_remote_operation(&op_id(1, &c2), &op2).unwrap();
+        reg2.execute_remote_operation(&op_id(1, &c1), &op1).unwrap();
+        assert_eq!(values(&reg1).len(), 2);
+        assert_eq!(reg1.to_bytes(), reg2.to_bytes());
+
+        let mut op3 = Operation::new_multi_value_set("c".into());
+        reg1.execute_local_operation(&op_id(2, &c1), &mut op3)
+            .unwrap();
+        reg2.execute_remote_operation(&op_id(2, &c1), &op3).unwrap();
+        assert_eq!(values(&reg2), vec![Value::from("c")]);
+
+        // a superseded write delivered again does not come back
+        reg2.execute_remote_operation(&op_id(1, &c2), &op2).unwrap();
+        assert_eq!(values(&reg2), vec![Value::from("c")]);
+        assert_eq!(reg1.to_bytes(), reg2.to_bytes());
+    }
+
+    #[test]
+    fn can_undo_local_writes() {
+        let (c1, c2) = (Cuid::new(), Cuid::new());
+        let mut reg = MultiValueRegisterCrdt::default();
+        let mut remote = Operation::new_multi_value_set(1.into());
+        MultiValueRegisterCrdt::default()
+            .execute_local_operation(&op_id(1, &c2), &mut remote)
+            .unwrap();
+        reg.execute_remote_operation(&op_id(1, &c2), &remote)
+            .unwrap();
+
+        let mut local = Operation::new_multi_value_set(2.into());
+        reg.execute_local_operation(&op_id(2, &c1), &mut local)
+            .unwrap();
+        assert_eq!(values(&reg), vec![Value::Int(2)]);
+        reg.execute_inverse_operation(&op_id(2, &c1), &local)
+            .unwrap();
+        assert_eq!(values(&reg), vec![Value::Int(1)]);
+    }
+
+    #[test]
+    fn can_serialize_and_deserialize_multi_value_register_crdt() {
+        let (c1, c2) = (Cuid::new(), Cuid::new());
+        let mut reg = MultiValueRegisterCrdt::default();
+        let mut op1 = Operation::new_multi_value_set("x".into());
+        let mut op2 = Operation::new_multi_value_set(Value::Null);
+        reg.execute_local_operation(&op_id(1, &c1), &mut op1)
+            .unwrap();
+        MultiValueRegisterCrdt::default()
+            .execute_local_operation(&op_id(1, &c2), &mut op2)
+            .unwrap();
+        reg.execute_remote_operation(&op_id(1, &c2), &op2).unwrap();
+
+        let bytes = reg.to_bytes();
+        let restored = MultiValueRegisterCrdt::from_bytes(&bytes).unwrap();
+        assert_eq!(restored.values(), reg.values());
+        assert_eq!(restored.context, reg.context);
+        assert!(MultiValueRegisterCrdt::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
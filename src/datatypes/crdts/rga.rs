@@ -0,0 +1,444 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    DatatypeError,
+    errors::datatypes::InternalReason,
+    types::{node_id::NodeId, operation_id::OperationId, value::Value},
+    utils::codec::{ByteReader, ByteWriter},
+};
+
+/// An element type that can be stored in an [`Rga`] and encoded into its snapshot.
+pub trait RgaValue: Clone {
+    fn encode(&self, w: &mut ByteWriter);
+    fn decode(r: &mut ByteReader) -> Result<Self, DatatypeError>;
+}
+
+impl RgaValue for Value {
+    fn encode(&self, w: &mut ByteWriter) {
+        Value::encode(self, w)
+    }
+
+    fn decode(r: &mut ByteReader) -> Result<Self, DatatypeError> {
+        Value::decode(r)
+    }
+}
+
+impl RgaValue for char {
+    fn encode(&self, w: &mut ByteWriter) {
+        w.write_varint(*self as u64)
+    }
+
+    fn decode(r: &mut ByteReader) -> Result<Self, DatatypeError> {
+        let code = r.read_varint()?;
+        u32::try_from(code)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| InternalReason::Deserialize(format!("invalid char {code}")).into_error())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RgaNode<T> {
+    id: NodeId,
+    value: T,
+    /// The operation that removed this node, which a rollback of that operation checks.
+    removed_by: Option<OperationId>,
+}
+
+/// A replicated growable array: the sequence core shared by `List` and `Text`.
+///
+/// Nodes are kept in document order, tombstones included. A new node is placed right
+/// after its origin (the node it was inserted behind), skipping nodes with a larger
+/// [`NodeId`]; because a node is always larger than everything it was inserted after,
+/// concurrent inserts at the same origin end up ordered by descending id on every replica.
+///
+/// A single insert may carry a run of values; the `i`-th value of a run starting at
+/// `first` gets the id `(first.lamport + i, first.cuid)`.
+#[derive(Debug, Clone)]
+pub struct Rga<T> {
+    nodes: Vec<RgaNode<T>>,
+    size: usize,
+}
+
+impl<T> Default for Rga<T> {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            size: 0,
+        }
+    }
+}
+
+impl<T: RgaValue> Rga<T> {
+    /// Returns the number of visible elements.
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Iterates over visible elements in order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.nodes
+            .iter()
+            .filter(|n| n.removed_by.is_none())
+            .map(|n| &n.value)
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.iter().nth(index)
+    }
+
+    /// Returns the visible element with `id`, if any, for updating it in place.
+    pub fn get_mut_by_id(&mut self, id: &NodeId) -> Option<&mut T> {
+        self.nodes
+            .iter_mut()
+            .find(|n| n.id == *id && n.removed_by.is_none())
+            .map(|n| &mut n.value)
+    }
+
+    fn position_of(&self, id: &NodeId) -> Option<usize> {
+        self.nodes.iter().position(|n| n.id == *id)
+    }
+
+    fn visible_positions(&self) -> impl Iterator<Item = usize> + '_ {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| n.removed_by.is_none())
+            .map(|(pos, _)| pos)
+    }
+
+    fn visible_index_of(&self, pos: usize) -> usize {
+        self.nodes[..pos]
+            .iter()
+            .filter(|n| n.removed_by.is_none())
+            .count()
+    }
+
+    /// Returns the origin for an insert at visible `index`: the visible node right before it,
+    /// or the head for index 0. Returns `None` if `index` is larger than the length.
+    pub fn origin_at(&self, index: usize) -> Option<NodeId> {
+        if index > self.size {
+            return None;
+        }
+        if index == 0 {
+            return Some(NodeId::head());
+        }
+        self.visible_positions()
+            .nth(index - 1)
+            .map(|pos| self.nodes[pos].id.clone())
+    }
+
+    /// Returns the ids of `count` visible nodes starting at `index`,
+    /// or `None` if the range exceeds the length.
+    pub fn ids_at(&self, index: usize, count: usize) -> Option<Vec<NodeId>> {
+        if index.checked_add(count)? > self.size {
+            return None;
+        }
+        Some(
+            self.visible_positions()
+                .skip(index)
+                .take(count)
+                .map(|pos| self.nodes[pos].id.clone())
+                .collect(),
+        )
+    }
+
+    /// Integrates a run of `values` starting at `first` after `origin`.
+    ///
+    /// Returns the visible index of the first value, or `None` if the run was already
+    /// integrated (e.g., received once in a snapshot and again as a transaction).
+    pub fn insert(
+        &mut self,
+        origin: &NodeId,
+        first: &NodeId,
+        values: Vec<T>,
+    ) -> Result<Option<usize>, DatatypeError> {
+        if self.position_of(first).is_some() {
+            return Ok(None);
+        }
+        let mut pos = if origin.is_head() {
+            0
+        } else {
+            self.position_of(origin).ok_or_else(|| {
+                InternalReason::ExecuteOperation(format!("unknown rga origin {origin}"))
+                    .into_error()
+            })? + 1
+        };
+        while pos < self.nodes.len() && self.nodes[pos].id > *first {
+            pos += 1;
+        }
+        let index = self.visible_index_of(pos);
+        self.size += values.len();
+        let run = values.into_iter().enumerate().map(|(i, value)| RgaNode {
+            id: NodeId::new(first.lamport + i as u64, &first.cuid),
+            value,
+            removed_by: None,
+        });
+        self.nodes.splice(pos..pos, run);
+        Ok(Some(index))
+    }
+
+    /// Hides the nodes of `ids` on behalf of `op_id`, returning the values that became hidden.
+    ///
+    /// A node removed again, e.g., by a concurrent remote removal, is handed over to the
+    /// later remover, so that rolling back the earlier one keeps it hidden.
+    pub fn remove(&mut self, ids: &[NodeId], op_id: &OperationId) -> Vec<T> {
+        let mut removed = Vec::new();
+        for id in ids {
+            let Some(pos) = self.position_of(id) else {
+                continue;
+            };
+            let node = &mut self.nodes[pos];
+            if node.removed_by.is_none() {
+                self.size -= 1;
+                removed.push(node.value.clone());
+            }
+            node.removed_by = Some(op_id.clone());
+        }
+        removed
+    }
+
+    /// Drops the run of `count` nodes starting at `first`; used to roll back a local insert.
+    ///
+    /// The nodes are matched by id, since remote nodes may have been integrated into the
+    /// middle of the run in the meantime.
+    pub fn undo_insert(&mut self, first: &NodeId, count: usize) {
+        let lamports = first.lamport..first.lamport + count as u64;
+        let mut hidden = 0;
+        self.nodes.retain(|n| {
+            let in_run = n.id.cuid == first.cuid && lamports.contains(&n.id.lamport);
+            if in_run && n.removed_by.is_none() {
+                hidden += 1;
+            }
+            !in_run
+        });
+        self.size -= hidden;
+    }
+
+    /// Reveals the nodes of `ids` still hidden by `op_id`; used to roll back a local removal.
+    pub fn undo_remove(&mut self, ids: &[NodeId], op_id: &OperationId) {
+        for id in ids {
+            if let Some(pos) = self.position_of(id)
+                && self.nodes[pos].removed_by.as_ref() == Some(op_id)
+            {
+                self.nodes[pos].removed_by = None;
+                self.size += 1;
+            }
+        }
+    }
+
+    /// Returns the visible ranges `(index, len)` of the nodes of `ids`, last range first, so
+    /// that deleting them in turn keeps the other indexes valid.
+    pub fn visible_ranges_of(&self, ids: &BTreeSet<NodeId>) -> Vec<(usize, usize)> {
+        let mut ranges: Vec<(usize, usize)> = Vec::new();
+        for (index, node) in self
+            .nodes
+            .iter()
+            .filter(|n| n.removed_by.is_none())
+            .enumerate()
+        {
+            if !ids.contains(&node.id) {
+                continue;
+            }
+            match ranges.last_mut() {
+                Some((start, len)) if *start + *len == index => *len += 1,
+                _ => ranges.push((index, 1)),
+            }
+        }
+        ranges.reverse();
+        ranges
+    }
+
+    /// Returns the nodes of `ids` still hidden with their values, grouped by the index they
+    /// would take if revealed. Each index accounts for the groups before it, so that
+    /// inserting the groups in turn puts the values back in place.
+    pub fn hidden_groups_of(&self, ids: &BTreeSet<NodeId>) -> Vec<(usize, Vec<(NodeId, T)>)> {
+        let mut groups: Vec<(usize, Vec<(NodeId, T)>)> = Vec::new();
+        let mut visible = 0;
+        let mut last_visible = None;
+        let mut inserted = 0;
+        for node in &self.nodes {
+            if node.removed_by.is_none() {
+                visible += 1;
+                continue;
+            }
+            if !ids.contains(&node.id) {
+                continue;
+            }
+            match groups.last_mut() {
+                Some((_, nodes)) if last_visible == Some(visible) => {
+                    nodes.push((node.id.clone(), node.value.clone()))
+                }
+                _ => groups.push((
+                    visible + inserted,
+                    vec![(node.id.clone(), node.value.clone())],
+                )),
+            }
+            last_visible = Some(visible);
+            inserted += 1;
+        }
+        groups
+    }
+
+    /// Encodes the nodes as runs of consecutive ids with the same removal status.
+    pub fn encode(&self, w: &mut ByteWriter) {
+        let runs = self.runs();
+        w.write_varint(runs.len() as u64);
+        for run in runs {
+            let head = &run[0];
+            w.write_node_id(&head.id);
+            w.write_bool(head.removed_by.is_some());
+            w.write_varint(run.len() as u64);
+            for node in run {
+                node.value.encode(w);
+            }
+        }
+    }
+
+    pub fn decode(r: &mut ByteReader) -> Result<Self, DatatypeError> {
+        let run_count = r.read_len()?;
+        let mut rga = Self::default();
+        for _ in 0..run_count {
+            let head = r.read_node_id()?;
+            // Snapshots carry no pending local removals, so any remover id does.
+            let removed_by = r.read_bool()?.then(OperationId::new);
+            let len = r.read_len()?;
+            for i in 0..len as u64 {
+                rga.nodes.push(RgaNode {
+                    id: NodeId::new(head.lamport + i, &head.cuid),
+                    value: T::decode(r)?,
+                    removed_by: removed_by.clone(),
+                });
+            }
+            if removed_by.is_none() {
+                rga.size += len;
+            }
+        }
+        Ok(rga)
+    }
+
+    fn runs(&self) -> Vec<&[RgaNode<T>]> {
+        let mut runs = Vec::new();
+        let mut start = 0;
+        for i in 1..=self.nodes.len() {
+            let continues = i < self.nodes.len() && {
+                let (prev, node) = (&self.nodes[i - 1], &self.nodes[i]);
+                node.id.cuid == prev.id.cuid
+                    && node.id.lamport == prev.id.lamport + 1
+                    && node.removed_by.is_some() == prev.removed_by.is_some()
+            };
+            if !continues {
+                runs.push(&self.nodes[start..i]);
+                start = i;
+            }
+        }
+        runs
+    }
+}
+
+#[cfg(test)]
+mod tests_rga {
+    use crate::{
+        datatypes::crdts::rga::Rga,
+        types::{node_id::NodeId, operation_id::OperationId, uid::Cuid},
+        utils::codec::{ByteReader, ByteWriter},
+    };
+
+    fn to_string(rga: &Rga<char>) -> String {
+        rga.iter().collect()
+    }
+
+    #[test]
+    fn can_converge_concurrent_inserts_at_same_origin() {
+        let (c1, c2) = (Cuid::new(), Cuid::new());
+        let head = NodeId::head();
+        let a = NodeId::new(1, &c1);
+        let inserts = [
+            (head.clone(), a.clone(), vec!['a']),
+            (a.clone(), NodeId::new(2, &c1), vec!['b']),
+            (a.clone(), NodeId::new(2, &c2), vec!['c']),
+            (a.clone(), NodeId::new(3, &c2), vec!['d']),
+        ];
+        let mut forward = Rga::default();
+        for (origin, first, values) in inserts.iter() {
+            forward.insert(origin, first, values.clone()).unwrap();
+        }
+        let mut backward = Rga::default();
+        backward
+            .insert(&inserts[0].0, &inserts[0].1, inserts[0].2.clone())
+            .unwrap();
+        for (origin, first, values) in inserts[1..].iter().rev() {
+            backward.insert(origin, first, values.clone()).unwrap();
+        }
+        let expected = if c1 > c2 { "adbc" } else { "adcb" };
+        assert_eq!(to_string(&forward), expected);
+        assert_eq!(to_string(&backward), expected);
+    }
+
+    #[test]
+    fn can_insert_runs_and_remove() {
+        let cuid = Cuid::new();
+        let mut rga = Rga::default();
+        let first = NodeId::new(1, &cuid);
+        assert_eq!(
+            rga.insert(&NodeId::head(), &first, "hello".chars().collect())
+                .unwrap(),
+            Some(0)
+        );
+        // a duplicated run is ignored
+        assert_eq!(
+            rga.insert(&NodeId::head(), &first, "hello".chars().collect())
+                .unwrap(),
+            None
+        );
+        let origin = rga.origin_at(5).unwrap();
+        assert_eq!(origin, NodeId::new(5, &cuid));
+        rga.insert(&origin, &NodeId::new(6, &cuid), "!".chars().collect())
+            .unwrap();
+        assert_eq!(to_string(&rga), "hello!");
+
+        let mut remover = OperationId::new_with_cuid(&cuid);
+        remover.lamport = 7;
+        let ids = rga.ids_at(1, 4).unwrap();
+        assert_eq!(rga.remove(&ids, &remover), vec!['e', 'l', 'l', 'o']);
+        assert_eq!(to_string(&rga), "h!");
+        assert_eq!(rga.len(), 2);
+        assert!(rga.ids_at(1, 2).is_none());
+        assert!(rga.origin_at(3).is_none());
+
+        rga.undo_remove(&ids, &remover);
+        assert_eq!(to_string(&rga), "hello!");
+        rga.undo_insert(&NodeId::new(6, &cuid), 1);
+        assert_eq!(to_string(&rga), "hello");
+        assert_eq!(rga.len(), 5);
+    }
+
+    #[test]
+    fn can_encode_and_decode_rga_in_runs() {
+        let cuid = Cuid::new();
+        let mut rga = Rga::default();
+        rga.insert(
+            &NodeId::head(),
+            &NodeId::new(1, &cuid),
+            "abcdef".chars().collect(),
+        )
+        .unwrap();
+        let ids = rga.ids_at(2, 2).unwrap();
+        rga.remove(&ids, &OperationId::new());
+        assert_eq!(rga.runs().len(), 3);
+
+        let mut w = ByteWriter::new();
+        rga.encode(&mut w);
+        let bytes = w.into_boxed_slice();
+        let mut r = ByteReader::new(&bytes);
+        let decoded = Rga::<char>::decode(&mut r).unwrap();
+        assert!(r.finish().is_ok());
+        assert_eq!(to_string(&decoded), "abef");
+        assert_eq!(decoded.len(), 4);
+        assert_eq!(decoded.nodes.len(), 6);
+    }
+}
@@ -0,0 +1,368 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use derive_more::Display;
+
+use crate::{
+    DatatypeError,
+    datatypes::common::ReturnType,
+    errors::datatypes::InternalReason,
+    operations::{Operation, body::OperationBody},
+    types::{node_id::NodeId, operation_id::OperationId, uid::Cuid, value::Value},
+    utils::codec::{ByteReader, ByteWriter},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+struct SetElement {
+    value: Value,
+    /// The tags of the adds not yet removed; the element is present while any is left.
+    tags: BTreeSet<NodeId>,
+}
+
+/// An observed-remove set of [`Value`]s with add-wins semantics.
+///
+/// Every add attaches a unique tag, the [`NodeId`] of its operation, to the element, and a
+/// remove only drops the tags it has observed. An add concurrent with a remove therefore
+/// keeps the element present.
+///
+/// Removed tags are kept as tombstones, so that an add delivered twice (e.g., once in a
+/// snapshot and again as a transaction) cannot resurrect the element. A tombstone is
+/// garbage once the server has ordered its remove, since every later delivery then
+/// follows it; see [`SetCrdt::collect_garbage`].
+#[derive(Debug, Default, Clone, Display)]
+#[display("{:?}", self.iter().collect::<Vec<_>>())]
+pub struct SetCrdt {
+    /// Elements keyed by their encoding, which is canonical like [`Value`] equality.
+    elements: BTreeMap<Box<[u8]>, SetElement>,
+    tombstones: BTreeMap<NodeId, OperationId>,
+}
+
+fn element_key(value: &Value) -> Box<[u8]> {
+    let mut w = ByteWriter::new();
+    value.encode(&mut w);
+    w.into_boxed_slice()
+}
+
+impl SetCrdt {
+    pub fn contains(&self, value: &Value) -> bool {
+        self.elements.contains_key(&element_key(value))
+    }
+
+    /// Iterates over the elements in the order of their encoding.
+    pub fn iter(&self) -> impl Iterator<Item = &Value> {
+        self.elements.values().map(|e| &e.value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    #[cfg(test)]
+    pub fn tombstone_count(&self) -> usize {
+        self.tombstones.len()
+    }
+
+    /// Attaches `tag` to `value` unless the tag has been removed, returning whether the
+    /// element was absent before.
+    fn add(&mut self, value: &Value, tag: NodeId) -> bool {
+        if self.tombstones.contains_key(&tag) {
+            return false;
+        }
+        let element = self
+            .elements
+            .entry(element_key(value))
+            .or_insert_with(|| SetElement {
+                value: value.clone(),
+                tags: BTreeSet::new(),
+            });
+        let was_absent = element.tags.is_empty();
+        element.tags.insert(tag);
+        was_absent
+    }
+
+    /// Drops `tags` from `value` on behalf of `op_id`, returning whether the element
+    /// was present before.
+    fn remove(&mut self, value: &Value, tags: &[NodeId], op_id: &OperationId) -> bool {
+        for tag in tags {
+            self.tombstones.insert(tag.clone(), op_id.clone());
+        }
+        let key = element_key(value);
+        let Some(element) = self.elements.get_mut(&key) else {
+            return false;
+        };
+        for tag in tags {
+            element.tags.remove(tag);
+        }
+        if element.tags.is_empty() {
+            self.elements.remove(&key);
+        }
+        true
+    }
+
+    pub fn execute_local_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &mut Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::SetAdd(ref body) => {
+                Ok(ReturnType::Set(self.add(&body.value, NodeId::from(op_id))))
+            }
+            OperationBody::SetRemove(ref mut body) => {
+                body.tags = self
+                    .elements
+                    .get(&element_key(&body.value))
+                    .map(|e| e.tags.iter().cloned().collect())
+                    .unwrap_or_default();
+                Ok(ReturnType::Set(self.remove(&body.value, &body.tags, op_id)))
+            }
+            _ => Err(
+                InternalReason::ExecuteOperation(format!("unexpected {}", op.body)).into_error(),
+            ),
+        }
+    }
+
+    pub fn execute_remote_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::SetAdd(ref body) => {
+                self.add(&body.value, NodeId::from(op_id));
+                Ok(ReturnType::Set(self.contains(&body.value)))
+            }
+            OperationBody::SetRemove(ref body) => {
+                self.remove(&body.value, &body.tags, op_id);
+                Ok(ReturnType::Set(self.contains(&body.value)))
+            }
+            _ => Err(
+                InternalReason::ExecuteOperation(format!("unexpected {}", op.body)).into_error(),
+            ),
+        }
+    }
+
+    pub fn execute_inverse_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::SetAdd(ref body) => {
+                let key = element_key(&body.value);
+                if let Some(element) = self.elements.get_mut(&key) {
+                    element.tags.remove(&NodeId::from(op_id));
+                    if element.tags.is_empty() {
+                        self.elements.remove(&key);
+                    }
+                }
+            }
+            OperationBody::SetRemove(ref body) => {
+                for tag in body.tags.iter() {
+                    // A tag removed again later, e.g., by a remote remove, stays removed.
+                    if self.tombstones.get(tag) == Some(op_id) {
+                        self.tombstones.remove(tag);
+                        self.add(&body.value, tag.clone());
+                    }
+                }
+            }
+            _ => {
+                return Err(
+                    InternalReason::ExecuteOperation(format!("unexpected {}", op.body))
+                        .into_error(),
+                );
+            }
+        }
+        Ok(ReturnType::Set(false))
+    }
+
+    /// Drops the tombstones of removes the server has ordered: those of other clients,
+    /// which are only received from the server, and those of `cuid` up to `synced_cseq`.
+    pub fn collect_garbage(&mut self, cuid: &Cuid, synced_cseq: u64) {
+        self.tombstones
+            .retain(|_, removed_by| removed_by.cuid == *cuid && removed_by.cseq > synced_cseq);
+    }
+
+    /// Returns a remove of the value a local add added, or an add of the value a remove
+    /// removed, while no other tag keeps or re-adds it.
+    pub fn compensate(&self, op_id: &OperationId, op: &Operation) -> Vec<Operation> {
+        match op.body {
+            // Tags of the local client only, so that adds of others are kept; a previous undo
+            // may have added the value again under another tag.
+            OperationBody::SetAdd(ref body) => match self.elements.get(&element_key(&body.value)) {
+                Some(e) if e.tags.iter().all(|tag| tag.cuid == op_id.cuid) => {
+                    vec![Operation::new_set_remove(body.value.clone())]
+                }
+                _ => vec![],
+            },
+            OperationBody::SetRemove(ref body) => {
+                if body.tags.is_empty() || self.contains(&body.value) {
+                    return vec![];
+                }
+                vec![Operation::new_set_add(body.value.clone())]
+            }
+            _ => vec![],
+        }
+    }
+
+    pub fn to_bytes(&self) -> Box<[u8]> {
+        let mut w = ByteWriter::new();
+        w.write_varint(self.elements.len() as u64);
+        for element in self.elements.values() {
+            element.value.encode(&mut w);
+            w.write_varint(element.tags.len() as u64);
+            for tag in element.tags.iter() {
+                w.write_node_id(tag);
+            }
+        }
+        w.write_varint(self.tombstones.len() as u64);
+        for (tag, removed_by) in self.tombstones.iter() {
+            w.write_node_id(tag);
+            w.write_op_id(removed_by);
+        }
+        w.into_boxed_slice()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DatatypeError> {
+        let mut r = ByteReader::new(bytes);
+        let mut set = Self::default();
+        for _ in 0..r.read_len()? {
+            let value = Value::decode(&mut r)?;
+            let mut tags = BTreeSet::new();
+            for _ in 0..r.read_len()? {
+                tags.insert(r.read_node_id()?);
+            }
+            set.elements
+                .insert(element_key(&value), SetElement { value, tags });
+        }
+        for _ in 0..r.read_len()? {
+            let tag = r.read_node_id()?;
+            set.tombstones.insert(tag, r.read_op_id()?);
+        }
+        r.finish()?;
+        Ok(set)
+    }
+}
+
+#[cfg(test)]
+mod tests_set_crdt {
+    use crate::{
+        datatypes::crdts::set_crdt::SetCrdt,
+        operations::Operation,
+        types::{operation_id::OperationId, uid::Cuid, value::Value},
+    };
+
+    fn op_id(lamport: u64, cuid: &Cuid, cseq: u64) -> OperationId {
+        let mut op_id = OperationId::new_with_cuid(cuid);
+        op_id.lamport = lamport;
+        op_id.cseq = cseq;
+        op_id
+    }
+
+    #[test]
+    fn can_resolve_concurrent_add_and_remove_as_add_wins() {
+        let (c1, c2) = (Cuid::new(), Cuid::new());
+        let mut set1 = SetCrdt::default();
+        let mut set2 = SetCrdt::default();
+
+        let mut add = Operation::new_set_add("x".into());
+        set1.execute_local_operation(&op_id(1, &c1, 1), &mut add)
+            .unwrap();
+        set2.execute_remote_operation(&op_id(1, &c1, 1), &add)
+            .unwrap();
+
+        // client2 removes the add it observed while client1 adds again concurrently
+        let mut remove = Operation::new_set_remove("x".into());
+        let mut re_add = Operation::new_set_add("x".into());
+        set2.execute_local_operation(&op_id(2, &c2, 1), &mut remove)
+            .unwrap();
+        set1.execute_local_operation(&op_id(2, &c1, 2), &mut re_add)
+            .unwrap();
+        assert!(!set2.contains(&Value::from("x")));
+        set1.execute_remote_operation(&op_id(2, &c2, 1), &remove)
+            .unwrap();
+        set2.execute_remote_operation(&op_id(2, &c1, 2), &re_add)
+            .unwrap();
+
+        assert!(set1.contains(&Value::from("x")));
+        assert!(set2.contains(&Value::from("x")));
+        assert_eq!(set1.to_bytes(), set2.to_bytes());
+    }
+
+    #[test]
+    fn can_ignore_duplicated_adds_until_garbage_collected() {
+        let (c1, c2) = (Cuid::new(), Cuid::new());
+        let mut set = SetCrdt::default();
+        let add = Operation::new_set_add(1.into());
+        set.execute_remote_operation(&op_id(1, &c2, 1), &add)
+            .unwrap();
+        let mut remove = Operation::new_set_remove(1.into());
+        set.execute_local_operation(&op_id(2, &c1, 1), &mut remove)
+            .unwrap();
+
+        set.execute_remote_operation(&op_id(1, &c2, 1), &add)
+            .unwrap();
+        assert!(set.is_empty());
+
+        set.collect_garbage(&c1, 0);
+        assert_eq!(
+            set.tombstone_count(),
+            1,
+            "the remove is not acknowledged yet"
+        );
+        set.collect_garbage(&c1, 1);
+        assert_eq!(set.tombstone_count(), 0);
+    }
+
+    #[test]
+    fn can_undo_local_operations() {
+        let cuid = Cuid::new();
+        let mut set = SetCrdt::default();
+        let mut add1 = Operation::new_set_add("a".into());
+        let mut add2 = Operation::new_set_add("a".into());
+        let mut remove = Operation::new_set_remove("a".into());
+        set.execute_local_operation(&op_id(1, &cuid, 1), &mut add1)
+            .unwrap();
+        set.execute_local_operation(&op_id(2, &cuid, 1), &mut add2)
+            .unwrap();
+        set.execute_local_operation(&op_id(3, &cuid, 1), &mut remove)
+            .unwrap();
+        assert!(set.is_empty());
+
+        set.execute_inverse_operation(&op_id(3, &cuid, 1), &remove)
+            .unwrap();
+        assert_eq!(set.tombstone_count(), 0);
+        set.execute_inverse_operation(&op_id(2, &cuid, 1), &add2)
+            .unwrap();
+        assert!(set.contains(&Value::from("a")));
+        set.execute_inverse_operation(&op_id(1, &cuid, 1), &add1)
+            .unwrap();
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn can_serialize_and_deserialize_set_crdt() {
+        let cuid = Cuid::new();
+        let mut set = SetCrdt::default();
+        for (i, v) in [Value::from("a"), Value::Int(1), Value::Null]
+            .into_iter()
+            .enumerate()
+        {
+            let mut op = Operation::new_set_add(v);
+            set.execute_local_operation(&op_id(i as u64 + 1, &cuid, 1), &mut op)
+                .unwrap();
+        }
+        let mut remove = Operation::new_set_remove(Value::Null);
+        set.execute_local_operation(&op_id(4, &cuid, 1), &mut remove)
+            .unwrap();
+
+        let bytes = set.to_bytes();
+        let restored = SetCrdt::from_bytes(&bytes).unwrap();
+        assert_eq!(restored.elements, set.elements);
+        assert_eq!(restored.tombstones, set.tombstones);
+        assert!(SetCrdt::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
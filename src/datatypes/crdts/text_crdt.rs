@@ -0,0 +1,295 @@
+use derive_more::Display;
+
+use crate::{
+    DatatypeError,
+    datatypes::{
+        common::ReturnType,
+        crdts::{Compensation, rga::Rga},
+        undo::NodeAliases,
+    },
+    errors::datatypes::InternalReason,
+    operations::{Operation, body::OperationBody},
+    types::{
+        node_id::{NodeId, NodeRun},
+        operation_id::OperationId,
+    },
+    utils::codec::{ByteReader, ByteWriter},
+};
+
+/// A text of chars on top of an [`Rga`].
+///
+/// An insert carries its chars as one run, which takes a lamport per char, and a delete
+/// carries the removed nodes as runs, so editing a range costs a single operation.
+#[derive(Debug, Default, Clone, Display)]
+#[display("{:?}", rga.iter().collect::<String>())]
+pub struct TextCrdt {
+    rga: Rga<char>,
+}
+
+impl TextCrdt {
+    /// Returns the number of chars.
+    pub fn len(&self) -> usize {
+        self.rga.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rga.is_empty()
+    }
+
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.rga.iter().copied()
+    }
+
+    pub fn execute_local_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &mut Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::TextInsert(ref mut body) => {
+                body.origin = self.rga.origin_at(body.index).ok_or_else(|| {
+                    DatatypeError::OutOfBounds(format!(
+                        "insert position {} > len {}",
+                        body.index,
+                        self.len()
+                    ))
+                })?;
+                self.rga.insert(
+                    &body.origin,
+                    &NodeId::from(op_id),
+                    body.text.chars().collect(),
+                )?;
+                Ok(ReturnType::Text(String::new()))
+            }
+            OperationBody::TextDelete(ref mut body) => {
+                let ids = self.rga.ids_at(body.index, body.len).ok_or_else(|| {
+                    DatatypeError::OutOfBounds(format!(
+                        "delete range {}..{} > len {}",
+                        body.index,
+                        body.index.saturating_add(body.len),
+                        self.len()
+                    ))
+                })?;
+                body.targets = NodeRun::from_ids(&ids);
+                let removed = self.rga.remove(&ids, op_id);
+                Ok(ReturnType::Text(removed.into_iter().collect()))
+            }
+            _ => Err(
+                InternalReason::ExecuteOperation(format!("unexpected {}", op.body)).into_error(),
+            ),
+        }
+    }
+
+    pub fn execute_remote_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::TextInsert(ref body) => {
+                self.rga.insert(
+                    &body.origin,
+                    &NodeId::from(op_id),
+                    body.text.chars().collect(),
+                )?;
+                Ok(ReturnType::Text(String::new()))
+            }
+            OperationBody::TextDelete(ref body) => {
+                let removed = self.rga.remove(&expand(&body.targets), op_id);
+                Ok(ReturnType::Text(removed.into_iter().collect()))
+            }
+            _ => Err(
+                InternalReason::ExecuteOperation(format!("unexpected {}", op.body)).into_error(),
+            ),
+        }
+    }
+
+    pub fn execute_inverse_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::TextInsert(ref body) => {
+                self.rga
+                    .undo_insert(&NodeId::from(op_id), body.char_count() as usize);
+            }
+            OperationBody::TextDelete(ref body) => {
+                self.rga.undo_remove(&expand(&body.targets), op_id);
+            }
+            _ => {
+                return Err(
+                    InternalReason::ExecuteOperation(format!("unexpected {}", op.body))
+                        .into_error(),
+                );
+            }
+        }
+        Ok(ReturnType::Text(String::new()))
+    }
+
+    /// Returns deletes of the still-visible inserted chars, or re-inserts of the removed ones.
+    pub fn compensate(
+        &self,
+        op_id: &OperationId,
+        op: &Operation,
+        aliases: &NodeAliases,
+    ) -> Vec<Compensation> {
+        match op.body {
+            OperationBody::TextInsert(ref body) => {
+                let run = NodeRun {
+                    first: NodeId::from(op_id),
+                    len: body.char_count(),
+                };
+                let ids = run.ids().map(|id| aliases.resolve(&id)).collect();
+                self.rga
+                    .visible_ranges_of(&ids)
+                    .into_iter()
+                    .map(|(index, len)| Operation::new_text_delete(index, len).into())
+                    .collect()
+            }
+            OperationBody::TextDelete(ref body) => {
+                let ids = body
+                    .targets
+                    .iter()
+                    .flat_map(|run| run.ids())
+                    .map(|id| aliases.resolve(&id))
+                    .collect();
+                self.rga
+                    .hidden_groups_of(&ids)
+                    .into_iter()
+                    .map(|(index, nodes)| {
+                        let (revives, chars): (Vec<_>, String) = nodes.into_iter().unzip();
+                        Compensation {
+                            op: Operation::new_text_insert(index, chars),
+                            revives,
+                        }
+                    })
+                    .collect()
+            }
+            _ => vec![],
+        }
+    }
+
+    pub fn to_bytes(&self) -> Box<[u8]> {
+        let mut w = ByteWriter::new();
+        self.rga.encode(&mut w);
+        w.into_boxed_slice()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DatatypeError> {
+        let mut r = ByteReader::new(bytes);
+        let rga = Rga::decode(&mut r)?;
+        r.finish()?;
+        Ok(Self { rga })
+    }
+}
+
+fn expand(runs: &[NodeRun]) -> Vec<NodeId> {
+    runs.iter().flat_map(|run| run.ids()).collect()
+}
+
+#[cfg(test)]
+mod tests_text_crdt {
+    use crate::{
+        DatatypeError,
+        datatypes::crdts::text_crdt::TextCrdt,
+        operations::Operation,
+        types::{operation_id::OperationId, uid::Cuid},
+    };
+
+    fn op_id(lamport: u64, cuid: &Cuid) -> OperationId {
+        let mut op_id = OperationId::new_with_cuid(cuid);
+        op_id.lamport = lamport;
+        op_id.cseq = 1;
+        op_id
+    }
+
+    fn text(crdt: &TextCrdt) -> String {
+        crdt.chars().collect()
+    }
+
+    #[test]
+    fn can_edit_with_multibyte_chars() {
+        let cuid = Cuid::new();
+        let mut crdt = TextCrdt::default();
+        let mut insert = Operation::new_text_insert(0, "héllo wörld".into());
+        crdt.execute_local_operation(&op_id(1, &cuid), &mut insert)
+            .unwrap();
+        assert_eq!(crdt.len(), 11);
+
+        // the run of 11 chars takes lamports 1..=11
+        let mut insert = Operation::new_text_insert(5, ",".into());
+        crdt.execute_local_operation(&op_id(12, &cuid), &mut insert)
+            .unwrap();
+        let mut delete = Operation::new_text_delete(7, 5);
+        crdt.execute_local_operation(&op_id(13, &cuid), &mut delete)
+            .unwrap();
+        assert_eq!(text(&crdt), "héllo, ");
+        let crate::operations::body::OperationBody::TextDelete(body) = &delete.body else {
+            unreachable!()
+        };
+        assert_eq!(body.targets.len(), 1, "a contiguous range is one run");
+
+        assert!(matches!(
+            crdt.execute_local_operation(&op_id(14, &cuid), &mut Operation::new_text_delete(5, 3)),
+            Err(DatatypeError::OutOfBounds(_))
+        ));
+
+        crdt.execute_inverse_operation(&op_id(13, &cuid), &delete)
+            .unwrap();
+        crdt.execute_inverse_operation(&op_id(12, &cuid), &insert)
+            .unwrap();
+        assert_eq!(text(&crdt), "héllo wörld");
+    }
+
+    #[test]
+    fn can_converge_concurrent_edits() {
+        let (c1, c2) = (Cuid::new(), Cuid::new());
+        let mut text1 = TextCrdt::default();
+        let mut text2 = TextCrdt::default();
+
+        let mut base = Operation::new_text_insert(0, "ac".into());
+        text1
+            .execute_local_operation(&op_id(1, &c1), &mut base)
+            .unwrap();
+        text2
+            .execute_remote_operation(&op_id(1, &c1), &base)
+            .unwrap();
+
+        let mut op1 = Operation::new_text_insert(1, "b".into());
+        let mut op2 = Operation::new_text_delete(0, 2);
+        text1
+            .execute_local_operation(&op_id(3, &c1), &mut op1)
+            .unwrap();
+        text2
+            .execute_local_operation(&op_id(3, &c2), &mut op2)
+            .unwrap();
+        text1
+            .execute_remote_operation(&op_id(3, &c2), &op2)
+            .unwrap();
+        text2
+            .execute_remote_operation(&op_id(3, &c1), &op1)
+            .unwrap();
+
+        assert_eq!(text(&text1), "b");
+        assert_eq!(text(&text2), "b");
+        assert_eq!(text1.to_bytes(), text2.to_bytes());
+    }
+
+    #[test]
+    fn can_serialize_and_deserialize_text_crdt() {
+        let cuid = Cuid::new();
+        let mut crdt = TextCrdt::default();
+        let mut insert = Operation::new_text_insert(0, "hello".into());
+        crdt.execute_local_operation(&op_id(1, &cuid), &mut insert)
+            .unwrap();
+        let mut delete = Operation::new_text_delete(1, 1);
+        crdt.execute_local_operation(&op_id(6, &cuid), &mut delete)
+            .unwrap();
+
+        let bytes = crdt.to_bytes();
+        let restored = TextCrdt::from_bytes(&bytes).unwrap();
+        assert_eq!(text(&restored), "hllo");
+        assert!(TextCrdt::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
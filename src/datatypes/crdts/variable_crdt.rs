@@ -0,0 +1,195 @@
+use derive_more::Display;
+
+use crate::{
+    DatatypeError,
+    datatypes::common::ReturnType,
+    errors::datatypes::InternalReason,
+    operations::{Operation, body::OperationBody},
+    types::{operation_id::OperationId, value::Value},
+    utils::codec::{ByteReader, ByteWriter},
+};
+
+/// A last-writer-wins register.
+///
+/// Concurrent writes are ordered by their [`OperationId`] (lamport, then cuid),
+/// so every replica keeps the same winner regardless of delivery order.
+#[derive(Debug, Default, Clone, Display)]
+#[display("{value}")]
+pub struct VariableCrdt {
+    value: Value,
+    written_by: OperationId,
+}
+
+impl VariableCrdt {
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// Stores `value` if `op_id` wins over the current write, returning whether it was applied.
+    pub fn set(&mut self, op_id: &OperationId, value: Value) -> bool {
+        if *op_id > self.written_by {
+            self.value = value;
+            self.written_by = op_id.clone();
+            return true;
+        }
+        false
+    }
+
+    pub fn execute_local_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &mut Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::VariableSet(ref mut body) => {
+                // A local write always carries the largest lamport seen so far, so it wins.
+                let prev_value = std::mem::replace(&mut self.value, body.value.clone());
+                let prev_id = std::mem::replace(&mut self.written_by, op_id.clone());
+                body.prev = Some((prev_value.clone(), prev_id));
+                Ok(ReturnType::Variable(prev_value))
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(
+                InternalReason::ExecuteOperation(format!("unexpected {}", op.body)).into_error(),
+            ),
+        }
+    }
+
+    pub fn execute_remote_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::VariableSet(ref body) => {
+                self.set(op_id, body.value.clone());
+                Ok(ReturnType::Variable(self.value.clone()))
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(
+                InternalReason::ExecuteOperation(format!("unexpected {}", op.body)).into_error(),
+            ),
+        }
+    }
+
+    pub fn execute_inverse_operation(
+        &mut self,
+        op_id: &OperationId,
+        op: &Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::VariableSet(ref body) => {
+                // Only undo the write if it is still the winner; a later write stays in place.
+                if self.written_by == *op_id
+                    && let Some((prev_value, prev_id)) = &body.prev
+                {
+                    self.value = prev_value.clone();
+                    self.written_by = prev_id.clone();
+                }
+                Ok(ReturnType::Variable(self.value.clone()))
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(
+                InternalReason::ExecuteOperation(format!("unexpected {}", op.body)).into_error(),
+            ),
+        }
+    }
+
+    /// Returns the set writing back the value `op` replaced, if the value it wrote is still in
+    /// place.
+    pub fn compensate(&self, op: &Operation) -> Vec<Operation> {
+        match op.body {
+            OperationBody::VariableSet(ref body) => match &body.prev {
+                Some((prev_value, _)) if self.value == body.value => {
+                    vec![Operation::new_variable_set(prev_value.clone())]
+                }
+                _ => vec![],
+            },
+            _ => vec![],
+        }
+    }
+
+    pub fn to_bytes(&self) -> Box<[u8]> {
+        let mut w = ByteWriter::new();
+        w.write_op_id(&self.written_by);
+        self.value.encode(&mut w);
+        w.into_boxed_slice()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DatatypeError> {
+        let mut r = ByteReader::new(bytes);
+        let written_by = r.read_op_id()?;
+        let value = Value::decode(&mut r)?;
+        r.finish()?;
+        Ok(Self { value, written_by })
+    }
+}
+
+#[cfg(test)]
+mod tests_variable_crdt {
+    use crate::{
+        datatypes::crdts::variable_crdt::VariableCrdt,
+        operations::Operation,
+        types::{operation_id::OperationId, uid::Cuid, value::Value},
+    };
+
+    fn op_id(lamport: u64, cuid: &Cuid) -> OperationId {
+        let mut op_id = OperationId::new_with_cuid(cuid);
+        op_id.lamport = lamport;
+        op_id.cseq = 1;
+        op_id
+    }
+
+    #[test]
+    fn can_converge_regardless_of_order() {
+        let (c1, c2) = (Cuid::new(), Cuid::new());
+        let writes = [
+            (op_id(1, &c1), Operation::new_variable_set("a".into())),
+            (op_id(2, &c1), Operation::new_variable_set("b".into())),
+            (op_id(2, &c2), Operation::new_variable_set("c".into())),
+        ];
+        let winner = if c1 > c2 { "b" } else { "c" };
+
+        let mut forward = VariableCrdt::default();
+        for (id, op) in writes.iter() {
+            forward.execute_remote_operation(id, op).unwrap();
+        }
+        let mut backward = VariableCrdt::default();
+        for (id, op) in writes.iter().rev() {
+            backward.execute_remote_operation(id, op).unwrap();
+        }
+        assert_eq!(forward.value(), &Value::from(winner));
+        assert_eq!(backward.value(), &Value::from(winner));
+    }
+
+    #[test]
+    fn can_undo_local_writes() {
+        let cuid = Cuid::new();
+        let mut crdt = VariableCrdt::default();
+        let mut op1 = Operation::new_variable_set(1.into());
+        let mut op2 = Operation::new_variable_set(2.into());
+        crdt.execute_local_operation(&op_id(1, &cuid), &mut op1)
+            .unwrap();
+        crdt.execute_local_operation(&op_id(2, &cuid), &mut op2)
+            .unwrap();
+        assert_eq!(crdt.value(), &Value::Int(2));
+
+        crdt.execute_inverse_operation(&op_id(2, &cuid), &op2)
+            .unwrap();
+        assert_eq!(crdt.value(), &Value::Int(1));
+        crdt.execute_inverse_operation(&op_id(1, &cuid), &op1)
+            .unwrap();
+        assert_eq!(crdt.value(), &Value::Null);
+    }
+
+    #[test]
+    fn can_serialize_and_deserialize_variable_crdt() {
+        let mut crdt = VariableCrdt::default();
+        crdt.set(&op_id(3, &Cuid::new()), "hello".into());
+        let bytes = crdt.to_bytes();
+        let restored = VariableCrdt::from_bytes(&bytes).unwrap();
+        assert_eq!(restored.value(), crdt.value());
+        assert_eq!(restored.written_by, crdt.written_by);
+        assert!(VariableCrdt::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,461 @@
+use std::{fmt::Debug, marker::PhantomData, sync::Arc};
+
+use tracing::trace;
+
+use crate::{
+    DatatypeError, IntoString,
+    datatypes::{
+        common::{ReturnType, datatype_instrument},
+        crdts::Crdt,
+        datatype::DatatypeBlanket,
+        transactional::{TransactionContext, TransactionalDatatype},
+    },
+    errors::{BoxedError, datatypes::InternalReason},
+    operations::Operation,
+    types::{operation_id::OperationId, value::Value},
+};
+
+/// Identifies an operation executed on a [`CustomCrdt`].
+///
+/// Ids are totally ordered consistently with causality: an operation is ordered after every
+/// operation its client had applied when executing it, and concurrent operations are ordered
+/// by `lamport` and then by `cuid`, the same on every replica.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CustomOperationId {
+    /// The Lamport timestamp of the operation.
+    pub lamport: u64,
+    /// The unique id of the client that executed the operation.
+    pub cuid: String,
+    /// The sequence number of the operation among those of its client.
+    pub cseq: u64,
+}
+
+impl From<&OperationId> for CustomOperationId {
+    fn from(op_id: &OperationId) -> Self {
+        Self {
+            lamport: op_id.lamport,
+            cuid: op_id.cuid.to_string(),
+            cseq: op_id.cseq,
+        }
+    }
+}
+
+/// A user-defined conflict-free replicated datatype.
+///
+/// Implement this trait to add a domain-specific CRDT, register it with
+/// [`ClientBuilder::with_custom_crdt`](crate::ClientBuilder::with_custom_crdt), and build
+/// instances with [`DatatypeBuilder::build_custom`](crate::DatatypeBuilder::build_custom).
+/// Custom datatypes get transactions, push buffering and synchronization like the built-in
+/// ones.
+///
+/// Operation bodies are opaque bytes that only the implementation interprets. Every replica
+/// applies the same operations in an order consistent with causality, so the implementation
+/// must converge when concurrent operations are applied in different orders.
+///
+/// # Examples
+///
+/// ```
+/// use qortoo::{Client, CustomCrdt, CustomOperationId, DatatypeError, Value};
+///
+/// /// A register keeping the largest number ever written.
+/// #[derive(Debug, Default, Clone)]
+/// struct MaxRegister(u64);
+///
+/// impl CustomCrdt for MaxRegister {
+///     const TYPE_NAME: &'static str = "max-register";
+///
+///     fn execute_local_operation(
+///         &mut self,
+///         op_id: &CustomOperationId,
+///         body: &mut Vec<u8>,
+///     ) -> Result<Value, DatatypeError> {
+///         // record the overwritten state so that a rollback can restore it
+///         body.extend_from_slice(&self.0.to_le_bytes());
+///         self.execute_remote_operation(op_id, body)
+///     }
+///
+///     fn execute_remote_operation(
+///         &mut self,
+///         _: &CustomOperationId,
+///         body: &[u8],
+///     ) -> Result<Value, DatatypeError> {
+///         self.0 = self.0.max(u64::from_le_bytes(body[..8].try_into().unwrap()));
+///         Ok(Value::Int(self.0 as i64))
+///     }
+///
+///     fn execute_inverse_operation(
+///         &mut self,
+///         _: &CustomOperationId,
+///         body: &[u8],
+///     ) -> Result<Value, DatatypeError> {
+///         self.0 = u64::from_le_bytes(body[8..].try_into().unwrap());
+///         Ok(Value::Int(self.0 as i64))
+///     }
+///
+///     fn to_bytes(&self) -> Vec<u8> {
+///         self.0.to_le_bytes().to_vec()
+///     }
+///
+///     fn from_bytes(bytes: &[u8]) -> Result<Self, DatatypeError> {
+///         let bytes = bytes
+///             .try_into()
+///             .map_err(|_| DatatypeError::Disallowed("invalid max-register".into()))?;
+///         Ok(Self(u64::from_le_bytes(bytes)))
+///     }
+/// }
+///
+/// let client = Client::builder("doc-example", "custom-crdt-test")
+///     .with_custom_crdt::<MaxRegister>()
+///     .build()
+///     .unwrap();
+/// let register = client
+///     .create_datatype("test-max")
+///     .build_custom::<MaxRegister>()
+///     .unwrap();
+/// register.execute(7u64.to_le_bytes().to_vec()).unwrap();
+/// register.execute(3u64.to_le_bytes().to_vec()).unwrap();
+/// assert_eq!(register.read(|r| r.0), 7);
+/// ```
+pub trait CustomCrdt: Debug + Default + Clone + Send + Sync + 'static {
+    /// The name identifying this type; every client sharing a datatype uses the same name.
+    const TYPE_NAME: &'static str;
+
+    /// Executes an operation of this client.
+    ///
+    /// The body may be extended with what is needed to invert the operation on rollback;
+    /// the body as left here is what other replicas receive. Returning an error rejects
+    /// the operation. Defaults to [`execute_remote_operation`](Self::execute_remote_operation).
+    fn execute_local_operation(
+        &mut self,
+        op_id: &CustomOperationId,
+        body: &mut Vec<u8>,
+    ) -> Result<Value, DatatypeError> {
+        self.execute_remote_operation(op_id, body)
+    }
+
+    /// Executes an operation of another client.
+    fn execute_remote_operation(
+        &mut self,
+        op_id: &CustomOperationId,
+        body: &[u8],
+    ) -> Result<Value, DatatypeError>;
+
+    /// Undoes an operation of this client when its transaction is rolled back.
+    ///
+    /// The operation is the latest not yet inverted, and `body` is as left by
+    /// [`execute_local_operation`](Self::execute_local_operation).
+    fn execute_inverse_operation(
+        &mut self,
+        op_id: &CustomOperationId,
+        body: &[u8],
+    ) -> Result<Value, DatatypeError>;
+
+    /// Serializes the state into a snapshot, e.g., for subscribing clients.
+    fn to_bytes(&self) -> Vec<u8>;
+
+    /// Restores the state from a snapshot made by [`to_bytes`](Self::to_bytes).
+    fn from_bytes(bytes: &[u8]) -> Result<Self, DatatypeError>;
+
+    /// Returns the memory the state occupies in bytes.
+    ///
+    /// Defaults to the inline size of the type; override it if the state owns heap memory.
+    fn size(&self) -> u64 {
+        size_of::<Self>() as u64
+    }
+}
+
+/// A datatype whose CRDT is a [`CustomCrdt`], without knowing which one.
+///
+/// This is what [`Client::get_datatype`](crate::Client::get_datatype) returns for custom
+/// datatypes; use [`downcast`](Self::downcast) to get the typed [`Custom`].
+#[derive(Clone)]
+pub struct CustomDatatype {
+    datatype: Arc<TransactionalDatatype>,
+}
+
+impl CustomDatatype {
+    pub(crate) fn new(datatype: Arc<TransactionalDatatype>) -> Self {
+        Self { datatype }
+    }
+
+    /// Returns the [`CustomCrdt::TYPE_NAME`] of the CRDT of this datatype.
+    pub fn get_type_name(&self) -> &'static str {
+        let mutable = self.datatype.mutable.read();
+        match &mutable.crdt {
+            Crdt::Custom(c) => c.type_name(),
+            _ => unreachable!("custom datatype must have a custom crdt"),
+        }
+    }
+
+    /// Returns the typed datatype if its CRDT is a `T`.
+    pub fn downcast<T: CustomCrdt>(self) -> Option<Custom<T>> {
+        let is_t = {
+            let mutable = self.datatype.mutable.read();
+            match &mutable.crdt {
+                Crdt::Custom(c) => c.downcast_ref::<T>().is_some(),
+                _ => unreachable!("custom datatype must have a custom crdt"),
+            }
+        };
+        is_t.then(|| Custom::new(self.datatype))
+    }
+}
+
+impl DatatypeBlanket for CustomDatatype {
+    fn get_core(&self) -> &TransactionalDatatype {
+        self.datatype.as_ref()
+    }
+}
+
+/// A datatype whose CRDT is the user-defined `T`.
+///
+/// See [`CustomCrdt`] for an example.
+pub struct Custom<T: CustomCrdt> {
+    datatype: Arc<TransactionalDatatype>,
+    tx_ctx: Arc<TransactionContext>,
+    _crdt: PhantomData<fn() -> T>,
+}
+
+impl<T: CustomCrdt> Clone for Custom<T> {
+    fn clone(&self) -> Self {
+        Self {
+            datatype: self.datatype.clone(),
+            tx_ctx: self.tx_ctx.clone(),
+            _crdt: PhantomData,
+        }
+    }
+}
+
+impl<T: CustomCrdt> Custom<T> {
+    fn new(datatype: Arc<TransactionalDatatype>) -> Self {
+        Self {
+            datatype,
+            tx_ctx: Default::default(),
+            _crdt: PhantomData,
+        }
+    }
+
+    datatype_instrument! {
+    /// Executes an operation with the given opaque `body` on the CRDT.
+    ///
+    /// Returns what [`CustomCrdt::execute_local_operation`] returns.
+    ///
+    /// # Errors
+    /// - [`DatatypeError::NotWritable`] if the datatype is not writable
+    /// - any error returned by [`CustomCrdt::execute_local_operation`]
+    pub fn execute(&self, body: Vec<u8>) -> Result<Value, DatatypeError> {
+        let ret = self
+            .datatype
+            .execute_local_operation_as_tx(self.tx_ctx.clone(), Operation::new_custom(body))?;
+        trace!("executed -> {ret:?}");
+        match ret {
+            ReturnType::Custom(v) => Ok(v),
+            _ => {
+                Err(InternalReason::ExecuteOperation("unexpected return type".into()).into_error())
+            }
+        }
+    }}
+
+    /// Reads the state of the CRDT.
+    pub fn read<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        let mutable = self.datatype.mutable.read();
+        match &mutable.crdt {
+            Crdt::Custom(c) => f(c
+                .downcast_ref()
+                .expect("custom datatype must have the crdt it was built with")),
+            _ => unreachable!("custom datatype must have a custom crdt"),
+        }
+    }
+
+    /// Returns the memory the state of the CRDT occupies, as reported by [`CustomCrdt::size`].
+    pub fn get_memory_size(&self) -> u64 {
+        let mutable = self.datatype.mutable.read();
+        match &mutable.crdt {
+            Crdt::Custom(c) => c.size(),
+            _ => unreachable!("custom datatype must have a custom crdt"),
+        }
+    }
+
+    datatype_instrument! {
+    /// Executes multiple operations atomically within a transaction.
+    ///
+    /// If the transaction function returns an error, all operations within the transaction
+    /// are rolled back by [`CustomCrdt::execute_inverse_operation`] in reverse order.
+    pub fn transaction<F>(
+        &self,
+        tag: impl IntoString,
+        tx_func: F,
+    ) -> Result<(), DatatypeError>
+    where
+        F: FnOnce(Self) -> Result<(), BoxedError> + Send + Sync + 'static,
+    {
+        self.datatype.check_writable()?;
+        let this_tx_ctx = Arc::new(TransactionContext::new(tag));
+        let this_tx_ctx_clone = this_tx_ctx.clone();
+        let do_tx_func = move || {
+            let mut custom_clone = self.clone();
+            custom_clone.tx_ctx = this_tx_ctx_clone.clone();
+            match tx_func(custom_clone) {
+                Ok(_) => Ok(()),
+                Err(e) => Err(DatatypeError::TransactionFailed(e.to_string())),
+            }
+        };
+        self.datatype.do_transaction(this_tx_ctx, do_tx_func)
+    }}
+}
+
+impl<T: CustomCrdt> DatatypeBlanket for Custom<T> {
+    fn get_core(&self) -> &TransactionalDatatype {
+        self.datatype.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests_custom {
+    use std::collections::BTreeSet;
+
+    use tracing::instrument;
+
+    use crate::{
+        Client, ClientError, CustomCrdt, CustomOperationId, DataType, Datatype, DatatypeError,
+        DatatypeSet, LocalConnectivity, Value,
+        utils::test_utils::{get_test_collection_name, get_test_func_name, get_test_ids},
+    };
+
+    /// A grow-only set of strings; its operations add the string in the body.
+    #[derive(Debug, Default, Clone)]
+    struct GrowOnlySet(BTreeSet<String>);
+
+    impl CustomCrdt for GrowOnlySet {
+        const TYPE_NAME: &'static str = "grow-only-set";
+
+        fn execute_local_operation(
+            &mut self,
+            op_id: &CustomOperationId,
+            body: &mut Vec<u8>,
+        ) -> Result<Value, DatatypeError> {
+            if body.is_empty() {
+                return Err(DatatypeError::Disallowed("empty element".into()));
+            }
+            self.execute_remote_operation(op_id, body)
+        }
+
+        fn execute_remote_operation(
+            &mut self,
+            _: &CustomOperationId,
+            body: &[u8],
+        ) -> Result<Value, DatatypeError> {
+            let added = self.0.insert(String::from_utf8_lossy(body).into_owned());
+            Ok(Value::from(added))
+        }
+
+        fn execute_inverse_operation(
+            &mut self,
+            _: &CustomOperationId,
+            body: &[u8],
+        ) -> Result<Value, DatatypeError> {
+            Ok(Value::from(self.0.remove(&*String::from_utf8_lossy(body))))
+        }
+
+        fn to_bytes(&self) -> Vec<u8> {
+            self.0
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join("\n")
+                .into_bytes()
+        }
+
+        fn from_bytes(bytes: &[u8]) -> Result<Self, DatatypeError> {
+            let s = String::from_utf8_lossy(bytes);
+            Ok(Self(
+                s.split('\n')
+                    .filter(|e| !e.is_empty())
+                    .map(Into::into)
+                    .collect(),
+            ))
+        }
+    }
+
+    #[test]
+    #[instrument]
+    fn can_use_custom_datatype() {
+        let client = Client::builder(get_test_collection_name!(), get_test_func_name!())
+            .with_custom_crdt::<GrowOnlySet>()
+            .build()
+            .unwrap();
+        let set = client
+            .create_datatype("set")
+            .build_custom::<GrowOnlySet>()
+            .unwrap();
+        assert_eq!(set.get_type(), DataType::Custom);
+        assert_eq!(set.execute(b"a".to_vec()).unwrap(), Value::Bool(true));
+        assert_eq!(set.execute(b"a".to_vec()).unwrap(), Value::Bool(false));
+        assert!(set.execute(vec![]).is_err());
+
+        let result = set.transaction("failure", |s| {
+            s.execute(b"b".to_vec())?;
+            s.execute(vec![])?;
+            Ok(())
+        });
+        assert!(result.is_err());
+        assert_eq!(set.read(|s| s.0.len()), 1);
+
+        let Some(DatatypeSet::Custom(untyped)) = client.get_datatype("set") else {
+            unreachable!()
+        };
+        assert_eq!(untyped.get_type_name(), "grow-only-set");
+        assert!(untyped.downcast::<GrowOnlySet>().is_some());
+
+        let unregistered = Client::builder(get_test_collection_name!(), get_test_func_name!())
+            .build()
+            .unwrap();
+        assert_eq!(
+            unregistered
+                .create_datatype("set")
+                .build_custom::<GrowOnlySet>()
+                .err()
+                .unwrap(),
+            ClientError::FailedToSubscribeOrCreateDatatype(String::new())
+        );
+    }
+
+    #[test]
+    #[instrument]
+    fn can_sync_custom_datatype_between_clients() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let (collection, key, _) = get_test_ids!();
+        let client1 = Client::builder(collection.clone(), "client1")
+            .with_connectivity(connectivity.clone())
+            .with_custom_crdt::<GrowOnlySet>()
+            .build()
+            .unwrap();
+        let client2 = Client::builder(collection, "client2")
+            .with_connectivity(connectivity)
+            .with_custom_crdt::<GrowOnlySet>()
+            .build()
+            .unwrap();
+
+        let set1 = client1
+            .create_datatype(key.clone())
+            .build_custom::<GrowOnlySet>()
+            .unwrap();
+        set1.execute(b"x".to_vec()).unwrap();
+        set1.sync().unwrap();
+
+        let set2 = client2
+            .subscribe_datatype(key)
+            .build_custom::<GrowOnlySet>()
+            .unwrap();
+        set2.sync().unwrap();
+        assert_eq!(set2.read(|s| s.0.clone()), set1.read(|s| s.0.clone()));
+
+        set1.execute(b"y".to_vec()).unwrap();
+        set2.execute(b"z".to_vec()).unwrap();
+        set1.sync().unwrap();
+        set2.sync().unwrap();
+        set1.sync().unwrap();
+        assert_eq!(set1.read(|s| s.0.len()), 3);
+        assert_eq!(set2.read(|s| s.0.clone()), set1.read(|s| s.0.clone()));
+    }
+}
@@ -1,6 +1,12 @@
+use std::time::Duration;
+
 use crate::{
     DataType, DatatypeError, DatatypeState,
-    datatypes::{handler::DatatypeHandler, transactional::TransactionalDatatype},
+    datatypes::{
+        handler::DatatypeHandler,
+        history::{HistoryEntry, HistoryFilter},
+        transactional::TransactionalDatatype,
+    },
 };
 
 /// The `Datatype` trait defines the common interface for all
@@ -52,6 +58,9 @@ pub trait Datatype {
     /// remote changes. After successful synchronization, the datatype state
     /// transitions to [`DatatypeState::Subscribed`].
     ///
+    /// This blocks the calling thread until the response arrives; in async code, use
+    /// [`sync_async()`](Self::sync_async) instead.
+    ///
     /// # Errors
     ///
     /// Returns [`DatatypeError`] if the synchronization fails.
@@ -86,10 +95,256 @@ pub trait Datatype {
     /// their owning client.
     fn unsubscribe(&self) -> Result<(), DatatypeError>;
 
+    /// Deletes this datatype from the connectivity backend.
+    ///
+    /// Like [`unsubscribe()`](Self::unsubscribe), this records local intent by transitioning
+    /// the datatype to [`DatatypeState::Deleting`], and the backend acknowledges it on the
+    /// next push/pull, after which the datatype is [`DatatypeState::Disabled`]. The backend
+    /// then removes the datatype: the other subscribers transition to `Disabled` with
+    /// [`ServerRejectReason::Deleted`](crate::ServerRejectReason::Deleted) once they
+    /// push/pull, which realtime connectivity notifies them to do, and later attempts to
+    /// subscribe to it are rejected the same way.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatatypeError::NotWritable`] if the datatype is not
+    /// [`DatatypeState::Subscribed`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qortoo::{Client, Datatype, DatatypeState, LocalConnectivity};
+    ///
+    /// let connectivity = LocalConnectivity::new_arc();
+    /// connectivity.set_realtime(false);
+    /// let client = Client::builder("doc-example", "Datatype-delete")
+    ///     .with_connectivity(connectivity)
+    ///     .build()
+    ///     .unwrap();
+    /// let counter = client.create_datatype("key").build_counter().unwrap();
+    /// counter.sync().unwrap();
+    /// counter.delete().unwrap();
+    /// assert_eq!(counter.get_state(), DatatypeState::Deleting);
+    /// counter.sync().unwrap();
+    /// assert_eq!(counter.get_state(), DatatypeState::Disabled);
+    /// ```
+    fn delete(&self) -> Result<(), DatatypeError>;
+
+    /// Subscribes this datatype again after it became [`DatatypeState::Disabled`], e.g., by
+    /// a rejection of the server.
+    ///
+    /// The datatype transitions to [`DatatypeState::SubscribingOrCreating`] and is attached
+    /// back to its client. Local transactions the server had not acknowledged are kept:
+    /// once the server responds, they are executed again on top of its state and pushed as
+    /// new transactions. One that no longer applies is dropped and reported to the error
+    /// handler.
+    ///
+    /// # Errors
+    ///
+    /// - [`DatatypeError::NotWritable`] if the datatype is not [`DatatypeState::Disabled`].
+    /// - [`DatatypeError::Disallowed`] if the client has another datatype for the key.
+    fn resubscribe(&self) -> Result<(), DatatypeError>;
+
+    /// Synchronizes like [`sync()`](Self::sync), but returns a future of the result instead
+    /// of blocking the calling thread.
+    ///
+    /// The push is requested when this method is called; the future only waits for the
+    /// response, so it can be awaited on any executor, e.g., in a tokio task.
+    ///
+    /// # Errors
+    ///
+    /// The future resolves to [`DatatypeError`] if the synchronization fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qortoo::{Client, Datatype, DatatypeState, LocalConnectivity};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let connectivity = LocalConnectivity::new_arc();
+    /// connectivity.set_realtime(false);
+    /// let client = Client::builder("doc-example", "Datatype-sync_async")
+    ///     .with_connectivity(connectivity)
+    ///     .build()
+    ///     .unwrap();
+    /// let counter = client.create_datatype("key").build_counter().unwrap();
+    /// counter.increase().unwrap();
+    /// counter.sync_async().await.unwrap();
+    /// assert_eq!(counter.get_state(), DatatypeState::Subscribed);
+    /// # }
+    /// ```
+    fn sync_async(&self) -> impl Future<Output = Result<(), DatatypeError>> + Send + 'static;
+
+    /// Unsubscribes like [`unsubscribe()`](Self::unsubscribe), and returns a future that
+    /// resolves once the unsubscription has been pushed to the connectivity backend,
+    /// in manual mode as well.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatatypeError`] if the datatype is not subscribed; the future resolves to
+    /// it if the push fails.
+    fn unsubscribe_async(
+        &self,
+    ) -> Result<impl Future<Output = Result<(), DatatypeError>> + Send + 'static, DatatypeError>;
+
+    /// Runs the writes of `write_func` and returns a future that resolves to its result once
+    /// they have been pushed like [`sync_async()`](Self::sync_async), in manual mode as well.
+    ///
+    /// The writes are applied locally before this method returns, as with the synchronous
+    /// methods; the future only waits for the push.
+    ///
+    /// # Errors
+    ///
+    /// The future resolves to the error of `write_func`, in which case nothing is pushed, or
+    /// to the error of the push, in which case the writes stay applied locally.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qortoo::{Client, Datatype};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let client = Client::builder("doc-example", "Datatype-write_async").build().unwrap();
+    /// let counter = client.create_datatype("key").build_counter().unwrap();
+    /// let value = counter.write_async(|c| c.increase_by(3)).await.unwrap();
+    /// assert_eq!(value, 3);
+    /// assert_eq!(counter.get_synced_client_version(), counter.get_client_version());
+    /// # }
+    /// ```
+    fn write_async<R, F>(
+        &self,
+        write_func: F,
+    ) -> impl Future<Output = Result<R, DatatypeError>> + Send + 'static
+    where
+        Self: Sized,
+        F: FnOnce(&Self) -> Result<R, DatatypeError>,
+        R: Send + 'static,
+    {
+        let written = write_func(self);
+        let pushed = written.is_ok().then(|| self.sync_async());
+        async move {
+            let value = written?;
+            if let Some(pushed) = pushed {
+                pushed.await?;
+            }
+            Ok(value)
+        }
+    }
+
+    /// Blocks until the server has acknowledged every local transaction committed before
+    /// this call, i.e., until [`get_synced_client_version()`](Self::get_synced_client_version)
+    /// catches up with the [`get_client_version()`](Self::get_client_version) of the call.
+    ///
+    /// This does not push by itself: in manual mode, a [`sync()`](Self::sync) from another
+    /// thread or task must push the transactions.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatatypeError::Timeout`] if they are not acknowledged within `timeout`,
+    /// [`DatatypeError::NotWritable`] if the datatype becomes [`DatatypeState::Disabled`]
+    /// first, and [`DatatypeError::Interrupted`] if it is resubscribed meanwhile.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use qortoo::{Client, Datatype};
+    ///
+    /// let client = Client::builder("doc-example", "Datatype-wait_until_synced").build().unwrap();
+    /// let counter = client.create_datatype("key").build_counter().unwrap();
+    /// counter.increase().unwrap();
+    /// counter.wait_until_synced(Duration::from_secs(5)).unwrap();
+    /// assert_eq!(counter.get_synced_client_version(), counter.get_client_version());
+    /// ```
+    fn wait_until_synced(&self, timeout: Duration) -> Result<(), DatatypeError>;
+
+    /// Waits like [`wait_until_synced()`](Self::wait_until_synced), but returns a future
+    /// instead of blocking the calling thread.
+    fn wait_until_synced_async(
+        &self,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<(), DatatypeError>> + Send + 'static;
+
     fn set_handler(&self, id: usize, handler: DatatypeHandler);
 
     fn unset_handler(&self, id: usize) -> Option<DatatypeHandler>;
 
+    /// Undoes the latest committed local transaction, or the latest run of consecutive
+    /// transactions with the same tag.
+    ///
+    /// The undo is a new transaction of compensating operations, so other replicas see
+    /// it as a normal change. Only the effects still in place are compensated: a value
+    /// overwritten since, e.g., by another client, is left as it is. Returns `false` if
+    /// there is nothing to undo, including when no effect of the entry is still in place;
+    /// the entry is then dropped.
+    ///
+    /// The history is kept per datatype and client, up to 100 entries. Operations that
+    /// cannot be compensated, i.e., transfers of a [`BoundedCounter`](crate::BoundedCounter)
+    /// and operations of a [`Document`](crate::Document) or a custom datatype, clear it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatatypeError`] if the datatype is not writable or the compensation fails;
+    /// the entry then stays in the history.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qortoo::{Client, Datatype};
+    ///
+    /// let client = Client::builder("doc-example", "Datatype-undo").build().unwrap();
+    /// let counter = client.create_datatype("key").build_counter().unwrap();
+    /// counter.increase_by(2).unwrap();
+    /// counter.increase_by(3).unwrap();
+    /// assert!(counter.undo().unwrap());
+    /// assert_eq!(counter.get_value(), 2);
+    /// assert!(counter.redo().unwrap());
+    /// assert_eq!(counter.get_value(), 5);
+    /// ```
+    fn undo(&self) -> Result<bool, DatatypeError>;
+
+    /// Redoes the latest undone entry; see [`undo()`](Self::undo).
+    ///
+    /// A new local transaction, other than an undo or redo, discards what could be redone.
+    /// Returns `false` if there is nothing to redo.
+    fn redo(&self) -> Result<bool, DatatypeError>;
+
+    /// Returns whether [`undo()`](Self::undo) has an entry to undo.
+    fn can_undo(&self) -> bool;
+
+    /// Returns whether [`redo()`](Self::redo) has an entry to redo.
+    fn can_redo(&self) -> bool;
+
+    /// Returns the committed transactions this replica has applied, local and remote,
+    /// oldest first.
+    ///
+    /// Each [`HistoryEntry`] tells who made the transaction, with which tag, and what its
+    /// operations were and when. The number of transactions kept is set by
+    /// [`DatatypeBuilder::with_history_retention`](crate::DatatypeBuilder::with_history_retention);
+    /// a replica that subscribed from a snapshot does not know the transactions before it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qortoo::{Client, Datatype};
+    ///
+    /// let client = Client::builder("doc-example", "Datatype-history").build().unwrap();
+    /// let counter = client.create_datatype("key").build_counter().unwrap();
+    /// counter.increase_by(2).unwrap();
+    /// let history = counter.history();
+    /// assert_eq!(history.len(), 1);
+    /// assert!(history[0].is_local);
+    /// ```
+    fn history(&self) -> Vec<HistoryEntry> {
+        self.history_filtered(&HistoryFilter::default())
+    }
+
+    /// Returns the entries of [`history()`](Self::history) selected by `filter`, e.g., the
+    /// transactions of one client or with one tag.
+    fn history_filtered(&self, filter: &HistoryFilter) -> Vec<HistoryEntry>;
+
     #[cfg(test)]
     fn get_attr(&self) -> std::sync::Arc<crate::datatypes::common::Attribute>;
 }
@@ -134,6 +389,36 @@ where
         self.get_core().unsubscribe()
     }
 
+    fn delete(&self) -> Result<(), DatatypeError> {
+        self.get_core().delete()
+    }
+
+    fn resubscribe(&self) -> Result<(), DatatypeError> {
+        self.get_core().resubscribe()
+    }
+
+    fn sync_async(&self) -> impl Future<Output = Result<(), DatatypeError>> + Send + 'static {
+        self.get_core().sync_async()
+    }
+
+    fn unsubscribe_async(
+        &self,
+    ) -> Result<impl Future<Output = Result<(), DatatypeError>> + Send + 'static, DatatypeError>
+    {
+        self.get_core().unsubscribe_async()
+    }
+
+    fn wait_until_synced(&self, timeout: Duration) -> Result<(), DatatypeError> {
+        self.get_core().wait_until_synced(timeout)
+    }
+
+    fn wait_until_synced_async(
+        &self,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<(), DatatypeError>> + Send + 'static {
+        self.get_core().wait_until_synced_async(timeout)
+    }
+
     fn set_handler(&self, id: usize, handler: DatatypeHandler) {
         self.get_core().set_handler(id, handler)
     }
@@ -142,6 +427,26 @@ where
         self.get_core().unset_handler(id)
     }
 
+    fn undo(&self) -> Result<bool, DatatypeError> {
+        self.get_core().undo()
+    }
+
+    fn redo(&self) -> Result<bool, DatatypeError> {
+        self.get_core().redo()
+    }
+
+    fn can_undo(&self) -> bool {
+        self.get_core().can_undo()
+    }
+
+    fn can_redo(&self) -> bool {
+        self.get_core().can_redo()
+    }
+
+    fn history_filtered(&self, filter: &HistoryFilter) -> Vec<HistoryEntry> {
+        self.get_core().history_filtered(filter)
+    }
+
     #[cfg(test)]
     fn get_attr(&self) -> std::sync::Arc<crate::datatypes::common::Attribute> {
         self.get_core().attr.clone()
@@ -172,7 +477,8 @@ mod tests_datatype_trait {
             attr.clone(),
             DatatypeState::Creating,
             Default::default(),
-        );
+        )
+        .unwrap();
         assert_eq!(data.get_key(), key);
         assert_eq!(data.get_type(), DataType::Counter);
         assert_eq!(data.get_state(), DatatypeState::Creating);
@@ -195,7 +501,7 @@ mod tests_datatype_trait {
         let counter1 = client1.create_datatype(key).build_counter().unwrap();
 
         let interceptor1 = connectivity
-            .get_wired_interceptor(&resource_id, &client1.get_cuid())
+            .get_wired_interceptor(&resource_id, client1.get_cuid())
             .unwrap();
 
         // produce push_pull error
@@ -324,7 +630,7 @@ mod tests_datatype_trait {
         counter.unsubscribe().unwrap();
 
         let interceptor = connectivity
-            .get_wired_interceptor(&resource_id, &client.get_cuid())
+            .get_wired_interceptor(&resource_id, client.get_cuid())
             .unwrap();
         interceptor.set_after_pull(|pull| {
             pull.state = DatatypeState::Subscribed;
@@ -396,4 +702,55 @@ mod tests_datatype_trait {
             .poll_interval(Duration::from_micros(100))
             .until(|| counter.get_state() == DatatypeState::Disabled);
     }
+
+    #[tokio::test(flavor = "current_thread")]
+    #[instrument]
+    async fn can_sync_write_and_unsubscribe_asynchronously() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let (collection, key, _) = get_test_ids!();
+        let client1 = Client::builder(collection.clone(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(collection, "client2")
+            .with_connectivity(connectivity)
+            .build()
+            .unwrap();
+
+        let counter1 = client1
+            .create_datatype(key.clone())
+            .build_counter()
+            .unwrap();
+        counter1.sync_async().await.unwrap();
+        let counter2 = client2.subscribe_datatype(key).build_counter().unwrap();
+        // the future does not borrow the datatype, so it can be spawned
+        tokio::spawn(counter2.sync_async()).await.unwrap().unwrap();
+        assert_eq!(counter2.get_state(), DatatypeState::Subscribed);
+
+        let value = counter1.write_async(|c| c.increase_by(7)).await.unwrap();
+        assert_eq!(value, 7);
+        // resolved once pushed, without a manual sync
+        assert_eq!(counter1.get_client_version(), 1);
+        assert_eq!(
+            counter1.get_synced_client_version(),
+            counter1.get_client_version()
+        );
+        let err = counter1
+            .write_async(|c| c.transaction("failed", |_| Err("abort".into())))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DatatypeError::TransactionFailed(_)));
+
+        counter1.unsubscribe_async().unwrap().await.unwrap();
+        assert_eq!(counter1.get_state(), DatatypeState::Disabled);
+        assert!(counter1.unsubscribe_async().is_err());
+        assert!(matches!(
+            counter1.sync_async().await,
+            Err(DatatypeError::NotWritable(_))
+        ));
+
+        counter2.sync_async().await.unwrap();
+        assert_eq!(counter2.get_value(), 7);
+    }
 }
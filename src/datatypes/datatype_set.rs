@@ -1,7 +1,8 @@
 use std::{collections::BTreeMap, sync::Arc};
 
 use crate::{
-    Counter, DataType, Datatype, DatatypeState,
+    BoundedCounter, Counter, CustomDatatype, DataType, Datatype, DatatypeError, DatatypeState,
+    Document, Flag, List, Map, MultiValueRegister, Set, Text, Variable,
     clients::common::ClientCommon,
     datatypes::{
         common::Attribute, datatype::DatatypeBlanket, option::DatatypeOption,
@@ -17,6 +18,16 @@ use crate::{
 #[derive(Clone)]
 pub enum DatatypeSet {
     Counter(Counter),
+    Variable(Variable),
+    Map(Map),
+    List(List),
+    Text(Text),
+    Set(Set),
+    MultiValueRegister(MultiValueRegister),
+    Document(Document),
+    BoundedCounter(BoundedCounter),
+    Flag(Flag),
+    Custom(CustomDatatype),
 }
 
 impl DatatypeSet {
@@ -24,6 +35,16 @@ impl DatatypeSet {
     pub fn get_type(&self) -> DataType {
         match self {
             DatatypeSet::Counter(_) => DataType::Counter,
+            DatatypeSet::Variable(_) => DataType::Variable,
+            DatatypeSet::Map(_) => DataType::Map,
+            DatatypeSet::List(_) => DataType::List,
+            DatatypeSet::Text(_) => DataType::Text,
+            DatatypeSet::Set(_) => DataType::Set,
+            DatatypeSet::MultiValueRegister(_) => DataType::MultiValueRegister,
+            DatatypeSet::Document(_) => DataType::Document,
+            DatatypeSet::BoundedCounter(_) => DataType::BoundedCounter,
+            DatatypeSet::Flag(_) => DataType::Flag,
+            DatatypeSet::Custom(_) => DataType::Custom,
         }
     }
 
@@ -32,18 +53,52 @@ impl DatatypeSet {
     pub fn get_state(&self) -> DatatypeState {
         match self {
             DatatypeSet::Counter(cnt) => cnt.get_state(),
+            DatatypeSet::Variable(var) => var.get_state(),
+            DatatypeSet::Map(map) => map.get_state(),
+            DatatypeSet::List(list) => list.get_state(),
+            DatatypeSet::Text(text) => text.get_state(),
+            DatatypeSet::Set(set) => set.get_state(),
+            DatatypeSet::MultiValueRegister(reg) => reg.get_state(),
+            DatatypeSet::Document(doc) => doc.get_state(),
+            DatatypeSet::BoundedCounter(cnt) => cnt.get_state(),
+            DatatypeSet::Flag(flag) => flag.get_state(),
+            DatatypeSet::Custom(custom) => custom.get_state(),
         }
     }
 
-    pub(crate) fn get_core_id(&self) -> usize {
+    pub(crate) fn get_core(&self) -> &TransactionalDatatype {
         match self {
-            DatatypeSet::Counter(cnt) => cnt.get_core() as *const TransactionalDatatype as usize,
+            DatatypeSet::Counter(cnt) => cnt.get_core(),
+            DatatypeSet::Variable(var) => var.get_core(),
+            DatatypeSet::Map(map) => map.get_core(),
+            DatatypeSet::List(list) => list.get_core(),
+            DatatypeSet::Text(text) => text.get_core(),
+            DatatypeSet::Set(set) => set.get_core(),
+            DatatypeSet::MultiValueRegister(reg) => reg.get_core(),
+            DatatypeSet::Document(doc) => doc.get_core(),
+            DatatypeSet::BoundedCounter(cnt) => cnt.get_core(),
+            DatatypeSet::Flag(flag) => flag.get_core(),
+            DatatypeSet::Custom(custom) => custom.get_core(),
         }
     }
 
+    pub(crate) fn get_core_id(&self) -> usize {
+        self.get_core() as *const TransactionalDatatype as usize
+    }
+
     pub(crate) fn unsubscribe(&self) -> Result<(), crate::DatatypeError> {
         match self {
             DatatypeSet::Counter(cnt) => cnt.unsubscribe(),
+            DatatypeSet::Variable(var) => var.unsubscribe(),
+            DatatypeSet::Map(map) => map.unsubscribe(),
+            DatatypeSet::List(list) => list.unsubscribe(),
+            DatatypeSet::Text(text) => text.unsubscribe(),
+            DatatypeSet::Set(set) => set.unsubscribe(),
+            DatatypeSet::MultiValueRegister(reg) => reg.unsubscribe(),
+            DatatypeSet::Document(doc) => doc.unsubscribe(),
+            DatatypeSet::BoundedCounter(cnt) => cnt.unsubscribe(),
+            DatatypeSet::Flag(flag) => flag.unsubscribe(),
+            DatatypeSet::Custom(custom) => custom.unsubscribe(),
         }
     }
 
@@ -51,6 +106,9 @@ impl DatatypeSet {
     ///
     /// This is primarily used by the client internals to construct
     /// a concrete datatype variant tied to a specific client context.
+    ///
+    /// Fails if the CRDT of `type` cannot be created, e.g., for a custom datatype whose
+    /// `option` lacks the factory of its CRDT.
     pub(crate) fn new(
         r#type: DataType,
         key: ArcStr,
@@ -59,7 +117,7 @@ impl DatatypeSet {
         option: DatatypeOption,
         is_readonly: bool,
         handlers: BTreeMap<usize, crate::DatatypeHandler>,
-    ) -> Self {
+    ) -> Result<Self, DatatypeError> {
         let attr = Arc::new(Attribute::new(
             key,
             r#type,
@@ -67,12 +125,26 @@ impl DatatypeSet {
             option,
             is_readonly,
         ));
-        let datatype = TransactionalDatatype::new_arc(attr.clone(), state, handlers);
-        match r#type {
+        let datatype = TransactionalDatatype::new_arc(attr.clone(), state, handlers)?;
+        Ok(Self::from_transactional(datatype))
+    }
+
+    /// Wraps an existing core into the variant matching its [`DataType`].
+    pub(crate) fn from_transactional(datatype: Arc<TransactionalDatatype>) -> Self {
+        match datatype.attr.r#type {
             DataType::Counter => DatatypeSet::Counter(Counter::new(datatype)),
-            _ => {
-                todo!()
+            DataType::Variable => DatatypeSet::Variable(Variable::new(datatype)),
+            DataType::Map => DatatypeSet::Map(Map::new(datatype)),
+            DataType::List => DatatypeSet::List(List::new(datatype)),
+            DataType::Text => DatatypeSet::Text(Text::new(datatype)),
+            DataType::Set => DatatypeSet::Set(Set::new(datatype)),
+            DataType::MultiValueRegister => {
+                DatatypeSet::MultiValueRegister(MultiValueRegister::new(datatype))
             }
+            DataType::Document => DatatypeSet::Document(Document::new(datatype)),
+            DataType::BoundedCounter => DatatypeSet::BoundedCounter(BoundedCounter::new(datatype)),
+            DataType::Flag => DatatypeSet::Flag(Flag::new(datatype)),
+            DataType::Custom => DatatypeSet::Custom(CustomDatatype::new(datatype)),
         }
     }
 }
@@ -83,12 +155,73 @@ impl From<Counter> for DatatypeSet {
     }
 }
 
+impl From<Variable> for DatatypeSet {
+    fn from(value: Variable) -> Self {
+        Self::Variable(value)
+    }
+}
+
+impl From<Map> for DatatypeSet {
+    fn from(value: Map) -> Self {
+        Self::Map(value)
+    }
+}
+
+impl From<List> for DatatypeSet {
+    fn from(value: List) -> Self {
+        Self::List(value)
+    }
+}
+
+impl From<Text> for DatatypeSet {
+    fn from(value: Text) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl From<Set> for DatatypeSet {
+    fn from(value: Set) -> Self {
+        Self::Set(value)
+    }
+}
+
+impl From<MultiValueRegister> for DatatypeSet {
+    fn from(value: MultiValueRegister) -> Self {
+        Self::MultiValueRegister(value)
+    }
+}
+
+impl From<Document> for DatatypeSet {
+    fn from(value: Document) -> Self {
+        Self::Document(value)
+    }
+}
+
+impl From<BoundedCounter> for DatatypeSet {
+    fn from(value: BoundedCounter) -> Self {
+        Self::BoundedCounter(value)
+    }
+}
+
+impl From<Flag> for DatatypeSet {
+    fn from(value: Flag) -> Self {
+        Self::Flag(value)
+    }
+}
+
+impl From<CustomDatatype> for DatatypeSet {
+    fn from(value: CustomDatatype) -> Self {
+        Self::Custom(value)
+    }
+}
+
 #[cfg(test)]
 mod tests_datatype_set {
     use tracing::instrument;
 
     use crate::{
-        Counter, DataType, Datatype, DatatypeState,
+        BoundedCounter, Counter, DataType, Datatype, DatatypeState, Document, Flag, List, Map,
+        MultiValueRegister, Set, Text, Variable,
         clients::common::new_client_common,
         datatypes::{
             datatype::DatatypeBlanket, datatype_set::DatatypeSet,
@@ -107,10 +240,12 @@ mod tests_datatype_set {
             Default::default(),
             false,
             Default::default(),
-        );
+        )
+        .unwrap();
         let ds2 = ds1.clone();
-        let DatatypeSet::Counter(cnt1) = ds1;
-        let DatatypeSet::Counter(cnt2) = ds2;
+        let (DatatypeSet::Counter(cnt1), DatatypeSet::Counter(cnt2)) = (ds1, ds2) else {
+            unreachable!()
+        };
 
         // Cloned DatatypeSet contains a cloned Counter (same variant, same key)
         assert_eq!(cnt1.get_key(), cnt2.get_key());
@@ -138,5 +273,23 @@ mod tests_datatype_set {
         let counter = Counter::new_for_test(Default::default());
         fn assert_datatype_set(_ds: DatatypeSet) {}
         assert_datatype_set(counter.into());
+        let variable: DatatypeSet = Variable::new_for_test(Default::default()).into();
+        assert_eq!(variable.get_type(), DataType::Variable);
+        let map: DatatypeSet = Map::new_for_test(Default::default()).into();
+        assert_eq!(map.get_type(), DataType::Map);
+        let list: DatatypeSet = List::new_for_test(Default::default()).into();
+        assert_eq!(list.get_type(), DataType::List);
+        let text: DatatypeSet = Text::new_for_test(Default::default()).into();
+        assert_eq!(text.get_type(), DataType::Text);
+        let set: DatatypeSet = Set::new_for_test(Default::default()).into();
+        assert_eq!(set.get_type(), DataType::Set);
+        let reg: DatatypeSet = MultiValueRegister::new_for_test(Default::default()).into();
+        assert_eq!(reg.get_type(), DataType::MultiValueRegister);
+        let doc: DatatypeSet = Document::new_for_test(Default::default()).into();
+        assert_eq!(doc.get_type(), DataType::Document);
+        let bounded: DatatypeSet = BoundedCounter::new_for_test(Default::default()).into();
+        assert_eq!(bounded.get_type(), DataType::BoundedCounter);
+        let flag: DatatypeSet = Flag::new_for_test(Default::default()).into();
+        assert_eq!(flag.get_type(), DataType::Flag);
     }
 }
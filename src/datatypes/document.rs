@@ -0,0 +1,427 @@
+use std::{
+    fmt::{Display, Formatter},
+    sync::Arc,
+};
+
+use tracing::trace;
+
+use crate::{
+    DatatypeError, IntoString, Value,
+    datatypes::{
+        common::{ReturnType, datatype_instrument},
+        crdts::{Crdt, document_crdt::DocumentCrdt},
+        datatype::DatatypeBlanket,
+        transactional::{TransactionContext, TransactionalDatatype},
+    },
+    errors::{BoxedError, datatypes::InternalReason},
+    operations::{MemoryMeasurable, Operation},
+    utils::codec::{ByteReader, ByteWriter},
+};
+
+const TAG_PRIMITIVE: u8 = 0;
+const TAG_OBJECT: u8 = 1;
+const TAG_ARRAY: u8 = 2;
+
+/// A value at a path of a [`Document`]: a primitive [`Value`] or a container.
+///
+/// Writing [`DocumentValue::Object`] or [`DocumentValue::Array`] puts a new empty container
+/// at the path; reading one back tells which kind of container is there, without its content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DocumentValue {
+    Primitive(Value),
+    Object,
+    Array,
+}
+
+impl DocumentValue {
+    pub(crate) fn encode(&self, w: &mut ByteWriter) {
+        match self {
+            DocumentValue::Primitive(value) => {
+                w.write_u8(TAG_PRIMITIVE);
+                value.encode(w);
+            }
+            DocumentValue::Object => w.write_u8(TAG_OBJECT),
+            DocumentValue::Array => w.write_u8(TAG_ARRAY),
+        }
+    }
+
+    pub(crate) fn decode(r: &mut ByteReader) -> Result<Self, DatatypeError> {
+        Ok(match r.read_u8()? {
+            TAG_PRIMITIVE => DocumentValue::Primitive(Value::decode(r)?),
+            TAG_OBJECT => DocumentValue::Object,
+            TAG_ARRAY => DocumentValue::Array,
+            tag => {
+                return Err(InternalReason::Deserialize(format!(
+                    "unknown document value tag {tag}"
+                ))
+                .into_error());
+            }
+        })
+    }
+}
+
+impl Display for DocumentValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DocumentValue::Primitive(value) => write!(f, "{value}"),
+            DocumentValue::Object => f.write_str("{}"),
+            DocumentValue::Array => f.write_str("[]"),
+        }
+    }
+}
+
+impl MemoryMeasurable for DocumentValue {
+    fn size(&self) -> u64 {
+        match self {
+            DocumentValue::Primitive(value) => value.size(),
+            _ => size_of::<DocumentValue>() as u64,
+        }
+    }
+}
+
+macro_rules! impl_from_primitive {
+    ($($t:ty),*) => {$(
+        impl From<$t> for DocumentValue {
+            fn from(value: $t) -> Self {
+                DocumentValue::Primitive(value.into())
+            }
+        }
+    )*};
+}
+
+impl_from_primitive!(Value, bool, i64, i32, f64, &str, String, Vec<u8>, &[u8]);
+
+/// A document is a conflict-free JSON-like tree of objects, arrays and primitive values.
+///
+/// Entries are addressed by paths of dot-separated fields and bracketed array indexes,
+/// e.g., `"user.tags[2]"`; the empty path is the root object. Field names therefore
+/// cannot contain `.`, `[` or `]`.
+///
+/// Every object and array merges on its own: concurrent writes to different fields or
+/// elements are all kept, a field resolves concurrent writes by last-writer-wins like a
+/// [`Map`](crate::Map), and an array orders concurrent inserts like a [`List`](crate::List).
+///
+/// # Examples
+///
+/// ```
+/// # use qortoo::{Client, DocumentValue};
+/// let client = Client::builder("doc-example", "document-test").build().unwrap();
+/// let doc = client.create_datatype("test-document").build_document().unwrap();
+/// doc.set("user", DocumentValue::Object).unwrap();
+/// doc.set("user.name", "kim").unwrap();
+/// doc.set("user.tags", DocumentValue::Array).unwrap();
+/// doc.insert_at("user.tags", 0, "admin").unwrap();
+/// assert_eq!(doc.to_json(), r#"{"user":{"name":"kim","tags":["admin"]}}"#);
+/// ```
+#[derive(Clone)]
+pub struct Document {
+    datatype: Arc<TransactionalDatatype>,
+    tx_ctx: Arc<TransactionContext>,
+}
+
+impl Document {
+    pub(crate) fn new(datatype: Arc<TransactionalDatatype>) -> Self {
+        Document {
+            datatype,
+            tx_ctx: Default::default(),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn new_for_test(state: crate::DatatypeState) -> Self {
+        Self::new(
+            TransactionalDatatype::new_arc(
+                crate::datatypes::common::new_attribute!(crate::DataType::Document),
+                state,
+                Default::default(),
+            )
+            .unwrap(),
+        )
+    }
+
+    fn execute(&self, op: Operation) -> Result<Option<DocumentValue>, DatatypeError> {
+        let ret = self
+            .datatype
+            .execute_local_operation_as_tx(self.tx_ctx.clone(), op)?;
+        trace!("executed -> {ret:?}");
+        match ret {
+            ReturnType::Document(value) => Ok(value),
+            _ => {
+                Err(InternalReason::ExecuteOperation("unexpected return type".into()).into_error())
+            }
+        }
+    }
+
+    fn read<R>(&self, f: impl FnOnce(&DocumentCrdt) -> R) -> R {
+        let mutable = self.datatype.mutable.read();
+        match &mutable.crdt {
+            Crdt::Document(d) => f(d),
+            _ => unreachable!("document must have a document crdt"),
+        }
+    }
+
+    datatype_instrument! {
+    /// Sets the field or array element at `path` to `value`.
+    ///
+    /// A field is created if its object exists; an array element must exist.
+    /// Returns the overwritten value, if any.
+    ///
+    /// # Errors
+    /// - [`DatatypeError::InvalidPath`] if `path` is malformed, is the root, or goes
+    ///   through something that is not the expected container
+    /// - [`DatatypeError::OutOfBounds`] if an index is not smaller than its array's length
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::{Client, DocumentValue};
+    /// let client = Client::builder("doc-example", "document-set-test").build().unwrap();
+    /// let doc = client.create_datatype("test-document").build_document().unwrap();
+    /// doc.set("a", DocumentValue::Object).unwrap();
+    /// doc.set("a.b", DocumentValue::Array).unwrap();
+    /// doc.insert_at("a.b", 0, 1).unwrap();
+    /// assert_eq!(doc.set("a.b[0]", 2).unwrap(), Some(DocumentValue::from(1)));
+    /// assert!(doc.set("a.c.d", 3).is_err());
+    /// ```
+    pub fn set(
+        &self,
+        path: impl Into<String>,
+        value: impl Into<DocumentValue>,
+    ) -> Result<Option<DocumentValue>, DatatypeError> {
+        self.execute(Operation::new_document_set(path, value.into()))
+    }}
+
+    datatype_instrument! {
+    /// Deletes the field or array element at `path`, returning the deleted value.
+    ///
+    /// # Errors
+    /// - [`DatatypeError::InvalidPath`] if `path` is malformed, is the root, or has no entry
+    /// - [`DatatypeError::OutOfBounds`] if an index is not smaller than its array's length
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::{Client, DocumentValue};
+    /// let client = Client::builder("doc-example", "document-delete-test").build().unwrap();
+    /// let doc = client.create_datatype("test-document").build_document().unwrap();
+    /// doc.set("a", true).unwrap();
+    /// assert_eq!(doc.delete("a").unwrap(), DocumentValue::from(true));
+    /// assert!(doc.delete("a").is_err());
+    /// ```
+    pub fn delete(&self, path: impl Into<String>) -> Result<DocumentValue, DatatypeError> {
+        self.execute(Operation::new_document_delete(path))?
+            .ok_or_else(|| InternalReason::ExecuteOperation("nothing deleted".into()).into_error())
+    }}
+
+    datatype_instrument! {
+    /// Inserts `value` at `index` of the array at `path`, shifting later elements.
+    ///
+    /// # Errors
+    /// - [`DatatypeError::InvalidPath`] if `path` is malformed or is not an array
+    /// - [`DatatypeError::OutOfBounds`] if `index` is larger than the array's length
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::{Client, DocumentValue};
+    /// let client = Client::builder("doc-example", "document-insert-test").build().unwrap();
+    /// let doc = client.create_datatype("test-document").build_document().unwrap();
+    /// doc.set("list", DocumentValue::Array).unwrap();
+    /// doc.insert_at("list", 0, "b").unwrap();
+    /// doc.insert_at("list", 0, "a").unwrap();
+    /// assert_eq!(doc.to_json(), r#"{"list":["a","b"]}"#);
+    /// ```
+    pub fn insert_at(
+        &self,
+        path: impl Into<String>,
+        index: usize,
+        value: impl Into<DocumentValue>,
+    ) -> Result<(), DatatypeError> {
+        self.execute(Operation::new_document_insert(path, index, value.into()))?;
+        Ok(())
+    }}
+
+    /// Returns the value at `path`, or `None` if there is none or `path` is malformed.
+    ///
+    /// Objects and arrays are returned as [`DocumentValue::Object`] and
+    /// [`DocumentValue::Array`]; use [`len`](Self::len) and paths into them to read on.
+    pub fn get(&self, path: &str) -> Option<DocumentValue> {
+        self.read(|d| d.get(path))
+    }
+
+    /// Returns the number of fields or elements of the object or array at `path`.
+    pub fn len(&self, path: &str) -> Option<usize> {
+        self.read(|d| d.len(path))
+    }
+
+    /// Exports the whole document as a JSON string, with object fields in key order.
+    ///
+    /// Bytes are exported as arrays of numbers, and non-finite floats as `null`.
+    pub fn to_json(&self) -> String {
+        self.read(|d| d.to_json())
+    }
+
+    datatype_instrument! {
+    /// Executes multiple operations atomically within a transaction.
+    ///
+    /// If the transaction function returns an error, all operations within
+    /// the transaction are rolled back, leaving the document unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::{Client, DocumentValue};
+    /// let client = Client::builder("doc-example", "document-transaction-test").build().unwrap();
+    /// let doc = client.create_datatype("test-document").build_document().unwrap();
+    ///
+    /// let result = doc.transaction("failing-update", |d| {
+    ///     d.set("a", DocumentValue::Object)?;
+    ///     d.set("a.b", 1)?;
+    ///     Err("something went wrong".into())
+    /// });
+    /// assert!(result.is_err());
+    /// assert_eq!(doc.to_json(), "{}"); // unchanged
+    /// ```
+    pub fn transaction<T>(
+        &self,
+        tag: impl IntoString,
+        tx_func: T,
+    ) -> Result<(), DatatypeError>
+    where
+        T: FnOnce(Self) -> Result<(), BoxedError> + Send + Sync + 'static,
+    {
+        self.datatype.check_writable()?;
+        let this_tx_ctx = Arc::new(TransactionContext::new(tag));
+        let this_tx_ctx_clone = this_tx_ctx.clone();
+        let do_tx_func = move || {
+            let mut doc_clone = self.clone();
+            doc_clone.tx_ctx = this_tx_ctx_clone.clone();
+            match tx_func(doc_clone) {
+                Ok(_) => Ok(()),
+                Err(e) => Err(DatatypeError::TransactionFailed(e.to_string())),
+            }
+        };
+        self.datatype.do_transaction(this_tx_ctx, do_tx_func)
+    }}
+}
+
+impl DatatypeBlanket for Document {
+    fn get_core(&self) -> &TransactionalDatatype {
+        self.datatype.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests_document {
+    use tracing::instrument;
+
+    use crate::{
+        Client, DataType, Datatype, DatatypeError, DatatypeState, DocumentValue, LocalConnectivity,
+        datatypes::document::Document,
+        utils::test_utils::{get_test_collection_name, get_test_func_name, get_test_ids},
+    };
+
+    #[test]
+    fn can_assert_send_and_sync_traits() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Document>();
+    }
+
+    #[test]
+    #[instrument]
+    fn can_use_document_operations() {
+        let doc = Document::new_for_test(DatatypeState::Creating);
+        assert_eq!(doc.get_type(), DataType::Document);
+        assert_eq!(doc.set("title", "draft").unwrap(), None);
+        assert_eq!(
+            doc.set("title", "final").unwrap(),
+            Some(DocumentValue::from("draft"))
+        );
+        doc.set("items", DocumentValue::Array).unwrap();
+        doc.insert_at("items", 0, DocumentValue::Object).unwrap();
+        doc.set("items[0].qty", 2).unwrap();
+        doc.insert_at("items", 1, 1.5).unwrap();
+        assert_eq!(doc.len("items"), Some(2));
+        assert_eq!(doc.get("items[0].qty"), Some(DocumentValue::from(2)));
+        assert_eq!(doc.get("items[0]"), Some(DocumentValue::Object));
+        assert_eq!(
+            doc.to_json(),
+            r#"{"items":[{"qty":2},1.5],"title":"final"}"#
+        );
+
+        assert_eq!(doc.delete("items[1]").unwrap(), DocumentValue::from(1.5));
+        assert!(matches!(
+            doc.insert_at("items", 2, 0).unwrap_err(),
+            DatatypeError::OutOfBounds(_)
+        ));
+        assert!(matches!(
+            doc.set("title.x", 0).unwrap_err(),
+            DatatypeError::InvalidPath(_)
+        ));
+        assert!(matches!(
+            doc.set("", 0).unwrap_err(),
+            DatatypeError::InvalidPath(_)
+        ));
+    }
+
+    #[test]
+    #[instrument]
+    fn can_rollback_transaction() {
+        let doc = Document::new_for_test(DatatypeState::Creating);
+        doc.set("list", DocumentValue::Array).unwrap();
+        doc.insert_at("list", 0, "a").unwrap();
+        let result = doc.transaction("failure", |d| {
+            d.set("list[0]", "b")?;
+            d.insert_at("list", 1, DocumentValue::Object)?;
+            d.set("list[1].x", 1)?;
+            d.delete("list[0]")?;
+            d.set("list", 0)?;
+            Err("failed".into())
+        });
+        assert!(result.is_err());
+        assert_eq!(doc.to_json(), r#"{"list":["a"]}"#);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_merge_nested_edits_between_clients() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let (collection, key, _) = get_test_ids!();
+        let client1 = Client::builder(collection.clone(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(collection, "client2")
+            .with_connectivity(connectivity)
+            .build()
+            .unwrap();
+
+        let doc1 = client1
+            .create_datatype(key.clone())
+            .build_document()
+            .unwrap();
+        doc1.set("profile", DocumentValue::Object).unwrap();
+        doc1.set("todos", DocumentValue::Array).unwrap();
+        doc1.insert_at("todos", 0, "write").unwrap();
+        doc1.sync().unwrap();
+
+        let doc2 = client2.subscribe_datatype(key).build_document().unwrap();
+        doc2.sync().unwrap();
+        assert_eq!(doc2.to_json(), doc1.to_json());
+
+        doc1.set("profile.name", "kim").unwrap();
+        doc1.insert_at("todos", 0, "plan").unwrap();
+        doc2.set("profile.age", 30).unwrap();
+        doc2.insert_at("todos", 1, "review").unwrap();
+        doc1.sync().unwrap();
+        doc2.sync().unwrap();
+        doc1.sync().unwrap();
+
+        assert_eq!(
+            doc1.to_json(),
+            r#"{"profile":{"age":30,"name":"kim"},"todos":["plan","write","review"]}"#
+        );
+        assert_eq!(doc2.to_json(), doc1.to_json());
+    }
+}
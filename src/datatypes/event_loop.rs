@@ -1,4 +1,10 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 
 use backon::{BackoffBuilder, ExponentialBackoff, ExponentialBuilder};
 use crossbeam_channel::{Receiver, Sender};
@@ -7,7 +13,7 @@ use tokio::sync::oneshot;
 use tracing::{Span, error, instrument};
 
 use crate::{
-    DatatypeError,
+    DatatypeError, DatatypeState,
     connectivity::Connectivity,
     datatypes::wired::WiredDatatype,
     defaults::DEFAULT_EVENT_LOOP_TIMEOUT_MS,
@@ -73,6 +79,7 @@ pub struct EventLoop {
     bounded_rx: Receiver<Event>,
     unbounded_tx: Sender<Event>,
     unbounded_rx: Receiver<Event>,
+    stopped: AtomicBool,
 }
 
 impl EventLoop {
@@ -93,6 +100,7 @@ impl EventLoop {
             unbounded_tx,
             bounded_tx,
             bounded_rx,
+            stopped: AtomicBool::new(false),
         })
     }
 
@@ -109,11 +117,9 @@ impl EventLoop {
         let unbounded_rx = self.unbounded_rx.clone();
         let bounded_rx = self.bounded_rx.clone();
         let rt_handle = wired.attr.client_common.handle.clone();
-        let unbounded_tx = self.unbounded_tx.clone();
         let bounded_tx = self.bounded_tx.clone();
-        let connectivity = wired.attr.client_common.connectivity.clone();
         let span = Span::current();
-        connectivity.register(wired.clone(), unbounded_tx);
+        self.register(wired.clone());
         rt_handle.spawn_blocking(move || {
             span.in_scope(|| {
                 add_span_event!("start event_loop");
@@ -136,7 +142,11 @@ impl EventLoop {
                                 break;
                             }
                             Event::PushTransaction(resp_tx) => {
-                                if matches!(loop_mode, LoopMode::Stopped) {
+                                // a stopped loop rejects pushes unless the datatype has been
+                                // resubscribed; then this push-pull decides the mode from now on.
+                                if matches!(loop_mode, LoopMode::Stopped)
+                                    && wired.mutable.read().get_state() == DatatypeState::Disabled
+                                {
                                     Self::process_blocking_resp(
                                         resp_tx,
                                         Some(
@@ -183,6 +193,11 @@ impl EventLoop {
         });
     }
 
+    /// Registers `wired` to the connectivity, so that it is notified through this loop.
+    pub fn register(&self, wired: Arc<WiredDatatype>) {
+        self.connectivity.register(wired, self.unbounded_tx.clone());
+    }
+
     fn process_blocking_resp(
         blocking_resp_tx: Option<oneshot::Sender<Option<DatatypeError>>>,
         opt_datatype_error: Option<DatatypeError>,
@@ -240,6 +255,9 @@ impl EventLoop {
     }
 
     pub fn send_stop(&self) {
+        if self.stopped.swap(true, Ordering::AcqRel) {
+            return;
+        }
         let (tx, rx) = crossbeam_channel::bounded::<()>(1);
         match self.send_to_unbounded(Event::Stop(tx)) {
             Ok(_) => {
@@ -272,7 +290,7 @@ impl EventLoop {
     }
 
     pub fn send_push_transaction_with_best_effort(&self) {
-        if !self.connectivity.is_realtime() {
+        if !self.connectivity.is_realtime() || self.stopped.load(Ordering::Acquire) {
             return;
         }
         self.send_to_bounded(Event::PushTransaction(None))
@@ -280,9 +298,23 @@ impl EventLoop {
     }
 
     pub fn send_push_transaction_with_guarantee(&self) -> Result<(), DatatypeError> {
+        futures::executor::block_on(self.send_push_transaction_with_response())
+    }
+
+    /// Sends a PushTransaction event right away and returns a future of its response.
+    ///
+    /// The future does not borrow the event loop, so it can be awaited on any executor.
+    pub fn send_push_transaction_with_response(
+        &self,
+    ) -> impl Future<Output = Result<(), DatatypeError>> + Send + 'static {
         let (tx, rx) = oneshot::channel();
-        self.send_to_unbounded(Event::PushTransaction(Some(tx)))?;
-        futures::executor::block_on(async {
+        let sent = if self.stopped.load(Ordering::Acquire) {
+            Err(InternalReason::EventLoop("event loop stopped".into()).into_error())
+        } else {
+            self.send_to_unbounded(Event::PushTransaction(Some(tx)))
+        };
+        async move {
+            sent?;
             match rx.await {
                 Ok(Some(err)) => Err(err),
                 Ok(None) => Ok(()),
@@ -291,7 +323,7 @@ impl EventLoop {
                 ))
                 .into_error()),
             }
-        })
+        }
     }
 }
 
@@ -342,7 +374,7 @@ mod tests_event_loop {
         let counter = client.create_datatype(key).build_counter().unwrap();
 
         let interceptor = connectivity
-            .get_wired_interceptor(&resource_id, &client.get_cuid())
+            .get_wired_interceptor(&resource_id, client.get_cuid())
             .unwrap();
 
         // inject SyncFailed → maps to RecoveryAction::RetryWithBackOff
@@ -375,7 +407,7 @@ mod tests_event_loop {
         let counter = client.create_datatype(key).build_counter().unwrap();
 
         let interceptor = connectivity
-            .get_wired_interceptor(&resource_id, &client.get_cuid())
+            .get_wired_interceptor(&resource_id, client.get_cuid())
             .unwrap();
 
         let pull_count = Arc::new(AtomicUsize::new(0));
@@ -412,7 +444,7 @@ mod tests_event_loop {
         let counter = client.create_datatype(key).build_counter().unwrap();
 
         let interceptor = connectivity
-            .get_wired_interceptor(&resource_id, &client.get_cuid())
+            .get_wired_interceptor(&resource_id, client.get_cuid())
             .unwrap();
 
         let should_fail = Arc::new(AtomicBool::new(true));
@@ -458,7 +490,7 @@ mod tests_event_loop {
         let counter = client.create_datatype(key).build_counter().unwrap();
 
         let interceptor = connectivity
-            .get_wired_interceptor(&resource_id, &client.get_cuid())
+            .get_wired_interceptor(&resource_id, client.get_cuid())
             .unwrap();
 
         let pull_count = Arc::new(AtomicUsize::new(0));
@@ -0,0 +1,284 @@
+use std::sync::Arc;
+
+use derive_more::Display;
+use futures::Stream;
+use tracing::trace;
+
+use crate::{
+    DatatypeError, IntoString,
+    datatypes::{
+        common::{ReturnType, datatype_instrument},
+        crdts::Crdt,
+        datatype::DatatypeBlanket,
+        transactional::{TransactionContext, TransactionalDatatype},
+        watch,
+    },
+    errors::{BoxedError, datatypes::InternalReason},
+    operations::Operation,
+};
+
+/// How a [`Flag`] resolves an enable and a disable made concurrently.
+///
+/// The policy is chosen by [`DatatypeBuilder::with_flag_policy`](crate::DatatypeBuilder::with_flag_policy)
+/// when the flag is created; it is carried by the snapshots of the flag, so every subscriber
+/// follows the policy of the creator.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Display)]
+#[repr(u8)]
+pub enum FlagPolicy {
+    /// The flag is enabled if any concurrent write enabled it.
+    #[default]
+    #[display("EnableWins")]
+    EnableWins = 0,
+    /// The flag is disabled if any concurrent write disabled it.
+    #[display("DisableWins")]
+    DisableWins = 1,
+}
+
+/// A flag is a conflict-free boolean, e.g., a feature toggle or an "is done" checkbox.
+///
+/// A flag starts disabled. An enable or a disable supersedes every write its replica had
+/// observed, and a concurrent enable and disable are resolved by the [`FlagPolicy`].
+///
+/// # Examples
+///
+/// ```
+/// # use qortoo::{Client, FlagPolicy};
+/// let client = Client::builder("doc-example", "flag-test").build().unwrap();
+/// let done = client
+///     .create_datatype("test-flag")
+///     .with_flag_policy(FlagPolicy::DisableWins)
+///     .build_flag()
+///     .unwrap();
+/// assert!(!done.is_enabled());
+/// done.enable().unwrap();
+/// assert!(done.is_enabled());
+/// assert_eq!(done.get_policy(), FlagPolicy::DisableWins);
+/// ```
+#[derive(Clone)]
+pub struct Flag {
+    datatype: Arc<TransactionalDatatype>,
+    tx_ctx: Arc<TransactionContext>,
+}
+
+impl Flag {
+    pub(crate) fn new(datatype: Arc<TransactionalDatatype>) -> Self {
+        Flag {
+            datatype,
+            tx_ctx: Default::default(),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn new_for_test(state: crate::DatatypeState) -> Self {
+        Self::new(
+            TransactionalDatatype::new_arc(
+                crate::datatypes::common::new_attribute!(crate::DataType::Flag),
+                state,
+                Default::default(),
+            )
+            .unwrap(),
+        )
+    }
+
+    fn execute(&self, op: Operation) -> Result<bool, DatatypeError> {
+        let ret = self
+            .datatype
+            .execute_local_operation_as_tx(self.tx_ctx.clone(), op)?;
+        trace!("executed -> {ret:?}");
+        match ret {
+            ReturnType::Flag(was_enabled) => Ok(was_enabled),
+            _ => {
+                Err(InternalReason::ExecuteOperation("unexpected return type".into()).into_error())
+            }
+        }
+    }
+
+    datatype_instrument! {
+    /// Enables the flag.
+    ///
+    /// Returns whether the flag was enabled before.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::Client;
+    /// let client = Client::builder("doc-example", "flag-enable-test").build().unwrap();
+    /// let flag = client.create_datatype("test-flag").build_flag().unwrap();
+    /// assert!(!flag.enable().unwrap());
+    /// assert!(flag.enable().unwrap());
+    /// ```
+    pub fn enable(&self) -> Result<bool, DatatypeError> {
+        self.execute(Operation::new_flag_enable())
+    }}
+
+    datatype_instrument! {
+    /// Disables the flag.
+    ///
+    /// Returns whether the flag was enabled before.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::Client;
+    /// let client = Client::builder("doc-example", "flag-disable-test").build().unwrap();
+    /// let flag = client.create_datatype("test-flag").build_flag().unwrap();
+    /// flag.enable().unwrap();
+    /// assert!(flag.disable().unwrap());
+    /// assert!(!flag.is_enabled());
+    /// ```
+    pub fn disable(&self) -> Result<bool, DatatypeError> {
+        self.execute(Operation::new_flag_disable())
+    }}
+
+    /// Returns whether the flag is enabled.
+    pub fn is_enabled(&self) -> bool {
+        let mutable = self.datatype.mutable.read();
+        match &mutable.crdt {
+            Crdt::Flag(f) => f.is_enabled(),
+            _ => unreachable!("flag must have a flag crdt"),
+        }
+    }
+
+    /// Returns a stream of whether the flag is enabled after every change.
+    ///
+    /// See [`Counter::watch`](crate::Counter::watch) for when values are yielded.
+    pub fn watch(&self) -> impl Stream<Item = bool> + Send + 'static {
+        watch::watch(&self.datatype, |crdt| match crdt {
+            Crdt::Flag(f) => f.is_enabled(),
+            _ => unreachable!("flag must have a flag crdt"),
+        })
+    }
+
+    /// Returns the policy resolving concurrent enables and disables of this flag.
+    pub fn get_policy(&self) -> FlagPolicy {
+        let mutable = self.datatype.mutable.read();
+        match &mutable.crdt {
+            Crdt::Flag(f) => f.policy(),
+            _ => unreachable!("flag must have a flag crdt"),
+        }
+    }
+
+    datatype_instrument! {
+    /// Executes multiple operations atomically within a transaction.
+    ///
+    /// If the transaction function returns an error, all operations within
+    /// the transaction are rolled back, leaving the flag unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::Client;
+    /// let client = Client::builder("doc-example", "flag-transaction-test").build().unwrap();
+    /// let flag = client.create_datatype("test-flag").build_flag().unwrap();
+    ///
+    /// let result = flag.transaction("failing-update", |f| {
+    ///     f.enable()?;
+    ///     Err("something went wrong".into())
+    /// });
+    /// assert!(result.is_err());
+    /// assert!(!flag.is_enabled()); // unchanged
+    /// ```
+    pub fn transaction<T>(
+        &self,
+        tag: impl IntoString,
+        tx_func: T,
+    ) -> Result<(), DatatypeError>
+    where
+        T: FnOnce(Self) -> Result<(), BoxedError> + Send + Sync + 'static,
+    {
+        self.datatype.check_writable()?;
+        let this_tx_ctx = Arc::new(TransactionContext::new(tag));
+        let this_tx_ctx_clone = this_tx_ctx.clone();
+        let do_tx_func = move || {
+            let mut flag_clone = self.clone();
+            flag_clone.tx_ctx = this_tx_ctx_clone.clone();
+            match tx_func(flag_clone) {
+                Ok(_) => Ok(()),
+                Err(e) => Err(DatatypeError::TransactionFailed(e.to_string())),
+            }
+        };
+        self.datatype.do_transaction(this_tx_ctx, do_tx_func)
+    }}
+}
+
+impl DatatypeBlanket for Flag {
+    fn get_core(&self) -> &TransactionalDatatype {
+        self.datatype.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests_flag {
+    use tracing::instrument;
+
+    use crate::{
+        Client, DataType, Datatype, DatatypeState, FlagPolicy, LocalConnectivity,
+        datatypes::flag::Flag,
+        utils::test_utils::{get_test_collection_name, get_test_func_name, get_test_ids},
+    };
+
+    #[test]
+    fn can_assert_send_and_sync_traits() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Flag>();
+    }
+
+    #[test]
+    #[instrument]
+    fn can_use_flag_operations() {
+        let flag = Flag::new_for_test(DatatypeState::Creating);
+        assert_eq!(flag.get_type(), DataType::Flag);
+        assert_eq!(flag.get_policy(), FlagPolicy::EnableWins);
+        assert!(!flag.is_enabled());
+        assert!(!flag.enable().unwrap());
+        assert!(flag.disable().unwrap());
+        assert!(!flag.disable().unwrap());
+
+        flag.enable().unwrap();
+        let result = flag.transaction("failure", |f| {
+            f.disable()?;
+            f.enable()?;
+            f.disable()?;
+            Err("failed".into())
+        });
+        assert!(result.is_err());
+        assert!(flag.is_enabled());
+    }
+
+    #[test]
+    #[instrument]
+    fn can_resolve_concurrent_writes_by_creator_policy() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let (collection, key, _) = get_test_ids!();
+        let client1 = Client::builder(collection.clone(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(collection, "client2")
+            .with_connectivity(connectivity)
+            .build()
+            .unwrap();
+
+        let flag1 = client1
+            .create_datatype(key.clone())
+            .with_flag_policy(FlagPolicy::DisableWins)
+            .build_flag()
+            .unwrap();
+        flag1.enable().unwrap();
+        flag1.sync().unwrap();
+
+        let flag2 = client2.subscribe_datatype(key).build_flag().unwrap();
+        flag2.sync().unwrap();
+        assert!(flag2.is_enabled());
+        assert_eq!(flag2.get_policy(), FlagPolicy::DisableWins);
+
+        flag1.enable().unwrap();
+        flag2.disable().unwrap();
+        flag1.sync().unwrap();
+        flag2.sync().unwrap();
+        flag1.sync().unwrap();
+        assert!(!flag1.is_enabled());
+        assert!(!flag2.is_enabled());
+    }
+}
@@ -3,7 +3,8 @@ use std::{collections::BTreeMap, sync::Arc};
 use tracing::{Span, error, instrument};
 
 use crate::{
-    DatatypeError, DatatypeSet, DatatypeState, datatypes::common::Attribute,
+    DatatypeError, DatatypeSet, DatatypeState, RemoteTransaction,
+    datatypes::{common::Attribute, crdts::Crdt, watch::Watcher},
     observability::trace::add_span_event,
 };
 
@@ -17,11 +18,18 @@ pub type OnStateChangeFn = Box<dyn Fn(DatatypeSet, DatatypeState, DatatypeState)
 /// Receives: `(datatype_set, error)`
 pub type OnErrorFn = Box<dyn Fn(DatatypeSet, DatatypeError) + Send + Sync>;
 
-/// Holds per-datatype event handlers for state changes and errors.
+/// Signature for a remote-operations handler.
+///
+/// Receives: `(datatype_set, transactions)`, the transactions of other clients applied by
+/// a single pull, in the order they were applied.
+pub type OnRemoteOperationsFn = Box<dyn Fn(DatatypeSet, Vec<RemoteTransaction>) + Send + Sync>;
+
+/// Holds per-datatype event handlers for state changes, errors and remote operations.
 /// Default handlers are no-ops.
 pub struct DatatypeHandler {
     on_state_change: OnStateChangeFn,
     on_error: OnErrorFn,
+    on_remote_operations: OnRemoteOperationsFn,
 }
 
 impl DatatypeHandler {
@@ -29,6 +37,7 @@ impl DatatypeHandler {
         Self {
             on_state_change: Box::new(|_datatype_set, _old_state, _new_state| {}),
             on_error: Box::new(|_datatype_set, _err| {}),
+            on_remote_operations: Box::new(|_datatype_set, _transactions| {}),
         }
     }
 
@@ -48,6 +57,14 @@ impl DatatypeHandler {
         self
     }
 
+    pub fn set_on_remote_operations(
+        mut self,
+        f: impl Fn(DatatypeSet, Vec<RemoteTransaction>) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_remote_operations = Box::new(f);
+        self
+    }
+
     pub(crate) fn notify_state_change(
         &self,
         datatype_set: DatatypeSet,
@@ -68,10 +85,23 @@ impl DatatypeHandler {
             error!("on_error handler panicked: {e:?}");
         }
     }
+
+    pub(crate) fn notify_remote_operations(
+        &self,
+        datatype_set: DatatypeSet,
+        transactions: Vec<RemoteTransaction>,
+    ) {
+        if let Err(e) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            (self.on_remote_operations)(datatype_set, transactions)
+        })) {
+            error!("on_remote_operations handler panicked: {e:?}");
+        }
+    }
 }
 
 pub struct HandlersManager {
     handlers: BTreeMap<usize, Arc<DatatypeHandler>>,
+    watchers: Vec<Watcher>,
     attr: Arc<Attribute>,
 }
 
@@ -85,6 +115,7 @@ impl std::fmt::Debug for HandlersManager {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("HandlersManager")
             .field("handlers_count", &self.handlers.len())
+            .field("watchers_count", &self.watchers.len())
             .finish()
     }
 }
@@ -96,6 +127,7 @@ impl HandlersManager {
                 .into_iter()
                 .map(|(k, v)| (k, Arc::new(v)))
                 .collect(),
+            watchers: Vec::new(),
             attr,
         }
     }
@@ -110,6 +142,18 @@ impl HandlersManager {
             .and_then(|arc| Arc::try_unwrap(arc).ok())
     }
 
+    pub(crate) fn add_watcher(&mut self, watcher: Watcher) {
+        self.watchers.push(watcher);
+    }
+
+    /// Delivers the changed state to every watch stream, dropping the closed ones.
+    ///
+    /// Unlike handlers, watchers run synchronously, so that each stream observes the value
+    /// the change produced rather than a later one.
+    pub(crate) fn notify_watchers(&mut self, crdt: &Crdt) {
+        self.watchers.retain(|watcher| watcher(crdt));
+    }
+
     fn dispatch<F>(&self, event_name: &'static str, notify: F)
     where
         F: Fn(&DatatypeHandler, DatatypeSet) + Send + 'static,
@@ -144,6 +188,13 @@ impl HandlersManager {
             handler.notify_error(ds, err.clone());
         });
     }
+
+    #[instrument(skip_all)]
+    pub(crate) fn notify_remote_operations(&self, transactions: Vec<RemoteTransaction>) {
+        self.dispatch("notify_remote_operations", move |handler, ds| {
+            handler.notify_remote_operations(ds, transactions.clone());
+        });
+    }
 }
 
 #[cfg(test)]
@@ -160,6 +211,7 @@ mod tests_handers_manager {
 
     use crate::{
         Client, Datatype, DatatypeError, DatatypeHandler, DatatypeState, LocalConnectivity,
+        RemoteOperation,
         utils::test_utils::{get_test_collection_name, get_test_func_name},
     };
 
@@ -252,4 +304,71 @@ mod tests_handers_manager {
             .poll_interval(Duration::from_micros(100))
             .until(|| call_count.load(Ordering::Relaxed) == 2);
     }
+
+    #[test]
+    #[instrument]
+    fn can_notify_remote_operations() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let client1 = Client::builder(get_test_collection_name!(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(get_test_collection_name!(), "client2")
+            .with_connectivity(connectivity)
+            .build()
+            .unwrap();
+
+        let counter1 = client1
+            .create_datatype(get_test_func_name!())
+            .build_counter()
+            .unwrap();
+        counter1.sync().unwrap();
+
+        let call_count = Arc::new(AtomicUsize::new(0));
+        let count_for_h1 = call_count.clone();
+        let count_for_h2 = call_count.clone();
+        let cuid1 = client1.get_cuid().to_string();
+
+        let handler1 = DatatypeHandler::new().set_on_remote_operations(move |ds, txs| {
+            assert_eq!(count_for_h1.fetch_add(1, Ordering::Relaxed), 0);
+            assert_eq!(ds.get_state(), DatatypeState::Subscribed);
+            assert_eq!(txs.len(), 1);
+            assert_eq!(txs[0].cuid, cuid1);
+            assert_eq!(txs[0].tag.as_deref(), Some("bump"));
+            assert_eq!(
+                txs[0].operations,
+                vec![
+                    RemoteOperation::CounterIncrease { delta: 2 },
+                    RemoteOperation::CounterIncrease { delta: 3 },
+                ]
+            );
+        });
+        let handler2 = DatatypeHandler::new().set_on_remote_operations(move |_ds, txs| {
+            assert_eq!(count_for_h2.fetch_add(1, Ordering::Relaxed), 1);
+            assert_eq!(txs.len(), 1);
+        });
+
+        let counter2 = client2
+            .subscribe_datatype(get_test_func_name!())
+            .with_handler(1, handler2)
+            .with_handler(0, handler1)
+            .build_counter()
+            .unwrap();
+        counter2.sync().unwrap();
+
+        counter1
+            .transaction("bump", |c| {
+                c.increase_by(2)?;
+                c.increase_by(3)?;
+                Ok(())
+            })
+            .unwrap();
+        counter1.sync().unwrap();
+        counter2.sync().unwrap();
+        assert_eq!(counter2.get_value(), 5);
+        awaitility::at_most(Duration::from_secs(2))
+            .poll_interval(Duration::from_micros(100))
+            .until(|| call_count.load(Ordering::Relaxed) == 2);
+    }
 }
@@ -0,0 +1,256 @@
+use std::{collections::VecDeque, sync::Arc, time::SystemTime};
+
+use crate::{
+    operations::{Operation, body::OperationBody, transaction::Transaction},
+    types::{remote::RemoteOperation, uid::Cuid},
+};
+
+/// A committed transaction of a datatype, returned by
+/// [`Datatype::history`](crate::Datatype::history).
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    /// The unique id of the client that made the transaction.
+    pub cuid: String,
+    /// The sequence number of the transaction among those of its client.
+    pub cseq: u64,
+    /// The sequence number the server assigned to the transaction; 0 for a local
+    /// transaction, whose order the server does not report back.
+    pub sseq: u64,
+    /// The tag given to the transaction, if it was made by a tagged `transaction()`.
+    pub tag: Option<String>,
+    /// Whether the transaction was made by this client.
+    pub is_local: bool,
+    /// The operations of the transaction, in the order they were applied.
+    pub operations: Vec<HistoryOperation>,
+}
+
+/// An operation of a [`HistoryEntry`] with the time its client executed it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryOperation {
+    pub at: SystemTime,
+    pub operation: RemoteOperation,
+}
+
+impl From<&Operation> for HistoryOperation {
+    fn from(op: &Operation) -> Self {
+        Self {
+            at: op.at(),
+            operation: RemoteOperation::from(op),
+        }
+    }
+}
+
+/// Selects the entries of [`Datatype::history_filtered`](crate::Datatype::history_filtered).
+///
+/// An empty filter selects every entry; each condition set narrows the selection.
+///
+/// # Examples
+///
+/// ```
+/// use qortoo::HistoryFilter;
+/// let filter = HistoryFilter::new().with_tag("checkout").with_cuid("some-cuid");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HistoryFilter {
+    tag: Option<String>,
+    cuid: Option<String>,
+}
+
+impl HistoryFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Selects the transactions made with `tag`.
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    /// Selects the transactions made by the client of `cuid`.
+    pub fn with_cuid(mut self, cuid: impl Into<String>) -> Self {
+        self.cuid = Some(cuid.into());
+        self
+    }
+
+    fn matches(&self, tx: &Transaction) -> bool {
+        self.tag
+            .as_ref()
+            .is_none_or(|tag| tx.tag.as_ref() == Some(tag))
+            && self
+                .cuid
+                .as_ref()
+                .is_none_or(|cuid| tx.cuid.to_string() == *cuid)
+    }
+}
+
+/// The latest committed transactions of a datatype, local and remote, in the order
+/// this replica applied them.
+///
+/// Only the last `retention` transactions are kept; a snapshot, which replaces the state
+/// without its transactions, is not recorded.
+#[derive(Debug)]
+pub struct TransactionHistory {
+    transactions: VecDeque<Arc<Transaction>>,
+    retention: usize,
+}
+
+impl TransactionHistory {
+    pub fn new(retention: usize) -> Self {
+        Self {
+            transactions: VecDeque::new(),
+            retention,
+        }
+    }
+
+    pub fn record(&mut self, tx: Arc<Transaction>) {
+        if self.retention == 0
+            || tx
+                .iter()
+                .any(|op| matches!(op.body, OperationBody::Snapshot(_)))
+        {
+            return;
+        }
+        if self.transactions.len() == self.retention {
+            self.transactions.pop_front();
+        }
+        self.transactions.push_back(tx);
+    }
+
+    /// Records `tx`, a transaction the push buffer coalesced, in place of the entry it grew
+    /// from if that is still the latest one.
+    pub fn record_coalesced(&mut self, tx: Arc<Transaction>) {
+        match self.transactions.back_mut() {
+            Some(last) if last.cuid == tx.cuid && last.cseq == tx.cseq => *last = tx,
+            _ => self.record(tx),
+        }
+    }
+
+    /// Returns the entries `filter` selects, oldest first; `local` tells the own transactions.
+    pub fn entries(&self, filter: &HistoryFilter, local: &Cuid) -> Vec<HistoryEntry> {
+        self.transactions
+            .iter()
+            .filter(|tx| filter.matches(tx))
+            .map(|tx| HistoryEntry {
+                cuid: tx.cuid.to_string(),
+                cseq: tx.cseq,
+                sseq: tx.sseq,
+                tag: tx.tag.clone(),
+                is_local: tx.cuid == *local,
+                operations: tx.iter().map(HistoryOperation::from).collect(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests_history {
+    use tracing::instrument;
+
+    use crate::{
+        Client, Datatype, HistoryFilter, LocalConnectivity, RemoteOperation,
+        utils::test_utils::{get_test_collection_name, get_test_func_name},
+    };
+
+    #[test]
+    #[instrument]
+    fn can_query_local_and_remote_history() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let client1 = Client::builder(get_test_collection_name!(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(get_test_collection_name!(), "client2")
+            .with_connectivity(connectivity)
+            .build()
+            .unwrap();
+        let counter1 = client1
+            .create_datatype(get_test_func_name!())
+            .build_counter()
+            .unwrap();
+        counter1.increase_by(2).unwrap();
+        counter1
+            .transaction("bump", |c| {
+                c.increase_by(3)?;
+                c.increase_by(4)?;
+                Ok(())
+            })
+            .unwrap();
+        counter1.sync().unwrap();
+        let counter2 = client2
+            .subscribe_datatype(get_test_func_name!())
+            .build_counter()
+            .unwrap();
+        counter2.sync().unwrap();
+        counter2
+            .transaction("bump", |c| {
+                c.increase_by(5)?;
+                Ok(())
+            })
+            .unwrap();
+        counter2.sync().unwrap();
+        counter1.sync().unwrap();
+
+        let history = counter1.history();
+        assert_eq!(history.len(), 3);
+        assert!(history[0].is_local && history[1].is_local);
+        assert_eq!(history[1].tag.as_deref(), Some("bump"));
+        assert_eq!(history[1].operations.len(), 2);
+        let remote = &history[2];
+        assert!(!remote.is_local);
+        assert_eq!(remote.cuid, client2.get_cuid().to_string());
+        assert!(remote.sseq > 0);
+        assert_eq!(
+            remote.operations[0].operation,
+            RemoteOperation::CounterIncrease { delta: 5 }
+        );
+
+        let bumps = counter1.history_filtered(&HistoryFilter::new().with_tag("bump"));
+        assert_eq!(bumps.len(), 2);
+        let bumps_of_client2 = counter1.history_filtered(
+            &HistoryFilter::new()
+                .with_tag("bump")
+                .with_cuid(client2.get_cuid().to_string()),
+        );
+        assert_eq!(bumps_of_client2, vec![remote.clone()]);
+        // The subscriber started from a snapshot, so it only knows its own transaction.
+        assert_eq!(counter2.history().len(), 1);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_keep_history_within_retention() {
+        let client = Client::builder(get_test_collection_name!(), get_test_func_name!())
+            .build()
+            .unwrap();
+        let counter = client
+            .create_datatype(get_test_func_name!())
+            .with_history_retention(2)
+            .build_counter()
+            .unwrap();
+        for delta in 1..=3 {
+            counter.increase_by(delta).unwrap();
+        }
+        let deltas: Vec<_> = counter
+            .history()
+            .into_iter()
+            .map(|e| e.operations[0].operation.clone())
+            .collect();
+        assert_eq!(
+            deltas,
+            vec![
+                RemoteOperation::CounterIncrease { delta: 2 },
+                RemoteOperation::CounterIncrease { delta: 3 },
+            ]
+        );
+
+        let counter = client
+            .create_datatype(format!("{}-off", get_test_func_name!()))
+            .with_history_retention(0)
+            .build_counter()
+            .unwrap();
+        counter.increase().unwrap();
+        assert!(counter.history().is_empty());
+    }
+}
@@ -0,0 +1,302 @@
+use std::sync::Arc;
+
+use tracing::trace;
+
+use crate::{
+    DatatypeError, IntoString, Value,
+    datatypes::{
+        common::{ReturnType, datatype_instrument},
+        crdts::{Crdt, list_crdt::ListCrdt},
+        datatype::DatatypeBlanket,
+        transactional::{TransactionContext, TransactionalDatatype},
+    },
+    errors::{BoxedError, datatypes::InternalReason},
+    operations::Operation,
+};
+
+/// A list is a conflict-free ordered sequence of [`Value`]s.
+///
+/// Elements are addressed by index. Concurrent inserts at the same position are all kept
+/// and ordered identically on every replica: the insert with the larger lamport timestamp
+/// (ties broken by client id) comes first.
+#[derive(Clone)]
+pub struct List {
+    datatype: Arc<TransactionalDatatype>,
+    tx_ctx: Arc<TransactionContext>,
+}
+
+impl List {
+    pub(crate) fn new(datatype: Arc<TransactionalDatatype>) -> Self {
+        List {
+            datatype,
+            tx_ctx: Default::default(),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn new_for_test(state: crate::DatatypeState) -> Self {
+        Self::new(
+            TransactionalDatatype::new_arc(
+                crate::datatypes::common::new_attribute!(crate::DataType::List),
+                state,
+                Default::default(),
+            )
+            .unwrap(),
+        )
+    }
+
+    fn execute(&self, op: Operation) -> Result<Option<Value>, DatatypeError> {
+        let ret = self
+            .datatype
+            .execute_local_operation_as_tx(self.tx_ctx.clone(), op)?;
+        trace!("executed -> {ret:?}");
+        match ret {
+            ReturnType::List(removed) => Ok(removed),
+            _ => {
+                Err(InternalReason::ExecuteOperation("unexpected return type".into()).into_error())
+            }
+        }
+    }
+
+    fn read<R>(&self, f: impl FnOnce(&ListCrdt) -> R) -> R {
+        let mutable = self.datatype.mutable.read();
+        match &mutable.crdt {
+            Crdt::List(l) => f(l),
+            _ => unreachable!("list must have a list crdt"),
+        }
+    }
+
+    datatype_instrument! {
+    /// Inserts `value` at `index`, shifting later elements to the right.
+    ///
+    /// # Errors
+    /// Returns [`DatatypeError::OutOfBounds`] if `index` is larger than the length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::{Client, Value};
+    /// let client = Client::builder("doc-example", "list-insert-test").build().unwrap();
+    /// let list = client.create_datatype("test-list").build_list().unwrap();
+    /// list.insert(0, "b").unwrap();
+    /// list.insert(0, "a").unwrap();
+    /// assert_eq!(list.get(1), Some(Value::from("b")));
+    /// assert!(list.insert(5, "x").is_err());
+    /// ```
+    pub fn insert(&self, index: usize, value: impl Into<Value>) -> Result<(), DatatypeError> {
+        self.execute(Operation::new_list_insert(index, value.into()))?;
+        Ok(())
+    }}
+
+    datatype_instrument! {
+    /// Removes the element at `index`, shifting later elements to the left.
+    ///
+    /// Returns the removed value.
+    ///
+    /// # Errors
+    /// Returns [`DatatypeError::OutOfBounds`] if `index` is not smaller than the length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::{Client, Value};
+    /// let client = Client::builder("doc-example", "list-delete-test").build().unwrap();
+    /// let list = client.create_datatype("test-list").build_list().unwrap();
+    /// list.insert(0, 1).unwrap();
+    /// assert_eq!(list.delete(0).unwrap(), Value::Int(1));
+    /// assert!(list.is_empty());
+    /// ```
+    pub fn delete(&self, index: usize) -> Result<Value, DatatypeError> {
+        self.execute(Operation::new_list_delete(index))?
+            .ok_or_else(|| InternalReason::ExecuteOperation("nothing deleted".into()).into_error())
+    }}
+
+    /// Returns the element at `index`, if any.
+    pub fn get(&self, index: usize) -> Option<Value> {
+        self.read(|l| l.get(index).cloned())
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.read(|l| l.len())
+    }
+
+    /// Returns `true` if the list has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.read(|l| l.is_empty())
+    }
+
+    /// Returns an iterator over a snapshot of the elements, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::{Client, Value};
+    /// let client = Client::builder("doc-example", "list-iter-test").build().unwrap();
+    /// let list = client.create_datatype("test-list").build_list().unwrap();
+    /// list.insert(0, 1).unwrap();
+    /// list.insert(1, 2).unwrap();
+    /// let sum: i64 = list
+    ///     .iter()
+    ///     .map(|v| if let Value::Int(i) = v { i } else { 0 })
+    ///     .sum();
+    /// assert_eq!(sum, 3);
+    /// ```
+    pub fn iter(&self) -> std::vec::IntoIter<Value> {
+        self.read(|l| l.iter().cloned().collect::<Vec<_>>())
+            .into_iter()
+    }
+
+    datatype_instrument! {
+    /// Executes multiple operations atomically within a transaction.
+    ///
+    /// If the transaction function returns an error, all operations within
+    /// the transaction are rolled back, leaving the list unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::Client;
+    /// let client = Client::builder("doc-example", "list-transaction-test").build().unwrap();
+    /// let list = client.create_datatype("test-list").build_list().unwrap();
+    ///
+    /// let result = list.transaction("failing-update", |l| {
+    ///     l.insert(0, 1)?;
+    ///     l.insert(1, 2)?;
+    ///     Err("something went wrong".into())
+    /// });
+    /// assert!(result.is_err());
+    /// assert!(list.is_empty()); // unchanged
+    /// ```
+    pub fn transaction<T>(
+        &self,
+        tag: impl IntoString,
+        tx_func: T,
+    ) -> Result<(), DatatypeError>
+    where
+        T: FnOnce(Self) -> Result<(), BoxedError> + Send + Sync + 'static,
+    {
+        self.datatype.check_writable()?;
+        let this_tx_ctx = Arc::new(TransactionContext::new(tag));
+        let this_tx_ctx_clone = this_tx_ctx.clone();
+        let do_tx_func = move || {
+            let mut list_clone = self.clone();
+            list_clone.tx_ctx = this_tx_ctx_clone.clone();
+            match tx_func(list_clone) {
+                Ok(_) => Ok(()),
+                Err(e) => Err(DatatypeError::TransactionFailed(e.to_string())),
+            }
+        };
+        self.datatype.do_transaction(this_tx_ctx, do_tx_func)
+    }}
+}
+
+impl DatatypeBlanket for List {
+    fn get_core(&self) -> &TransactionalDatatype {
+        self.datatype.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests_list {
+    use tracing::instrument;
+
+    use crate::{
+        Client, DataType, Datatype, DatatypeError, DatatypeState, LocalConnectivity, Value,
+        datatypes::list::List,
+        utils::test_utils::{get_test_collection_name, get_test_func_name, get_test_ids},
+    };
+
+    #[test]
+    fn can_assert_send_and_sync_traits() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<List>();
+    }
+
+    #[test]
+    #[instrument]
+    fn can_use_list_operations() {
+        let list = List::new_for_test(DatatypeState::Creating);
+        assert_eq!(list.get_type(), DataType::List);
+        list.insert(0, "c").unwrap();
+        list.insert(0, "a").unwrap();
+        list.insert(1, "b").unwrap();
+        assert_eq!(
+            list.iter().collect::<Vec<_>>(),
+            vec![Value::from("a"), Value::from("b"), Value::from("c")]
+        );
+        assert_eq!(list.delete(1).unwrap(), Value::from("b"));
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.get(1), Some(Value::from("c")));
+        assert_eq!(list.get(2), None);
+        assert!(matches!(
+            list.delete(2).unwrap_err(),
+            DatatypeError::OutOfBounds(_)
+        ));
+    }
+
+    #[test]
+    #[instrument]
+    fn can_rollback_transaction() {
+        let list = List::new_for_test(DatatypeState::Creating);
+        list.insert(0, 1).unwrap();
+        list.insert(1, 2).unwrap();
+        let result = list.transaction("failure", |l| {
+            l.insert(1, 3)?;
+            l.delete(0)?;
+            l.delete(0)?;
+            Err("failed".into())
+        });
+        assert!(result.is_err());
+        assert_eq!(
+            list.iter().collect::<Vec<_>>(),
+            vec![Value::Int(1), Value::Int(2)]
+        );
+
+        // a failing operation also rolls back the ones before it
+        let result = list.transaction("out-of-bounds", |l| {
+            l.insert(0, 0)?;
+            l.delete(10)?;
+            Ok(())
+        });
+        assert!(result.is_err());
+        assert_eq!(list.len(), 2);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_converge_concurrent_inserts_between_clients() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let (collection, key, _) = get_test_ids!();
+        let client1 = Client::builder(collection.clone(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(collection, "client2")
+            .with_connectivity(connectivity)
+            .build()
+            .unwrap();
+
+        let list1 = client1.create_datatype(key.clone()).build_list().unwrap();
+        list1.insert(0, "first").unwrap();
+        list1.insert(1, "last").unwrap();
+        list1.sync().unwrap();
+
+        let list2 = client2.subscribe_datatype(key).build_list().unwrap();
+        list2.sync().unwrap();
+        assert_eq!(list2.len(), 2);
+
+        list1.insert(1, "from-1").unwrap();
+        list2.insert(1, "from-2").unwrap();
+        list2.delete(0).unwrap();
+        list1.sync().unwrap();
+        list2.sync().unwrap();
+        list1.sync().unwrap();
+
+        let values1: Vec<_> = list1.iter().collect();
+        assert_eq!(values1, list2.iter().collect::<Vec<_>>());
+        assert_eq!(values1.len(), 3);
+        assert_eq!(values1[2], Value::from("last"));
+    }
+}
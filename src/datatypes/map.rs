@@ -0,0 +1,275 @@
+use std::sync::Arc;
+
+use tracing::trace;
+
+use crate::{
+    DatatypeError, IntoString, Value,
+    datatypes::{
+        common::{ReturnType, datatype_instrument},
+        crdts::{Crdt, map_crdt::MapCrdt},
+        datatype::DatatypeBlanket,
+        transactional::{TransactionContext, TransactionalDatatype},
+    },
+    errors::{BoxedError, datatypes::InternalReason},
+    operations::Operation,
+};
+
+/// A map is a conflict-free datatype of string keys to [`Value`]s.
+///
+/// Each key is resolved independently by last-writer-wins, so concurrent puts to the
+/// same key converge to the write with the larger lamport timestamp (ties broken by
+/// client id). A removal is a write as well: it hides the key until a later put.
+#[derive(Clone)]
+pub struct Map {
+    datatype: Arc<TransactionalDatatype>,
+    tx_ctx: Arc<TransactionContext>,
+}
+
+impl Map {
+    pub(crate) fn new(datatype: Arc<TransactionalDatatype>) -> Self {
+        Map {
+            datatype,
+            tx_ctx: Default::default(),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn new_for_test(state: crate::DatatypeState) -> Self {
+        Self::new(
+            TransactionalDatatype::new_arc(
+                crate::datatypes::common::new_attribute!(crate::DataType::Map),
+                state,
+                Default::default(),
+            )
+            .unwrap(),
+        )
+    }
+
+    fn execute(&self, op: Operation) -> Result<Option<Value>, DatatypeError> {
+        let ret = self
+            .datatype
+            .execute_local_operation_as_tx(self.tx_ctx.clone(), op)?;
+        trace!("executed -> {ret:?}");
+        match ret {
+            ReturnType::Map(prev) => Ok(prev),
+            _ => {
+                Err(InternalReason::ExecuteOperation("unexpected return type".into()).into_error())
+            }
+        }
+    }
+
+    fn read<R>(&self, f: impl FnOnce(&MapCrdt) -> R) -> R {
+        let mutable = self.datatype.mutable.read();
+        match &mutable.crdt {
+            Crdt::Map(m) => f(m),
+            _ => unreachable!("map must have a map crdt"),
+        }
+    }
+
+    datatype_instrument! {
+    /// Associates `value` with `key`.
+    ///
+    /// Returns the value previously stored under `key`, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::{Client, Value};
+    /// let client = Client::builder("doc-example", "map-put-test").build().unwrap();
+    /// let map = client.create_datatype("test-map").build_map().unwrap();
+    /// assert_eq!(map.put("k", 1).unwrap(), None);
+    /// assert_eq!(map.put("k", 2).unwrap(), Some(Value::Int(1)));
+    /// ```
+    pub fn put(&self, key: impl IntoString, value: impl Into<Value>) -> Result<Option<Value>, DatatypeError> {
+        self.execute(Operation::new_map_put(key.into(), value.into()))
+    }}
+
+    datatype_instrument! {
+    /// Removes `key` from the map.
+    ///
+    /// Returns the value that was stored under `key`, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::{Client, Value};
+    /// let client = Client::builder("doc-example", "map-remove-test").build().unwrap();
+    /// let map = client.create_datatype("test-map").build_map().unwrap();
+    /// map.put("k", "v").unwrap();
+    /// assert_eq!(map.remove("k").unwrap(), Some(Value::from("v")));
+    /// assert!(!map.contains_key("k"));
+    /// ```
+    pub fn remove(&self, key: impl IntoString) -> Result<Option<Value>, DatatypeError> {
+        self.execute(Operation::new_map_remove(key.into()))
+    }}
+
+    /// Returns the value stored under `key`, if any.
+    pub fn get(&self, key: &str) -> Option<Value> {
+        self.read(|m| m.get(key).cloned())
+    }
+
+    /// Returns `true` if the map holds a value for `key`.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.read(|m| m.get(key).is_some())
+    }
+
+    /// Returns the keys of the map in ascending order.
+    pub fn keys(&self) -> Vec<String> {
+        self.read(|m| m.iter().map(|(k, _)| k.clone()).collect())
+    }
+
+    /// Returns the number of keys in the map.
+    pub fn len(&self) -> usize {
+        self.read(|m| m.len())
+    }
+
+    /// Returns `true` if the map holds no keys.
+    pub fn is_empty(&self) -> bool {
+        self.read(|m| m.is_empty())
+    }
+
+    datatype_instrument! {
+    /// Executes multiple operations atomically within a transaction.
+    ///
+    /// If the transaction function returns an error, all operations within
+    /// the transaction are rolled back, leaving the map unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::Client;
+    /// let client = Client::builder("doc-example", "map-transaction-test").build().unwrap();
+    /// let map = client.create_datatype("test-map").build_map().unwrap();
+    ///
+    /// let result = map.transaction("failing-update", |m| {
+    ///     m.put("a", 1)?;
+    ///     m.put("b", 2)?;
+    ///     Err("something went wrong".into())
+    /// });
+    /// assert!(result.is_err());
+    /// assert!(map.is_empty()); // unchanged
+    /// ```
+    pub fn transaction<T>(
+        &self,
+        tag: impl IntoString,
+        tx_func: T,
+    ) -> Result<(), DatatypeError>
+    where
+        T: FnOnce(Self) -> Result<(), BoxedError> + Send + Sync + 'static,
+    {
+        self.datatype.check_writable()?;
+        let this_tx_ctx = Arc::new(TransactionContext::new(tag));
+        let this_tx_ctx_clone = this_tx_ctx.clone();
+        let do_tx_func = move || {
+            let mut map_clone = self.clone();
+            map_clone.tx_ctx = this_tx_ctx_clone.clone();
+            match tx_func(map_clone) {
+                Ok(_) => Ok(()),
+                Err(e) => Err(DatatypeError::TransactionFailed(e.to_string())),
+            }
+        };
+        self.datatype.do_transaction(this_tx_ctx, do_tx_func)
+    }}
+}
+
+impl DatatypeBlanket for Map {
+    fn get_core(&self) -> &TransactionalDatatype {
+        self.datatype.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests_map {
+    use tracing::instrument;
+
+    use crate::{
+        Client, DataType, Datatype, DatatypeState, LocalConnectivity, Value,
+        datatypes::map::Map,
+        utils::test_utils::{get_test_collection_name, get_test_func_name, get_test_ids},
+    };
+
+    #[test]
+    fn can_assert_send_and_sync_traits() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Map>();
+    }
+
+    #[test]
+    #[instrument]
+    fn can_use_map_operations() {
+        let map = Map::new_for_test(DatatypeState::Creating);
+        assert_eq!(map.get_type(), DataType::Map);
+        assert!(map.is_empty());
+        assert_eq!(map.put("b", 1).unwrap(), None);
+        assert_eq!(map.put("a", "x").unwrap(), None);
+        assert_eq!(map.put("b", 2).unwrap(), Some(Value::Int(1)));
+        assert_eq!(map.keys(), vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(map.len(), 2);
+
+        assert_eq!(map.remove("a").unwrap(), Some(Value::from("x")));
+        assert_eq!(map.remove("a").unwrap(), None);
+        assert!(!map.contains_key("a"));
+        assert_eq!(map.get("b"), Some(Value::Int(2)));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_rollback_transaction() {
+        let map = Map::new_for_test(DatatypeState::Creating);
+        map.put("keep", 1).unwrap();
+        let result = map.transaction("failure", |m| {
+            m.put("keep", 2)?;
+            m.put("new", 3)?;
+            m.remove("keep")?;
+            Err("failed".into())
+        });
+        assert!(result.is_err());
+        assert_eq!(map.keys(), vec!["keep".to_string()]);
+        assert_eq!(map.get("keep"), Some(Value::Int(1)));
+    }
+
+    #[test]
+    #[instrument]
+    fn can_converge_and_subscribe_with_snapshot() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let (collection, key, _) = get_test_ids!();
+        let client1 = Client::builder(collection.clone(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(collection.clone(), "client2")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client3 = Client::builder(collection, "client3")
+            .with_connectivity(connectivity)
+            .build()
+            .unwrap();
+
+        let map1 = client1.create_datatype(key.clone()).build_map().unwrap();
+        map1.put("shared", 0).unwrap();
+        map1.put("removed", 0).unwrap();
+        map1.sync().unwrap();
+
+        let map2 = client2.subscribe_datatype(key.clone()).build_map().unwrap();
+        map2.sync().unwrap();
+        assert_eq!(map2.get("shared"), Some(Value::Int(0)));
+
+        map1.put("shared", 1).unwrap();
+        map2.put("shared", 2).unwrap();
+        map2.remove("removed").unwrap();
+        map1.sync().unwrap();
+        map2.sync().unwrap();
+        map1.sync().unwrap();
+
+        assert_eq!(map1.keys(), vec!["shared".to_string()]);
+        assert_eq!(map1.get("shared"), map2.get("shared"));
+
+        let map3 = client3.subscribe_datatype(key).build_map().unwrap();
+        map3.sync().unwrap();
+        assert_eq!(map3.keys(), map1.keys());
+        assert_eq!(map3.get("shared"), map1.get("shared"));
+    }
+}
@@ -1,17 +1,30 @@
+pub mod bounded_counter;
 pub mod builder;
 pub mod common;
 pub mod counter;
-mod crdts;
+pub(crate) mod crdts;
+pub mod custom;
 pub mod datatype;
 pub mod datatype_set;
+pub mod document;
 pub mod event_loop;
+pub mod flag;
 pub mod handler;
+pub mod history;
+pub mod list;
+pub mod map;
+pub mod multi_value_register;
 mod mutable;
 pub mod option;
 pub mod pull_handler;
 pub mod push_buffer;
-mod transactional;
+pub mod set;
+pub mod text;
+pub(crate) mod transactional;
 mod tx_record;
+pub mod undo;
+pub mod variable;
+pub mod watch;
 pub mod wired;
 #[cfg(test)]
 pub mod wired_interceptor;
@@ -0,0 +1,275 @@
+use std::sync::Arc;
+
+use tracing::trace;
+
+use crate::{
+    DatatypeError, IntoString, Value,
+    datatypes::{
+        common::{ReturnType, datatype_instrument},
+        crdts::Crdt,
+        datatype::DatatypeBlanket,
+        transactional::{TransactionContext, TransactionalDatatype},
+    },
+    errors::{BoxedError, datatypes::InternalReason},
+    operations::Operation,
+    types::node_id::NodeId,
+};
+
+/// A value of a [`MultiValueRegister`] together with the write that produced it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConcurrentValue {
+    pub value: Value,
+    /// The cuid of the client that wrote the value.
+    pub cuid: String,
+    /// The lamport timestamp of the write.
+    pub lamport: u64,
+}
+
+impl ConcurrentValue {
+    pub(crate) fn new(value: Value, written_by: &NodeId) -> Self {
+        Self {
+            value,
+            cuid: written_by.cuid.to_string(),
+            lamport: written_by.lamport,
+        }
+    }
+}
+
+/// A multi-value register is a conflict-free register exposing concurrent writes.
+///
+/// Unlike a [`crate::Variable`], concurrent writes are not resolved: all of them are kept
+/// and returned by [`get`](Self::get), so that the application or the user can choose.
+/// A write supersedes every value its client had observed, so the conflict is resolved
+/// by writing again.
+#[derive(Clone)]
+pub struct MultiValueRegister {
+    datatype: Arc<TransactionalDatatype>,
+    tx_ctx: Arc<TransactionContext>,
+}
+
+impl MultiValueRegister {
+    pub(crate) fn new(datatype: Arc<TransactionalDatatype>) -> Self {
+        MultiValueRegister {
+            datatype,
+            tx_ctx: Default::default(),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn new_for_test(state: crate::DatatypeState) -> Self {
+        Self::new(
+            TransactionalDatatype::new_arc(
+                crate::datatypes::common::new_attribute!(crate::DataType::MultiValueRegister),
+                state,
+                Default::default(),
+            )
+            .unwrap(),
+        )
+    }
+
+    datatype_instrument! {
+    /// Sets the value of the register, superseding every value observed so far.
+    ///
+    /// Returns the superseded values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::{Client, Value};
+    /// let client = Client::builder("doc-example", "mvr-set-test").build().unwrap();
+    /// let register = client
+    ///     .create_datatype("test-register")
+    ///     .build_multi_value_register()
+    ///     .unwrap();
+    /// assert!(register.set("draft").unwrap().is_empty());
+    /// let superseded = register.set("final").unwrap();
+    /// assert_eq!(superseded[0].value, Value::from("draft"));
+    /// ```
+    pub fn set(&self, value: impl Into<Value>) -> Result<Vec<ConcurrentValue>, DatatypeError> {
+        let op = Operation::new_multi_value_set(value.into());
+
+        let ret = self
+            .datatype
+            .execute_local_operation_as_tx(self.tx_ctx.clone(), op)?;
+        trace!("set -> {ret:?}");
+        match ret {
+            ReturnType::MultiValueRegister(superseded) => Ok(superseded),
+            _ => Err(InternalReason::ExecuteOperation("unexpected return type".into()).into_error())
+        }
+    }}
+
+    /// Gets the values written concurrently, ordered by lamport and then cuid.
+    ///
+    /// A register that has never been written has no values; one written without
+    /// conflicts has exactly one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::{Client, Value};
+    /// let client = Client::builder("doc-example", "mvr-get-test").build().unwrap();
+    /// let register = client
+    ///     .create_datatype("test-register")
+    ///     .build_multi_value_register()
+    ///     .unwrap();
+    /// register.set(42).unwrap();
+    /// let values = register.get();
+    /// assert_eq!(values.len(), 1);
+    /// assert_eq!(values[0].value, Value::Int(42));
+    /// ```
+    pub fn get(&self) -> Vec<ConcurrentValue> {
+        let mutable = self.datatype.mutable.read();
+        match &mutable.crdt {
+            Crdt::MultiValueRegister(r) => r.values(),
+            _ => unreachable!("multi-value register must have a multi-value register crdt"),
+        }
+    }
+
+    datatype_instrument! {
+    /// Executes multiple operations atomically within a transaction.
+    ///
+    /// If the transaction function returns an error, all operations within
+    /// the transaction are rolled back, leaving the register unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::{Client, Value};
+    /// let client = Client::builder("doc-example", "mvr-transaction-test").build().unwrap();
+    /// let register = client
+    ///     .create_datatype("test-register")
+    ///     .build_multi_value_register()
+    ///     .unwrap();
+    /// register.set(1).unwrap();
+    ///
+    /// let result = register.transaction("failing-update", |r| {
+    ///     r.set(2)?;
+    ///     Err("something went wrong".into())
+    /// });
+    /// assert!(result.is_err());
+    /// assert_eq!(register.get()[0].value, Value::Int(1)); // unchanged
+    /// ```
+    pub fn transaction<T>(
+        &self,
+        tag: impl IntoString,
+        tx_func: T,
+    ) -> Result<(), DatatypeError>
+    where
+        T: FnOnce(Self) -> Result<(), BoxedError> + Send + Sync + 'static,
+    {
+        self.datatype.check_writable()?;
+        let this_tx_ctx = Arc::new(TransactionContext::new(tag));
+        let this_tx_ctx_clone = this_tx_ctx.clone();
+        let do_tx_func = move || {
+            let mut register_clone = self.clone();
+            register_clone.tx_ctx = this_tx_ctx_clone.clone();
+            match tx_func(register_clone) {
+                Ok(_) => Ok(()),
+                Err(e) => Err(DatatypeError::TransactionFailed(e.to_string())),
+            }
+        };
+        self.datatype.do_transaction(this_tx_ctx, do_tx_func)
+    }}
+}
+
+impl DatatypeBlanket for MultiValueRegister {
+    fn get_core(&self) -> &TransactionalDatatype {
+        self.datatype.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests_multi_value_register {
+    use tracing::instrument;
+
+    use crate::{
+        Client, DataType, Datatype, DatatypeState, LocalConnectivity, Value,
+        datatypes::multi_value_register::MultiValueRegister,
+        utils::test_utils::{get_test_collection_name, get_test_func_name, get_test_ids},
+    };
+
+    #[test]
+    fn can_assert_send_and_sync_traits() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<MultiValueRegister>();
+    }
+
+    #[test]
+    #[instrument]
+    fn can_use_multi_value_register_operations() {
+        let register = MultiValueRegister::new_for_test(DatatypeState::Creating);
+        assert_eq!(register.get_type(), DataType::MultiValueRegister);
+        assert!(register.get().is_empty());
+        register.set("a").unwrap();
+        let superseded = register.set("b").unwrap();
+        assert_eq!(superseded.len(), 1);
+        assert_eq!(superseded[0].value, Value::from("a"));
+        let values = register.get();
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].value, Value::from("b"));
+        assert_eq!(values[0].lamport, superseded[0].lamport + 1);
+
+        let result = register.transaction("failure", |r| {
+            r.set("c")?;
+            r.set("d")?;
+            Err("failed".into())
+        });
+        assert!(result.is_err());
+        assert_eq!(register.get(), values);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_expose_and_resolve_concurrent_writes() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let (collection, key, _) = get_test_ids!();
+        let client1 = Client::builder(collection.clone(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(collection, "client2")
+            .with_connectivity(connectivity)
+            .build()
+            .unwrap();
+
+        let register1 = client1
+            .create_datatype(key.clone())
+            .build_multi_value_register()
+            .unwrap();
+        register1.set("initial").unwrap();
+        register1.sync().unwrap();
+
+        let register2 = client2
+            .subscribe_datatype(key)
+            .build_multi_value_register()
+            .unwrap();
+        register2.sync().unwrap();
+        assert_eq!(register2.get(), register1.get());
+
+        register1.set("from-1").unwrap();
+        register2.set("from-2").unwrap();
+        register1.sync().unwrap();
+        register2.sync().unwrap();
+        register1.sync().unwrap();
+
+        let values = register1.get();
+        assert_eq!(values, register2.get());
+        let mut conflicting: Vec<_> = values.iter().map(|v| v.value.clone()).collect();
+        conflicting.sort_by_key(|v| v.to_string());
+        assert_eq!(
+            conflicting,
+            vec![Value::from("from-1"), Value::from("from-2")]
+        );
+        let writers: Vec<_> = values.iter().map(|v| v.cuid.clone()).collect();
+        assert!(writers.contains(&client1.get_cuid().to_string()));
+        assert!(writers.contains(&client2.get_cuid().to_string()));
+
+        // writing again supersedes both conflicting values
+        assert_eq!(register2.set("resolved").unwrap().len(), 2);
+        register2.sync().unwrap();
+        register1.sync().unwrap();
+        assert_eq!(register1.get().len(), 1);
+        assert_eq!(register1.get()[0].value, Value::from("resolved"));
+    }
+}
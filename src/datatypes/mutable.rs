@@ -8,15 +8,24 @@ use crate::{
         common::{Attribute, ReturnType},
         crdts::Crdt,
         handler::HandlersManager,
+        history::{HistoryEntry, HistoryFilter, TransactionHistory},
         push_buffer::{MemoryPushBuffer, PushBuffer},
         tx_record::TxRecord,
+        undo::UndoHistory,
+        watch::{SyncProgress, Watcher},
     },
     errors::{
         datatypes::{DatatypeErrorWithAction, RecoveryAction},
         with_err_out,
     },
-    operations::{Operation, body::OperationBody, transaction::Transaction},
-    types::{checkpoint::CheckPoint, operation_id::OperationId},
+    operations::{
+        Operation,
+        body::OperationBody,
+        transaction::{Transaction, TxGroup},
+    },
+    types::{
+        checkpoint::CheckPoint, operation_id::OperationId, remote::RemoteTransaction, uid::Duid,
+    },
 };
 
 pub(crate) const DATATYPE_ERR_MSG_NO_SNAPSHOT: &str = "no snapshot operation";
@@ -30,7 +39,13 @@ pub struct MutableDatatype {
     pub checkpoint: CheckPoint,
     state: DatatypeState,
     tx_record: TxRecord,
+    pub undo_history: UndoHistory,
+    history: TransactionHistory,
     handlers_manager: HandlersManager,
+    sync_progress: tokio::sync::watch::Sender<SyncProgress>,
+    /// Local transactions not yet acknowledged when this was disabled, kept for a
+    /// resubscription to rebase.
+    unsynced: Vec<Arc<Transaction>>,
 }
 
 impl MutableDatatype {
@@ -38,31 +53,108 @@ impl MutableDatatype {
         attr: Arc<Attribute>,
         state: DatatypeState,
         handlers: BTreeMap<usize, DatatypeHandler>,
-    ) -> Self {
-        let crdt = Crdt::new(attr.r#type);
+    ) -> Result<Self, DatatypeError> {
+        let crdt = Crdt::new(attr.r#type, &attr.option)?;
         let op_id = OperationId::new_with_cuid(&attr.client_common.cuid);
-        Self {
+        Ok(Self {
             push_buffer: MemoryPushBuffer::new(attr.option.clone()),
             tx_record: TxRecord::new(state, op_id.clone()),
+            undo_history: UndoHistory::default(),
+            history: TransactionHistory::new(attr.option.history_retention),
             checkpoint: CheckPoint::default(),
             handlers_manager: HandlersManager::new(attr.clone(), handlers),
+            sync_progress: tokio::sync::watch::Sender::new(SyncProgress {
+                synced_cseq: 0,
+                state,
+                resubscriptions: 0,
+            }),
+            unsynced: Vec::new(),
             attr,
             crdt,
             state,
             op_id,
-        }
+        })
     }
 
     pub(crate) fn reset(&mut self) {
         self.push_buffer = MemoryPushBuffer::new(self.attr.option.clone());
         self.tx_record = TxRecord::new(self.state, self.op_id.clone());
+        self.undo_history.clear();
     }
 
     pub fn disable(&mut self) {
+        self.keep_unsynced();
         self.reset();
         self.set_state(DatatypeState::Disabled);
     }
 
+    fn keep_unsynced(&mut self) {
+        let synced_cseq = self.checkpoint.cseq;
+        self.unsynced.extend(
+            self.push_buffer
+                .iter()
+                .filter(|tx| tx.cseq > synced_cseq)
+                .cloned(),
+        );
+    }
+
+    /// Returns `true` if local transactions have not been acknowledged by the server,
+    /// including those kept when this was disabled.
+    pub(crate) fn has_unsynced(&self) -> bool {
+        !self.unsynced.is_empty() || self.push_buffer.last_cseq > self.checkpoint.cseq
+    }
+
+    /// Prepares a disabled datatype to subscribe again.
+    ///
+    /// The unsynced local transactions are rolled back so that the state matches the last
+    /// checkpoint; [`Self::rebase_unsynced`] executes them again once the server responds.
+    /// A new duid makes the response apply the snapshot of the server, if it has one.
+    pub(crate) fn resubscribe(&mut self) {
+        self.keep_unsynced();
+        for tx in self.unsynced.iter().rev() {
+            for op in tx.iter().rev() {
+                if let Err(e) = self
+                    .crdt
+                    .execute_inverse_operation(&tx.get_op_id_of(op), op)
+                {
+                    with_err_out!(e);
+                }
+            }
+        }
+        self.op_id.cseq = 0;
+        self.checkpoint.cseq = 0;
+        // the waiters for the old cseqs are interrupted, since they are numbered anew
+        self.sync_progress
+            .send_modify(|progress| progress.resubscriptions += 1);
+        self.reset();
+        self.attr.set_duid(Duid::new());
+        self.set_state(DatatypeState::SubscribingOrCreating);
+    }
+
+    /// Executes the transactions kept by [`Self::resubscribe`] again as new local ones.
+    ///
+    /// A transaction that no longer applies is dropped and reported to the error handler.
+    /// Returns whether any transaction was kept.
+    pub(crate) fn rebase_unsynced(&mut self) -> bool {
+        let unsynced = std::mem::take(&mut self.unsynced);
+        let rebased = !unsynced.is_empty();
+        for tx in unsynced {
+            let executed = tx
+                .iter()
+                .try_for_each(|op| self.execute_local_operation(op.clone()).map(|_| ()));
+            if let Err(e) = executed {
+                self.do_rollback();
+                self.call_error_handler(e);
+                continue;
+            }
+            if let Err(e) = self.end_transaction(tx.tag.clone(), None, true) {
+                self.apply_action(e.recovery);
+                self.call_error_handler(e.error);
+            }
+        }
+        rebased
+    }
+
     pub fn apply_snapshot_transaction(
         &mut self,
         tx: Arc<Transaction>,
@@ -75,6 +167,11 @@ impl MutableDatatype {
         let snap_op = &tx.operations[0];
         if let OperationBody::Snapshot(body) = &snap_op.body {
             self.crdt.deserialize(&body.data)?;
+            self.checkpoint = CheckPoint::new(tx.sseq, 0);
+            if self.state == DatatypeState::SubscribingOrCreating {
+                // pushed to create the datatype, but it already exists
+                self.keep_unsynced();
+            }
             self.op_id.cseq = 0;
             self.op_id.lamport = snap_op.lamport;
             self.reset();
@@ -90,24 +187,29 @@ impl MutableDatatype {
     pub fn do_rollback(&mut self) {
         if let Some(tx) = self.tx_record.pending.take() {
             for op in tx.iter().rev() {
-                if let Err(e) = self.crdt.execute_inverse_operation(op) {
+                let op_id = tx.get_op_id_of(op);
+                if let Err(e) = self.crdt.execute_inverse_operation(&op_id, op) {
                     with_err_out!(e);
                 }
             }
             self.op_id = self.tx_record.rollback_op_id.clone();
             self.set_state(self.tx_record.rollback_state);
         }
+        self.undo_history.reset_mode();
     }
 
     /// Ends the in-progress transaction.
     ///
     /// Returns `Ok(true)` when a committed transaction was enqueued into the push buffer,
     /// `Ok(false)` when the transaction was rolled back (`committed == false`).
+    /// A local transaction the push buffer coalesces into the previous one gives its cseq
+    /// back, so that the next transaction follows the merged one.
     /// On an enqueue failure, `pending` is restored so that the routed
     /// `RecoveryAction::RollbackTransaction` can undo the transaction, and the error is returned.
     pub fn end_transaction(
         &mut self,
         tag: Option<String>,
+        group: Option<TxGroup>,
         committed: bool,
     ) -> Result<bool, DatatypeErrorWithAction> {
         if !committed {
@@ -117,19 +219,43 @@ impl MutableDatatype {
 
         if let Some(mut tx) = self.tx_record.pending.take() {
             tx.set_tag(tag);
+            tx.group = group;
             let tx = Arc::new(tx);
-            if tx.cuid == self.op_id.cuid {
-                if let Err(err) = self.push_buffer.enqueue(tx.clone()) {
+            if tx.cuid == self.op_id.cuid && self.push_buffer.coalesce(&tx) {
+                self.op_id.prev_cseq();
+                let merged = self.push_buffer.iter().last().unwrap().clone();
+                self.history.record_coalesced(merged);
+            } else {
+                if tx.cuid == self.op_id.cuid
+                    && let Err(err) = self.push_buffer.enqueue(tx.clone())
+                {
                     // The clone passed to enqueue is dropped on failure, so this Arc is unique
                     // again; restore pending so RecoveryAction::RollbackTransaction can undo it.
                     self.tx_record.pending = Arc::try_unwrap(tx).ok();
                     return Err(err);
                 }
+                self.history.record(tx.clone());
             }
+            self.undo_history.record(tx);
+            self.notify_watchers();
+        } else {
+            self.undo_history.reset_mode();
         }
         Ok(true)
     }
 
+    /// Checks that the pending transaction, tagged with `tag`, can be committed, without
+    /// committing it.
+    pub fn check_commit(&mut self, tag: Option<String>) -> Result<(), DatatypeErrorWithAction> {
+        match self.tx_record.pending.as_mut() {
+            Some(tx) if tx.cuid == self.op_id.cuid => {
+                tx.set_tag(tag);
+                self.push_buffer.check_enqueue(tx)
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Applies the datatype-lifecycle side effect of a routed error.
     ///
     /// Single dispatch point for `RecoveryAction`, shared by the event-loop path
@@ -147,31 +273,60 @@ impl MutableDatatype {
         }
     }
 
+    pub fn has_pending_transaction(&self) -> bool {
+        self.tx_record.pending.is_some()
+    }
+
     pub fn execute_remote_transaction(
         &mut self,
         tx: Arc<Transaction>,
     ) -> Result<(), DatatypeError> {
         for op in tx.iter() {
-            self.op_id.lamport = self.op_id.lamport.max(op.lamport);
-            self.crdt.execute_remote_operation(op)?;
+            self.op_id.lamport = self.op_id.lamport.max(op.last_lamport());
+            self.crdt
+                .execute_remote_operation(&tx.get_op_id_of(op), op)?;
         }
+        self.history.record(tx);
         Ok(())
     }
 
+    pub fn history(&self, filter: &HistoryFilter) -> Vec<HistoryEntry> {
+        self.history.entries(filter, &self.op_id.cuid)
+    }
+
     #[instrument(skip_all)]
     pub fn execute_local_operation(
         &mut self,
         mut op: Operation,
     ) -> Result<ReturnType, DatatypeError> {
         op.set_lamport(self.op_id.lamport + 1);
-        let result = self.crdt.execute_local_operation(&op);
+        let op_id = self.next_local_op_id(&op);
+        let result = self.crdt.execute_local_operation(&op_id, &mut op);
         if result.is_ok() {
+            let last_lamport = op.last_lamport();
             let is_new_tx = self.tx_record.record_operation(&self.op_id, self.state, op);
             self.op_id.next(is_new_tx);
+            self.op_id.lamport = last_lamport;
         }
         result
     }
 
+    /// Returns the id `op` will have once it is recorded into the pending transaction.
+    fn next_local_op_id(&self, op: &Operation) -> OperationId {
+        let mut op_id = self.op_id.clone();
+        op_id.lamport = op.lamport;
+        if self.tx_record.pending.is_none() {
+            op_id.cseq += 1;
+        }
+        op_id
+    }
+
+    /// Lets the CRDT drop what it kept only until the checkpoint was acknowledged.
+    pub fn collect_garbage(&mut self) {
+        self.crdt
+            .collect_garbage(&self.attr.client_common.cuid, self.checkpoint.cseq);
+    }
+
     pub fn new_snapshot_operation(&self) -> Operation {
         let data = self.crdt.serialize();
         let mut snap_op = Operation::new_snapshot(data);
@@ -195,6 +350,7 @@ impl MutableDatatype {
         let old_state = self.state;
         if old_state != new_state {
             self.state = new_state;
+            self.publish_sync_progress();
             self.handlers_manager
                 .notify_state_change(old_state, new_state);
             if new_state == DatatypeState::Disabled {
@@ -203,9 +359,36 @@ impl MutableDatatype {
         }
     }
 
+    pub(crate) fn subscribe_sync_progress(&self) -> tokio::sync::watch::Receiver<SyncProgress> {
+        self.sync_progress.subscribe()
+    }
+
+    pub(crate) fn publish_sync_progress(&self) {
+        self.sync_progress.send_if_modified(|progress| {
+            let new = SyncProgress {
+                synced_cseq: self.checkpoint.cseq,
+                state: self.state,
+                resubscriptions: progress.resubscriptions,
+            };
+            std::mem::replace(progress, new) != new
+        });
+    }
+
     pub fn call_error_handler(&self, err: DatatypeError) {
         self.handlers_manager.notify_error(err)
     }
+
+    pub fn notify_remote_operations(&self, transactions: Vec<RemoteTransaction>) {
+        self.handlers_manager.notify_remote_operations(transactions)
+    }
+
+    pub(crate) fn add_watcher(&mut self, watcher: Watcher) {
+        self.handlers_manager.add_watcher(watcher);
+    }
+
+    pub fn notify_watchers(&mut self) {
+        self.handlers_manager.notify_watchers(&self.crdt);
+    }
 }
 
 #[cfg(test)]
@@ -222,7 +405,8 @@ mod tests_mutable_datatype {
     #[instrument]
     fn can_fail_operation_execution() {
         let attr = new_attribute!(DataType::Counter);
-        let tx_dt = TransactionalDatatype::new_arc(attr, Default::default(), Default::default());
+        let tx_dt =
+            TransactionalDatatype::new_arc(attr, Default::default(), Default::default()).unwrap();
         {
             let mutable = tx_dt.mutable.write();
             assert_eq!(0, mutable.op_id.cseq);
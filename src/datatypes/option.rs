@@ -1,11 +1,21 @@
-use crate::defaults::{
-    DEFAULT_MAX_MEM_SIZE_OF_PUSH_BUFFER, LOWER_MAX_MEM_SIZE_OF_PUSH_BUFFER,
-    UPPER_MAX_MEM_SIZE_OF_PUSH_BUFFER,
+use crate::{
+    FlagPolicy,
+    datatypes::crdts::custom_crdt::CustomCrdtFactory,
+    defaults::{
+        DEFAULT_HISTORY_RETENTION, DEFAULT_MAX_MEM_SIZE_OF_PUSH_BUFFER,
+        LOWER_MAX_MEM_SIZE_OF_PUSH_BUFFER, UPPER_MAX_MEM_SIZE_OF_PUSH_BUFFER,
+    },
 };
 
 #[derive(Debug, Clone)]
 pub struct DatatypeOption {
     pub max_mem_size_of_push_buffer: u64,
+    /// Conflict policy of a new `Flag`; ignored by other datatypes.
+    pub flag_policy: FlagPolicy,
+    /// Creates the CRDT of a custom datatype; set only when building one.
+    pub custom_crdt: Option<CustomCrdtFactory>,
+    /// The number of committed transactions kept for `Datatype::history()`.
+    pub history_retention: usize,
 }
 
 impl DatatypeOption {
@@ -15,6 +25,9 @@ impl DatatypeOption {
                 LOWER_MAX_MEM_SIZE_OF_PUSH_BUFFER,
                 UPPER_MAX_MEM_SIZE_OF_PUSH_BUFFER,
             ),
+            flag_policy: FlagPolicy::default(),
+            custom_crdt: None,
+            history_retention: DEFAULT_HISTORY_RETENTION,
         }
     }
 }
@@ -5,7 +5,7 @@ use crate::{
     datatypes::mutable::MutableDatatype,
     errors::datatypes::DatatypeErrorWithAction,
     observability::trace::add_span_event,
-    types::{checkpoint::CheckPoint, push_pull_pack::PushPullPack},
+    types::{checkpoint::CheckPoint, push_pull_pack::PushPullPack, remote::RemoteTransaction},
 };
 
 type PendingStep<'b> = fn(&mut PullHandler<'b>) -> Result<(), DatatypeErrorWithAction>;
@@ -18,6 +18,8 @@ pub struct PullHandler<'a> {
     is_created: bool,
     pending_steps: Vec<PendingStep<'a>>,
     skip: usize,
+    applied: Vec<RemoteTransaction>,
+    changed: bool,
 }
 
 impl<'a> PullHandler<'a> {
@@ -31,6 +33,8 @@ impl<'a> PullHandler<'a> {
             is_created: false,
             pending_steps: Vec::new(),
             skip: 0,
+            applied: Vec::new(),
+            changed: false,
         }
     }
 
@@ -41,6 +45,9 @@ impl<'a> PullHandler<'a> {
             self.enqueue_step(Self::skip_duplicated_transactions);
             self.enqueue_step(Self::execute_transactions);
             self.enqueue_step(Self::sync_checkpoint);
+            if self.old_state == DatatypeState::SubscribingOrCreating {
+                self.enqueue_step(Self::rebase_unsynced);
+            }
             Ok(())
         })();
         self.commit()?;
@@ -87,6 +94,8 @@ impl<'a> PullHandler<'a> {
                     self.process_illegal_state_response(self.old_state, self.pulled_ppp.state)?;
                 }
                 if self.pulled_ppp.duid == self.mutable.attr.get_duid() {
+                    // the server starts sequencing anew for a datatype it has just created
+                    self.mutable.checkpoint = CheckPoint::default();
                     self.is_created = true;
                 } else {
                     self.enqueue_step(Self::apply_subscribe_response);
@@ -98,18 +107,12 @@ impl<'a> PullHandler<'a> {
                     self.process_illegal_state_response(self.old_state, self.pulled_ppp.state)?;
                 }
             }
-            DatatypeState::Unsubscribing => {
+            DatatypeState::Unsubscribing | DatatypeState::Deleting | DatatypeState::Disabled => {
                 if self.pulled_ppp.state != DatatypeState::Disabled {
                     self.new_state = DatatypeState::Disabled;
                     self.process_illegal_state_response(self.old_state, self.pulled_ppp.state)?;
                 }
             }
-            DatatypeState::Deleting => {
-                todo!()
-            }
-            DatatypeState::Disabled => {
-                todo!()
-            }
         }
 
         if self.new_state != self.old_state {
@@ -127,6 +130,7 @@ impl<'a> PullHandler<'a> {
             self.mutable
                 .apply_snapshot_transaction(snapshot_tx)
                 .map_err(|e| e.mapping())?;
+            self.changed = true;
         }
         self.mutable.attr.set_duid(self.pulled_ppp.duid.clone());
         Ok(())
@@ -150,17 +154,25 @@ impl<'a> PullHandler<'a> {
     fn execute_transactions(&mut self) -> Result<(), DatatypeErrorWithAction> {
         let transactions = self.pulled_ppp.transactions[self.skip..].to_vec();
         for tx in transactions {
+            let remote = RemoteTransaction::from(tx.as_ref());
             self.mutable
                 .execute_remote_transaction(tx)
                 .map_err(|e| e.mapping())?;
+            self.applied.push(remote);
         }
         Ok(())
     }
 
+    fn rebase_unsynced(&mut self) -> Result<(), DatatypeErrorWithAction> {
+        self.changed |= self.mutable.rebase_unsynced();
+        Ok(())
+    }
+
     fn sync_checkpoint(&mut self) -> Result<(), DatatypeErrorWithAction> {
         self.mutable
             .checkpoint
             .check_with(&self.pulled_ppp.checkpoint);
+        self.mutable.collect_garbage();
         Ok(())
     }
 
@@ -170,6 +182,14 @@ impl<'a> PullHandler<'a> {
             step(self)?;
         }
         self.mutable.set_state(self.new_state);
+        self.mutable.publish_sync_progress();
+        if self.changed || !self.applied.is_empty() {
+            self.mutable.notify_watchers();
+        }
+        if !self.applied.is_empty() {
+            self.mutable
+                .notify_remote_operations(std::mem::take(&mut self.applied));
+        }
         Ok(())
     }
 }
@@ -16,6 +16,18 @@ pub trait PushBuffer {
     /// `DatatypeError::Internal`. The routing pairs themselves are still defined in the
     /// errors module (`InternalReason::mapping` / `DatatypeError::mapping`).
     fn enqueue(&mut self, tx: Arc<Transaction>) -> Result<(), DatatypeErrorWithAction>;
+    /// Checks that [`Self::enqueue`] would take `tx`, without enqueuing it.
+    fn check_enqueue(&self, tx: &Transaction) -> Result<(), DatatypeErrorWithAction>;
+    /// Merges `tx`, the transaction following the last enqueued one, into the last one
+    /// instead of enqueuing it, so that consecutive keystrokes are transmitted as one run.
+    ///
+    /// Both must be untagged single-operation transactions outside any group whose
+    /// operations can be encoded as one (see [`Operation::try_merge`]), and the last one
+    /// must not have been handed out for pushing yet. Returns whether `tx` was merged;
+    /// its cseq is then left unused.
+    ///
+    /// [`Operation::try_merge`]: crate::operations::Operation::try_merge
+    fn coalesce(&mut self, tx: &Transaction) -> bool;
     fn get_pushing_transactions(
         &mut self,
         cseq: u64,
@@ -31,6 +43,8 @@ pub struct MemoryPushBuffer {
     pub option: Arc<DatatypeOption>,
     pub first_cseq: u64,
     pub last_cseq: u64,
+    /// The last cseq handed out by [`PushBuffer::get_pushing_transactions`].
+    pushed_cseq: u64,
 }
 
 impl MemoryPushBuffer {
@@ -41,6 +55,7 @@ impl MemoryPushBuffer {
             mem_size: 0u64,
             first_cseq: 0u64,
             last_cseq: 0u64,
+            pushed_cseq: 0u64,
         }
     }
 
@@ -49,6 +64,10 @@ impl MemoryPushBuffer {
         self.transaction.iter()
     }
 
+    fn is_coalescable(tx: &Transaction) -> bool {
+        tx.tag.is_none() && tx.group.is_none() && tx.operations.len() == 1
+    }
+
     #[allow(dead_code)]
     fn need_to_deque(tx: Option<&Arc<Transaction>>, cseq: u64) -> bool {
         if let Some(tx) = tx {
@@ -62,12 +81,7 @@ impl MemoryPushBuffer {
 
 impl PushBuffer for MemoryPushBuffer {
     fn enqueue(&mut self, tx: Arc<Transaction>) -> Result<(), DatatypeErrorWithAction> {
-        if self.last_cseq != 0 && self.last_cseq + 1 != tx.cseq {
-            return Err(InternalReason::NonSequentialCseq.mapping());
-        }
-        if self.mem_size + tx.size() > self.option.max_mem_size_of_push_buffer {
-            return Err(DatatypeError::PushBufferExceededMaxMemSize.mapping());
-        }
+        self.check_enqueue(&tx)?;
         if self.first_cseq == 0 {
             self.first_cseq = tx.cseq;
         }
@@ -77,6 +91,41 @@ impl PushBuffer for MemoryPushBuffer {
         Ok(())
     }
 
+    fn check_enqueue(&self, tx: &Transaction) -> Result<(), DatatypeErrorWithAction> {
+        if self.last_cseq != 0 && self.last_cseq + 1 != tx.cseq {
+            return Err(InternalReason::NonSequentialCseq.mapping());
+        }
+        if self.mem_size + tx.size() > self.option.max_mem_size_of_push_buffer {
+            return Err(DatatypeError::PushBufferExceededMaxMemSize.mapping());
+        }
+        Ok(())
+    }
+
+    fn coalesce(&mut self, tx: &Transaction) -> bool {
+        let Some(last) = self.transaction.back_mut() else {
+            return false;
+        };
+        if last.cseq <= self.pushed_cseq
+            || last.cseq + 1 != tx.cseq
+            || last.cuid != tx.cuid
+            || !Self::is_coalescable(last)
+            || !Self::is_coalescable(tx)
+        {
+            return false;
+        }
+        let mut merged = Transaction::clone(last);
+        if !merged.operations[0].try_merge(&tx.operations[0], &tx.cuid) {
+            return false;
+        }
+        let grown = merged.size() - last.size();
+        if self.mem_size + grown > self.option.max_mem_size_of_push_buffer {
+            return false;
+        }
+        self.mem_size += grown;
+        *last = Arc::new(merged);
+        true
+    }
+
     fn get_pushing_transactions(
         &mut self,
         cseq: u64,
@@ -101,6 +150,9 @@ impl PushBuffer for MemoryPushBuffer {
             total_size += tx.size();
             popped.push(tx);
         }
+        if let Some(last) = popped.last() {
+            self.pushed_cseq = self.pushed_cseq.max(last.cseq);
+        }
         Ok((popped, total_size))
     }
 
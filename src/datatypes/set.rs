@@ -0,0 +1,296 @@
+use std::sync::Arc;
+
+use tracing::trace;
+
+use crate::{
+    DatatypeError, IntoString, Value,
+    datatypes::{
+        common::{ReturnType, datatype_instrument},
+        crdts::{Crdt, set_crdt::SetCrdt},
+        datatype::DatatypeBlanket,
+        transactional::{TransactionContext, TransactionalDatatype},
+    },
+    errors::{BoxedError, datatypes::InternalReason},
+    operations::Operation,
+};
+
+/// A set is a conflict-free collection of distinct [`Value`]s with add-wins semantics.
+///
+/// A remove only takes back the adds it has observed, so when an element is added and
+/// removed concurrently, it stays in the set on every replica.
+#[derive(Clone)]
+pub struct Set {
+    datatype: Arc<TransactionalDatatype>,
+    tx_ctx: Arc<TransactionContext>,
+}
+
+impl Set {
+    pub(crate) fn new(datatype: Arc<TransactionalDatatype>) -> Self {
+        Set {
+            datatype,
+            tx_ctx: Default::default(),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn new_for_test(state: crate::DatatypeState) -> Self {
+        Self::new(
+            TransactionalDatatype::new_arc(
+                crate::datatypes::common::new_attribute!(crate::DataType::Set),
+                state,
+                Default::default(),
+            )
+            .unwrap(),
+        )
+    }
+
+    fn execute(&self, op: Operation) -> Result<bool, DatatypeError> {
+        let ret = self
+            .datatype
+            .execute_local_operation_as_tx(self.tx_ctx.clone(), op)?;
+        trace!("executed -> {ret:?}");
+        match ret {
+            ReturnType::Set(changed) => Ok(changed),
+            _ => {
+                Err(InternalReason::ExecuteOperation("unexpected return type".into()).into_error())
+            }
+        }
+    }
+
+    fn read<R>(&self, f: impl FnOnce(&SetCrdt) -> R) -> R {
+        let mutable = self.datatype.mutable.read();
+        match &mutable.crdt {
+            Crdt::Set(s) => f(s),
+            _ => unreachable!("set must have a set crdt"),
+        }
+    }
+
+    datatype_instrument! {
+    /// Adds `value` to the set.
+    ///
+    /// Returns `true` if the value was not in the set before.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::Client;
+    /// let client = Client::builder("doc-example", "set-add-test").build().unwrap();
+    /// let set = client.create_datatype("test-set").build_set().unwrap();
+    /// assert!(set.add("alice").unwrap());
+    /// assert!(!set.add("alice").unwrap());
+    /// assert_eq!(set.len(), 1);
+    /// ```
+    pub fn add(&self, value: impl Into<Value>) -> Result<bool, DatatypeError> {
+        self.execute(Operation::new_set_add(value.into()))
+    }}
+
+    datatype_instrument! {
+    /// Removes `value` from the set.
+    ///
+    /// Returns `true` if the value was in the set before.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::Client;
+    /// let client = Client::builder("doc-example", "set-remove-test").build().unwrap();
+    /// let set = client.create_datatype("test-set").build_set().unwrap();
+    /// set.add(1).unwrap();
+    /// assert!(set.remove(1).unwrap());
+    /// assert!(!set.remove(1).unwrap());
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn remove(&self, value: impl Into<Value>) -> Result<bool, DatatypeError> {
+        self.execute(Operation::new_set_remove(value.into()))
+    }}
+
+    /// Returns `true` if `value` is in the set.
+    pub fn contains(&self, value: impl Into<Value>) -> bool {
+        let value = value.into();
+        self.read(|s| s.contains(&value))
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.read(|s| s.len())
+    }
+
+    /// Returns `true` if the set has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.read(|s| s.is_empty())
+    }
+
+    /// Returns an iterator over a snapshot of the elements.
+    ///
+    /// The order is the same on every replica, but otherwise unspecified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::{Client, Value};
+    /// let client = Client::builder("doc-example", "set-iter-test").build().unwrap();
+    /// let set = client.create_datatype("test-set").build_set().unwrap();
+    /// set.add("online").unwrap();
+    /// set.add("away").unwrap();
+    /// let mut statuses: Vec<Value> = set.iter().collect();
+    /// statuses.sort_by_key(|v| v.to_string());
+    /// assert_eq!(statuses, vec![Value::from("away"), Value::from("online")]);
+    /// ```
+    pub fn iter(&self) -> std::vec::IntoIter<Value> {
+        self.read(|s| s.iter().cloned().collect::<Vec<_>>())
+            .into_iter()
+    }
+
+    datatype_instrument! {
+    /// Executes multiple operations atomically within a transaction.
+    ///
+    /// If the transaction function returns an error, all operations within
+    /// the transaction are rolled back, leaving the set unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::Client;
+    /// let client = Client::builder("doc-example", "set-transaction-test").build().unwrap();
+    /// let set = client.create_datatype("test-set").build_set().unwrap();
+    /// set.add("a").unwrap();
+    ///
+    /// let result = set.transaction("failing-update", |s| {
+    ///     s.remove("a")?;
+    ///     s.add("b")?;
+    ///     Err("something went wrong".into())
+    /// });
+    /// assert!(result.is_err());
+    /// assert!(set.contains("a")); // unchanged
+    /// assert!(!set.contains("b"));
+    /// ```
+    pub fn transaction<T>(
+        &self,
+        tag: impl IntoString,
+        tx_func: T,
+    ) -> Result<(), DatatypeError>
+    where
+        T: FnOnce(Self) -> Result<(), BoxedError> + Send + Sync + 'static,
+    {
+        self.datatype.check_writable()?;
+        let this_tx_ctx = Arc::new(TransactionContext::new(tag));
+        let this_tx_ctx_clone = this_tx_ctx.clone();
+        let do_tx_func = move || {
+            let mut set_clone = self.clone();
+            set_clone.tx_ctx = this_tx_ctx_clone.clone();
+            match tx_func(set_clone) {
+                Ok(_) => Ok(()),
+                Err(e) => Err(DatatypeError::TransactionFailed(e.to_string())),
+            }
+        };
+        self.datatype.do_transaction(this_tx_ctx, do_tx_func)
+    }}
+}
+
+impl DatatypeBlanket for Set {
+    fn get_core(&self) -> &TransactionalDatatype {
+        self.datatype.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests_set {
+    use tracing::instrument;
+
+    use crate::{
+        Client, DataType, Datatype, DatatypeState, LocalConnectivity, Value,
+        datatypes::{crdts::Crdt, set::Set},
+        utils::test_utils::{get_test_collection_name, get_test_func_name, get_test_ids},
+    };
+
+    #[test]
+    fn can_assert_send_and_sync_traits() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Set>();
+    }
+
+    #[test]
+    #[instrument]
+    fn can_use_set_operations() {
+        let set = Set::new_for_test(DatatypeState::Creating);
+        assert_eq!(set.get_type(), DataType::Set);
+        assert!(set.add("a").unwrap());
+        assert!(set.add(1).unwrap());
+        assert!(!set.add("a").unwrap());
+        assert!(set.contains("a"));
+        assert!(!set.contains("b"));
+        assert_eq!(set.len(), 2);
+
+        assert!(set.remove("a").unwrap());
+        assert!(!set.remove("a").unwrap());
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![Value::Int(1)]);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_rollback_transaction() {
+        let set = Set::new_for_test(DatatypeState::Creating);
+        set.add("a").unwrap();
+        let result = set.transaction("failure", |s| {
+            s.add("a")?;
+            s.remove("a")?;
+            s.add("b")?;
+            Err("failed".into())
+        });
+        assert!(result.is_err());
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![Value::from("a")]);
+        assert!(set.remove("a").unwrap());
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    #[instrument]
+    fn can_resolve_concurrent_add_and_remove_between_clients() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let (collection, key, _) = get_test_ids!();
+        let client1 = Client::builder(collection.clone(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(collection, "client2")
+            .with_connectivity(connectivity)
+            .build()
+            .unwrap();
+
+        let set1 = client1.create_datatype(key.clone()).build_set().unwrap();
+        set1.add("alice").unwrap();
+        set1.add("bob").unwrap();
+        set1.sync().unwrap();
+
+        let set2 = client2.subscribe_datatype(key).build_set().unwrap();
+        set2.sync().unwrap();
+        assert_eq!(set2.len(), 2);
+
+        // client1 re-adds alice while client2 removes the alice it has observed
+        set1.add("alice").unwrap();
+        set1.remove("bob").unwrap();
+        set2.remove("alice").unwrap();
+        set2.add("carol").unwrap();
+        set1.sync().unwrap();
+        set2.sync().unwrap();
+        set1.sync().unwrap();
+
+        let mut members1: Vec<_> = set1.iter().collect();
+        members1.sort_by_key(|v| v.to_string());
+        assert_eq!(members1, vec![Value::from("alice"), Value::from("carol")]);
+        assert_eq!(
+            set2.iter().collect::<Vec<_>>(),
+            set1.iter().collect::<Vec<_>>()
+        );
+
+        // tombstones are collected once every remove has been acknowledged
+        for set in [&set1, &set2] {
+            let mutable = set.datatype.mutable.read();
+            let Crdt::Set(crdt) = &mutable.crdt else {
+                unreachable!()
+            };
+            assert_eq!(crdt.tombstone_count(), 0);
+        }
+    }
+}
@@ -0,0 +1,365 @@
+use std::{
+    fmt::{Display, Formatter},
+    sync::Arc,
+};
+
+use tracing::trace;
+
+use crate::{
+    DatatypeError, IntoString,
+    datatypes::{
+        common::{ReturnType, datatype_instrument},
+        crdts::{Crdt, text_crdt::TextCrdt},
+        datatype::DatatypeBlanket,
+        transactional::{TransactionContext, TransactionalDatatype},
+    },
+    errors::{BoxedError, datatypes::InternalReason},
+    operations::Operation,
+};
+
+/// A text is a conflict-free sequence of characters for collaborative editing.
+///
+/// Positions and lengths count Unicode scalar values (`char`s), not bytes, so that
+/// multi-byte characters are never split. Concurrent inserts at the same position are
+/// all kept and ordered identically on every replica.
+///
+/// Consecutive inserts within a transaction, such as keystrokes, are merged into a
+/// single operation, so typing does not grow the push buffer by one operation per char.
+///
+/// The current content is available through [`Display`], e.g., `text.to_string()`.
+#[derive(Clone)]
+pub struct Text {
+    datatype: Arc<TransactionalDatatype>,
+    tx_ctx: Arc<TransactionContext>,
+}
+
+impl Text {
+    pub(crate) fn new(datatype: Arc<TransactionalDatatype>) -> Self {
+        Text {
+            datatype,
+            tx_ctx: Default::default(),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn new_for_test(state: crate::DatatypeState) -> Self {
+        Self::new(
+            TransactionalDatatype::new_arc(
+                crate::datatypes::common::new_attribute!(crate::DataType::Text),
+                state,
+                Default::default(),
+            )
+            .unwrap(),
+        )
+    }
+
+    fn execute(&self, op: Operation) -> Result<String, DatatypeError> {
+        let ret = self
+            .datatype
+            .execute_local_operation_as_tx(self.tx_ctx.clone(), op)?;
+        trace!("executed -> {ret:?}");
+        match ret {
+            ReturnType::Text(removed) => Ok(removed),
+            _ => {
+                Err(InternalReason::ExecuteOperation("unexpected return type".into()).into_error())
+            }
+        }
+    }
+
+    fn read<R>(&self, f: impl FnOnce(&TextCrdt) -> R) -> R {
+        let mutable = self.datatype.mutable.read();
+        match &mutable.crdt {
+            Crdt::Text(t) => f(t),
+            _ => unreachable!("text must have a text crdt"),
+        }
+    }
+
+    datatype_instrument! {
+    /// Inserts `s` at char position `pos`.
+    ///
+    /// Inserting an empty string is a no-op.
+    ///
+    /// # Errors
+    /// Returns [`DatatypeError::OutOfBounds`] if `pos` is larger than the length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::Client;
+    /// let client = Client::builder("doc-example", "text-insert-test").build().unwrap();
+    /// let text = client.create_datatype("test-text").build_text().unwrap();
+    /// text.insert_str(0, "wörld").unwrap();
+    /// text.insert_str(0, "hello ").unwrap();
+    /// assert_eq!(text.to_string(), "hello wörld");
+    /// assert_eq!(text.len(), 11);
+    /// ```
+    pub fn insert_str(&self, pos: usize, s: &str) -> Result<(), DatatypeError> {
+        if s.is_empty() {
+            return Ok(());
+        }
+        self.execute(Operation::new_text_insert(pos, s.to_owned()))?;
+        Ok(())
+    }}
+
+    datatype_instrument! {
+    /// Deletes `len` chars starting at char position `pos`.
+    ///
+    /// Returns the deleted string.
+    ///
+    /// # Errors
+    /// Returns [`DatatypeError::OutOfBounds`] if the range exceeds the length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::Client;
+    /// let client = Client::builder("doc-example", "text-delete-test").build().unwrap();
+    /// let text = client.create_datatype("test-text").build_text().unwrap();
+    /// text.insert_str(0, "héllo").unwrap();
+    /// assert_eq!(text.delete_range(1, 3).unwrap(), "éll");
+    /// assert_eq!(text.to_string(), "ho");
+    /// ```
+    pub fn delete_range(&self, pos: usize, len: usize) -> Result<String, DatatypeError> {
+        if len == 0 {
+            return Ok(String::new());
+        }
+        self.execute(Operation::new_text_delete(pos, len))
+    }}
+
+    /// Returns the number of chars.
+    pub fn len(&self) -> usize {
+        self.read(|t| t.len())
+    }
+
+    /// Returns `true` if the text has no chars.
+    pub fn is_empty(&self) -> bool {
+        self.read(|t| t.is_empty())
+    }
+
+    datatype_instrument! {
+    /// Executes multiple operations atomically within a transaction.
+    ///
+    /// If the transaction function returns an error, all edits within
+    /// the transaction are rolled back, leaving the text unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::Client;
+    /// let client = Client::builder("doc-example", "text-transaction-test").build().unwrap();
+    /// let text = client.create_datatype("test-text").build_text().unwrap();
+    /// text.insert_str(0, "draft").unwrap();
+    ///
+    /// let result = text.transaction("failing-edit", |t| {
+    ///     t.delete_range(0, 5)?;
+    ///     t.insert_str(0, "final")?;
+    ///     Err("something went wrong".into())
+    /// });
+    /// assert!(result.is_err());
+    /// assert_eq!(text.to_string(), "draft"); // unchanged
+    /// ```
+    pub fn transaction<T>(
+        &self,
+        tag: impl IntoString,
+        tx_func: T,
+    ) -> Result<(), DatatypeError>
+    where
+        T: FnOnce(Self) -> Result<(), BoxedError> + Send + Sync + 'static,
+    {
+        self.datatype.check_writable()?;
+        let this_tx_ctx = Arc::new(TransactionContext::new(tag));
+        let this_tx_ctx_clone = this_tx_ctx.clone();
+        let do_tx_func = move || {
+            let mut text_clone = self.clone();
+            text_clone.tx_ctx = this_tx_ctx_clone.clone();
+            match tx_func(text_clone) {
+                Ok(_) => Ok(()),
+                Err(e) => Err(DatatypeError::TransactionFailed(e.to_string())),
+            }
+        };
+        self.datatype.do_transaction(this_tx_ctx, do_tx_func)
+    }}
+}
+
+impl Display for Text {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s: String = self.read(|t| t.chars().collect());
+        f.write_str(&s)
+    }
+}
+
+impl DatatypeBlanket for Text {
+    fn get_core(&self) -> &TransactionalDatatype {
+        self.datatype.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests_text {
+    use tracing::instrument;
+
+    use crate::{
+        Client, DataType, Datatype, DatatypeError, DatatypeState, LocalConnectivity,
+        datatypes::{push_buffer::PushBuffer, text::Text},
+        operations::{MemoryMeasurable, body::OperationBody},
+        utils::test_utils::{get_test_collection_name, get_test_func_name, get_test_ids},
+    };
+
+    #[test]
+    fn can_assert_send_and_sync_traits() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Text>();
+    }
+
+    #[test]
+    #[instrument]
+    fn can_use_text_operations() {
+        let text = Text::new_for_test(DatatypeState::Creating);
+        assert_eq!(text.get_type(), DataType::Text);
+        text.insert_str(0, "안녕 world").unwrap();
+        text.insert_str(2, ",").unwrap();
+        assert_eq!(text.to_string(), "안녕, world");
+        assert_eq!(text.delete_range(4, 5).unwrap(), "world");
+        assert_eq!(text.len(), 4);
+        text.insert_str(4, "").unwrap();
+        assert!(matches!(
+            text.insert_str(5, "x").unwrap_err(),
+            DatatypeError::OutOfBounds(_)
+        ));
+        assert!(matches!(
+            text.delete_range(3, 2).unwrap_err(),
+            DatatypeError::OutOfBounds(_)
+        ));
+        assert_eq!(text.to_string(), "안녕, ");
+    }
+
+    #[test]
+    #[instrument]
+    fn can_merge_keystrokes_and_rollback() {
+        let text = Text::new_for_test(DatatypeState::Creating);
+        text.transaction("typing", |t| {
+            for (i, c) in "hello".chars().enumerate() {
+                t.insert_str(i, &c.to_string())?;
+            }
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(text.to_string(), "hello");
+        {
+            let mutable = text.datatype.mutable.read();
+            let tx = mutable.push_buffer.iter().last().unwrap();
+            assert_eq!(tx.operations.len(), 1, "keystrokes are merged into one run");
+            assert!(matches!(
+                &tx.operations[0].body,
+                OperationBody::TextInsert(body) if body.text == "hello"
+            ));
+        }
+
+        let result = text.transaction("failure", |t| {
+            t.insert_str(5, " world")?;
+            t.insert_str(11, "!")?;
+            t.delete_range(0, 6)?;
+            Err("failed".into())
+        });
+        assert!(result.is_err());
+        assert_eq!(text.to_string(), "hello");
+
+        // the lamports taken by the rolled-back run are reused without conflicts
+        text.insert_str(5, "!").unwrap();
+        assert_eq!(text.to_string(), "hello!");
+        let mut mutable = text.datatype.mutable.write();
+        assert_eq!(mutable.push_buffer.deque(u64::MAX).len(), 2);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_coalesce_keystrokes_in_push_buffer() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let client = Client::builder(get_test_collection_name!(), get_test_func_name!())
+            .with_connectivity(connectivity)
+            .build()
+            .unwrap();
+        let text = client
+            .create_datatype(get_test_func_name!())
+            .build_text()
+            .unwrap();
+        for (i, c) in "hello".chars().enumerate() {
+            text.insert_str(i, &c.to_string()).unwrap();
+        }
+        assert_eq!(text.to_string(), "hello");
+        assert_eq!(text.get_client_version(), 1);
+        {
+            let mutable = text.datatype.mutable.read();
+            assert_eq!(mutable.push_buffer.iter().count(), 1);
+            let tx = mutable.push_buffer.iter().last().unwrap();
+            assert_eq!(tx.cseq, 1);
+            assert_eq!(tx.operations.len(), 1, "keystrokes are merged into one run");
+            assert!(matches!(
+                &tx.operations[0].body,
+                OperationBody::TextInsert(body) if body.text == "hello"
+            ));
+            assert_eq!(mutable.push_buffer.mem_size, tx.size());
+        }
+
+        // a run handed out for pushing is not extended any more
+        text.datatype
+            .mutable
+            .write()
+            .push_buffer
+            .get_pushing_transactions(1, u64::MAX)
+            .unwrap();
+        text.insert_str(5, "!").unwrap();
+        text.insert_str(6, "?").unwrap();
+        assert_eq!(text.to_string(), "hello!?");
+        assert_eq!(text.get_client_version(), 2);
+        let mutable = text.datatype.mutable.read();
+        assert_eq!(mutable.push_buffer.last_cseq, 2);
+        let tx = mutable.push_buffer.iter().last().unwrap();
+        assert!(matches!(
+            &tx.operations[0].body,
+            OperationBody::TextInsert(body) if body.text == "!?"
+        ));
+    }
+
+    #[test]
+    #[instrument]
+    fn can_converge_concurrent_edits_between_clients() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let (collection, key, _) = get_test_ids!();
+        let client1 = Client::builder(collection.clone(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(collection, "client2")
+            .with_connectivity(connectivity)
+            .build()
+            .unwrap();
+
+        let text1 = client1.create_datatype(key.clone()).build_text().unwrap();
+        text1.insert_str(0, "the fox").unwrap();
+        text1.sync().unwrap();
+
+        let text2 = client2.subscribe_datatype(key).build_text().unwrap();
+        text2.sync().unwrap();
+        assert_eq!(text2.to_string(), "the fox");
+
+        text1.insert_str(4, "quick ").unwrap();
+        text1.insert_str(10, "brown ").unwrap();
+        text2.insert_str(7, " jumps").unwrap();
+        text2.delete_range(0, 4).unwrap();
+        text1.sync().unwrap();
+        text2.sync().unwrap();
+        text1.sync().unwrap();
+
+        assert_eq!(text1.to_string(), "quick brown fox jumps");
+        assert_eq!(text2.to_string(), text1.to_string());
+
+        // later local edits are ordered after everything observed remotely
+        text2.insert_str(21, "!").unwrap();
+        text2.sync().unwrap();
+        text1.sync().unwrap();
+        assert_eq!(text1.to_string(), "quick brown fox jumps!");
+    }
+}
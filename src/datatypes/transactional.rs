@@ -1,23 +1,32 @@
-use std::{collections::BTreeMap, sync::Arc};
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use parking_lot::RwLock;
 use tracing::{Span, info_span, instrument};
 
 use crate::{
     DataType, DatatypeHandler, DatatypeState, IntoString,
+    clients::transaction as client_transaction,
     datatypes::{
         common::{Attribute, ReturnType, internal_datatype_instrument},
         datatype::Datatype,
         event_loop::EventLoop,
+        history::{HistoryEntry, HistoryFilter},
         mutable::MutableDatatype,
+        undo::UndoMode,
+        watch,
         wired::WiredDatatype,
     },
     errors::{
-        datatypes::{DatatypeError, RecoveryAction},
+        datatypes::{DatatypeError, InternalReason, RecoveryAction},
         with_err_out,
     },
     observability::trace::add_span_event,
-    operations::Operation,
+    operations::{Operation, transaction::TxGroup},
+    types::node_id::NodeId,
     utils::{defer_guard::DeferGuard, no_guard_mutex::NoGuardMutex},
 };
 
@@ -82,6 +91,7 @@ pub struct TransactionalDatatype {
     op_mutex: NoGuardMutex,
     tx_mutex: NoGuardMutex,
     event_loop: Arc<EventLoop>,
+    wired: Arc<WiredDatatype>,
 }
 
 impl Datatype for TransactionalDatatype {
@@ -121,6 +131,65 @@ impl Datatype for TransactionalDatatype {
         Ok(())
     }
 
+    fn delete(&self) -> Result<(), DatatypeError> {
+        self.check_subscribed("delete")?;
+        self.mutable.write().set_state(DatatypeState::Deleting);
+        self.event_loop.send_push_transaction_with_best_effort();
+        Ok(())
+    }
+
+    fn resubscribe(&self) -> Result<(), DatatypeError> {
+        let state = self.mutable.read().get_state();
+        if state != DatatypeState::Disabled {
+            return Err(with_err_out!(DatatypeError::NotWritable(format!(
+                "resubscribe is only allowed in Disabled state, current state is {:?}",
+                state
+            ))));
+        }
+        if let Some(datatype_set) = self.attr.get_datatype_set()
+            && !self
+                .attr
+                .client_common
+                .attach_datatype(&self.attr.key, datatype_set)
+        {
+            return Err(with_err_out!(DatatypeError::Disallowed(format!(
+                "resubscribe '{}' since the client has another datatype for the key",
+                self.attr.key
+            ))));
+        }
+        self.mutable.write().resubscribe();
+        self.event_loop.register(self.get_wired_datatype());
+        self.event_loop.send_push_transaction_with_best_effort();
+        Ok(())
+    }
+
+    fn sync_async(&self) -> impl Future<Output = Result<(), DatatypeError>> + Send + 'static {
+        let pushed = self
+            .check_disabled("sync")
+            .map(|_| self.event_loop.send_push_transaction_with_response());
+        async move { pushed?.await }
+    }
+
+    fn wait_until_synced(&self, timeout: Duration) -> Result<(), DatatypeError> {
+        futures::executor::block_on(self.wait_until_synced_async(timeout))
+    }
+
+    fn wait_until_synced_async(
+        &self,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<(), DatatypeError>> + Send + 'static {
+        watch::wait_until_synced(self, timeout)
+    }
+
+    fn unsubscribe_async(
+        &self,
+    ) -> Result<impl Future<Output = Result<(), DatatypeError>> + Send + 'static, DatatypeError>
+    {
+        self.check_subscribed("unsubscribe")?;
+        self.mutable.write().set_state(DatatypeState::Unsubscribing);
+        Ok(self.event_loop.send_push_transaction_with_response())
+    }
+
     fn set_handler(&self, id: usize, handler: DatatypeHandler) {
         self.mutable.write().set_handler(id, handler)
     }
@@ -129,6 +198,26 @@ impl Datatype for TransactionalDatatype {
         self.mutable.write().unset_handler(id)
     }
 
+    fn undo(&self) -> Result<bool, DatatypeError> {
+        self.replay_history(UndoMode::Undo)
+    }
+
+    fn redo(&self) -> Result<bool, DatatypeError> {
+        self.replay_history(UndoMode::Redo)
+    }
+
+    fn can_undo(&self) -> bool {
+        self.mutable.read().undo_history.can_undo()
+    }
+
+    fn can_redo(&self) -> bool {
+        self.mutable.read().undo_history.can_redo()
+    }
+
+    fn history_filtered(&self, filter: &HistoryFilter) -> Vec<HistoryEntry> {
+        self.mutable.read().history(filter)
+    }
+
     #[cfg(test)]
     fn get_attr(&self) -> std::sync::Arc<Attribute> {
         self.attr.clone()
@@ -140,14 +229,16 @@ impl TransactionalDatatype {
         attr: Arc<Attribute>,
         state: DatatypeState,
         handlers: BTreeMap<usize, DatatypeHandler>,
-    ) -> Arc<Self> {
+    ) -> Result<Arc<Self>, DatatypeError> {
+        let mutable = Arc::new(RwLock::new(MutableDatatype::new(
+            attr.clone(),
+            state,
+            handlers,
+        )?));
         let event_loop = EventLoop::new_arc(attr.client_common.connectivity.clone());
         let arc_td = Arc::new(Self {
-            mutable: Arc::new(RwLock::new(MutableDatatype::new(
-                attr.clone(),
-                state,
-                handlers,
-            ))),
+            wired: Arc::new(WiredDatatype::new(mutable.clone(), attr.clone())),
+            mutable,
             attr: attr.clone(),
             event_loop: event_loop.clone(),
             tx_ctx: Default::default(),
@@ -160,11 +251,11 @@ impl TransactionalDatatype {
         span.in_scope(|| {
             event_loop.run(arc_td.get_wired_datatype());
         });
-        arc_td
+        Ok(arc_td)
     }
 
-    fn get_wired_datatype(&self) -> Arc<WiredDatatype> {
-        Arc::new(WiredDatatype::new(self.mutable.clone(), self.attr.clone()))
+    pub(crate) fn get_wired_datatype(&self) -> Arc<WiredDatatype> {
+        self.wired.clone()
     }
 
     /// Checks whether this datatype is writable based on its readonly flag and state.
@@ -217,6 +308,7 @@ impl TransactionalDatatype {
         op: Operation,
     ) -> Result<ReturnType, DatatypeError> {
         self.check_writable()?;
+        let tx_ctx = client_transaction::enlist(self).unwrap_or(tx_ctx);
         let mut _defer_guard = None;
         let mut retries = 0;
         loop {
@@ -252,10 +344,15 @@ impl TransactionalDatatype {
     }
 
     #[instrument(skip_all)]
-    fn end_transaction(&self, tag: Option<String>, committed: bool) {
+    pub(crate) fn end_transaction(
+        &self,
+        tag: Option<String>,
+        group: Option<TxGroup>,
+        committed: bool,
+    ) {
         let error = {
             let mut mutable = self.mutable.write();
-            match mutable.end_transaction(tag, committed) {
+            match mutable.end_transaction(tag, group, committed) {
                 Ok(true) => {
                     self.event_loop.send_push_transaction_with_best_effort();
                     None
@@ -300,7 +397,7 @@ impl TransactionalDatatype {
 
             let mut defer_guard = DeferGuard::new();
             defer_guard.add_defer_func(move |committed| {
-                self.end_transaction(curr_tx_ctx.tag.to_owned(), committed);
+                self.end_transaction(curr_tx_ctx.tag.to_owned(), None, committed);
             });
             add_span_event!("BeginTx", "tag" => tag);
             BeginTransactionResult::BeginTx(defer_guard)
@@ -325,6 +422,10 @@ impl TransactionalDatatype {
     where
         F: FnOnce() -> Result<(), DatatypeError>,
     {
+        if client_transaction::enlist(self).is_some() {
+            // Within a client transaction, this one is part of it and rolls back with it.
+            return tx_func();
+        }
         let mut retries = 0;
         loop {
             match self.begin_transaction(tx_ctx.clone()) {
@@ -349,6 +450,97 @@ impl TransactionalDatatype {
         }
     }
 
+    /// Begins a transaction of `tx_ctx` that several datatypes share, waiting for the one in
+    /// progress to end; it lasts until [`Self::end_transaction`] is called.
+    pub(crate) fn begin_shared_transaction(&self, tx_ctx: Arc<TransactionContext>) {
+        loop {
+            let mut self_tx_ctx = self.tx_ctx.write();
+            match self_tx_ctx.as_ref() {
+                None => {
+                    *self_tx_ctx = Some(tx_ctx);
+                    drop(self_tx_ctx);
+                    self.tx_mutex.lock();
+                    return;
+                }
+                Some(curr) if curr == &tx_ctx => return,
+                Some(_) => {
+                    drop(self_tx_ctx);
+                    self.wait_for_tx_mutex();
+                }
+            }
+        }
+    }
+
+    /// Checks that the pending transaction, tagged with `tag`, can be committed.
+    pub(crate) fn check_commit(&self, tag: Option<String>) -> Result<(), DatatypeError> {
+        self.mutable
+            .write()
+            .check_commit(tag)
+            .map_err(|dewa| dewa.error)
+    }
+
+    pub(crate) fn has_pending_transaction(&self) -> bool {
+        self.mutable.read().has_pending_transaction()
+    }
+
+    /// Pushes the pending transactions and unsubscribes within `timeout`, then disables this
+    /// datatype and stops its event loop, whether or not that succeeded.
+    ///
+    /// A datatype that becomes disabled on the way, e.g., by finishing a deletion, is flushed
+    /// if it has no unsynced transactions left.
+    pub(crate) fn close(&self, timeout: Duration) -> Result<(), DatatypeError> {
+        let deadline = Instant::now() + timeout;
+        let flushed = (|| {
+            loop {
+                match self.get_state() {
+                    DatatypeState::Disabled => {
+                        if self.mutable.read().has_unsynced() {
+                            return Err(DatatypeError::NotWritable(format!(
+                                "'{}' is Disabled with unsynced transactions",
+                                self.attr.key
+                            )));
+                        }
+                        return Ok(());
+                    }
+                    DatatypeState::Subscribed
+                        if self.get_synced_client_version() >= self.get_client_version() =>
+                    {
+                        break;
+                    }
+                    _ => self.sync_before(deadline)?,
+                }
+            }
+            self.unsubscribe()?;
+            self.sync_before(deadline)
+        })();
+        if self.get_state() != DatatypeState::Disabled {
+            self.mutable.write().disable();
+        }
+        self.event_loop.send_stop();
+        flushed
+    }
+
+    fn sync_before(&self, deadline: Instant) -> Result<(), DatatypeError> {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        let pushed = self.event_loop.send_push_transaction_with_response();
+        let key = self.attr.key.clone();
+        let syncing = self.attr.client_common.handle.spawn(async move {
+            tokio::time::timeout(timeout, pushed)
+                .await
+                .unwrap_or_else(|_| {
+                    Err(DatatypeError::Timeout(format!(
+                        "'{key}' did not sync within {timeout:?}"
+                    )))
+                })
+        });
+        futures::executor::block_on(syncing).unwrap_or_else(|e| {
+            Err(
+                InternalReason::EventLoop(format!("failed to sync before closing: {e}"))
+                    .into_error(),
+            )
+        })
+    }
+
     #[inline]
     fn wait_for_mutex(&self) {
         self.op_mutex.lock();
@@ -361,6 +553,62 @@ impl TransactionalDatatype {
         self.tx_mutex.unlock();
     }
 
+    /// Undoes (or redoes) the latest entry of the local history with a new transaction of
+    /// compensating operations, which is synchronized like any other transaction.
+    ///
+    /// The entry is put back if the compensation fails, so that it can be retried.
+    fn replay_history(&self, mode: UndoMode) -> Result<bool, DatatypeError> {
+        self.check_writable()?;
+        let Some(entry) = self.mutable.write().undo_history.take(mode) else {
+            return Ok(false);
+        };
+        let tag = entry.tag.clone().unwrap_or_else(|| match mode {
+            UndoMode::Redo => "redo".to_owned(),
+            _ => "undo".to_owned(),
+        });
+        let tx_ctx = Arc::new(TransactionContext::new(tag));
+        let mut aliases = Vec::new();
+        let mut compensated = false;
+        let result = self.do_transaction(tx_ctx.clone(), || {
+            self.mutable.write().undo_history.set_mode(mode);
+            for tx in entry.transactions.iter().rev() {
+                for op in tx.iter().rev() {
+                    let compensations = {
+                        let mutable = self.mutable.read();
+                        let op_id = tx.get_op_id_of(op);
+                        mutable
+                            .crdt
+                            .compensate(&op_id, op, &mutable.undo_history.aliases)?
+                    };
+                    for compensation in compensations {
+                        compensated = true;
+                        self.execute_local_operation_as_tx(tx_ctx.clone(), compensation.op)?;
+                        // The revived nodes are the last ones the operation created.
+                        let last = NodeId::from(&self.mutable.read().op_id);
+                        let first = last.lamport + 1 - compensation.revives.len() as u64;
+                        for (lamport, revived) in (first..).zip(compensation.revives) {
+                            aliases.push((revived, NodeId::new(lamport, &last.cuid)));
+                        }
+                    }
+                }
+            }
+            Ok(())
+        });
+        match result {
+            Ok(()) => {
+                let history = &mut self.mutable.write().undo_history;
+                for (revived, by) in aliases {
+                    history.aliases.insert(revived, by);
+                }
+                Ok(compensated)
+            }
+            Err(err) => {
+                self.mutable.write().undo_history.restore(mode, entry);
+                Err(err)
+            }
+        }
+    }
+
     pub fn push_transaction(&self) {
         // TODO: implement pushing transactions to remote server
     }
@@ -397,7 +645,8 @@ mod tests_transactional {
     #[instrument]
     fn can_rollback_on_enqueue_failure() {
         let attr = new_attribute!(DataType::Counter);
-        let tx_dt = TransactionalDatatype::new_arc(attr, Default::default(), Default::default());
+        let tx_dt =
+            TransactionalDatatype::new_arc(attr, Default::default(), Default::default()).unwrap();
         let tx_ctx = Arc::new(TransactionContext::new("non_sequential_cseq"));
 
         let tx_dt_in_tx = tx_dt.clone();
@@ -416,7 +665,9 @@ mod tests_transactional {
         assert!(result.is_ok());
 
         let mutable = tx_dt.mutable.read();
-        let Crdt::Counter(c) = &mutable.crdt;
+        let Crdt::Counter(c) = &mutable.crdt else {
+            unreachable!()
+        };
         assert_eq!(c.value(), 0);
         assert_eq!(mutable.op_id.cseq, 0);
         assert_ne!(mutable.get_state(), DatatypeState::Disabled);
@@ -426,7 +677,8 @@ mod tests_transactional {
     #[instrument]
     async fn can_do_transaction() {
         let attr = new_attribute!(DataType::Counter);
-        let tx_dt = TransactionalDatatype::new_arc(attr, Default::default(), Default::default());
+        let tx_dt =
+            TransactionalDatatype::new_arc(attr, Default::default(), Default::default()).unwrap();
         let parent_span = Span::current();
 
         let mut join_handles = vec![];
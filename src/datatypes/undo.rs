@@ -0,0 +1,394 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
+};
+
+use crate::{
+    defaults::DEFAULT_MAX_UNDO_ENTRIES,
+    operations::{Operation, body::OperationBody, transaction::Transaction},
+    types::node_id::NodeId,
+};
+
+/// Tells which history a committed local transaction goes to.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum UndoMode {
+    /// A transaction of the user: it can be undone and invalidates what could be redone.
+    #[default]
+    Normal,
+    /// A compensating transaction of `undo()`: it can be redone.
+    Undo,
+    /// A compensating transaction of `redo()`: it can be undone again.
+    Redo,
+}
+
+/// Committed local transactions undone or redone together.
+#[derive(Debug, Clone)]
+pub struct UndoEntry {
+    pub tag: Option<String>,
+    pub transactions: Vec<Arc<Transaction>>,
+}
+
+/// The nodes of a [`List`](crate::List) or a [`Text`](crate::Text) that undoing a delete
+/// revived as new nodes, so that entries recorded before still find them.
+#[derive(Debug, Default)]
+pub struct NodeAliases(BTreeMap<NodeId, NodeId>);
+
+impl NodeAliases {
+    pub fn insert(&mut self, revived: NodeId, by: NodeId) {
+        self.0.insert(revived, by);
+    }
+
+    /// Returns the node that currently stands for `id`.
+    pub fn resolve(&self, id: &NodeId) -> NodeId {
+        let mut id = id;
+        while let Some(next) = self.0.get(id) {
+            id = next;
+        }
+        id.clone()
+    }
+}
+
+/// The undo and redo stacks of a datatype, scoped to the local client.
+///
+/// Consecutive transactions with the same tag form a single entry, so that, e.g., the
+/// keystrokes of a word typed under one tag are undone at once. A transaction that cannot
+/// be compensated, like a transfer of rights, clears the history, since entries before it
+/// could no longer be undone in order.
+#[derive(Debug, Default)]
+pub struct UndoHistory {
+    undo: VecDeque<UndoEntry>,
+    redo: Vec<UndoEntry>,
+    mode: UndoMode,
+    pub aliases: NodeAliases,
+}
+
+/// Returns whether [`Crdt::compensate`](crate::datatypes::crdts::Crdt::compensate) can undo `op`.
+fn is_undoable(op: &Operation) -> bool {
+    !matches!(
+        op.body,
+        OperationBody::BoundedCounterTransfer(_)
+            | OperationBody::DocumentSet(_)
+            | OperationBody::DocumentDelete(_)
+            | OperationBody::DocumentInsert(_)
+            | OperationBody::Custom(_)
+            | OperationBody::Snapshot(_)
+    )
+}
+
+impl UndoHistory {
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Sets where the next committed transaction goes, until [`Self::record`] or
+    /// [`Self::reset_mode`].
+    pub fn set_mode(&mut self, mode: UndoMode) {
+        self.mode = mode;
+    }
+
+    /// Forgets the mode when the transaction it was set for ends without being recorded,
+    /// e.g., when it was rolled back or had nothing to compensate.
+    pub fn reset_mode(&mut self) {
+        self.mode = UndoMode::Normal;
+    }
+
+    /// Records a committed local transaction.
+    pub fn record(&mut self, tx: Arc<Transaction>) {
+        let mode = std::mem::take(&mut self.mode);
+        if !tx.iter().all(is_undoable) {
+            self.clear();
+            return;
+        }
+        let entry = UndoEntry {
+            tag: tx.tag.clone(),
+            transactions: vec![tx],
+        };
+        match mode {
+            UndoMode::Normal => {
+                self.redo.clear();
+                if let Some(last) = self.undo.back_mut()
+                    && last.tag.is_some()
+                    && last.tag == entry.tag
+                {
+                    last.transactions.extend(entry.transactions);
+                    return;
+                }
+                self.push_undo(entry);
+            }
+            UndoMode::Undo => self.redo.push(entry),
+            UndoMode::Redo => self.push_undo(entry),
+        }
+    }
+
+    fn push_undo(&mut self, entry: UndoEntry) {
+        if self.undo.len() == DEFAULT_MAX_UNDO_ENTRIES {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(entry);
+    }
+
+    /// Takes the entry that `mode` replays: the last done one for undo, the last undone one
+    /// for redo.
+    pub fn take(&mut self, mode: UndoMode) -> Option<UndoEntry> {
+        match mode {
+            UndoMode::Undo => self.undo.pop_back(),
+            UndoMode::Redo => self.redo.pop(),
+            UndoMode::Normal => None,
+        }
+    }
+
+    /// Puts back an entry taken by [`Self::take`] whose replay failed.
+    pub fn restore(&mut self, mode: UndoMode, entry: UndoEntry) {
+        match mode {
+            UndoMode::Undo => self.undo.push_back(entry),
+            UndoMode::Redo => self.redo.push(entry),
+            UndoMode::Normal => {}
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.aliases = NodeAliases::default();
+    }
+}
+
+#[cfg(test)]
+mod tests_undo {
+    use std::sync::Arc;
+
+    use tracing::instrument;
+
+    use crate::{
+        Client, Datatype, DatatypeError, LocalConnectivity, Value,
+        datatypes::undo::{UndoHistory, UndoMode},
+        defaults::DEFAULT_MAX_UNDO_ENTRIES,
+        operations::{Operation, transaction::Transaction},
+        types::uid::Cuid,
+        utils::test_utils::{get_test_collection_name, get_test_func_name},
+    };
+
+    fn new_tx(tag: Option<&str>, op: Operation) -> Arc<Transaction> {
+        let mut tx = Transaction::new(&Cuid::new(), 1);
+        tx.set_tag(tag.map(String::from));
+        tx.push_operation(op);
+        Arc::new(tx)
+    }
+
+    #[test]
+    fn can_group_entries_by_tag() {
+        let mut history = UndoHistory::default();
+        history.record(new_tx(Some("typing"), Operation::new_counter_increase(1)));
+        history.record(new_tx(Some("typing"), Operation::new_counter_increase(2)));
+        history.record(new_tx(None, Operation::new_counter_increase(3)));
+        history.record(new_tx(None, Operation::new_counter_increase(4)));
+        assert_eq!(history.take(UndoMode::Undo).unwrap().transactions.len(), 1);
+        assert_eq!(history.take(UndoMode::Undo).unwrap().transactions.len(), 1);
+        let typing = history.take(UndoMode::Undo).unwrap();
+        assert_eq!(typing.tag.as_deref(), Some("typing"));
+        assert_eq!(typing.transactions.len(), 2);
+        assert!(!history.can_undo());
+
+        history.set_mode(UndoMode::Undo);
+        history.record(new_tx(Some("typing"), Operation::new_counter_increase(-3)));
+        assert!(history.can_redo());
+        history.record(new_tx(None, Operation::new_counter_increase(5)));
+        assert!(!history.can_redo());
+
+        history.record(new_tx(None, Operation::new_custom(vec![])));
+        assert!(!history.can_undo());
+
+        for _ in 0..DEFAULT_MAX_UNDO_ENTRIES + 1 {
+            history.record(new_tx(None, Operation::new_counter_increase(1)));
+        }
+        assert_eq!(history.undo.len(), DEFAULT_MAX_UNDO_ENTRIES);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_undo_and_redo_counter_and_map() {
+        let client = Client::builder(get_test_collection_name!(), get_test_func_name!())
+            .build()
+            .unwrap();
+        let counter = client
+            .create_datatype(get_test_func_name!())
+            .build_counter()
+            .unwrap();
+        assert!(!counter.undo().unwrap());
+        counter.increase_by(2).unwrap();
+        counter
+            .transaction("bump", |c| {
+                c.increase_by(3)?;
+                c.increase_by(4)?;
+                Ok(())
+            })
+            .unwrap();
+        assert!(counter.undo().unwrap());
+        assert_eq!(counter.get_value(), 2);
+        assert!(counter.undo().unwrap());
+        assert_eq!(counter.get_value(), 0);
+        assert!(!counter.can_undo());
+        assert!(counter.redo().unwrap());
+        assert!(counter.redo().unwrap());
+        assert_eq!(counter.get_value(), 9);
+        assert!(!counter.can_redo());
+        assert!(counter.undo().unwrap());
+        assert_eq!(counter.get_value(), 2);
+
+        let map = client
+            .create_datatype(format!("{}-map", get_test_func_name!()))
+            .build_map()
+            .unwrap();
+        map.put("k", 1).unwrap();
+        map.put("k", 2).unwrap();
+        map.remove("k").unwrap();
+        assert!(map.undo().unwrap());
+        assert_eq!(map.get("k"), Some(Value::from(2)));
+        assert!(map.undo().unwrap());
+        assert_eq!(map.get("k"), Some(Value::from(1)));
+        assert!(map.undo().unwrap());
+        assert_eq!(map.get("k"), None);
+        assert!(map.redo().unwrap());
+        assert_eq!(map.get("k"), Some(Value::from(1)));
+    }
+
+    #[test]
+    #[instrument]
+    fn can_undo_text_edits() {
+        let client = Client::builder(get_test_collection_name!(), get_test_func_name!())
+            .build()
+            .unwrap();
+        let text = client
+            .create_datatype(get_test_func_name!())
+            .build_text()
+            .unwrap();
+        text.insert_str(0, "hello world").unwrap();
+        text.delete_range(2, 6).unwrap();
+        assert_eq!(text.to_string(), "herld");
+        text.insert_str(3, "XY").unwrap();
+        assert!(text.undo().unwrap());
+        assert_eq!(text.to_string(), "herld");
+        assert!(text.undo().unwrap());
+        assert_eq!(text.to_string(), "hello world");
+        assert!(text.redo().unwrap());
+        assert_eq!(text.to_string(), "herld");
+        assert!(text.undo().unwrap());
+        assert!(text.undo().unwrap());
+        assert_eq!(text.to_string(), "");
+
+        let list = client
+            .create_datatype(format!("{}-list", get_test_func_name!()))
+            .build_list()
+            .unwrap();
+        list.insert(0, "a").unwrap();
+        list.insert(1, "b").unwrap();
+        list.delete(0).unwrap();
+        assert!(list.undo().unwrap());
+        assert_eq!(
+            list.iter().collect::<Vec<_>>(),
+            vec!["a".into(), "b".into()]
+        );
+        assert!(list.undo().unwrap());
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![Value::from("a")]);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_undo_set_and_flag() {
+        let client = Client::builder(get_test_collection_name!(), get_test_func_name!())
+            .build()
+            .unwrap();
+        let set = client
+            .create_datatype(get_test_func_name!())
+            .build_set()
+            .unwrap();
+        set.add("x").unwrap();
+        set.remove("x").unwrap();
+        assert!(set.undo().unwrap());
+        assert!(set.contains("x"));
+        assert!(set.undo().unwrap());
+        assert!(!set.contains("x"));
+
+        let flag = client
+            .create_datatype(format!("{}-flag", get_test_func_name!()))
+            .build_flag()
+            .unwrap();
+        flag.enable().unwrap();
+        flag.disable().unwrap();
+        assert!(flag.undo().unwrap());
+        assert!(flag.is_enabled());
+        assert!(flag.undo().unwrap());
+        assert!(!flag.is_enabled());
+        assert!(flag.redo().unwrap());
+        assert!(flag.is_enabled());
+    }
+
+    #[test]
+    #[instrument]
+    fn can_undo_only_local_changes() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let client1 = Client::builder(get_test_collection_name!(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(get_test_collection_name!(), "client2")
+            .with_connectivity(connectivity)
+            .build()
+            .unwrap();
+        let var1 = client1
+            .create_datatype(get_test_func_name!())
+            .build_variable()
+            .unwrap();
+        var1.set(1).unwrap();
+        var1.sync().unwrap();
+        let var2 = client2
+            .subscribe_datatype(get_test_func_name!())
+            .build_variable()
+            .unwrap();
+        var2.sync().unwrap();
+        assert!(!var2.can_undo());
+
+        var2.set(2).unwrap();
+        var2.sync().unwrap();
+        var1.sync().unwrap();
+        assert_eq!(var1.get(), Value::from(2));
+        // The own write has been overwritten by the other client, so there is nothing to undo.
+        assert!(!var1.undo().unwrap());
+        assert_eq!(var1.get(), Value::from(2));
+        assert!(!var1.can_undo());
+
+        assert!(var2.undo().unwrap());
+        var2.sync().unwrap();
+        var1.sync().unwrap();
+        assert_eq!(var1.get(), Value::from(1));
+        assert_eq!(var2.get(), Value::from(1));
+    }
+
+    #[test]
+    #[instrument]
+    fn can_keep_entry_when_undo_fails() {
+        let client = Client::builder(get_test_collection_name!(), get_test_func_name!())
+            .build()
+            .unwrap();
+        let register = client
+            .create_datatype(get_test_func_name!())
+            .build_multi_value_register()
+            .unwrap();
+        register.set(1).unwrap();
+        register.set(2).unwrap();
+        assert!(register.undo().unwrap());
+        assert_eq!(register.get()[0].value, Value::from(1));
+        // The first write superseded no value to write back.
+        assert!(matches!(
+            register.undo().unwrap_err(),
+            DatatypeError::Disallowed(_)
+        ));
+        assert!(register.can_undo());
+        assert_eq!(register.get()[0].value, Value::from(1));
+    }
+}
@@ -0,0 +1,253 @@
+use std::sync::Arc;
+
+use futures::Stream;
+use tracing::trace;
+
+use crate::{
+    DatatypeError, IntoString, Value,
+    datatypes::{
+        common::{ReturnType, datatype_instrument},
+        crdts::Crdt,
+        datatype::DatatypeBlanket,
+        transactional::{TransactionContext, TransactionalDatatype},
+        watch,
+    },
+    errors::{BoxedError, datatypes::InternalReason},
+    operations::Operation,
+};
+
+/// A variable is a conflict-free register holding a single [`Value`].
+///
+/// Concurrent writes are resolved by last-writer-wins: the write with the larger
+/// lamport timestamp wins, and ties are broken by the client id, so that all
+/// replicas converge to the same value.
+#[derive(Clone)]
+pub struct Variable {
+    datatype: Arc<TransactionalDatatype>,
+    tx_ctx: Arc<TransactionContext>,
+}
+
+impl Variable {
+    pub(crate) fn new(datatype: Arc<TransactionalDatatype>) -> Self {
+        Variable {
+            datatype,
+            tx_ctx: Default::default(),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn new_for_test(state: crate::DatatypeState) -> Self {
+        Self::new(
+            TransactionalDatatype::new_arc(
+                crate::datatypes::common::new_attribute!(crate::DataType::Variable),
+                state,
+                Default::default(),
+            )
+            .unwrap(),
+        )
+    }
+
+    datatype_instrument! {
+    /// Sets the value of the variable.
+    ///
+    /// Returns the value that was overwritten by this write.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The new value; anything convertible into [`Value`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::{Client, Value};
+    /// let client = Client::builder("doc-example", "variable-set-test").build().unwrap();
+    /// let variable = client.create_datatype("test-variable").build_variable().unwrap();
+    /// assert_eq!(variable.set("hello").unwrap(), Value::Null);
+    /// assert_eq!(variable.set(42).unwrap(), Value::from("hello"));
+    /// ```
+    pub fn set(&self, value: impl Into<Value>) -> Result<Value, DatatypeError> {
+        let op = Operation::new_variable_set(value.into());
+
+        let ret = self
+            .datatype
+            .execute_local_operation_as_tx(self.tx_ctx.clone(), op)?;
+        trace!("set -> {ret:?}");
+        match ret {
+            ReturnType::Variable(prev) => Ok(prev),
+            _ => Err(InternalReason::ExecuteOperation("unexpected return type".into()).into_error())
+        }
+    }}
+
+    /// Gets the current value of the variable.
+    ///
+    /// A variable that has never been written holds [`Value::Null`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::{Client, Value};
+    /// let client = Client::builder("doc-example", "variable-get-test").build().unwrap();
+    /// let variable = client.create_datatype("test-variable").build_variable().unwrap();
+    /// assert_eq!(variable.get(), Value::Null);
+    /// variable.set(true).unwrap();
+    /// assert_eq!(variable.get(), Value::Bool(true));
+    /// ```
+    pub fn get(&self) -> Value {
+        let mutable = self.datatype.mutable.read();
+        match &mutable.crdt {
+            Crdt::Variable(v) => v.value().clone(),
+            _ => unreachable!("variable must have a variable crdt"),
+        }
+    }
+
+    /// Returns a stream of the value after every change.
+    ///
+    /// See [`Counter::watch`](crate::Counter::watch) for when values are yielded.
+    pub fn watch(&self) -> impl Stream<Item = Value> + Send + 'static {
+        watch::watch(&self.datatype, |crdt| match crdt {
+            Crdt::Variable(v) => v.value().clone(),
+            _ => unreachable!("variable must have a variable crdt"),
+        })
+    }
+
+    datatype_instrument! {
+    /// Executes multiple operations atomically within a transaction.
+    ///
+    /// If the transaction function returns an error, all writes within
+    /// the transaction are rolled back, leaving the variable unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use qortoo::{Client, Value};
+    /// let client = Client::builder("doc-example", "variable-transaction-test").build().unwrap();
+    /// let variable = client.create_datatype("test-variable").build_variable().unwrap();
+    /// variable.set(1).unwrap();
+    ///
+    /// let result = variable.transaction("failing-update", |v| {
+    ///     v.set(2)?;
+    ///     Err("something went wrong".into())
+    /// });
+    /// assert!(result.is_err());
+    /// assert_eq!(variable.get(), Value::Int(1)); // unchanged
+    /// ```
+    pub fn transaction<T>(
+        &self,
+        tag: impl IntoString,
+        tx_func: T,
+    ) -> Result<(), DatatypeError>
+    where
+        T: FnOnce(Self) -> Result<(), BoxedError> + Send + Sync + 'static,
+    {
+        self.datatype.check_writable()?;
+        let this_tx_ctx = Arc::new(TransactionContext::new(tag));
+        let this_tx_ctx_clone = this_tx_ctx.clone();
+        let do_tx_func = move || {
+            let mut variable_clone = self.clone();
+            variable_clone.tx_ctx = this_tx_ctx_clone.clone();
+            match tx_func(variable_clone) {
+                Ok(_) => Ok(()),
+                Err(e) => Err(DatatypeError::TransactionFailed(e.to_string())),
+            }
+        };
+        self.datatype.do_transaction(this_tx_ctx, do_tx_func)
+    }}
+}
+
+impl DatatypeBlanket for Variable {
+    fn get_core(&self) -> &TransactionalDatatype {
+        self.datatype.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests_variable {
+    use tracing::instrument;
+
+    use crate::{
+        Client, DataType, Datatype, DatatypeState, LocalConnectivity, Value,
+        datatypes::variable::Variable,
+        utils::test_utils::{get_test_collection_name, get_test_func_name, get_test_ids},
+    };
+
+    #[test]
+    fn can_assert_send_and_sync_traits() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Variable>();
+    }
+
+    #[test]
+    #[instrument]
+    fn can_use_variable_operations() {
+        let variable = Variable::new_for_test(DatatypeState::Creating);
+        assert_eq!(variable.get_type(), DataType::Variable);
+        assert_eq!(variable.get_key(), get_test_func_name!());
+        assert_eq!(variable.set("a").unwrap(), Value::Null);
+        assert_eq!(variable.set(1.5).unwrap(), Value::from("a"));
+        assert_eq!(variable.get(), Value::Float(1.5));
+    }
+
+    #[test]
+    #[instrument]
+    fn can_rollback_transaction() {
+        let variable = Variable::new_for_test(DatatypeState::Creating);
+        variable.set(1).unwrap();
+        let result = variable.transaction("success", |v| {
+            v.set(2)?;
+            v.set(3)?;
+            Ok(())
+        });
+        assert!(result.is_ok());
+        assert_eq!(variable.get(), Value::Int(3));
+
+        let result = variable.transaction("failure", |v| {
+            v.set(4)?;
+            v.set(5)?;
+            Err("failed".into())
+        });
+        assert!(result.is_err());
+        assert_eq!(variable.get(), Value::Int(3));
+    }
+
+    #[test]
+    #[instrument]
+    fn can_converge_concurrent_writes() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let (collection, key, _) = get_test_ids!();
+        let client1 = Client::builder(collection.clone(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(collection, "client2")
+            .with_connectivity(connectivity)
+            .build()
+            .unwrap();
+
+        let variable1 = client1
+            .create_datatype(key.clone())
+            .build_variable()
+            .unwrap();
+        variable1.set("initial").unwrap();
+        variable1.sync().unwrap();
+
+        let variable2 = client2.subscribe_datatype(key).build_variable().unwrap();
+        variable2.sync().unwrap();
+        assert_eq!(variable2.get(), Value::from("initial"));
+
+        // both writes have the same lamport, so the tie is broken by cuid
+        variable1.set("from-1").unwrap();
+        variable2.set("from-2").unwrap();
+        variable1.sync().unwrap();
+        variable2.sync().unwrap();
+        variable1.sync().unwrap();
+
+        assert_eq!(variable1.get(), variable2.get());
+        let expected = if client1.get_cuid() > client2.get_cuid() {
+            "from-1"
+        } else {
+            "from-2"
+        };
+        assert_eq!(variable1.get(), Value::from(expected));
+    }
+}
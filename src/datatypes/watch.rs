@@ -0,0 +1,288 @@
+use std::time::Duration;
+
+use futures::{Stream, stream};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::debug;
+
+use crate::{
+    DatatypeError, DatatypeState,
+    datatypes::{crdts::Crdt, transactional::TransactionalDatatype},
+    errors::datatypes::InternalReason,
+};
+
+/// The number of values a watch stream buffers for a slow consumer.
+///
+/// A consumer that falls further behind skips the oldest values it missed and resumes
+/// from the oldest one still buffered.
+pub(crate) const WATCH_CAPACITY: usize = 64;
+
+/// Reads the watched value out of the CRDT and delivers it.
+///
+/// Returns `false` once the stream is dropped, so that the watcher can be removed.
+pub(crate) type Watcher = Box<dyn Fn(&Crdt) -> bool + Send + Sync>;
+
+/// Registers a watcher on `datatype` and returns the stream of values it reads.
+///
+/// The value is read under the write lock that applied the change, so every local commit
+/// and every applied pull yields the value it produced, in order.
+pub(crate) fn watch<T, R>(
+    datatype: &TransactionalDatatype,
+    read: R,
+) -> impl Stream<Item = T> + Send + 'static
+where
+    T: Clone + Send + 'static,
+    R: Fn(&Crdt) -> T + Send + Sync + 'static,
+{
+    let (tx, rx) = broadcast::channel(WATCH_CAPACITY);
+    datatype
+        .mutable
+        .write()
+        .add_watcher(Box::new(move |crdt| tx.send(read(crdt)).is_ok()));
+    stream::unfold(rx, |mut rx| async move {
+        loop {
+            match rx.recv().await {
+                Ok(value) => return Some((value, rx)),
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!("watch stream lagged; skipped {skipped} values");
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    })
+}
+
+/// How far a datatype has synchronized, published whenever a pull or a state change
+/// updates it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SyncProgress {
+    pub synced_cseq: u64,
+    pub state: DatatypeState,
+    /// The number of resubscriptions, each of which numbers the local transactions anew.
+    pub resubscriptions: u64,
+}
+
+/// Waits until the server has acknowledged every local transaction `datatype` had committed
+/// when this is called.
+///
+/// The wait runs on the runtime of the client, so the returned future can be awaited on
+/// any executor.
+pub(crate) fn wait_until_synced(
+    datatype: &TransactionalDatatype,
+    timeout: Duration,
+) -> impl Future<Output = Result<(), DatatypeError>> + Send + 'static {
+    let (target_cseq, mut rx) = {
+        let mutable = datatype.mutable.read();
+        (mutable.op_id.cseq, mutable.subscribe_sync_progress())
+    };
+    let resubscriptions = rx.borrow().resubscriptions;
+    let key = datatype.attr.key.clone();
+    let waiting = datatype.attr.client_common.handle.spawn(async move {
+        let is_done = |p: &SyncProgress| {
+            p.resubscriptions != resubscriptions
+                || p.synced_cseq >= target_cseq
+                || p.state == DatatypeState::Disabled
+        };
+        match tokio::time::timeout(timeout, rx.wait_for(is_done)).await {
+            Ok(Ok(progress)) if progress.resubscriptions != resubscriptions => {
+                Err(DatatypeError::Interrupted(format!(
+                    "'{key}' was resubscribed before cseq {target_cseq} was synced"
+                )))
+            }
+            Ok(Ok(progress)) if progress.synced_cseq >= target_cseq => Ok(()),
+            Ok(Ok(_)) => Err(DatatypeError::NotWritable(format!(
+                "'{key}' became Disabled before cseq {target_cseq} was synced"
+            ))),
+            Ok(Err(_)) => Err(DatatypeError::NotWritable(format!(
+                "'{key}' was dropped before cseq {target_cseq} was synced"
+            ))),
+            Err(_) => Err(DatatypeError::Timeout(format!(
+                "'{key}' did not sync cseq {target_cseq} within {timeout:?}"
+            ))),
+        }
+    });
+    async move {
+        waiting.await.unwrap_or_else(|e| {
+            Err(InternalReason::EventLoop(format!("failed to wait until synced: {e}")).into_error())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests_watch {
+    use std::time::Duration;
+
+    use futures::{StreamExt, executor::block_on};
+    use tracing::instrument;
+
+    use crate::{
+        Client, Datatype, DatatypeError, DatatypeState, LocalConnectivity, ServerRejectReason,
+        datatypes::watch::WATCH_CAPACITY,
+        utils::test_utils::{get_test_collection_name, get_test_func_name, get_test_ids},
+    };
+
+    #[test]
+    #[instrument]
+    fn can_watch_local_and_remote_changes() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let client1 = Client::builder(get_test_collection_name!(), "client1")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(get_test_collection_name!(), "client2")
+            .with_connectivity(connectivity)
+            .build()
+            .unwrap();
+
+        let counter1 = client1
+            .create_datatype(get_test_func_name!())
+            .build_counter()
+            .unwrap();
+        counter1.sync().unwrap();
+        let counter2 = client2
+            .subscribe_datatype(get_test_func_name!())
+            .build_counter()
+            .unwrap();
+        let mut stream1 = Box::pin(counter1.watch());
+        let mut stream2 = Box::pin(counter2.watch());
+        counter2.sync().unwrap();
+
+        counter1.increase_by(2).unwrap();
+        counter1
+            .transaction("tx", |c| {
+                c.increase_by(3)?;
+                c.increase_by(4)?;
+                Ok(())
+            })
+            .unwrap();
+        assert!(
+            counter1
+                .transaction("failed", |c| {
+                    c.increase_by(100)?;
+                    Err("rollback".into())
+                })
+                .is_err()
+        );
+        counter1.sync().unwrap();
+        counter2.sync().unwrap();
+
+        block_on(async {
+            assert_eq!(stream1.next().await, Some(2));
+            assert_eq!(stream1.next().await, Some(9));
+            assert_eq!(stream2.next().await, Some(0));
+            assert_eq!(stream2.next().await, Some(9));
+        });
+    }
+
+    #[test]
+    #[instrument]
+    fn can_skip_lagged_values() {
+        let client = Client::builder(get_test_collection_name!(), get_test_func_name!())
+            .build()
+            .unwrap();
+        let counter = client
+            .create_datatype(get_test_func_name!())
+            .build_counter()
+            .unwrap();
+        let mut stream = Box::pin(counter.watch());
+        let total = WATCH_CAPACITY as i64 + 10;
+        for _ in 0..total {
+            counter.increase().unwrap();
+        }
+        block_on(async {
+            assert_eq!(stream.next().await, Some(11));
+        });
+        let rest: Vec<i64> = block_on(stream.take(WATCH_CAPACITY - 1).collect());
+        assert_eq!(rest.last(), Some(&total));
+        assert_eq!(counter.get_value(), total);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_wait_until_synced() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let client = Client::builder(get_test_collection_name!(), get_test_func_name!())
+            .with_connectivity(connectivity)
+            .build()
+            .unwrap();
+        let counter = client
+            .create_datatype(get_test_func_name!())
+            .build_counter()
+            .unwrap();
+        counter.increase().unwrap();
+
+        let err = counter
+            .wait_until_synced(Duration::from_millis(50))
+            .unwrap_err();
+        assert!(matches!(err, DatatypeError::Timeout(_)));
+
+        let waiting = counter.wait_until_synced_async(Duration::from_secs(5));
+        // a later write is not waited for
+        counter.increase().unwrap();
+        std::thread::scope(|s| {
+            s.spawn(|| counter.sync().unwrap());
+            block_on(waiting).unwrap();
+        });
+        assert!(counter.get_synced_client_version() >= 1);
+        counter.wait_until_synced(Duration::ZERO).unwrap();
+    }
+
+    #[test]
+    #[instrument]
+    fn can_fail_waiting_until_synced_when_disabled() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let (collection, key, resource_id) = get_test_ids!();
+        let client = Client::builder(collection, "client")
+            .with_connectivity(connectivity.clone())
+            .build()
+            .unwrap();
+        let counter = client.create_datatype(key).build_counter().unwrap();
+        counter.increase().unwrap();
+        let interceptor = connectivity
+            .get_wired_interceptor(&resource_id, client.get_cuid())
+            .unwrap();
+        interceptor.set_after_pull(|_| {
+            Err(
+                DatatypeError::ServerRejected(ServerRejectReason::ProtocolViolation(
+                    "injected".to_string(),
+                ))
+                .mapping(),
+            )
+        });
+
+        let waiting = counter.wait_until_synced_async(Duration::from_secs(5));
+        assert!(counter.sync().is_err());
+        assert_eq!(counter.get_state(), DatatypeState::Disabled);
+        let err = block_on(waiting).unwrap_err();
+        assert!(matches!(err, DatatypeError::NotWritable(_)));
+    }
+
+    #[test]
+    #[instrument]
+    fn can_interrupt_waiting_until_synced_when_resubscribed() {
+        let connectivity = LocalConnectivity::new_arc();
+        connectivity.set_realtime(false);
+        let (collection, key, _) = get_test_ids!();
+        let client = Client::builder(collection, get_test_func_name!())
+            .with_connectivity(connectivity)
+            .build()
+            .unwrap();
+        let counter = client.create_datatype(&key).build_counter().unwrap();
+        counter.increase().unwrap();
+
+        let waiting = counter.wait_until_synced_async(Duration::from_secs(5));
+        // A waiter can miss the Disabled state that precedes a resubscription if it wakes
+        // up after both; resubscribing straight away reproduces that.
+        client
+            .get_datatype(&key)
+            .unwrap()
+            .get_core()
+            .mutable
+            .write()
+            .resubscribe();
+        let err = block_on(waiting).unwrap_err();
+        assert!(matches!(err, DatatypeError::Interrupted(_)));
+    }
+}
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use tracing::{instrument, trace, warn};
 
 #[cfg(test)]
@@ -12,7 +12,10 @@ use crate::{
         push_buffer::PushBuffer,
     },
     defaults,
-    errors::datatypes::{DatatypeErrorWithAction, RecoveryAction},
+    errors::{
+        connectivity::ConnectivityError,
+        datatypes::{DatatypeErrorWithAction, RecoveryAction},
+    },
     observability::{metrics, trace::add_span_event},
     operations::transaction::Transaction,
     types::{notification::Notification, push_pull_pack::PushPullPack, uid::Cuid},
@@ -21,6 +24,8 @@ use crate::{
 pub struct WiredDatatype {
     pub mutable: Arc<RwLock<MutableDatatype>>,
     pub attr: Arc<Attribute>,
+    /// Held during a push-pull, so that the event loop and a batch never overlap.
+    sync_mutex: Mutex<()>,
     #[cfg(test)]
     interceptor: Arc<WiredInterceptor>,
 }
@@ -30,6 +35,7 @@ impl WiredDatatype {
         Self {
             mutable,
             attr,
+            sync_mutex: Mutex::new(()),
             #[cfg(test)]
             interceptor: WiredInterceptor::new_arc(),
         }
@@ -42,12 +48,11 @@ impl WiredDatatype {
         interceptor: Arc<WiredInterceptor>,
     ) -> Arc<Self> {
         Arc::new(Self {
-            mutable: Arc::new(RwLock::new(MutableDatatype::new(
-                attr.clone(),
-                state,
-                Default::default(),
-            ))),
+            mutable: Arc::new(RwLock::new(
+                MutableDatatype::new(attr.clone(), state, Default::default()).unwrap(),
+            )),
             attr,
+            sync_mutex: Mutex::new(()),
             interceptor,
         })
     }
@@ -106,8 +111,13 @@ impl WiredDatatype {
     }
 
     fn do_push_pull(&self) -> Result<(), DatatypeErrorWithAction> {
-        let connectivity = &self.attr.client_common.connectivity;
+        let _syncing = self.sync_mutex.lock();
+        let pushing_ppp = self.prepare_push()?;
+        let pulled = self.attr.client_common.connectivity.push_pull(&pushing_ppp);
+        self.apply_pull(pulled)
+    }
 
+    fn prepare_push(&self) -> Result<PushPullPack, DatatypeErrorWithAction> {
         #[cfg_attr(not(test), allow(unused_mut))]
         let mut pushing_ppp = {
             let mut mutable = self.mutable.write();
@@ -118,10 +128,15 @@ impl WiredDatatype {
         self.interceptor.before_push(&mut pushing_ppp);
 
         add_span_event!("send PUSH PushPullPack", "ppp"=> pushing_ppp.to_string());
+        Ok(pushing_ppp)
+    }
+
+    fn apply_pull(
+        &self,
+        pulled: Result<PushPullPack, ConnectivityError>,
+    ) -> Result<(), DatatypeErrorWithAction> {
         #[cfg_attr(not(test), allow(unused_mut))]
-        let mut pulled_ppp = connectivity
-            .push_pull(&pushing_ppp)
-            .map_err(|e| e.to_datatype_error().mapping())?;
+        let mut pulled_ppp = pulled.map_err(|e| e.to_datatype_error().mapping())?;
 
         #[cfg(test)]
         self.interceptor.after_pull(&mut pulled_ppp)?;
@@ -147,6 +162,67 @@ impl WiredDatatype {
     }
 }
 
+/// Push-pulls `wired_datatypes` of a client in one batched request of its connectivity.
+///
+/// As by [`WiredDatatype::push_pull`], every pulled pack is applied, and an error is routed
+/// through its [`RecoveryAction`]. Returns the results in the order of `wired_datatypes`.
+#[instrument(skip_all, fields(size = wired_datatypes.len()))]
+pub fn push_pull_batch(wired_datatypes: &[Arc<WiredDatatype>]) -> Vec<Result<(), DatatypeError>> {
+    let Some(first) = wired_datatypes.first() else {
+        return Vec::new();
+    };
+    let start = std::time::Instant::now();
+    let syncing: Vec<_> = wired_datatypes
+        .iter()
+        .map(|wired| wired.sync_mutex.lock())
+        .collect();
+    let mut results: Vec<Result<(), DatatypeErrorWithAction>> = Vec::new();
+    // the datatypes that prepared a pack, with their positions, paired with the packs
+    let mut prepared = Vec::new();
+    for (i, wired) in wired_datatypes.iter().enumerate() {
+        match wired.prepare_push() {
+            Ok(ppp) => {
+                prepared.push(((i, wired), ppp));
+                results.push(Ok(()));
+            }
+            Err(dewa) => results.push(Err(dewa)),
+        }
+    }
+    let (targets, pushing): (Vec<_>, Vec<_>) = prepared.into_iter().unzip();
+    let pulled = first
+        .attr
+        .client_common
+        .connectivity
+        .push_pull_batch(&pushing);
+    if pulled.len() != targets.len() {
+        let err = DatatypeError::SyncFailed(format!(
+            "{} results for {} packs in the batched push-pull",
+            pulled.len(),
+            targets.len()
+        ));
+        for (i, _) in &targets {
+            results[*i] = Err(err.clone().mapping());
+        }
+    } else {
+        for ((i, wired), pulled) in targets.into_iter().zip(pulled) {
+            results[i] = wired.apply_pull(pulled);
+        }
+    }
+    let results = wired_datatypes
+        .iter()
+        .zip(results)
+        .map(|(wired, result)| {
+            metrics::emit_sync(&wired.attr, result.is_ok(), start.elapsed());
+            result.map_err(|dewa| {
+                wired.handle_error(dewa.error.clone(), dewa.recovery);
+                dewa.error
+            })
+        })
+        .collect();
+    drop(syncing);
+    results
+}
+
 impl MutableDatatype {
     #[instrument(skip_all)]
     fn create_push_pull_pack(&mut self) -> Result<PushPullPack, DatatypeError> {
@@ -168,6 +244,7 @@ impl MutableDatatype {
             || state == DatatypeState::Subscribing
             || state == DatatypeState::SubscribingOrCreating
             || state == DatatypeState::Unsubscribing
+            || state == DatatypeState::Deleting
             || self.push_buffer.last_cseq > self.checkpoint.cseq
     }
 }
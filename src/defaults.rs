@@ -9,3 +9,7 @@ pub(crate) const UPPER_MAX_MEM_SIZE_OF_PUSH_BUFFER: u64 = ByteUnit::GB.as_u64();
 pub(crate) const DEFAULT_MAX_TRANSMISSION_SIZE: u64 = 4 * ByteUnit::MB.as_u64();
 
 pub(crate) const DEFAULT_EVENT_LOOP_TIMEOUT_MS: u64 = 100;
+
+pub(crate) const DEFAULT_MAX_UNDO_ENTRIES: usize = 100;
+
+pub(crate) const DEFAULT_HISTORY_RETENTION: usize = 1000;
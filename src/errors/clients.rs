@@ -24,6 +24,34 @@ pub enum ClientError {
     /// example, mismatched type or datatype state).
     #[error("[ClientError] cannot subscribe or create datatype: {0}")]
     FailedToSubscribeOrCreateDatatype(String) = 101,
+
+    /// Listing the datatypes of the server failed.
+    ///
+    /// Returned when the connectivity backend cannot answer
+    /// [`Client::list_remote_datatypes`](crate::Client::list_remote_datatypes).
+    #[error("[ClientError] cannot list datatypes: {0}")]
+    FailedToListDatatypes(String) = 102,
+
+    /// Invalid client identity provided.
+    ///
+    /// Returned when the cuid given to
+    /// [`ClientBuilder::with_cuid`](crate::ClientBuilder::with_cuid) is not a valid uid.
+    #[error("[ClientError] invalid cuid: {0}")]
+    InvalidCuid(String) = 103,
+
+    /// Another session of the same client is open.
+    ///
+    /// Returned when a client is built with the cuid of a client whose session on the
+    /// connectivity backend has not been closed yet.
+    #[error("[ClientError] duplicate session: {0}")]
+    DuplicateSession(String) = 104,
+
+    /// Opening the session of the client failed.
+    ///
+    /// Returned when the connectivity backend does not answer in time. This is a transient
+    /// error; building the client again may succeed.
+    #[error("[ClientError] cannot open session: {0}")]
+    FailedToOpenSession(String) = 105,
 }
 
 impl PartialEq for ClientError {
@@ -1,6 +1,6 @@
 use thiserror::Error;
 
-use crate::DatatypeError;
+use crate::{DatatypeError, ServerRejectReason};
 
 /// Errors related to connectivity operations.
 ///
@@ -15,12 +15,38 @@ pub enum ConnectivityError {
     /// This is a transient error. The event loop will retry with exponential backoff.
     #[error("[ConnectivityError] connection timed out: {_0}")]
     TimedOut(String),
+
+    /// Another session of the same client is open.
+    ///
+    /// A client identity can be used by one session at a time; the new session is rejected.
+    /// This is a permanent error; it is not retried.
+    #[error("[ConnectivityError] session conflict: {_0}")]
+    SessionConflict(String),
 }
 
 impl ConnectivityError {
     pub(crate) fn to_datatype_error(&self) -> DatatypeError {
         match self {
             ConnectivityError::TimedOut(_) => DatatypeError::SyncFailed(self.to_string()),
+            ConnectivityError::SessionConflict(msg) => {
+                DatatypeError::ServerRejected(ServerRejectReason::ProtocolViolation(msg.clone()))
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests_connectivity_error {
+    use crate::errors::{connectivity::ConnectivityError, datatypes::RecoveryAction};
+
+    #[test]
+    fn can_map_connectivity_errors_to_recovery_actions() {
+        let timed_out = ConnectivityError::TimedOut("t".into()).to_datatype_error();
+        assert_eq!(
+            timed_out.mapping().recovery,
+            RecoveryAction::RetryWithBackOff
+        );
+        let conflict = ConnectivityError::SessionConflict("c".into()).to_datatype_error();
+        assert_eq!(conflict.mapping().recovery, RecoveryAction::Disable);
+    }
+}
@@ -84,6 +84,8 @@ pub enum ServerRejectReason {
     MissingSubscription(String),
     /// The push violated the wire protocol (e.g., unexpected state transition, type mismatch).
     ProtocolViolation(String),
+    /// The datatype has been deleted, by this or another client.
+    Deleted(String),
 }
 
 /// Errors that can occur while working with Qortoo datatypes.
@@ -122,6 +124,24 @@ pub enum DatatypeError {
     /// Write rejected because the datatype is configured as readonly.
     #[error("[DatatypeError] readonly violation")]
     ReadonlyViolation = 207,
+    /// An index or range is outside the bounds of a sequence datatype (e.g., `List`, `Text`).
+    #[error("[DatatypeError] out of bounds: {0}")]
+    OutOfBounds(String) = 208,
+    /// A path does not address an entry of a `Document`, e.g., it is malformed or goes
+    /// through a missing field or a value that is not a container.
+    #[error("[DatatypeError] invalid path: {0}")]
+    InvalidPath(String) = 209,
+    /// A client tried to decrease a `BoundedCounter`, or to transfer its rights, beyond the
+    /// rights it holds in escrow.
+    #[error("[DatatypeError] insufficient rights: {0}")]
+    InsufficientRights(String) = 214,
+    /// Waiting for the datatype, e.g., by `wait_until_synced`, did not finish in time.
+    #[error("[DatatypeError] timed out: {0}")]
+    Timeout(String) = 215,
+    /// Waiting for the datatype, e.g., by `wait_until_synced`, was cut short because it
+    /// resubscribed, which numbers its local transactions anew.
+    #[error("[DatatypeError] interrupted: {0}")]
+    Interrupted(String) = 216,
 
     /// A transient sync failure that warrants a retry with backoff.
     ///
@@ -166,7 +186,12 @@ impl DatatypeError {
             // the event loop. Reaching here indicates a misrouted error.
             DatatypeError::TransactionFailed(_)
             | DatatypeError::Disallowed(_)
-            | DatatypeError::NotWritable(_) => {
+            | DatatypeError::NotWritable(_)
+            | DatatypeError::OutOfBounds(_)
+            | DatatypeError::InvalidPath(_)
+            | DatatypeError::InsufficientRights(_)
+            | DatatypeError::Timeout(_)
+            | DatatypeError::Interrupted(_) => {
                 unreachable!(
                     "variant {:?} must not be routed through DatatypeError::mapping()",
                     self
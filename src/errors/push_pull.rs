@@ -29,6 +29,10 @@ pub enum PushPullError {
     /// [`DatatypeError::SyncFailed`] → `RecoveryAction::RetryWithBackOff`.
     #[error("[PushPullError] server internal error - {0}")]
     ServerInternalError(String) = 306,
+    /// The datatype has been deleted and cannot be subscribed or created again while its
+    /// tombstone is kept.
+    #[error("[PushPullError] deleted - {0}")]
+    Deleted(String) = 307,
 }
 
 impl PushPullError {
@@ -48,6 +52,9 @@ impl PushPullError {
                 ServerRejectReason::MissingSubscription(msg.to_owned()),
             ),
             PushPullError::ServerInternalError(msg) => DatatypeError::SyncFailed(msg.to_owned()),
+            PushPullError::Deleted(msg) => {
+                DatatypeError::ServerRejected(ServerRejectReason::Deleted(msg.to_owned()))
+            }
         }
     }
 }
@@ -5,10 +5,27 @@
 //!
 //! # Features
 //!
-//! - **CRDT Datatypes**: Conflict-free replicated data types ([`Counter`], with more coming)
-//! - **Transaction Support**: Atomic transactions with automatic rollback on failure
+//! - **CRDT Datatypes**: Conflict-free replicated data types ([`Counter`], [`Variable`],
+//!   [`Map`], [`List`], [`Text`], [`Set`], [`MultiValueRegister`], [`Document`], [`BoundedCounter`], [`Flag`], with more coming)
+//! - **Custom CRDTs**: User-defined datatypes implementing [`CustomCrdt`]
+//! - **Transaction Support**: Atomic transactions with automatic rollback on failure, also
+//!   across datatypes of a client with [`Client::transaction`]
 //! - **Read-Only Mode**: Create read-only datatypes for observation without modification
+//! - **Undo/Redo**: [`Datatype::undo`] and [`Datatype::redo`] compensate the local client's
+//!   committed transactions, grouped by tag
+//! - **Async API**: [`Datatype::sync_async`], [`Datatype::unsubscribe_async`], and
+//!   [`Datatype::write_async`] resolve without blocking the executor
+//! - **Change Streams**: [`Counter::watch`] and friends yield a `futures::Stream` of values
+//!   after every local commit and remote pull
 //! - **Event Loop System**: Priority-based event processing with graceful shutdown
+//! - **Batched Sync**: [`Client::sync_all`] synchronizes every datatype of a client in one
+//!   batched push-pull
+//! - **Client Shutdown**: [`Client::close`] flushes and unsubscribes every datatype, then
+//!   shuts the client runtime down
+//! - **Stable Client Identity**: [`ClientBuilder::with_cuid`] resumes a client with the
+//!   cuid of [`Client::get_cuid`] after a restart, one session at a time
+//! - **Datatype Listing**: [`Client::datatypes`] lists the attached datatypes and
+//!   [`Client::list_remote_datatypes`] those existing on the server
 //! - **Enhanced Error Handling**: Structured stack traces with typed error codes
 //! - **Observability**: `tracing` instrumentation plus application-owned trace, log,
 //!   metrics, and profiling exporters
@@ -59,11 +76,29 @@
 #[cfg(feature = "log_layer")]
 pub use crate::observability::log_layer::QortooLogLayer;
 pub use crate::{
-    clients::client::Client,
+    clients::{
+        client::{Client, ClientBuilder, CloseReport},
+        listing::{DatatypeInfo, RemoteDatatypeFilter, RemoteDatatypeInfo},
+        transaction::ClientTransaction,
+    },
     connectivity::local_connectivity::LocalConnectivity,
     datatypes::{
-        builder::DatatypeBuilder, counter::Counter, datatype::Datatype, datatype_set::DatatypeSet,
+        bounded_counter::BoundedCounter,
+        builder::DatatypeBuilder,
+        counter::Counter,
+        custom::{Custom, CustomCrdt, CustomDatatype, CustomOperationId},
+        datatype::Datatype,
+        datatype_set::DatatypeSet,
+        document::{Document, DocumentValue},
+        flag::{Flag, FlagPolicy},
         handler::DatatypeHandler,
+        history::{HistoryEntry, HistoryFilter, HistoryOperation},
+        list::List,
+        map::Map,
+        multi_value_register::{ConcurrentValue, MultiValueRegister},
+        set::Set,
+        text::Text,
+        variable::Variable,
     },
     errors::{
         BoxedError,
@@ -73,6 +108,8 @@ pub use crate::{
     types::{
         common::IntoString,
         datatype::{DataType, DatatypeState},
+        remote::{RemoteOperation, RemoteTransaction},
+        value::Value,
     },
 };
 
@@ -152,7 +152,7 @@ mod tests_metrics {
         let counter = client.create_datatype(key).build_counter().unwrap();
 
         let interceptor = connectivity
-            .get_wired_interceptor(&resource_id, &client.get_cuid())
+            .get_wired_interceptor(&resource_id, client.get_cuid())
             .unwrap();
         interceptor.set_after_pull(|_| Err(DatatypeError::SyncFailed("injected".into()).mapping()));
 
@@ -1,8 +1,22 @@
-use std::fmt::{Debug, Formatter};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{Debug, Formatter},
+};
 
 use derive_more::Display;
 
-use crate::operations::MemoryMeasurable;
+use crate::{
+    DocumentValue,
+    operations::MemoryMeasurable,
+    types::{
+        node_id::{NodeId, NodeRun},
+        operation_id::OperationId,
+        uid,
+        uid::Cuid,
+        value::Value,
+        version_vector::VersionVector,
+    },
+};
 
 #[derive(Clone, Display, PartialEq, Eq)]
 pub enum OperationBody {
@@ -11,6 +25,46 @@ pub enum OperationBody {
     Delay4Test(Delay4TestBody),
     #[display("CounterIncrease{_0}")]
     CounterIncrease(CounterIncreaseBody),
+    #[display("CounterReset{_0}")]
+    CounterReset(CounterResetBody),
+    #[display("BoundedCounterIncrease{_0}")]
+    BoundedCounterIncrease(BoundedCounterDeltaBody),
+    #[display("BoundedCounterDecrease{_0}")]
+    BoundedCounterDecrease(BoundedCounterDeltaBody),
+    #[display("BoundedCounterTransfer{_0}")]
+    BoundedCounterTransfer(BoundedCounterTransferBody),
+    #[display("VariableSet{_0}")]
+    VariableSet(VariableSetBody),
+    #[display("MapPut{_0}")]
+    MapPut(MapPutBody),
+    #[display("MapRemove{_0}")]
+    MapRemove(MapRemoveBody),
+    #[display("ListInsert{_0}")]
+    ListInsert(ListInsertBody),
+    #[display("ListDelete{_0}")]
+    ListDelete(ListDeleteBody),
+    #[display("TextInsert{_0}")]
+    TextInsert(TextInsertBody),
+    #[display("TextDelete{_0}")]
+    TextDelete(TextDeleteBody),
+    #[display("SetAdd{_0}")]
+    SetAdd(SetAddBody),
+    #[display("SetRemove{_0}")]
+    SetRemove(SetRemoveBody),
+    #[display("MultiValueSet{_0}")]
+    MultiValueSet(MultiValueSetBody),
+    #[display("FlagEnable{_0}")]
+    FlagEnable(FlagBody),
+    #[display("FlagDisable{_0}")]
+    FlagDisable(FlagBody),
+    #[display("DocumentSet{_0}")]
+    DocumentSet(DocumentSetBody),
+    #[display("DocumentDelete{_0}")]
+    DocumentDelete(DocumentDeleteBody),
+    #[display("DocumentInsert{_0}")]
+    DocumentInsert(DocumentInsertBody),
+    #[display("Custom{_0}")]
+    Custom(CustomBody),
     #[display("Snapshot{_0}")]
     Snapshot(SnapshotBody),
 }
@@ -27,6 +81,26 @@ impl MemoryMeasurable for OperationBody {
             #[cfg(test)]
             OperationBody::Delay4Test(body) => body.size(),
             OperationBody::CounterIncrease(body) => body.size(),
+            OperationBody::CounterReset(body) => body.size(),
+            OperationBody::BoundedCounterIncrease(body) => body.size(),
+            OperationBody::BoundedCounterDecrease(body) => body.size(),
+            OperationBody::BoundedCounterTransfer(body) => body.size(),
+            OperationBody::VariableSet(body) => body.size(),
+            OperationBody::MapPut(body) => body.size(),
+            OperationBody::MapRemove(body) => body.size(),
+            OperationBody::ListInsert(body) => body.size(),
+            OperationBody::ListDelete(body) => body.size(),
+            OperationBody::TextInsert(body) => body.size(),
+            OperationBody::TextDelete(body) => body.size(),
+            OperationBody::SetAdd(body) => body.size(),
+            OperationBody::SetRemove(body) => body.size(),
+            OperationBody::MultiValueSet(body) => body.size(),
+            OperationBody::FlagEnable(body) => body.size(),
+            OperationBody::FlagDisable(body) => body.size(),
+            OperationBody::DocumentSet(body) => body.size(),
+            OperationBody::DocumentDelete(body) => body.size(),
+            OperationBody::DocumentInsert(body) => body.size(),
+            OperationBody::Custom(body) => body.size(),
             OperationBody::Snapshot(body) => body.size(),
         }
     }
@@ -81,6 +155,513 @@ impl MemoryMeasurable for CounterIncreaseBody {
     }
 }
 
+/// The increases of each client observed by a counter: how many were applied and their sum.
+pub type CounterMarks = BTreeMap<Cuid, (u64, i64)>;
+
+fn counter_marks_size(marks: &CounterMarks) -> u64 {
+    (marks.len() * (uid::UID_LEN + size_of::<u64>() + size_of::<i64>())) as u64
+}
+
+#[derive(Debug, Default, Clone, Display, PartialEq, Eq)]
+#[display("(observed={})", observed.len())]
+pub struct CounterResetBody {
+    /// The increases observed by local execution; only these are reset.
+    pub observed: CounterMarks,
+    /// The overwritten reset marks, captured by local execution for rollback; not used remotely.
+    pub prev: Option<CounterMarks>,
+}
+
+impl MemoryMeasurable for CounterResetBody {
+    fn size(&self) -> u64 {
+        counter_marks_size(&self.observed) + self.prev.as_ref().map_or(0, counter_marks_size)
+    }
+}
+
+#[derive(Debug, Clone, Display, PartialEq, Eq)]
+#[display("(delta={delta})")]
+pub struct BoundedCounterDeltaBody {
+    pub delta: u64,
+}
+
+impl BoundedCounterDeltaBody {
+    pub fn new(delta: u64) -> Self {
+        Self { delta }
+    }
+}
+
+impl MemoryMeasurable for BoundedCounterDeltaBody {
+    fn size(&self) -> u64 {
+        size_of::<u64>() as u64
+    }
+}
+
+#[derive(Debug, Clone, Display, PartialEq, Eq)]
+#[display("(to={to}, amount={amount})")]
+pub struct BoundedCounterTransferBody {
+    /// The client receiving the rights; the sender is the client of the operation.
+    pub to: Cuid,
+    pub amount: u64,
+}
+
+impl BoundedCounterTransferBody {
+    pub fn new(to: Cuid, amount: u64) -> Self {
+        Self { to, amount }
+    }
+}
+
+impl MemoryMeasurable for BoundedCounterTransferBody {
+    fn size(&self) -> u64 {
+        (uid::UID_LEN + size_of::<u64>()) as u64
+    }
+}
+
+#[derive(Debug, Clone, Display, PartialEq, Eq)]
+#[display("({value})")]
+pub struct VariableSetBody {
+    pub value: Value,
+    /// The overwritten value and the id of its write.
+    /// Filled in by local execution so that a rollback can restore it; not used remotely.
+    pub prev: Option<(Value, OperationId)>,
+}
+
+impl VariableSetBody {
+    pub fn new(value: Value) -> Self {
+        Self { value, prev: None }
+    }
+}
+
+impl MemoryMeasurable for VariableSetBody {
+    fn size(&self) -> u64 {
+        let prev_size = match &self.prev {
+            Some((value, _)) => value.size() + size_of::<OperationId>() as u64,
+            None => 0,
+        };
+        self.value.size() + prev_size
+    }
+}
+
+/// The state of a map key: its value, or `None` for a tombstone, and the id of its last write.
+pub type MapEntry = (Option<Value>, OperationId);
+
+/// Returns the size of a previous map entry kept for rollback.
+fn map_prev_size(prev: &Option<MapEntry>) -> u64 {
+    match prev {
+        Some((value, _)) => {
+            value.as_ref().map_or(0, |v| v.size()) + size_of::<OperationId>() as u64
+        }
+        None => 0,
+    }
+}
+
+#[derive(Debug, Clone, Display, PartialEq, Eq)]
+#[display("({key}={value})")]
+pub struct MapPutBody {
+    pub key: String,
+    pub value: Value,
+    /// The overwritten entry of `key`, if any, captured by local execution for rollback.
+    pub prev: Option<MapEntry>,
+}
+
+impl MapPutBody {
+    pub fn new(key: String, value: Value) -> Self {
+        Self {
+            key,
+            value,
+            prev: None,
+        }
+    }
+}
+
+impl MemoryMeasurable for MapPutBody {
+    fn size(&self) -> u64 {
+        self.key.len() as u64 + self.value.size() + map_prev_size(&self.prev)
+    }
+}
+
+#[derive(Debug, Clone, Display, PartialEq, Eq)]
+#[display("({key})")]
+pub struct MapRemoveBody {
+    pub key: String,
+    /// The removed entry of `key`, if any, captured by local execution for rollback.
+    pub prev: Option<MapEntry>,
+}
+
+impl MapRemoveBody {
+    pub fn new(key: String) -> Self {
+        Self { key, prev: None }
+    }
+}
+
+impl MemoryMeasurable for MapRemoveBody {
+    fn size(&self) -> u64 {
+        self.key.len() as u64 + map_prev_size(&self.prev)
+    }
+}
+
+#[derive(Debug, Clone, Display, PartialEq, Eq)]
+#[display("({index}:{value})")]
+pub struct ListInsertBody {
+    /// The index requested locally; remote replicas use `origin` instead.
+    pub index: usize,
+    /// The node the new element follows, resolved from `index` by local execution.
+    pub origin: NodeId,
+    pub value: Value,
+}
+
+impl ListInsertBody {
+    pub fn new(index: usize, value: Value) -> Self {
+        Self {
+            index,
+            origin: NodeId::head(),
+            value,
+        }
+    }
+}
+
+impl MemoryMeasurable for ListInsertBody {
+    fn size(&self) -> u64 {
+        size_of::<usize>() as u64 + self.origin.size() + self.value.size()
+    }
+}
+
+#[derive(Debug, Clone, Display, PartialEq, Eq)]
+#[display("({index})")]
+pub struct ListDeleteBody {
+    /// The index requested locally; remote replicas use `target` instead.
+    pub index: usize,
+    /// The node to remove, resolved from `index` by local execution.
+    pub target: NodeId,
+}
+
+impl ListDeleteBody {
+    pub fn new(index: usize) -> Self {
+        Self {
+            index,
+            target: NodeId::head(),
+        }
+    }
+}
+
+impl MemoryMeasurable for ListDeleteBody {
+    fn size(&self) -> u64 {
+        size_of::<usize>() as u64 + self.target.size()
+    }
+}
+
+#[derive(Debug, Clone, Display, PartialEq, Eq)]
+#[display("({index}:{text:?})")]
+pub struct TextInsertBody {
+    /// The char index requested locally; remote replicas use `origin` instead.
+    pub index: usize,
+    /// The node the inserted run follows, resolved from `index` by local execution.
+    pub origin: NodeId,
+    /// The inserted run; its `i`-th char gets the lamport of the operation plus `i`.
+    pub text: String,
+}
+
+impl TextInsertBody {
+    pub fn new(index: usize, text: String) -> Self {
+        Self {
+            index,
+            origin: NodeId::head(),
+            text,
+        }
+    }
+
+    /// Returns the number of chars, i.e., the number of lamports the run takes.
+    pub fn char_count(&self) -> u64 {
+        self.text.chars().count() as u64
+    }
+}
+
+impl MemoryMeasurable for TextInsertBody {
+    fn size(&self) -> u64 {
+        size_of::<usize>() as u64 + self.origin.size() + self.text.len() as u64
+    }
+}
+
+#[derive(Debug, Clone, Display, PartialEq, Eq)]
+#[display("({index}+{len})")]
+pub struct TextDeleteBody {
+    /// The char range requested locally; remote replicas use `targets` instead.
+    pub index: usize,
+    pub len: usize,
+    /// The nodes to remove, resolved from the range by local execution.
+    pub targets: Vec<NodeRun>,
+}
+
+impl TextDeleteBody {
+    pub fn new(index: usize, len: usize) -> Self {
+        Self {
+            index,
+            len,
+            targets: Vec::new(),
+        }
+    }
+}
+
+impl MemoryMeasurable for TextDeleteBody {
+    fn size(&self) -> u64 {
+        (size_of::<usize>() * 2) as u64 + self.targets.iter().map(|r| r.size()).sum::<u64>()
+    }
+}
+
+#[derive(Debug, Clone, Display, PartialEq, Eq)]
+#[display("({value})")]
+pub struct SetAddBody {
+    pub value: Value,
+}
+
+impl SetAddBody {
+    pub fn new(value: Value) -> Self {
+        Self { value }
+    }
+}
+
+impl MemoryMeasurable for SetAddBody {
+    fn size(&self) -> u64 {
+        self.value.size()
+    }
+}
+
+#[derive(Debug, Clone, Display, PartialEq, Eq)]
+#[display("({value})")]
+pub struct SetRemoveBody {
+    pub value: Value,
+    /// The tags of the adds observed by local execution; concurrent adds are not removed.
+    pub tags: Vec<NodeId>,
+}
+
+impl SetRemoveBody {
+    pub fn new(value: Value) -> Self {
+        Self {
+            value,
+            tags: Vec::new(),
+        }
+    }
+}
+
+impl MemoryMeasurable for SetRemoveBody {
+    fn size(&self) -> u64 {
+        self.value.size() + self.tags.iter().map(|t| t.size()).sum::<u64>()
+    }
+}
+
+/// The state of a multi-value register: its concurrent values and its observed writes.
+pub type MultiValueState = (BTreeMap<NodeId, Value>, VersionVector);
+
+#[derive(Debug, Clone, Display, PartialEq, Eq)]
+#[display("({value})")]
+pub struct MultiValueSetBody {
+    pub value: Value,
+    /// The writes observed by local execution; the values they wrote are superseded.
+    pub context: VersionVector,
+    /// The overwritten state, captured by local execution for rollback; not used remotely.
+    pub prev: Option<MultiValueState>,
+}
+
+impl MultiValueSetBody {
+    pub fn new(value: Value) -> Self {
+        Self {
+            value,
+            context: VersionVector::default(),
+            prev: None,
+        }
+    }
+}
+
+impl MemoryMeasurable for MultiValueSetBody {
+    fn size(&self) -> u64 {
+        let prev_size = match &self.prev {
+            Some((entries, context)) => {
+                entries
+                    .iter()
+                    .map(|(id, value)| id.size() + value.size())
+                    .sum::<u64>()
+                    + context.size()
+            }
+            None => 0,
+        };
+        self.value.size() + self.context.size() + prev_size
+    }
+}
+
+/// The state of a flag: the tags of its effective enables and of its effective disables.
+pub type FlagState = (BTreeSet<NodeId>, BTreeSet<NodeId>);
+
+#[derive(Debug, Default, Clone, Display, PartialEq, Eq)]
+#[display("(observed={})", observed.len())]
+pub struct FlagBody {
+    /// The tags of the enables and disables observed by local execution; they are superseded.
+    pub observed: Vec<NodeId>,
+    /// The overwritten state, captured by local execution for rollback; not used remotely.
+    pub prev: Option<FlagState>,
+}
+
+impl MemoryMeasurable for FlagBody {
+    fn size(&self) -> u64 {
+        let prev = self
+            .prev
+            .as_ref()
+            .map_or(0, |(e, d)| e.iter().chain(d.iter()).map(|t| t.size()).sum());
+        self.observed.iter().map(|t| t.size()).sum::<u64>() + prev
+    }
+}
+
+/// Addresses an entry of a document container: a field of an object or an element of an array.
+#[derive(Debug, Clone, Display, PartialEq, Eq)]
+pub enum DocumentKey {
+    #[display("{_0}")]
+    Field(String),
+    #[display("{_0}")]
+    Element(NodeId),
+}
+
+impl MemoryMeasurable for DocumentKey {
+    fn size(&self) -> u64 {
+        match self {
+            DocumentKey::Field(field) => field.len() as u64,
+            DocumentKey::Element(id) => id.size(),
+        }
+    }
+}
+
+/// The state of a document entry: its value, or `None` for a tombstone, and the id of its
+/// last write, which is also the id of the container the write created, if any.
+pub type DocumentSlot = (Option<DocumentValue>, OperationId);
+
+/// Returns the size of a previous document slot kept for rollback.
+fn document_prev_size(prev: &Option<DocumentSlot>) -> u64 {
+    match prev {
+        Some((value, _)) => {
+            value.as_ref().map_or(0, |v| v.size()) + size_of::<OperationId>() as u64
+        }
+        None => 0,
+    }
+}
+
+#[derive(Debug, Clone, Display, PartialEq, Eq)]
+#[display("({path}={value})")]
+pub struct DocumentSetBody {
+    /// The path requested locally; remote replicas use `container` and `key` instead.
+    pub path: String,
+    pub value: DocumentValue,
+    /// The container holding the entry, resolved from `path` by local execution.
+    pub container: NodeId,
+    pub key: DocumentKey,
+    /// The overwritten slot, if any, captured by local execution for rollback.
+    pub prev: Option<DocumentSlot>,
+}
+
+impl DocumentSetBody {
+    pub fn new(path: String, value: DocumentValue) -> Self {
+        Self {
+            path,
+            value,
+            container: NodeId::head(),
+            key: DocumentKey::Field(String::new()),
+            prev: None,
+        }
+    }
+}
+
+impl MemoryMeasurable for DocumentSetBody {
+    fn size(&self) -> u64 {
+        self.path.len() as u64
+            + self.value.size()
+            + self.container.size()
+            + self.key.size()
+            + document_prev_size(&self.prev)
+    }
+}
+
+#[derive(Debug, Clone, Display, PartialEq, Eq)]
+#[display("({path})")]
+pub struct DocumentDeleteBody {
+    /// The path requested locally; remote replicas use `container` and `key` instead.
+    pub path: String,
+    /// The container holding the entry, resolved from `path` by local execution.
+    pub container: NodeId,
+    pub key: DocumentKey,
+    /// The deleted field slot, if any, captured by local execution for rollback.
+    pub prev: Option<DocumentSlot>,
+}
+
+impl DocumentDeleteBody {
+    pub fn new(path: String) -> Self {
+        Self {
+            path,
+            container: NodeId::head(),
+            key: DocumentKey::Field(String::new()),
+            prev: None,
+        }
+    }
+}
+
+impl MemoryMeasurable for DocumentDeleteBody {
+    fn size(&self) -> u64 {
+        self.path.len() as u64
+            + self.container.size()
+            + self.key.size()
+            + document_prev_size(&self.prev)
+    }
+}
+
+#[derive(Debug, Clone, Display, PartialEq, Eq)]
+#[display("({path}[{index}]:{value})")]
+pub struct DocumentInsertBody {
+    /// The array path and index requested locally; remote replicas use `container` and
+    /// `origin` instead.
+    pub path: String,
+    pub index: usize,
+    pub value: DocumentValue,
+    /// The array to insert into, resolved from `path` by local execution.
+    pub container: NodeId,
+    /// The element the new element follows, resolved from `index` by local execution.
+    pub origin: NodeId,
+}
+
+impl DocumentInsertBody {
+    pub fn new(path: String, index: usize, value: DocumentValue) -> Self {
+        Self {
+            path,
+            index,
+            value,
+            container: NodeId::head(),
+            origin: NodeId::head(),
+        }
+    }
+}
+
+impl MemoryMeasurable for DocumentInsertBody {
+    fn size(&self) -> u64 {
+        self.path.len() as u64
+            + size_of::<usize>() as u64
+            + self.value.size()
+            + self.container.size()
+            + self.origin.size()
+    }
+}
+
+/// An operation of a user-defined CRDT, opaque to everything but that CRDT.
+#[derive(Debug, Clone, Display, PartialEq, Eq)]
+#[display("(size:{})", data.len())]
+pub struct CustomBody {
+    pub data: Vec<u8>,
+}
+
+impl CustomBody {
+    pub fn new(data: Vec<u8>) -> Self {
+        Self { data }
+    }
+}
+
+impl MemoryMeasurable for CustomBody {
+    fn size(&self) -> u64 {
+        self.data.len() as u64
+    }
+}
+
 #[derive(Debug, Clone, Display, PartialEq, Eq)]
 #[display("(size:{})", data.len())]
 pub struct SnapshotBody {
@@ -105,7 +686,10 @@ mod tests_operation_body {
 
     use crate::operations::{
         MemoryMeasurable,
-        body::{CounterIncreaseBody, Delay4TestBody, OperationBody},
+        body::{
+            CounterIncreaseBody, Delay4TestBody, MapPutBody, MapRemoveBody, OperationBody,
+            VariableSetBody,
+        },
     };
 
     #[test]
@@ -114,6 +698,9 @@ mod tests_operation_body {
         info!("{body} vs. {body:?}");
         let s = format!("{body}");
         assert!(s.starts_with("CounterIncrease(") && s.ends_with(')'));
+
+        let body = OperationBody::VariableSet(VariableSetBody::new("v".into()));
+        assert_eq!(format!("{body}"), "VariableSet(\"v\")");
     }
 
     #[test]
@@ -122,5 +709,14 @@ mod tests_operation_body {
         assert_eq!(body.size(), size_of::<i64>() as u64);
         let body = OperationBody::Delay4Test(Delay4TestBody::new(123, true));
         assert_eq!(body.size(), (size_of::<u64>() + size_of::<bool>()) as u64);
+        let body = OperationBody::VariableSet(VariableSetBody::new(1.into()));
+        assert_eq!(body.size(), size_of::<crate::types::value::Value>() as u64);
+        let body = OperationBody::MapPut(MapPutBody::new("key".into(), 1.into()));
+        assert_eq!(
+            body.size(),
+            3 + size_of::<crate::types::value::Value>() as u64
+        );
+        let body = OperationBody::MapRemove(MapRemoveBody::new("key".into()));
+        assert_eq!(body.size(), 3);
     }
 }
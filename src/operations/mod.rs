@@ -7,7 +7,17 @@ use chrono::Local;
 
 #[cfg(test)]
 use crate::operations::body::Delay4TestBody;
-use crate::operations::body::{CounterIncreaseBody, OperationBody, SnapshotBody};
+use crate::{
+    DocumentValue,
+    operations::body::{
+        BoundedCounterDeltaBody, BoundedCounterTransferBody, CounterIncreaseBody, CounterResetBody,
+        CustomBody, DocumentDeleteBody, DocumentInsertBody, DocumentSetBody, FlagBody,
+        ListDeleteBody, ListInsertBody, MapPutBody, MapRemoveBody, MultiValueSetBody,
+        OperationBody, SetAddBody, SetRemoveBody, SnapshotBody, TextDeleteBody, TextInsertBody,
+        VariableSetBody,
+    },
+    types::{node_id::NodeId, uid::Cuid, value::Value},
+};
 
 pub mod body;
 pub mod transaction;
@@ -38,6 +48,105 @@ impl Operation {
         )))
     }
 
+    pub fn new_counter_reset() -> Self {
+        Self::new(OperationBody::CounterReset(CounterResetBody::default()))
+    }
+
+    pub fn new_bounded_counter_increase(delta: u64) -> Self {
+        Self::new(OperationBody::BoundedCounterIncrease(
+            BoundedCounterDeltaBody::new(delta),
+        ))
+    }
+
+    pub fn new_bounded_counter_decrease(delta: u64) -> Self {
+        Self::new(OperationBody::BoundedCounterDecrease(
+            BoundedCounterDeltaBody::new(delta),
+        ))
+    }
+
+    pub fn new_bounded_counter_transfer(to: Cuid, amount: u64) -> Self {
+        Self::new(OperationBody::BoundedCounterTransfer(
+            BoundedCounterTransferBody::new(to, amount),
+        ))
+    }
+
+    pub fn new_variable_set(value: Value) -> Self {
+        Self::new(OperationBody::VariableSet(VariableSetBody::new(value)))
+    }
+
+    pub fn new_map_put(key: impl Into<String>, value: Value) -> Self {
+        Self::new(OperationBody::MapPut(MapPutBody::new(key.into(), value)))
+    }
+
+    pub fn new_map_remove(key: impl Into<String>) -> Self {
+        Self::new(OperationBody::MapRemove(MapRemoveBody::new(key.into())))
+    }
+
+    pub fn new_list_insert(index: usize, value: Value) -> Self {
+        Self::new(OperationBody::ListInsert(ListInsertBody::new(index, value)))
+    }
+
+    pub fn new_list_delete(index: usize) -> Self {
+        Self::new(OperationBody::ListDelete(ListDeleteBody::new(index)))
+    }
+
+    pub fn new_text_insert(index: usize, text: String) -> Self {
+        Self::new(OperationBody::TextInsert(TextInsertBody::new(index, text)))
+    }
+
+    pub fn new_text_delete(index: usize, len: usize) -> Self {
+        Self::new(OperationBody::TextDelete(TextDeleteBody::new(index, len)))
+    }
+
+    pub fn new_set_add(value: Value) -> Self {
+        Self::new(OperationBody::SetAdd(SetAddBody::new(value)))
+    }
+
+    pub fn new_set_remove(value: Value) -> Self {
+        Self::new(OperationBody::SetRemove(SetRemoveBody::new(value)))
+    }
+
+    pub fn new_multi_value_set(value: Value) -> Self {
+        Self::new(OperationBody::MultiValueSet(MultiValueSetBody::new(value)))
+    }
+
+    pub fn new_flag_enable() -> Self {
+        Self::new(OperationBody::FlagEnable(FlagBody::default()))
+    }
+
+    pub fn new_flag_disable() -> Self {
+        Self::new(OperationBody::FlagDisable(FlagBody::default()))
+    }
+
+    pub fn new_document_set(path: impl Into<String>, value: DocumentValue) -> Self {
+        Self::new(OperationBody::DocumentSet(DocumentSetBody::new(
+            path.into(),
+            value,
+        )))
+    }
+
+    pub fn new_document_delete(path: impl Into<String>) -> Self {
+        Self::new(OperationBody::DocumentDelete(DocumentDeleteBody::new(
+            path.into(),
+        )))
+    }
+
+    pub fn new_document_insert(
+        path: impl Into<String>,
+        index: usize,
+        value: DocumentValue,
+    ) -> Self {
+        Self::new(OperationBody::DocumentInsert(DocumentInsertBody::new(
+            path.into(),
+            index,
+            value,
+        )))
+    }
+
+    pub fn new_custom(data: Vec<u8>) -> Self {
+        Self::new(OperationBody::Custom(CustomBody::new(data)))
+    }
+
     pub fn new_snapshot(body: Box<[u8]>) -> Self {
         let op_body = SnapshotBody::new(body);
         Self::new(OperationBody::Snapshot(op_body))
@@ -51,9 +160,44 @@ impl Operation {
         )))
     }
 
+    /// Returns when the operation was created by its client.
+    pub fn at(&self) -> SystemTime {
+        self.at
+    }
+
     pub fn set_lamport(&mut self, lamport: u64) {
         self.lamport = lamport;
     }
+
+    /// Returns the last lamport this operation takes.
+    ///
+    /// Most operations take a single lamport, but an insert of a run of elements takes one
+    /// per element, so that every element gets its own id.
+    pub fn last_lamport(&self) -> u64 {
+        match &self.body {
+            OperationBody::TextInsert(body) => self.lamport + body.char_count().max(1) - 1,
+            _ => self.lamport,
+        }
+    }
+
+    /// Appends `next` to this operation if both can be encoded as one, returning whether
+    /// it did.
+    ///
+    /// Consecutive text inserts of `cuid`, where `next` continues right after the run of
+    /// this one (e.g., keystrokes), are merged into a single run.
+    pub fn try_merge(&mut self, next: &Operation, cuid: &Cuid) -> bool {
+        let last_lamport = self.last_lamport();
+        match (&mut self.body, &next.body) {
+            (OperationBody::TextInsert(body), OperationBody::TextInsert(next_body))
+                if next.lamport == last_lamport + 1
+                    && next_body.origin == NodeId::new(last_lamport, cuid) =>
+            {
+                body.text.push_str(&next_body.text);
+                true
+            }
+            _ => false,
+        }
+    }
 }
 
 impl Debug for Operation {
@@ -3,7 +3,10 @@ use std::fmt::{Debug, Display, Formatter};
 use crate::{
     operations::{MemoryMeasurable, Operation},
     types,
-    types::{operation_id::OperationId, uid::Cuid},
+    types::{
+        operation_id::OperationId,
+        uid::{Cuid, Uid},
+    },
 };
 
 const TRANSACTION_CONSTANT_SIZE: u64 = (size_of::<Vec<Operation>>() // operations
@@ -11,9 +14,20 @@ const TRANSACTION_CONSTANT_SIZE: u64 = (size_of::<Vec<Operation>>() // operation
     + size_of::<Option<String>>() // tag
     + size_of::<u64>() // cseq
     + size_of::<u64>() // sseq
-    + size_of::<bool>())  // event
+    + size_of::<bool>() // event
+    + size_of::<Option<TxGroup>>()) // group
     as u64;
 
+/// Marks the transactions that one client transaction made on several datatypes.
+///
+/// The server applies the transactions of a group all or none: it holds each of them until
+/// all `size` have arrived.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxGroup {
+    pub id: Uid,
+    pub size: usize,
+}
+
 #[derive(Clone, PartialEq, Eq, Default)]
 pub struct Transaction {
     pub cuid: Cuid,
@@ -21,6 +35,7 @@ pub struct Transaction {
     pub sseq: u64,
     pub tag: Option<String>,
     pub event: bool,
+    pub group: Option<TxGroup>,
     pub operations: Vec<Operation>,
 }
 
@@ -32,6 +47,7 @@ impl Transaction {
             sseq: 0,
             tag: None,
             event: false,
+            group: None,
             operations: vec![],
         }
     }
@@ -50,6 +66,13 @@ impl Transaction {
         op_id
     }
 
+    /// Returns the id of `op`, one of the operations of this transaction.
+    pub fn get_op_id_of(&self, op: &Operation) -> OperationId {
+        let mut op_id = self.get_op_id();
+        op_id.lamport = op.lamport;
+        op_id
+    }
+
     pub fn set_tag(&mut self, tag: Option<String>) {
         self.tag = tag;
     }
@@ -58,7 +81,14 @@ impl Transaction {
         self.event = event;
     }
 
+    /// Appends `op`, merging it into the last operation if both can be encoded as one
+    /// (see [`Operation::try_merge`]).
     pub fn push_operation(&mut self, op: Operation) {
+        if let Some(last) = self.operations.last_mut()
+            && last.try_merge(&op, &self.cuid)
+        {
+            return;
+        }
         self.operations.push(op);
     }
 
@@ -75,6 +105,7 @@ impl Transaction {
             sseq: 0,
             tag: None,
             event: false,
+            group: None,
             operations,
         })
     }
@@ -20,12 +20,36 @@ pub enum DataType {
     /// CRDT counter supporting increment/decrement operations
     #[display("Counter")]
     Counter = 0,
-    /// CRDT variable (planned)
+    /// CRDT last-writer-wins register holding a single value
     #[display("Variable")]
     Variable = 1,
-    /// CRDT map (planned)
+    /// CRDT map of last-writer-wins entries
     #[display("Map")]
     Map = 2,
+    /// CRDT ordered list based on a replicated growable array
+    #[display("List")]
+    List = 3,
+    /// CRDT text of characters based on a replicated growable array
+    #[display("Text")]
+    Text = 4,
+    /// CRDT observed-remove set with add-wins semantics
+    #[display("Set")]
+    Set = 5,
+    /// CRDT register keeping every concurrently written value
+    #[display("MultiValueRegister")]
+    MultiValueRegister = 6,
+    /// CRDT JSON-like document of nested objects, arrays and values
+    #[display("Document")]
+    Document = 7,
+    /// CRDT counter kept non-negative by per-client escrow of decrement rights
+    #[display("BoundedCounter")]
+    BoundedCounter = 8,
+    /// CRDT boolean flag with an enable-wins or disable-wins policy
+    #[display("Flag")]
+    Flag = 9,
+    /// User-defined CRDT implementing [`CustomCrdt`](crate::CustomCrdt)
+    #[display("Custom")]
+    Custom = 10,
 }
 
 /// Represents the lifecycle state and write-access control of a datatype.
@@ -144,6 +168,17 @@ mod tests_datatype {
         assert_eq!(format!("{}", DataType::Counter), "Counter");
         assert_eq!(format!("{}", DataType::Variable), "Variable");
         assert_eq!(format!("{}", DataType::Map), "Map");
+        assert_eq!(format!("{}", DataType::List), "List");
+        assert_eq!(format!("{}", DataType::Text), "Text");
+        assert_eq!(format!("{}", DataType::Set), "Set");
+        assert_eq!(
+            format!("{}", DataType::MultiValueRegister),
+            "MultiValueRegister"
+        );
+        assert_eq!(format!("{}", DataType::Document), "Document");
+        assert_eq!(format!("{}", DataType::BoundedCounter), "BoundedCounter");
+        assert_eq!(format!("{}", DataType::Flag), "Flag");
+        assert_eq!(format!("{}", DataType::Custom), "Custom");
     }
 
     #[rstest]
@@ -1,7 +1,11 @@
 pub mod checkpoint;
 pub mod common;
 pub mod datatype;
+pub mod node_id;
 pub mod notification;
 pub mod operation_id;
 pub mod push_pull_pack;
+pub mod remote;
 pub mod uid;
+pub mod value;
+pub mod version_vector;
@@ -0,0 +1,150 @@
+use std::fmt::{Debug, Display, Formatter};
+
+use crate::{
+    operations::MemoryMeasurable,
+    types::{operation_id::OperationId, uid, uid::Cuid},
+};
+
+/// Identifies an element of a sequence CRDT by the lamport timestamp and the client
+/// that inserted it.
+///
+/// [`NodeId::head`] stands for the position before the first element. The derived
+/// ordering compares lamport first and cuid second, like [`OperationId`].
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct NodeId {
+    pub lamport: u64,
+    pub cuid: Cuid,
+}
+
+impl NodeId {
+    pub fn new(lamport: u64, cuid: &Cuid) -> Self {
+        Self {
+            lamport,
+            cuid: cuid.clone(),
+        }
+    }
+
+    pub fn head() -> Self {
+        Self::default()
+    }
+
+    pub fn is_head(&self) -> bool {
+        self.lamport == 0
+    }
+}
+
+impl From<&OperationId> for NodeId {
+    fn from(op_id: &OperationId) -> Self {
+        Self::new(op_id.lamport, &op_id.cuid)
+    }
+}
+
+impl Debug for NodeId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.to_string().as_str())
+    }
+}
+
+impl Display for NodeId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{}:{}", self.lamport, self.cuid))
+    }
+}
+
+impl MemoryMeasurable for NodeId {
+    fn size(&self) -> u64 {
+        (size_of::<u64>() + uid::UID_LEN) as u64
+    }
+}
+
+/// A run of `len` ids of the same client with consecutive lamports, starting at `first`.
+///
+/// Elements inserted by one operation form a run, so runs keep operations that address
+/// many elements (e.g., deleting a range of text) compact.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeRun {
+    pub first: NodeId,
+    pub len: u64,
+}
+
+impl NodeRun {
+    /// Groups `ids` into runs, preserving their order.
+    pub fn from_ids(ids: &[NodeId]) -> Vec<NodeRun> {
+        let mut runs: Vec<NodeRun> = Vec::new();
+        for id in ids {
+            if let Some(last) = runs.last_mut()
+                && last.first.cuid == id.cuid
+                && last.first.lamport + last.len == id.lamport
+            {
+                last.len += 1;
+                continue;
+            }
+            runs.push(NodeRun {
+                first: id.clone(),
+                len: 1,
+            });
+        }
+        runs
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = NodeId> + '_ {
+        (0..self.len).map(|i| NodeId::new(self.first.lamport + i, &self.first.cuid))
+    }
+}
+
+impl Display for NodeRun {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{}+{}", self.first, self.len))
+    }
+}
+
+impl MemoryMeasurable for NodeRun {
+    fn size(&self) -> u64 {
+        self.first.size() + size_of::<u64>() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests_node_id {
+    use crate::types::{
+        node_id::{NodeId, NodeRun},
+        operation_id::OperationId,
+        uid::Cuid,
+    };
+
+    #[test]
+    fn can_order_and_convert_node_ids() {
+        let (c1, c2) = (Cuid::new(), Cuid::new());
+        assert!(NodeId::head().is_head());
+        assert!(NodeId::new(2, &c1) > NodeId::new(1, &c2));
+        assert_eq!(
+            NodeId::new(1, &c1) > NodeId::new(1, &c2),
+            c1 > c2,
+            "ties are broken by cuid"
+        );
+
+        let mut op_id = OperationId::new_with_cuid(&c1);
+        op_id.lamport = 7;
+        op_id.cseq = 3;
+        assert_eq!(NodeId::from(&op_id), NodeId::new(7, &c1));
+        assert_eq!(NodeId::head().to_string(), "0:0000000000000000");
+    }
+
+    #[test]
+    fn can_group_node_ids_into_runs() {
+        let (c1, c2) = (Cuid::new(), Cuid::new());
+        let ids = [
+            NodeId::new(3, &c1),
+            NodeId::new(4, &c1),
+            NodeId::new(5, &c1),
+            NodeId::new(6, &c2),
+            NodeId::new(9, &c1),
+        ];
+        let runs = NodeRun::from_ids(&ids);
+        assert_eq!(runs.len(), 3);
+        assert_eq!(runs[0].len, 3);
+        let expanded: Vec<_> = runs.iter().flat_map(|r| r.ids()).collect();
+        assert_eq!(expanded, ids);
+        assert!(NodeRun::from_ids(&[]).is_empty());
+    }
+}
@@ -0,0 +1,215 @@
+use crate::{
+    DocumentValue,
+    operations::{Operation, body::OperationBody, transaction::Transaction},
+    types::value::Value,
+};
+
+/// A transaction of another client, applied to a datatype by synchronization.
+///
+/// Delivered to [`DatatypeHandler::set_on_remote_operations`](crate::DatatypeHandler::set_on_remote_operations).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteTransaction {
+    /// The unique id of the client that made the transaction.
+    pub cuid: String,
+    /// The sequence number the server assigned to the transaction.
+    pub sseq: u64,
+    /// The tag given to the transaction, if it was made by a tagged `transaction()`.
+    pub tag: Option<String>,
+    /// The operations of the transaction, in the order they were applied.
+    pub operations: Vec<RemoteOperation>,
+}
+
+impl From<&Transaction> for RemoteTransaction {
+    fn from(tx: &Transaction) -> Self {
+        Self {
+            cuid: tx.cuid.to_string(),
+            sseq: tx.sseq,
+            tag: tx.tag.clone(),
+            operations: tx.iter().map(RemoteOperation::from).collect(),
+        }
+    }
+}
+
+/// An operation of a [`RemoteTransaction`] or a [`HistoryEntry`](crate::HistoryEntry),
+/// decoded from its wire form.
+///
+/// Indexes and paths are those requested by the originating client; concurrent operations
+/// may have moved the affected elements by the time the operation is applied here.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RemoteOperation {
+    CounterIncrease {
+        delta: i64,
+    },
+    CounterReset,
+    BoundedCounterIncrease {
+        delta: u64,
+    },
+    BoundedCounterDecrease {
+        delta: u64,
+    },
+    BoundedCounterTransfer {
+        to: String,
+        amount: u64,
+    },
+    VariableSet {
+        value: Value,
+    },
+    MapPut {
+        key: String,
+        value: Value,
+    },
+    MapRemove {
+        key: String,
+    },
+    ListInsert {
+        index: usize,
+        value: Value,
+    },
+    ListDelete {
+        index: usize,
+    },
+    TextInsert {
+        index: usize,
+        text: String,
+    },
+    TextDelete {
+        index: usize,
+        len: usize,
+    },
+    SetAdd {
+        value: Value,
+    },
+    SetRemove {
+        value: Value,
+    },
+    MultiValueSet {
+        value: Value,
+    },
+    FlagEnable,
+    FlagDisable,
+    DocumentSet {
+        path: String,
+        value: DocumentValue,
+    },
+    DocumentDelete {
+        path: String,
+    },
+    DocumentInsert {
+        path: String,
+        index: usize,
+        value: DocumentValue,
+    },
+    /// An operation of a [`CustomCrdt`](crate::CustomCrdt) with its opaque body.
+    Custom {
+        body: Vec<u8>,
+    },
+    /// An operation this version does not decode, e.g., one used only internally.
+    Other,
+}
+
+impl From<&Operation> for RemoteOperation {
+    fn from(op: &Operation) -> Self {
+        match &op.body {
+            OperationBody::CounterIncrease(b) => Self::CounterIncrease { delta: b.delta },
+            OperationBody::CounterReset(_) => Self::CounterReset,
+            OperationBody::BoundedCounterIncrease(b) => {
+                Self::BoundedCounterIncrease { delta: b.delta }
+            }
+            OperationBody::BoundedCounterDecrease(b) => {
+                Self::BoundedCounterDecrease { delta: b.delta }
+            }
+            OperationBody::BoundedCounterTransfer(b) => Self::BoundedCounterTransfer {
+                to: b.to.to_string(),
+                amount: b.amount,
+            },
+            OperationBody::VariableSet(b) => Self::VariableSet {
+                value: b.value.clone(),
+            },
+            OperationBody::MapPut(b) => Self::MapPut {
+                key: b.key.clone(),
+                value: b.value.clone(),
+            },
+            OperationBody::MapRemove(b) => Self::MapRemove { key: b.key.clone() },
+            OperationBody::ListInsert(b) => Self::ListInsert {
+                index: b.index,
+                value: b.value.clone(),
+            },
+            OperationBody::ListDelete(b) => Self::ListDelete { index: b.index },
+            OperationBody::TextInsert(b) => Self::TextInsert {
+                index: b.index,
+                text: b.text.clone(),
+            },
+            OperationBody::TextDelete(b) => Self::TextDelete {
+                index: b.index,
+                len: b.len,
+            },
+            OperationBody::SetAdd(b) => Self::SetAdd {
+                value: b.value.clone(),
+            },
+            OperationBody::SetRemove(b) => Self::SetRemove {
+                value: b.value.clone(),
+            },
+            OperationBody::MultiValueSet(b) => Self::MultiValueSet {
+                value: b.value.clone(),
+            },
+            OperationBody::FlagEnable(_) => Self::FlagEnable,
+            OperationBody::FlagDisable(_) => Self::FlagDisable,
+            OperationBody::DocumentSet(b) => Self::DocumentSet {
+                path: b.path.clone(),
+                value: b.value.clone(),
+            },
+            OperationBody::DocumentDelete(b) => Self::DocumentDelete {
+                path: b.path.clone(),
+            },
+            OperationBody::DocumentInsert(b) => Self::DocumentInsert {
+                path: b.path.clone(),
+                index: b.index,
+                value: b.value.clone(),
+            },
+            OperationBody::Custom(b) => Self::Custom {
+                body: b.data.clone(),
+            },
+            _ => Self::Other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_remote {
+    use crate::{
+        operations::{Operation, transaction::Transaction},
+        types::{
+            remote::{RemoteOperation, RemoteTransaction},
+            uid::Cuid,
+            value::Value,
+        },
+    };
+
+    #[test]
+    fn can_decode_remote_transaction() {
+        let cuid = Cuid::new();
+        let mut tx = Transaction::new(&cuid, 1);
+        tx.sseq = 7;
+        tx.set_tag(Some("tag".into()));
+        tx.operations = vec![
+            Operation::new_counter_increase(3),
+            Operation::new_map_put("k", Value::from(1)),
+            Operation::new_snapshot(Box::new([])),
+        ];
+        let remote = RemoteTransaction::from(&tx);
+        assert_eq!(remote.cuid, cuid.to_string());
+        assert_eq!(remote.sseq, 7);
+        assert_eq!(remote.tag.as_deref(), Some("tag"));
+        assert_eq!(
+            remote.operations,
+            vec![
+                RemoteOperation::CounterIncrease { delta: 3 },
+                RemoteOperation::MapPut {
+                    key: "k".into(),
+                    value: Value::from(1)
+                },
+                RemoteOperation::Other,
+            ]
+        );
+    }
+}
@@ -0,0 +1,487 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{Display, Formatter},
+};
+
+use crate::{
+    DatatypeError,
+    errors::datatypes::InternalReason,
+    operations::MemoryMeasurable,
+    utils::codec::{ByteReader, ByteWriter},
+};
+
+const TAG_NULL: u8 = 0;
+const TAG_BOOL: u8 = 1;
+const TAG_INT: u8 = 2;
+const TAG_FLOAT: u8 = 3;
+const TAG_STRING: u8 = 4;
+const TAG_BYTES: u8 = 5;
+const TAG_LIST: u8 = 6;
+const TAG_MAP: u8 = 7;
+
+/// The version written in front of [`Value::to_bytes`].
+const VALUE_ENCODING_VERSION: u8 = 1;
+/// Lists and maps nested deeper than this are rejected when decoding, so that malformed
+/// input cannot exhaust the stack.
+const MAX_NESTING_DEPTH: usize = 64;
+
+/// A dynamically typed value stored in Qortoo datatypes such as [`Variable`](crate::Variable).
+///
+/// Values convert from the common Rust types with [`From`], and back with [`TryFrom`],
+/// which returns the original value if it holds another type.
+///
+/// ```
+/// # use std::collections::BTreeMap;
+/// # use qortoo::Value;
+/// let value = Value::from(vec![Value::from(1), Value::from("two")]);
+/// assert_eq!(value.to_string(), "[1, \"two\"]");
+/// assert_eq!(Value::from_bytes(&value.to_bytes()).unwrap(), value);
+///
+/// let items: Vec<Value> = value.try_into().unwrap();
+/// assert_eq!(i64::try_from(items[0].clone()), Ok(1));
+/// assert!(String::try_from(items[0].clone()).is_err());
+/// ```
+///
+/// # Equality
+/// Floats are compared by their bit patterns, so `Value::Float(f64::NAN)` equals itself
+/// and `0.0` differs from `-0.0`. This keeps equality consistent across replicas.
+#[derive(Debug, Clone, Default)]
+pub enum Value {
+    #[default]
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Bytes(Vec<u8>),
+    List(Vec<Value>),
+    Map(BTreeMap<String, Value>),
+}
+
+impl Value {
+    /// Returns `true` if this value is [`Value::Null`].
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    /// Returns the boolean if this value is a [`Value::Bool`].
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer if this value is a [`Value::Int`].
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// Returns the float if this value is a [`Value::Float`].
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    /// Returns the string if this value is a [`Value::String`].
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the bytes if this value is a [`Value::Bytes`].
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::Bytes(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements if this value is a [`Value::List`].
+    pub fn as_list(&self) -> Option<&[Value]> {
+        match self {
+            Value::List(l) => Some(l),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries if this value is a [`Value::Map`].
+    pub fn as_map(&self) -> Option<&BTreeMap<String, Value>> {
+        match self {
+            Value::Map(m) => Some(m),
+            _ => None,
+        }
+    }
+
+    /// Encodes this value into a compact binary form, preceded by a version byte.
+    ///
+    /// Integers and lengths are written as varints, so small values take few bytes.
+    pub fn to_bytes(&self) -> Box<[u8]> {
+        let mut w = ByteWriter::new();
+        w.write_u8(VALUE_ENCODING_VERSION);
+        self.encode(&mut w);
+        w.into_boxed_slice()
+    }
+
+    /// Decodes a value encoded by [`to_bytes`](Self::to_bytes).
+    ///
+    /// # Errors
+    /// - [`DatatypeError::Internal`] if the version is unknown or the input is malformed
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DatatypeError> {
+        let mut r = ByteReader::new(bytes);
+        let version = r.read_u8()?;
+        if version != VALUE_ENCODING_VERSION {
+            return Err(InternalReason::Deserialize(format!(
+                "unknown value encoding version {version}"
+            ))
+            .into_error());
+        }
+        let value = Self::decode(&mut r)?;
+        r.finish()?;
+        Ok(value)
+    }
+
+    pub(crate) fn encode(&self, w: &mut ByteWriter) {
+        match self {
+            Value::Null => w.write_u8(TAG_NULL),
+            Value::Bool(b) => {
+                w.write_u8(TAG_BOOL);
+                w.write_bool(*b);
+            }
+            Value::Int(i) => {
+                w.write_u8(TAG_INT);
+                w.write_zigzag(*i);
+            }
+            Value::Float(f) => {
+                w.write_u8(TAG_FLOAT);
+                w.write_f64(*f);
+            }
+            Value::String(s) => {
+                w.write_u8(TAG_STRING);
+                w.write_str(s);
+            }
+            Value::Bytes(b) => {
+                w.write_u8(TAG_BYTES);
+                w.write_bytes(b);
+            }
+            Value::List(l) => {
+                w.write_u8(TAG_LIST);
+                w.write_varint(l.len() as u64);
+                for v in l.iter() {
+                    v.encode(w);
+                }
+            }
+            Value::Map(m) => {
+                w.write_u8(TAG_MAP);
+                w.write_varint(m.len() as u64);
+                for (k, v) in m.iter() {
+                    w.write_str(k);
+                    v.encode(w);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn decode(r: &mut ByteReader) -> Result<Self, DatatypeError> {
+        Self::decode_nested(r, 0)
+    }
+
+    fn decode_nested(r: &mut ByteReader, depth: usize) -> Result<Self, DatatypeError> {
+        let tag = r.read_u8()?;
+        if matches!(tag, TAG_LIST | TAG_MAP) && depth >= MAX_NESTING_DEPTH {
+            return Err(InternalReason::Deserialize(format!(
+                "value nested deeper than {MAX_NESTING_DEPTH}"
+            ))
+            .into_error());
+        }
+        Ok(match tag {
+            TAG_NULL => Value::Null,
+            TAG_BOOL => Value::Bool(r.read_bool()?),
+            TAG_INT => Value::Int(r.read_zigzag()?),
+            TAG_FLOAT => Value::Float(r.read_f64()?),
+            TAG_STRING => Value::String(r.read_string()?),
+            TAG_BYTES => Value::Bytes(r.read_bytes()?.to_vec()),
+            TAG_LIST => Value::List(
+                (0..r.read_len()?)
+                    .map(|_| Self::decode_nested(r, depth + 1))
+                    .collect::<Result<_, _>>()?,
+            ),
+            TAG_MAP => Value::Map(
+                (0..r.read_len()?)
+                    .map(|_| Ok((r.read_string()?, Self::decode_nested(r, depth + 1)?)))
+                    .collect::<Result<_, DatatypeError>>()?,
+            ),
+            tag => {
+                return Err(
+                    InternalReason::Deserialize(format!("unknown value tag {tag}")).into_error(),
+                );
+            }
+        })
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Null, Value::Null) => true,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::Float(a), Value::Float(b)) => a.to_bits() == b.to_bits(),
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Bytes(a), Value::Bytes(b)) => a == b,
+            (Value::List(a), Value::List(b)) => a == b,
+            (Value::Map(a), Value::Map(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Value {}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Null => f.write_str("null"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Int(i) => write!(f, "{i}"),
+            Value::Float(v) => write!(f, "{v}"),
+            Value::String(s) => write!(f, "{s:?}"),
+            Value::Bytes(b) => write!(f, "bytes[{}]", b.len()),
+            Value::List(l) => {
+                f.write_str("[")?;
+                for (i, v) in l.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{v}")?;
+                }
+                f.write_str("]")
+            }
+            Value::Map(m) => {
+                f.write_str("{")?;
+                for (i, (k, v)) in m.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{k:?}: {v}")?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+impl MemoryMeasurable for Value {
+    fn size(&self) -> u64 {
+        let heap = match self {
+            Value::String(s) => s.len() as u64,
+            Value::Bytes(b) => b.len() as u64,
+            Value::List(l) => l.iter().map(Value::size).sum(),
+            Value::Map(m) => m
+                .iter()
+                .map(|(k, v)| (size_of::<String>() + k.len()) as u64 + v.size())
+                .sum(),
+            _ => 0,
+        };
+        size_of::<Value>() as u64 + heap
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+macro_rules! impl_from_int {
+    ($($t:ty),*) => {$(
+        impl From<$t> for Value {
+            fn from(value: $t) -> Self {
+                Value::Int(value as i64)
+            }
+        }
+    )*};
+}
+
+impl_from_int!(i8, i16, i32, i64, u8, u16, u32);
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Value::Float(value as f64)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_owned())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    fn from(value: Vec<u8>) -> Self {
+        Value::Bytes(value)
+    }
+}
+
+impl From<&[u8]> for Value {
+    fn from(value: &[u8]) -> Self {
+        Value::Bytes(value.to_vec())
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(value: Vec<Value>) -> Self {
+        Value::List(value)
+    }
+}
+
+impl From<BTreeMap<String, Value>> for Value {
+    fn from(value: BTreeMap<String, Value>) -> Self {
+        Value::Map(value)
+    }
+}
+
+impl From<HashMap<String, Value>> for Value {
+    fn from(value: HashMap<String, Value>) -> Self {
+        Value::Map(value.into_iter().collect())
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+macro_rules! impl_try_from_value {
+    ($($t:ty => $variant:ident),*) => {$(
+        impl TryFrom<Value> for $t {
+            type Error = Value;
+
+            fn try_from(value: Value) -> Result<Self, Self::Error> {
+                match value {
+                    Value::$variant(v) => Ok(v),
+                    other => Err(other),
+                }
+            }
+        }
+    )*};
+}
+
+impl_try_from_value!(
+    bool => Bool,
+    i64 => Int,
+    f64 => Float,
+    String => String,
+    Vec<u8> => Bytes,
+    Vec<Value> => List,
+    BTreeMap<String, Value> => Map
+);
+
+#[cfg(test)]
+mod tests_value {
+    use std::collections::BTreeMap;
+
+    use rstest::rstest;
+
+    use crate::{
+        operations::MemoryMeasurable,
+        types::value::Value,
+        utils::codec::{ByteReader, ByteWriter},
+    };
+
+    #[rstest]
+    #[case::null(Value::Null)]
+    #[case::bool(Value::Bool(true))]
+    #[case::int(Value::Int(-7))]
+    #[case::float(Value::Float(3.25))]
+    #[case::string(Value::from("qortoo"))]
+    #[case::bytes(Value::from(vec![0u8, 1, 2]))]
+    #[case::list(Value::from(vec![Value::Null, Value::from(i64::MIN), Value::from(vec![Value::from(1.5)])]))]
+    #[case::map(Value::from(BTreeMap::from([
+        ("a".to_owned(), Value::from(true)),
+        ("b".to_owned(), Value::from(BTreeMap::new())),
+    ])))]
+    fn can_encode_and_decode_value(#[case] value: Value) {
+        let mut w = ByteWriter::new();
+        value.encode(&mut w);
+        let bytes = w.into_boxed_slice();
+        let mut r = ByteReader::new(&bytes);
+        assert_eq!(Value::decode(&mut r).unwrap(), value);
+        assert!(r.finish().is_ok());
+        assert_eq!(Value::from_bytes(&value.to_bytes()).unwrap(), value);
+    }
+
+    #[test]
+    fn can_encode_compactly_and_reject_malformed_bytes() {
+        assert_eq!(&*Value::Int(-1).to_bytes(), &[1, 2, 1]);
+        assert!(Value::from_bytes(&[2, 0]).is_err());
+        assert!(Value::from_bytes(&[1, 0, 0]).is_err());
+
+        let mut nested = Value::Null;
+        for _ in 0..100 {
+            nested = Value::from(vec![nested]);
+        }
+        assert!(Value::from_bytes(&nested.to_bytes()).is_err());
+    }
+
+    #[test]
+    fn can_convert_values_from_and_to_rust_types() {
+        assert_eq!(Value::from(7u8), Value::Int(7));
+        assert_eq!(Value::from(0.5f32), Value::Float(0.5));
+        assert_eq!(bool::try_from(Value::from(true)), Ok(true));
+        assert_eq!(String::try_from(Value::from(1)), Err(Value::Int(1)));
+        let list = Value::from(vec![Value::from("x")]);
+        assert_eq!(list.as_list().map(<[Value]>::len), Some(1));
+        assert_eq!(list.as_str(), None);
+        let map = Value::from(std::collections::HashMap::from([(
+            "k".to_owned(),
+            Value::from(1),
+        )]));
+        assert_eq!(map.as_map().unwrap().get("k"), Some(&Value::Int(1)));
+    }
+
+    #[test]
+    fn can_compare_display_and_measure_values() {
+        assert_eq!(Value::Float(f64::NAN), Value::Float(f64::NAN));
+        assert_ne!(Value::Float(0.0), Value::Float(-0.0));
+        assert_ne!(Value::Int(1), Value::Float(1.0));
+        assert_eq!(Value::from(None::<i64>), Value::Null);
+
+        assert_eq!(Value::from("a").to_string(), "\"a\"");
+        assert_eq!(Value::Null.to_string(), "null");
+        let map = Value::from(BTreeMap::from([(
+            "k".to_owned(),
+            Value::from(vec![Value::from(1), Value::Null]),
+        )]));
+        assert_eq!(map.to_string(), "{\"k\": [1, null]}");
+
+        let base = size_of::<Value>() as u64;
+        assert_eq!(Value::Int(1).size(), base);
+        assert_eq!(Value::from("abc").size(), base + 3);
+        assert_eq!(
+            Value::from(vec![Value::from("abc"), Value::Null]).size(),
+            3 * base + 3
+        );
+        assert!(Value::decode(&mut ByteReader::new(&[9])).is_err());
+    }
+}
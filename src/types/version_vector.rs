@@ -0,0 +1,103 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{Debug, Display, Formatter},
+};
+
+use crate::{
+    DatatypeError,
+    operations::MemoryMeasurable,
+    types::{node_id::NodeId, uid, uid::Cuid},
+    utils::codec::{ByteReader, ByteWriter},
+};
+
+/// The largest lamport observed from each client.
+///
+/// Operations of a client reach every replica in the order of their cseq, with increasing
+/// lamports, so a vector that has observed lamport `l` of a client covers every operation
+/// of that client up to `l`. Unlike a single lamport, it can tell whether two writes are
+/// concurrent.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct VersionVector(BTreeMap<Cuid, u64>);
+
+impl VersionVector {
+    pub fn observe(&mut self, id: &NodeId) {
+        let lamport = self.0.entry(id.cuid.clone()).or_default();
+        *lamport = (*lamport).max(id.lamport);
+    }
+
+    pub fn covers(&self, id: &NodeId) -> bool {
+        self.0.get(&id.cuid).is_some_and(|l| *l >= id.lamport)
+    }
+
+    pub fn merge(&mut self, other: &VersionVector) {
+        for (cuid, lamport) in other.0.iter() {
+            self.observe(&NodeId::new(*lamport, cuid));
+        }
+    }
+
+    pub fn encode(&self, w: &mut ByteWriter) {
+        w.write_varint(self.0.len() as u64);
+        for (cuid, lamport) in self.0.iter() {
+            w.write_node_id(&NodeId::new(*lamport, cuid));
+        }
+    }
+
+    pub fn decode(r: &mut ByteReader) -> Result<Self, DatatypeError> {
+        let mut vv = Self::default();
+        for _ in 0..r.read_len()? {
+            vv.observe(&r.read_node_id()?);
+        }
+        Ok(vv)
+    }
+}
+
+impl Debug for VersionVector {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.to_string().as_str())
+    }
+}
+
+impl Display for VersionVector {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.0.iter().map(|(c, l)| (c.to_string(), l)))
+            .finish()
+    }
+}
+
+impl MemoryMeasurable for VersionVector {
+    fn size(&self) -> u64 {
+        (self.0.len() * (size_of::<u64>() + uid::UID_LEN)) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests_version_vector {
+    use crate::{
+        types::{node_id::NodeId, uid::Cuid, version_vector::VersionVector},
+        utils::codec::{ByteReader, ByteWriter},
+    };
+
+    #[test]
+    fn can_observe_merge_and_encode() {
+        let (c1, c2) = (Cuid::new(), Cuid::new());
+        let mut vv1 = VersionVector::default();
+        vv1.observe(&NodeId::new(3, &c1));
+        vv1.observe(&NodeId::new(2, &c1));
+        assert!(vv1.covers(&NodeId::new(3, &c1)));
+        assert!(!vv1.covers(&NodeId::new(4, &c1)));
+        assert!(!vv1.covers(&NodeId::new(1, &c2)));
+
+        let mut vv2 = VersionVector::default();
+        vv2.observe(&NodeId::new(5, &c2));
+        vv1.merge(&vv2);
+        assert!(vv1.covers(&NodeId::new(5, &c2)));
+
+        let mut w = ByteWriter::new();
+        vv1.encode(&mut w);
+        let bytes = w.into_boxed_slice();
+        let mut r = ByteReader::new(&bytes);
+        assert_eq!(VersionVector::decode(&mut r).unwrap(), vv1);
+        r.finish().unwrap();
+    }
+}
@@ -0,0 +1,266 @@
+use crate::{
+    DatatypeError,
+    errors::datatypes::InternalReason,
+    types::{node_id::NodeId, operation_id::OperationId, uid::Cuid},
+};
+
+/// Appends CRDT state to a compact little-endian byte buffer.
+///
+/// Lengths and unsigned integers are written as LEB128 varints, so that small
+/// snapshots stay small. The matching [`ByteReader`] reads values back in the same order.
+#[derive(Debug, Default)]
+pub struct ByteWriter {
+    buf: Vec<u8>,
+}
+
+impl ByteWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write_u8(&mut self, v: u8) {
+        self.buf.push(v);
+    }
+
+    pub fn write_bool(&mut self, v: bool) {
+        self.buf.push(v as u8);
+    }
+
+    pub fn write_varint(&mut self, mut v: u64) {
+        while v >= 0x80 {
+            self.buf.push((v as u8) | 0x80);
+            v >>= 7;
+        }
+        self.buf.push(v as u8);
+    }
+
+    /// Writes a signed integer as a zigzag-encoded varint, so small magnitudes stay small.
+    pub fn write_zigzag(&mut self, v: i64) {
+        self.write_varint(((v << 1) ^ (v >> 63)) as u64);
+    }
+
+    pub fn write_i64(&mut self, v: i64) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn write_f64(&mut self, v: f64) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn write_bytes(&mut self, v: &[u8]) {
+        self.write_varint(v.len() as u64);
+        self.buf.extend_from_slice(v);
+    }
+
+    pub fn write_str(&mut self, v: &str) {
+        self.write_bytes(v.as_bytes());
+    }
+
+    pub fn write_op_id(&mut self, op_id: &OperationId) {
+        self.write_varint(op_id.lamport);
+        self.write_str(&op_id.cuid);
+        self.write_varint(op_id.cseq);
+    }
+
+    pub fn write_node_id(&mut self, node_id: &NodeId) {
+        self.write_varint(node_id.lamport);
+        self.write_str(&node_id.cuid);
+    }
+
+    pub fn into_boxed_slice(self) -> Box<[u8]> {
+        self.buf.into_boxed_slice()
+    }
+}
+
+/// Reads values written by [`ByteWriter`].
+///
+/// Every read fails with [`InternalReason::Deserialize`] on truncated or malformed input,
+/// so a broken snapshot is routed like any other deserialization failure.
+#[derive(Debug)]
+pub struct ByteReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DatatypeError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.buf.len())
+            .ok_or_else(|| {
+                InternalReason::Deserialize(format!(
+                    "unexpected end of input: need {len} bytes at {}, have {}",
+                    self.pos,
+                    self.buf.len()
+                ))
+                .into_error()
+            })?;
+        let slice = &self.buf[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, DatatypeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, DatatypeError> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            b => Err(InternalReason::Deserialize(format!("invalid bool byte {b}")).into_error()),
+        }
+    }
+
+    pub fn read_varint(&mut self) -> Result<u64, DatatypeError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_u8()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(InternalReason::Deserialize("varint overflow".into()).into_error())
+    }
+
+    /// Reads a varint length and checks it against the remaining input before allocating.
+    pub fn read_len(&mut self) -> Result<usize, DatatypeError> {
+        let len = self.read_varint()?;
+        if len > self.remaining() as u64 {
+            return Err(InternalReason::Deserialize(format!(
+                "length {len} exceeds remaining {} bytes",
+                self.remaining()
+            ))
+            .into_error());
+        }
+        Ok(len as usize)
+    }
+
+    pub fn read_zigzag(&mut self) -> Result<i64, DatatypeError> {
+        let v = self.read_varint()?;
+        Ok(((v >> 1) as i64) ^ -((v & 1) as i64))
+    }
+
+    pub fn read_i64(&mut self) -> Result<i64, DatatypeError> {
+        let mut array = [0u8; 8];
+        array.copy_from_slice(self.take(8)?);
+        Ok(i64::from_le_bytes(array))
+    }
+
+    pub fn read_f64(&mut self) -> Result<f64, DatatypeError> {
+        let mut array = [0u8; 8];
+        array.copy_from_slice(self.take(8)?);
+        Ok(f64::from_le_bytes(array))
+    }
+
+    pub fn read_bytes(&mut self) -> Result<&'a [u8], DatatypeError> {
+        let len = self.read_len()?;
+        self.take(len)
+    }
+
+    pub fn read_string(&mut self) -> Result<String, DatatypeError> {
+        let bytes = self.read_bytes()?;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|e| InternalReason::Deserialize(format!("invalid utf-8: {e}")).into_error())
+    }
+
+    fn read_cuid(&mut self) -> Result<Cuid, DatatypeError> {
+        let cuid = self.read_string()?;
+        Cuid::try_from(cuid).map_err(|e| InternalReason::Deserialize(e.to_string()).into_error())
+    }
+
+    pub fn read_op_id(&mut self) -> Result<OperationId, DatatypeError> {
+        let lamport = self.read_varint()?;
+        let cuid = self.read_cuid()?;
+        let cseq = self.read_varint()?;
+        Ok(OperationId {
+            lamport,
+            cuid,
+            cseq,
+        })
+    }
+
+    pub fn read_node_id(&mut self) -> Result<NodeId, DatatypeError> {
+        let lamport = self.read_varint()?;
+        let cuid = self.read_cuid()?;
+        Ok(NodeId { lamport, cuid })
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.buf.len() - self.pos
+    }
+
+    /// Fails if any input is left unread, which indicates a format mismatch.
+    pub fn finish(self) -> Result<(), DatatypeError> {
+        if self.remaining() != 0 {
+            return Err(InternalReason::Deserialize(format!(
+                "{} trailing bytes",
+                self.remaining()
+            ))
+            .into_error());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_codec {
+    use crate::{
+        DatatypeError,
+        types::{node_id::NodeId, operation_id::OperationId, uid::Cuid},
+        utils::codec::{ByteReader, ByteWriter},
+    };
+
+    #[test]
+    fn can_write_and_read_values() {
+        let op_id = OperationId {
+            lamport: 300,
+            cuid: Cuid::new(),
+            cseq: 7,
+        };
+        let mut w = ByteWriter::new();
+        w.write_varint(0);
+        w.write_varint(u64::MAX);
+        w.write_i64(-42);
+        w.write_zigzag(i64::MIN);
+        w.write_zigzag(-1);
+        w.write_f64(1.5);
+        w.write_bool(true);
+        w.write_str("héllo");
+        w.write_op_id(&op_id);
+        w.write_node_id(&NodeId::from(&op_id));
+        let bytes = w.into_boxed_slice();
+
+        let mut r = ByteReader::new(&bytes);
+        assert_eq!(r.read_varint().unwrap(), 0);
+        assert_eq!(r.read_varint().unwrap(), u64::MAX);
+        assert_eq!(r.read_i64().unwrap(), -42);
+        assert_eq!(r.read_zigzag().unwrap(), i64::MIN);
+        assert_eq!(r.read_zigzag().unwrap(), -1);
+        assert_eq!(r.read_f64().unwrap(), 1.5);
+        assert!(r.read_bool().unwrap());
+        assert_eq!(r.read_string().unwrap(), "héllo");
+        assert_eq!(r.read_op_id().unwrap(), op_id);
+        assert_eq!(r.read_node_id().unwrap(), NodeId::from(&op_id));
+        assert!(r.finish().is_ok());
+    }
+
+    #[test]
+    fn can_fail_on_malformed_input() {
+        let mut r = ByteReader::new(&[0xff, 0xff]);
+        assert!(matches!(r.read_varint(), Err(DatatypeError::Internal(_))));
+
+        // a length prefix larger than the input must not allocate or panic
+        let mut r = ByteReader::new(&[0x7f, b'a']);
+        assert!(matches!(r.read_bytes(), Err(DatatypeError::Internal(_))));
+
+        let r = ByteReader::new(&[1]);
+        assert!(matches!(r.finish(), Err(DatatypeError::Internal(_))));
+    }
+}
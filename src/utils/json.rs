@@ -0,0 +1,109 @@
+use std::fmt::Write;
+
+use crate::types::value::Value;
+
+/// Appends `s` to `out` as a JSON string literal.
+pub fn write_json_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Appends `value` to `out` as JSON.
+///
+/// JSON has no bytes, NaN or infinities: bytes are written as an array of numbers and
+/// non-finite floats as `null`.
+pub fn write_json_value(out: &mut String, value: &Value) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => {
+            let _ = write!(out, "{b}");
+        }
+        Value::Int(i) => {
+            let _ = write!(out, "{i}");
+        }
+        Value::Float(f) if f.is_finite() => {
+            let _ = write!(out, "{f}");
+        }
+        Value::Float(_) => out.push_str("null"),
+        Value::String(s) => write_json_string(out, s),
+        Value::Bytes(bytes) => {
+            out.push('[');
+            for (i, b) in bytes.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                let _ = write!(out, "{b}");
+            }
+            out.push(']');
+        }
+        Value::List(list) => {
+            out.push('[');
+            for (i, v) in list.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_json_value(out, v);
+            }
+            out.push(']');
+        }
+        Value::Map(map) => {
+            out.push('{');
+            for (i, (k, v)) in map.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_json_string(out, k);
+                out.push(':');
+                write_json_value(out, v);
+            }
+            out.push('}');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_json {
+    use crate::{
+        types::value::Value,
+        utils::json::{write_json_string, write_json_value},
+    };
+
+    #[test]
+    fn can_write_json_values() {
+        let json = |v: Value| {
+            let mut out = String::new();
+            write_json_value(&mut out, &v);
+            out
+        };
+        assert_eq!(json(Value::Null), "null");
+        assert_eq!(json(Value::Bool(true)), "true");
+        assert_eq!(json(Value::Int(-3)), "-3");
+        assert_eq!(json(Value::Float(1.5)), "1.5");
+        assert_eq!(json(Value::Float(f64::NAN)), "null");
+        assert_eq!(json(Value::Bytes(vec![1, 255])), "[1,255]");
+        assert_eq!(
+            json(Value::from(std::collections::BTreeMap::from([(
+                "a".to_owned(),
+                Value::from(vec![Value::Null, Value::from("b")]),
+            )]))),
+            r#"{"a":[null,"b"]}"#
+        );
+
+        let mut out = String::new();
+        write_json_string(&mut out, "a\"b\\c\n\u{1}é");
+        assert_eq!(out, r#""a\"b\\c\n\u0001é""#);
+    }
+}
@@ -1,4 +1,6 @@
+pub mod codec;
 pub mod defer_guard;
+pub mod json;
 pub mod name_validator;
 pub mod no_guard_mutex;
 pub mod runtime;
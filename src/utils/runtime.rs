@@ -3,6 +3,7 @@ use std::{
     num::NonZeroUsize,
     sync::{Arc, OnceLock},
     thread::available_parallelism,
+    time::Duration,
 };
 
 use parking_lot::Mutex;
@@ -51,6 +52,23 @@ pub fn reserve_to_shutdown_runtime(group: &str) {
     }
 }
 
+/// Shuts the runtime of `group` down, waiting up to `timeout` for its tasks to finish.
+///
+/// In an async context, where it cannot block, it does not wait like
+/// [`reserve_to_shutdown_runtime`].
+pub fn shutdown_runtime(group: &str, timeout: Duration) {
+    let rt = RUNTIME_MAP.get().and_then(|map| map.lock().remove(group));
+    if let Some(rt) = rt {
+        let tasks = rt.metrics().num_alive_tasks();
+        if Handle::try_current().is_ok() {
+            rt.shutdown_background();
+        } else {
+            rt.shutdown_timeout(timeout);
+        }
+        add_span_event!("shutdown runtime", "group"=>group, "tasks"=> tasks);
+    }
+}
+
 #[cfg(test)]
 mod tests_runtime {
     use std::{
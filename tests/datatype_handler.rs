@@ -15,7 +15,7 @@ mod tests_datatype_handler {
         let tx_priority_0 = tx.clone();
 
         let handler0 = DatatypeHandler::new().set_on_state_change(move |ds, old, new| {
-            let Counter(counter) = ds;
+            let Counter(counter) = ds else { unreachable!() };
             assert_ne!(old, new);
             assert_eq!(counter.get_state(), new);
             assert_eq!(counter.get_key(), "counter-1");
@@ -24,7 +24,7 @@ mod tests_datatype_handler {
         });
 
         let handler1 = DatatypeHandler::new().set_on_state_change(move |ds, _, _| {
-            let Counter(counter) = ds;
+            let Counter(counter) = ds else { unreachable!() };
             tx.send((1, counter.get_value())).unwrap();
         });
 